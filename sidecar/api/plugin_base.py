@@ -132,6 +132,36 @@ class PluginBase(ABC):
             {"percentage": percentage, "message": message}
         )
 
+    def checkpoint(
+        self,
+        operation_id: str,
+        state: Any,
+        progress: Optional[float] = None,
+        message: str = "",
+    ) -> None:
+        """
+        Record progress of a long-running operation.
+
+        ``state`` must be JSON-serializable; it is handed back to
+        ``resume_operation`` if the operation is interrupted.
+        """
+        self.brain.record_checkpoint(self.name, operation_id, state, progress, message or None)
+
+    def finish_operation(self, operation_id: str, message: str = "") -> None:
+        """Mark a checkpointed operation as completed so it is never resumed."""
+        previous = self.brain.operations.get(operation_id, {})
+        self.brain.record_checkpoint(
+            self.name, operation_id, previous.get("checkpoint"), 1.0, message or None, status="completed"
+        )
+
+    async def resume_operation(self, operation_id: str, checkpoint: Any) -> None:
+        """
+        Continue an interrupted operation from its last checkpoint.
+
+        Override in plugins that call ``checkpoint()``.
+        """
+        raise NotImplementedError
+
     def update_state(self, key: str, value: Any) -> None:
         """Update a key in the Frontend global/vault state."""
         self.brain.update_state(key, value)
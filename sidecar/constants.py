@@ -32,6 +32,18 @@ JSONRPC_INVALID_PARAMS: Final[int] = -32602
 JSONRPC_INTERNAL_ERROR: Final[int] = -32603
 """Internal JSON-RPC error."""
 
+JSONRPC_REQUEST_CANCELLED: Final[int] = -32800
+"""The request was cancelled by the client before it completed."""
+
+CANCEL_REQUEST_METHOD: Final[str] = "cancel_request"
+"""Notification asking to abort the in-flight request whose id is ``params.id``."""
+
+JSONRPC_SHUTTING_DOWN: Final[int] = -32801
+"""The sidecar is draining for shutdown and takes no new requests."""
+
+DRAIN_EXEMPT_METHODS: Final[frozenset] = frozenset({"system.ping", "system.drain", "system.shutdown"})
+"""Methods still served while the sidecar drains."""
+
 
 # ============================================================================
 # Timing Constants
@@ -40,6 +52,9 @@ JSONRPC_INTERNAL_ERROR: Final[int] = -32603
 DEFAULT_TICK_INTERVAL: Final[float] = 5.0
 """Default interval in seconds for plugin tick loop."""
 
+MIN_TICK_INTERVAL: Final[float] = 0.5
+"""Shortest tick interval ``system.apply_config`` accepts, in seconds."""
+
 WEBSOCKET_TIMEOUT: Final[float] = 30.0
 """WebSocket connection timeout in seconds."""
 
@@ -81,6 +96,21 @@ class EventType(str, Enum):
     CHAT_STREAM_END = "CHAT_STREAM_END"
     """Chat stream completed event - sent when streaming finishes."""
 
+    PLUGIN_LIFECYCLE = "PLUGIN_LIFECYCLE"
+    """Plugin lifecycle trace (load, unload, tick, command) - debug only."""
+
+    REQUEST_PERMIT = "REQUEST_PERMIT"
+    """Ask the host for a provider request permit - handled by Rust."""
+
+    RELEASE_PERMIT = "RELEASE_PERMIT"
+    """Return a provider request permit to the host - handled by Rust."""
+
+    PROVIDER_KEY_STATUS = "PROVIDER_KEY_STATUS"
+    """Provider API key status changed - sent after keys, category models or plugins change."""
+
+    STREAM_CHUNK = "STREAM_CHUNK"
+    """Partial output of a host-requested stream - handled by Rust."""
+
 
 class EventScope(str, Enum):
     """Event routing scopes."""
@@ -187,6 +217,9 @@ MEMORY_DIR: Final[str] = ".memory"
 PLUGINS_DIR: Final[str] = "plugins"
 """Plugins directory name within vault."""
 
+SHARED_PLUGINS_SETTING: Final[str] = "sharedPlugins"
+"""``.vault.json`` key listing plugins loaded from the shared plugins directory."""
+
 LIB_DIR: Final[str] = "lib"
 """Library directory name within vault."""
 
@@ -278,6 +311,7 @@ class CoreEvents(str, Enum):
     SYSTEM_STARTUP = "system:startup"
     SYSTEM_SHUTDOWN = "system:shutdown"
     PLUGIN_LOADED = "plugin:loaded"
+    PLUGIN_UNLOADED = "plugin:unloaded"
     ALL_PLUGINS_LOADED = "system:ready"
     
     # File Operations
@@ -4,20 +4,158 @@ Tailor - Event Bus
 Handles internal Pub/Sub with priority support.
 """
 import asyncio
+import contextlib
+import contextvars
 import inspect
-from typing import Dict, List, Tuple, Any, Callable, Awaitable
+import time
+import traceback
+from typing import Dict, List, Tuple, Any, Callable, Awaitable, Optional, AsyncIterator
 from collections import defaultdict
 from loguru import logger
 
+from .services.watchdog import LoopWatchdog, plugin_of_handler
+
 # Type aliases
 EventHandler = Callable[..., Awaitable[None]]
 
+# Set while a task holds a callback permit, so events published from inside a
+# handler don't wait on a permit their own caller is holding (deadlock at limit=1)
+_holding_permit: contextvars.ContextVar[bool] = contextvars.ContextVar("holding_permit", default=False)
+
+
+class ConcurrencyLimiter:
+    """
+    Resizable limit on concurrently running callbacks.
+    
+    A limit of None means unlimited; 1 runs callbacks serially.
+    """
+    def __init__(self, limit: Optional[int] = None):
+        if limit is not None and limit < 1:
+            raise ValueError("Concurrency limit must be at least 1")
+        self._limit = limit
+        self._in_flight = 0
+        self._waiting = 0
+        self._condition: Optional[asyncio.Condition] = None
+
+    @property
+    def limit(self) -> Optional[int]:
+        return self._limit
+
+    @property
+    def in_flight(self) -> int:
+        return self._in_flight
+
+    @property
+    def waiting(self) -> int:
+        return self._waiting
+
+    def _get_condition(self) -> asyncio.Condition:
+        # Created lazily so the limiter can be built outside a running loop
+        if self._condition is None:
+            self._condition = asyncio.Condition()
+        return self._condition
+
+    async def set_limit(self, limit: Optional[int]) -> None:
+        """Change the limit; waiters are re-checked immediately."""
+        if limit is not None and limit < 1:
+            raise ValueError("Concurrency limit must be at least 1")
+        self._limit = limit
+        condition = self._get_condition()
+        async with condition:
+            condition.notify_all()
+
+    @contextlib.asynccontextmanager
+    async def permit(self) -> AsyncIterator[None]:
+        """Hold a callback slot for the duration of the block."""
+        if _holding_permit.get():
+            # Re-entrant use from inside a handler that already holds a slot
+            yield
+            return
+
+        condition = self._get_condition()
+        async with condition:
+            self._waiting += 1
+            try:
+                await condition.wait_for(
+                    lambda: self._limit is None or self._in_flight < self._limit
+                )
+            finally:
+                self._waiting -= 1
+            self._in_flight += 1
+
+        token = _holding_permit.set(True)
+        try:
+            yield
+        finally:
+            _holding_permit.reset(token)
+            async with condition:
+                self._in_flight -= 1
+                condition.notify()
+
+
+class EventChannels:
+    """
+    Bookkeeping for frontend-bound event channels (``trigger_event`` types).
+
+    Every channel is enabled until muted; muted channels are dropped at the
+    source instead of being sent over the WebSocket.
+    """
+    def __init__(self, muted: Optional[List[str]] = None):
+        self._muted = set(muted or [])
+        self._stats: Dict[str, Dict[str, Any]] = {}
+
+    def _entry(self, channel: str) -> Dict[str, Any]:
+        return self._stats.setdefault(channel, {"emitted": 0, "suppressed": 0, "last_emitted": None})
+
+    def allow(self, channel: str) -> bool:
+        """Record an emit attempt and report whether it should be sent."""
+        entry = self._entry(channel)
+        if channel in self._muted:
+            entry["suppressed"] += 1
+            return False
+        entry["emitted"] += 1
+        entry["last_emitted"] = time.time()
+        return True
+
+    def set_enabled(self, channel: str, enabled: bool) -> None:
+        self._entry(channel)
+        if enabled:
+            self._muted.discard(channel)
+        else:
+            self._muted.add(channel)
+
+    def is_enabled(self, channel: str) -> bool:
+        return channel not in self._muted
+
+    @property
+    def muted(self) -> List[str]:
+        return sorted(self._muted)
+
+    def snapshot(self, subscribers: int) -> List[Dict[str, Any]]:
+        """Per-channel state; muted channels report zero subscribers."""
+        channels = set(self._stats) | self._muted
+        return [
+            {
+                "channel": channel,
+                "enabled": self.is_enabled(channel),
+                "subscribers": subscribers if self.is_enabled(channel) else 0,
+                **self._entry(channel),
+            }
+            for channel in sorted(channels)
+        ]
+
+
 class EventBus:
     """
     Internal Event Bus with priority support.
     """
     def __init__(self):
         self._subscribers: Dict[str, List[Tuple[int, EventHandler]]] = defaultdict(list)
+        self.limiter = ConcurrencyLimiter()
+        # Records which plugin handler is running, for hang diagnosis
+        self.watchdog: Optional[LoopWatchdog] = None
+        # Called as observer(plugin, event, phase, details) around plugin handlers
+        self.observer: Optional[Callable[[str, str, str, Dict[str, Any]], None]] = None
         self.logger = logger.bind(component="EventBus")
 
     def subscribe(self, event: str, handler: EventHandler, priority: int = 0) -> None:
@@ -53,6 +191,20 @@ class EventBus:
                     return True
         return False
 
+    def unsubscribe_owner(self, owner: Any) -> int:
+        """
+        Remove every handler that is a bound method of ``owner``, e.g. an
+        unloaded plugin. Returns how many were removed.
+        """
+        removed = 0
+        for event, handlers_list in self._subscribers.items():
+            kept = [(p, h) for p, h in handlers_list if getattr(h, "__self__", None) is not owner]
+            removed += len(handlers_list) - len(kept)
+            handlers_list[:] = kept
+        if removed:
+            self.logger.debug(f"Unsubscribed {removed} handler(s) of {type(owner).__name__}")
+        return removed
+
     def clear_subscribers(self, event: str) -> None:
         """Clear all subscribers for an event."""
         if event in self._subscribers:
@@ -78,8 +230,31 @@ class EventBus:
         handlers = [h for _, h in priority_handlers]
                     
         async def safe_exec(h: EventHandler) -> None:
+            plugin = plugin_of_handler(h)
             try:
-                await h(**kwargs)
+                async with self.limiter.permit():
+                    self._observe(plugin, event, "started")
+                    started = time.monotonic()
+                    error = None
+                    tracked = (
+                        self.watchdog.track(plugin, event)
+                        if self.watchdog else contextlib.nullcontext()
+                    )
+                    error_traceback = None
+                    try:
+                        with tracked:
+                            await h(**kwargs)
+                    except Exception as e:
+                        error = str(e)
+                        error_traceback = traceback.format_exc()
+                        raise
+                    finally:
+                        self._observe(
+                            plugin, event, "finished",
+                            duration_ms=round((time.monotonic() - started) * 1000, 1),
+                            error=error,
+                            traceback=error_traceback,
+                        )
             except Exception as e:
                 self.logger.exception(f"Event handler failed for '{event}': {e}")
 
@@ -88,3 +263,11 @@ class EventBus:
                 await safe_exec(h)
         else:
             await asyncio.gather(*(safe_exec(h) for h in handlers))
+
+    def _observe(self, plugin: Optional[str], event: str, phase: str, **details: Any) -> None:
+        if self.observer is None or plugin is None:
+            return
+        try:
+            self.observer(plugin, event, phase, details)
+        except Exception as e:
+            self.logger.debug(f"Handler observer failed: {e}")
@@ -17,6 +17,7 @@ from typing import Optional
 from .websocket_server import WebSocketServer
 from .vault_brain import VaultBrain
 from . import utils
+from . import constants
 from . import exceptions
 
 from loguru import logger
@@ -34,10 +35,12 @@ async def run_servers(ws_server: WebSocketServer, brain: VaultBrain) -> None:
     """
     # Initialize plugins
     await brain.initialize()
+    brain.watchdog.start()
     
     await asyncio.gather(
         ws_server.start(),
         brain.tick_loop(),
+        brain.watchdog.heartbeat(),
     )
 
 
@@ -69,7 +72,8 @@ def parse_arguments() -> argparse.Namespace:
     # Optional arguments
     parser.add_argument(
         "--log-level",
-        choices=["DEBUG", "INFO", "WARNING", "ERROR", "CRITICAL"],
+        type=str.upper,
+        choices=utils.LOG_LEVELS,
         help="Logging level (default: from environment or INFO)"
     )
     parser.add_argument(
@@ -77,6 +81,59 @@ def parse_arguments() -> argparse.Namespace:
         type=Path,
         help="Log file path (if not specified, logs only to console)"
     )
+    parser.add_argument(
+        "--plugin-concurrency",
+        type=int,
+        help="Max concurrently running plugin callbacks (default: unlimited)"
+    )
+    parser.add_argument(
+        "--mute-event",
+        action="append",
+        default=[],
+        metavar="CHANNEL",
+        help="Event channel not to emit to the frontend (repeatable)"
+    )
+    parser.add_argument(
+        "--restrict-plugin-fs",
+        action="store_true",
+        help="Block plugin writes outside the vault (and granted paths)"
+    )
+    parser.add_argument(
+        "--only-plugin",
+        action="append",
+        default=None,
+        metavar="NAME",
+        help="Load only this plugin (repeatable); used for per-plugin workers"
+    )
+    parser.add_argument(
+        "--no-plugins",
+        action="store_true",
+        help="Load no plugins; used when each plugin runs in its own worker"
+    )
+    parser.add_argument(
+        "--lifecycle-events",
+        action="store_true",
+        help="Emit plugin lifecycle trace events (load, tick, command)"
+    )
+    parser.add_argument(
+        "--request-budget",
+        action="store_true",
+        help="Ask the host for a permit before each provider request"
+    )
+    parser.add_argument(
+        "--shared-plugins-dir",
+        type=str,
+        default=None,
+        metavar="PATH",
+        help="Directory of plugins shared across vaults (see sharedPlugins in .vault.json)"
+    )
+    parser.add_argument(
+        "--tick-interval",
+        type=float,
+        default=None,
+        metavar="SECONDS",
+        help=f"Seconds between tick events (default: {constants.DEFAULT_TICK_INTERVAL})"
+    )
     parser.add_argument(
         "-v", "--verbose",
         action="store_true",
@@ -163,7 +220,18 @@ def main() -> None:
         
         # Initialize vault brain (creates emitter internally)
         logger.info("Initializing VaultBrain...")
-        brain = VaultBrain(vault_path=vault_path, ws_server=ws_server)
+        brain = VaultBrain(
+            vault_path=vault_path,
+            ws_server=ws_server,
+            plugin_concurrency=args.plugin_concurrency,
+            muted_event_channels=args.mute_event,
+            restrict_plugin_fs=args.restrict_plugin_fs,
+            only_plugins=[] if args.no_plugins else args.only_plugin,
+            lifecycle_events=args.lifecycle_events,
+            request_budget=args.request_budget,
+            shared_plugins_dir=Path(args.shared_plugins_dir) if args.shared_plugins_dir else None,
+            tick_interval=args.tick_interval,
+        )
         
         logger.info("=" * 60)
         logger.info("Sidecar initialized successfully!")
@@ -0,0 +1,125 @@
+"""
+Tailor - Plugin Validator
+
+Checks a plugin folder against the contract VaultBrain loads plugins by,
+without starting a vault: main.py imports, defines a ``Plugin`` class that
+subclasses ``PluginBase``, can be constructed the way the brain constructs
+it, and implements the required methods.
+
+Run as ``python -m sidecar.plugin_validator <plugin_dir>``; the result is
+printed to stdout as JSON.
+"""
+
+import contextlib
+import importlib.util
+import inspect
+import json
+import sys
+import traceback
+from pathlib import Path
+from typing import Any, Dict, List, Optional
+
+from . import constants
+from .api.plugin_base import PluginBase
+
+# Lifecycle hooks the brain awaits, so overrides must be coroutines
+ASYNC_HOOKS = ("on_load", "on_tick", "on_client_connected", "on_unload")
+
+
+def _check(name: str, passed: bool, message: Optional[str] = None) -> Dict[str, Any]:
+    return {"name": name, "passed": passed, "message": message}
+
+
+def _import_main(plugin_dir: Path) -> Any:
+    main_file = plugin_dir / constants.PLUGIN_MAIN_FILE
+    spec = importlib.util.spec_from_file_location(plugin_dir.name, main_file)
+    if not spec or not spec.loader:
+        raise ImportError(f"Cannot create a module spec for {main_file}")
+    module = importlib.util.module_from_spec(spec)
+    # Anything the plugin prints at import time would corrupt the JSON result
+    with contextlib.redirect_stdout(sys.stderr):
+        spec.loader.exec_module(module)
+    return module
+
+
+def validate_plugin(plugin_dir: Path) -> Dict[str, Any]:
+    """
+    Every contract check for the plugin in ``plugin_dir``, in order.
+    Checks that depend on a failed one are skipped. ``traceback`` is set
+    when importing main.py raised.
+    """
+    checks: List[Dict[str, Any]] = []
+    error_traceback = None
+
+    def result() -> Dict[str, Any]:
+        return {
+            "valid": all(check["passed"] for check in checks),
+            "checks": checks,
+            "traceback": error_traceback,
+        }
+
+    main_file = plugin_dir / constants.PLUGIN_MAIN_FILE
+    if not main_file.is_file():
+        checks.append(_check("main_file", False, f"{constants.PLUGIN_MAIN_FILE} not found in {plugin_dir}"))
+        return result()
+    checks.append(_check("main_file", True))
+
+    try:
+        module = _import_main(plugin_dir)
+    except Exception as e:
+        checks.append(_check("import", False, f"{type(e).__name__}: {e}"))
+        error_traceback = traceback.format_exc()
+        return result()
+    checks.append(_check("import", True))
+
+    plugin_class = getattr(module, constants.PLUGIN_CLASS_NAME, None)
+    if not inspect.isclass(plugin_class):
+        checks.append(_check("plugin_class", False, f"main.py defines no '{constants.PLUGIN_CLASS_NAME}' class"))
+        return result()
+    checks.append(_check("plugin_class", True))
+
+    if not issubclass(plugin_class, PluginBase):
+        checks.append(_check("subclasses_plugin_base", False, f"{constants.PLUGIN_CLASS_NAME} does not subclass PluginBase"))
+        return result()
+    checks.append(_check("subclasses_plugin_base", True))
+
+    try:
+        inspect.signature(plugin_class).bind(
+            plugin_dir=plugin_dir,
+            vault_path=plugin_dir.parent.parent,
+            config={},
+        )
+        checks.append(_check("constructor", True))
+    except (TypeError, ValueError) as e:
+        checks.append(_check("constructor", False, f"Plugin(plugin_dir=..., vault_path=..., config=...) would fail: {e}"))
+
+    missing = sorted(getattr(plugin_class, "__abstractmethods__", ()))
+    if missing:
+        checks.append(_check("required_methods", False, f"Missing required methods: {', '.join(missing)}"))
+    else:
+        checks.append(_check("required_methods", True))
+
+    sync_hooks = [
+        hook for hook in ASYNC_HOOKS
+        if getattr(plugin_class, hook, None) is not getattr(PluginBase, hook)
+        and not inspect.iscoroutinefunction(getattr(plugin_class, hook, None))
+    ]
+    if sync_hooks:
+        checks.append(_check("async_hooks", False, f"Must be 'async def': {', '.join(sync_hooks)}"))
+    else:
+        checks.append(_check("async_hooks", True))
+
+    return result()
+
+
+def main(argv: Optional[List[str]] = None) -> int:
+    args = sys.argv[1:] if argv is None else argv
+    if len(args) != 1:
+        print("usage: python -m sidecar.plugin_validator <plugin_dir>", file=sys.stderr)
+        return 2
+    print(json.dumps(validate_plugin(Path(args[0]).resolve())))
+    return 0
+
+
+if __name__ == "__main__":
+    sys.exit(main())
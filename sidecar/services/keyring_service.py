@@ -51,6 +51,13 @@ PROVIDERS = {
     },
 }
 
+# Names keys have been stored under in place of the provider id
+PROVIDER_ALIASES = {
+    "gemini": "google",
+    "claude": "anthropic",
+    "codestral": "mistral",
+}
+
 
 class KeyringService:
     """
@@ -60,6 +67,8 @@ class KeyringService:
     def __init__(self):
         self._logger = logger.bind(component="KeyringService")
         self._fallback_file = None
+        # Env vars set from stored keys, so they can be cleared when a key goes
+        self._env_vars_set: set = set()
         
         if not KEYRING_AVAILABLE:
             self._logger.warning("keyring package not available. Using local file fallback.")
@@ -254,16 +263,66 @@ class KeyringService:
     
     def set_env_vars(self) -> None:
         """
-        Set environment variables for all stored API keys.
+        Set environment variables for all stored API keys, and clear the
+        ones set earlier for keys that have since been deleted.
         """
         import os
         
         # Load from storage (keyring or fallback)
         for provider_id, info in PROVIDERS.items():
+            env_var = info["env_var"]
             api_key = self.get_api_key(provider_id)
             if api_key:
-                os.environ[info["env_var"]] = api_key
-                self._logger.debug(f"Set env var {info['env_var']}")
+                os.environ[env_var] = api_key
+                self._env_vars_set.add(env_var)
+                self._logger.debug(f"Set env var {env_var}")
+            elif env_var in self._env_vars_set:
+                os.environ.pop(env_var, None)
+                self._env_vars_set.discard(env_var)
+                self._logger.debug(f"Cleared env var {env_var}")
+
+    def heal_provider_keys(self) -> List[Dict[str, str]]:
+        """
+        Move keys stored under an alias ("gemini", "claude") to the provider
+        id they are looked up by. A key already present under the provider
+        id wins; the alias entry is left alone. Returns the moves made.
+        """
+        moved = []
+        for alias, provider in PROVIDER_ALIASES.items():
+            key = self._get_alias_key(alias)
+            if not key:
+                continue
+            if self.get_api_key(provider):
+                self._logger.warning(
+                    f"API key stored as '{alias}' ignored; '{provider}' already has a key"
+                )
+                continue
+            if self.store_api_key(provider, key) and self._delete_alias_key(alias):
+                self._logger.info(f"Moved API key stored as '{alias}' to '{provider}'")
+                moved.append({"from": alias, "to": provider})
+        if moved:
+            self.set_env_vars()
+        return moved
+
+    def _get_alias_key(self, alias: str) -> Optional[str]:
+        if KEYRING_AVAILABLE:
+            try:
+                return keyring.get_password(SERVICE_NAME, alias)
+            except KeyringError:
+                return None
+        return self._load_fallback().get(alias)
+
+    def _delete_alias_key(self, alias: str) -> bool:
+        if KEYRING_AVAILABLE:
+            try:
+                keyring.delete_password(SERVICE_NAME, alias)
+                return True
+            except KeyringError as e:
+                self._logger.error(f"Failed to delete API key stored as '{alias}': {e}")
+                return False
+        secrets = self._load_fallback()
+        secrets.pop(alias, None)
+        return self._save_fallback(secrets)
 
 
 # Module-level singleton
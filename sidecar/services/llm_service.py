@@ -7,6 +7,11 @@ Features:
 - Ollama auto-detection
 - Streaming support
 - Automatic API key injection from keyring
+- Retries and per-provider circuit breaking
+- Per-provider usage and rate-limit tracking
+- Embeddings
+- App-wide request budget (see request_budget)
+- Model id validation (see model_ids)
 """
 
 import os
@@ -19,7 +24,7 @@ from dataclasses import dataclass, field
 from loguru import logger
 
 import litellm
-from litellm import acompletion
+from litellm import acompletion, aembedding
 
 try:
     import httpx
@@ -28,6 +33,10 @@ except ImportError:
     HTTPX_AVAILABLE = False
 
 from .keyring_service import get_keyring_service, PROVIDERS
+from .request_budget import get_request_budget
+from .provider_resilience import ProviderResilience, ResilienceSettings, provider_of
+from .usage_tracker import UsageTracker, extract_rate_limits
+from .model_ids import ModelIdCheck, catalog_from_litellm, infer_provider, normalize_model_id
 
 
 @dataclass
@@ -91,9 +100,27 @@ class LLMService:
             "max_tokens": 4096
         })
         
+        # Retry policy and circuit breakers (llm.resilience in .vault.json)
+        try:
+            resilience_settings = ResilienceSettings.from_config(config.get("resilience"))
+        except (TypeError, ValueError) as e:
+            self._logger.warning(f"Invalid resilience settings, using defaults: {e}")
+            resilience_settings = ResilienceSettings()
+        self.resilience = ProviderResilience(resilience_settings)
+
+        # Request/token/cost totals per billing period (llm.usage in .vault.json)
+        usage_config = config.get("usage") or {}
+        try:
+            self.usage = UsageTracker(vault_path, usage_config.get("reset_day", 1))
+        except (TypeError, ValueError) as e:
+            self._logger.warning(f"Invalid usage settings, using defaults: {e}")
+            self.usage = UsageTracker(vault_path)
+        
         # Cached Ollama models
         self._ollama_models: Optional[List[OllamaModel]] = None
         self._ollama_available: Optional[bool] = None
+        # Known model ids per provider, rebuilt when Ollama is re-detected
+        self._model_catalog: Optional[Dict[str, set]] = None
         
 
     
@@ -128,6 +155,7 @@ class LLMService:
         """
         if not force_refresh and self._ollama_models is not None:
             return self._ollama_models
+        self._model_catalog = None
         
         if not HTTPX_AVAILABLE:
             self._ollama_available = False
@@ -236,18 +264,7 @@ class LLMService:
         # Group by provider (heuristic based on model name)
         for model_id in used_models:
             # Simple heuristic for provider
-            if "gpt" in model_id or "text-embedding" in model_id or "whisper" in model_id:
-                provider = "openai"
-            elif "claude" in model_id:
-                provider = "anthropic"
-            elif "gemini" in model_id:
-                provider = "google"
-            elif "mistral" in model_id or "codestral" in model_id:
-                provider = "mistral"
-            elif "llama" in model_id:
-                provider = "groq" # Defaulting Llama to Groq for now
-            else:
-                provider = "unknown"
+            provider = infer_provider(model_id) or "unknown"
 
             # Get specs from LiteLLM data if available
             specs = litellm_data.get(model_id, {})
@@ -327,6 +344,39 @@ class LLMService:
         
         return None
     
+    async def model_catalog(self) -> Dict[str, set]:
+        """
+        Every model id known per provider: LiteLLM's model table, the
+        registry's recommendations and the installed Ollama models.
+        """
+        if self._model_catalog is not None:
+            return self._model_catalog
+        catalog = catalog_from_litellm(await self._fetch_litellm_data())
+        # Only what is actually installed counts for Ollama
+        catalog.pop("ollama", None)
+        for models in (await self.get_available_models()).values():
+            for model in models:
+                catalog.setdefault(model.provider, set()).add(model.id)
+        catalog.pop("unknown", None)
+        self._model_catalog = catalog
+        return catalog
+
+    async def normalize_model_id(self, provider: Optional[str], model: str) -> ModelIdCheck:
+        """
+        Validate ``model`` for ``provider`` and return its canonical id, or
+        close matches when it is not recognised. Unknown providers pass
+        through with a warning.
+        """
+        check = normalize_model_id(
+            provider,
+            model,
+            await self.model_catalog(),
+            known_providers=list(PROVIDERS) + ["ollama"],
+        )
+        if check.warning:
+            self._logger.warning(check.warning)
+        return check
+    
     # =========================================================================
     # LLM Completions
     # =========================================================================
@@ -355,6 +405,12 @@ class LLMService:
 
         
         # Determine model to use
+        if model:
+            # Catch typos in ids passed by plugins before they reach a provider
+            check = await self.normalize_model_id(None, model)
+            if not check.valid:
+                raise ValueError(check.error())
+            model = check.canonical
         model_id = model or self.get_model_for_category(category)
         if not model_id:
             raise ValueError(f"No model configured for category: {category}")
@@ -384,20 +440,27 @@ class LLMService:
     ) -> LLMResponse:
         """Synchronous (non-streaming) completion."""
         try:
-            response = await acompletion(
-                model=model,
-                messages=messages,
-                **params
-            )
+            async with get_request_budget().permit(provider_of(model)):
+                response = await self.resilience.call(
+                    provider_of(model),
+                    lambda: acompletion(
+                        model=model,
+                        messages=messages,
+                        **params
+                    )
+                )
             
+            usage = {
+                "prompt_tokens": response.usage.prompt_tokens,
+                "completion_tokens": response.usage.completion_tokens,
+                "total_tokens": response.usage.total_tokens
+            } if response.usage else {}
+            self._record_usage(model, response, usage)
+
             return LLMResponse(
                 content=response.choices[0].message.content or "",
                 model=model,
-                usage={
-                    "prompt_tokens": response.usage.prompt_tokens,
-                    "completion_tokens": response.usage.completion_tokens,
-                    "total_tokens": response.usage.total_tokens
-                } if response.usage else {},
+                usage=usage,
                 finish_reason=response.choices[0].finish_reason
             )
         except Exception as e:
@@ -411,22 +474,97 @@ class LLMService:
         params: Dict[str, Any]
     ) -> AsyncGenerator[str, None]:
         """Streaming completion - yields tokens as they arrive."""
+        provider = provider_of(model)
         try:
-            response = await acompletion(
-                model=model,
-                messages=messages,
-                stream=True,
-                **params
-            )
-            
-            async for chunk in response:
-                if chunk.choices and chunk.choices[0].delta.content:
-                    yield chunk.choices[0].delta.content
+            # The permit is held until the stream is drained or abandoned
+            async with get_request_budget().permit(provider):
+                # Only opening the stream is retried; tokens already yielded can't be replayed
+                response = await self.resilience.call(
+                    provider,
+                    lambda: acompletion(
+                        model=model,
+                        messages=messages,
+                        stream=True,
+                        **params
+                    )
+                )
+
+                chunks = []
+                try:
+                    async for chunk in response:
+                        chunks.append(chunk)
+                        if chunk.choices and chunk.choices[0].delta.content:
+                            yield chunk.choices[0].delta.content
+                except Exception as e:
+                    self.resilience.record_failure(provider, e)
+                    raise
+                finally:
+                    self._record_stream_usage(model, messages, response, chunks)
                     
         except Exception as e:
             self._logger.error(f"Stream completion failed: {e}")
             raise
     
+    async def embed(self, texts: List[str], model: Optional[str] = None) -> List[List[float]]:
+        """Embedding vectors for ``texts``, using the embedding category's model."""
+        model_id = model or self.get_model_for_category("embedding")
+        if not model_id:
+            raise ValueError("No model configured for category: embedding")
+        litellm_model = self._format_model_for_litellm(model_id)
+
+        async with get_request_budget().permit(provider_of(litellm_model)):
+            response = await self.resilience.call(
+                provider_of(litellm_model),
+                lambda: aembedding(model=litellm_model, input=texts)
+            )
+        usage = getattr(response, "usage", None)
+        self._record_usage(litellm_model, response, {
+            "prompt_tokens": getattr(usage, "prompt_tokens", 0) or 0,
+            "total_tokens": getattr(usage, "total_tokens", 0) or 0,
+        })
+        return [
+            item["embedding"] if isinstance(item, dict) else item.embedding
+            for item in response.data
+        ]
+
+    def _record_usage(
+        self,
+        model: str,
+        response: Any,
+        usage: Dict[str, int],
+        rate_limits: Optional[Dict[str, str]] = None,
+    ) -> None:
+        """Add a finished request to the provider's usage totals."""
+        try:
+            cost = litellm.completion_cost(completion_response=response)
+        except Exception:
+            cost = None  # unpriced (e.g. local) models
+        if rate_limits is None:
+            rate_limits = extract_rate_limits(response)
+        try:
+            self.usage.record(provider_of(model), usage, cost, rate_limits)
+        except Exception as e:
+            self._logger.warning(f"Failed to record usage for {model}: {e}")
+
+    def _record_stream_usage(self, model: str, messages: List[Dict[str, str]], stream: Any, chunks: List[Any]) -> None:
+        """Usage for a stream, rebuilt from its chunks (tokens counted locally if needed)."""
+        usage: Dict[str, int] = {}
+        response = stream
+        try:
+            built = litellm.stream_chunk_builder(chunks, messages=messages) if chunks else None
+            if built is not None:
+                response = built
+                if built.usage:
+                    usage = {
+                        "prompt_tokens": built.usage.prompt_tokens,
+                        "completion_tokens": built.usage.completion_tokens,
+                        "total_tokens": built.usage.total_tokens,
+                    }
+        except Exception as e:
+            self._logger.debug(f"Could not rebuild stream usage for {model}: {e}")
+        # Headers arrive with the stream, not with the rebuilt response
+        self._record_usage(model, response, usage, extract_rate_limits(stream))
+
     def _format_model_for_litellm(self, model_id: str) -> str:
         """
         Format a model ID for LiteLLM.
@@ -438,17 +576,9 @@ class LLMService:
             return model_id
         
         # Heuristic for provider prefixes
-        if "gpt" in model_id or "text-embedding" in model_id or "whisper" in model_id:
-            return f"openai/{model_id}"
-        elif "claude" in model_id:
-            return f"anthropic/{model_id}"
-        elif "gemini" in model_id:
-            return f"google/{model_id}"
-        elif "mistral" in model_id or "codestral" in model_id:
-            return f"mistral/{model_id}"
-        elif "llama" in model_id and "ollama" not in model_id:
-             # Default Llama to Groq unless it's Ollama
-            return f"groq/{model_id}"
+        provider = infer_provider(model_id)
+        if provider:
+            return f"{provider}/{model_id}"
 
         # Check if it's an Ollama model
         if self._ollama_models:
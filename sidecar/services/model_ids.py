@@ -0,0 +1,172 @@
+"""
+Model IDs - Validation and Canonical Form for Provider Model Identifiers
+
+Providers spell model ids differently (``gpt-4o``, ``gemini/gemini-1.5-pro``,
+``llama3.1:8b``) and a typo only surfaces as a "model not found" error at
+request time. ``normalize_model_id`` checks an id against the models known
+for its provider and returns the canonical spelling, or the closest matches
+when it is not recognised.
+
+The canonical form is the bare id when the usual provider heuristic already
+routes it to the right provider (``gpt-4o`` -> openai), and
+``provider/model`` otherwise (``ollama/llama3.1:8b``), so stored ids resolve
+the same way every time they are used.
+"""
+
+import difflib
+from dataclasses import dataclass, field
+from typing import Any, Dict, Iterable, List, Mapping, Optional, Tuple
+
+# LiteLLM's ``litellm_provider`` values, mapped to our provider ids
+LITELLM_PROVIDERS = {
+    "openai": "openai",
+    "text-completion-openai": "openai",
+    "anthropic": "anthropic",
+    "gemini": "google",
+    "vertex_ai-language-models": "google",
+    "mistral": "mistral",
+    "codestral": "mistral",
+    "groq": "groq",
+    "ollama": "ollama",
+}
+# Prefixes accepted in place of the provider id (``gemini/gemini-1.5-pro``)
+PREFIX_ALIASES = {"gemini": "google", "vertex_ai": "google", "codestral": "mistral"}
+# How many close matches to suggest, and how close they must be
+MAX_SUGGESTIONS = 3
+SUGGESTION_CUTOFF = 0.75
+# Tag Ollama assumes when a model is named without one
+OLLAMA_DEFAULT_TAG = "latest"
+
+
+@dataclass
+class ModelIdCheck:
+    """Outcome of checking a model id; ``canonical`` is None when it is invalid."""
+    provider: str
+    model: str
+    canonical: Optional[str] = None
+    suggestions: List[str] = field(default_factory=list)
+    warning: Optional[str] = None
+
+    @property
+    def valid(self) -> bool:
+        return self.canonical is not None
+
+    def error(self) -> Optional[str]:
+        if self.valid:
+            return None
+        message = f"Unknown model '{self.model}' for provider '{self.provider}'"
+        if self.suggestions:
+            quoted = ", ".join(f"'{s}'" for s in self.suggestions)
+            message += f"; did you mean {quoted}?"
+        return message
+
+    def to_dict(self) -> Dict[str, Any]:
+        return {
+            "provider": self.provider,
+            "model": self.model,
+            "canonical": self.canonical,
+            "valid": self.valid,
+            "suggestions": self.suggestions,
+            "warning": self.warning,
+        }
+
+
+def infer_provider(model_id: str) -> Optional[str]:
+    """Provider a bare model id belongs to, by name; None if it can't tell."""
+    if "gpt" in model_id or "text-embedding" in model_id or "whisper" in model_id:
+        return "openai"
+    if "claude" in model_id:
+        return "anthropic"
+    if "gemini" in model_id:
+        return "google"
+    if "mistral" in model_id or "codestral" in model_id:
+        return "mistral"
+    if "llama" in model_id and "ollama" not in model_id:
+        # Default Llama to Groq unless it's Ollama
+        return "groq"
+    return None
+
+
+def split_model_id(model_id: str) -> Tuple[Optional[str], str]:
+    """``("openai", "gpt-4o")`` for ``openai/gpt-4o``; ``(None, id)`` when unprefixed."""
+    if "/" in model_id:
+        prefix, rest = model_id.split("/", 1)
+        return PREFIX_ALIASES.get(prefix, prefix), rest
+    return None, model_id
+
+
+def catalog_from_litellm(model_cost: Mapping[str, Any]) -> Dict[str, set]:
+    """Model ids per provider from LiteLLM's model table."""
+    catalog: Dict[str, set] = {}
+    for key, specs in model_cost.items():
+        provider = LITELLM_PROVIDERS.get((specs or {}).get("litellm_provider", ""))
+        if provider is None:
+            continue
+        _, model = split_model_id(key)
+        catalog.setdefault(provider, set()).add(model)
+    return catalog
+
+
+def canonical_form(provider: str, model: str) -> str:
+    """Bare id if name-based routing already picks ``provider``, else prefixed."""
+    return model if infer_provider(model) == provider else f"{provider}/{model}"
+
+
+def normalize_model_id(
+    provider: Optional[str],
+    model: str,
+    catalog: Mapping[str, Iterable[str]],
+    known_providers: Iterable[str] = (),
+) -> ModelIdCheck:
+    """
+    Check ``model`` against ``catalog`` (model ids per provider).
+
+    ``provider`` may be omitted when the id is prefixed or its provider can
+    be inferred from the name. Ids for providers not in the catalog or
+    ``known_providers`` pass through unchanged with a warning, as do ids
+    for known providers whose model list is unavailable.
+    """
+    model = (model or "").strip()
+    prefix, bare = split_model_id(model)
+    provider = (provider or "").strip().lower() or prefix or infer_provider(bare) or ""
+    if prefix and prefix != provider:
+        # "openai/..." under provider "groq": the prefix is part of the id
+        bare = model
+
+    if not bare:
+        return ModelIdCheck(provider=provider, model=model)
+
+    known = list(catalog.get(provider) or [])
+    if provider not in catalog and provider not in set(known_providers):
+        return ModelIdCheck(
+            provider=provider,
+            model=model,
+            canonical=model,
+            warning=f"Unknown provider '{provider or '?'}'; model id '{model}' was not checked",
+        )
+    if not known:
+        return ModelIdCheck(
+            provider=provider,
+            model=model,
+            canonical=canonical_form(provider, bare),
+            warning=f"No model list available for '{provider}'; model id '{bare}' was not checked",
+        )
+
+    by_lower = {m.lower(): m for m in known}
+    candidates = [bare.lower()]
+    if provider == "ollama" and ":" not in bare:
+        candidates.append(f"{bare.lower()}:{OLLAMA_DEFAULT_TAG}")
+    for candidate in candidates:
+        if candidate in by_lower:
+            return ModelIdCheck(
+                provider=provider,
+                model=model,
+                canonical=canonical_form(provider, by_lower[candidate]),
+            )
+
+    close = difflib.get_close_matches(bare.lower(), list(by_lower), n=MAX_SUGGESTIONS, cutoff=SUGGESTION_CUTOFF)
+    return ModelIdCheck(
+        provider=provider,
+        model=model,
+        suggestions=[canonical_form(provider, by_lower[m]) for m in close],
+    )
@@ -0,0 +1,84 @@
+"""
+Plugin Diagnostics - Support Info for Plugin Authors
+
+Remembers, per plugin, how long loading and activation took and the last
+error it raised (while loading, activating, running a command or handling
+an event), and snapshots a plugin instance's attributes.
+
+The snapshot lists attribute names and types only unless content is asked
+for, since plugin state routinely holds user notes and chat text.
+"""
+
+import time
+import traceback
+from typing import Any, Dict, Optional
+
+from loguru import logger
+
+logger = logger.bind(name=__name__)
+
+# Characters of an attribute's repr kept when content is included
+MAX_VALUE_CHARS = 500
+# Attributes every plugin has, already reported elsewhere in the bundle
+BASE_ATTRIBUTES = {"plugin_dir", "vault_path", "config", "name", "logger"}
+
+
+class PluginDiagnostics:
+    """Init timings and last error per plugin."""
+
+    def __init__(self):
+        self._timings: Dict[str, Dict[str, float]] = {}
+        self._errors: Dict[str, Dict[str, Any]] = {}
+
+    def record_timing(self, plugin: str, phase: str, duration_ms: float) -> None:
+        self._timings.setdefault(plugin, {})[f"{phase}_ms"] = duration_ms
+
+    def record_error(
+        self,
+        plugin: str,
+        where: str,
+        error: Any,
+        tb: Optional[str] = None,
+    ) -> None:
+        """Remember ``error``; ``tb`` defaults to the exception being handled."""
+        if tb is None and isinstance(error, BaseException):
+            tb = "".join(traceback.format_exception(type(error), error, error.__traceback__))
+        self._errors[plugin] = {
+            "where": where,
+            "error": str(error),
+            "traceback": tb,
+            "timestamp": time.time(),
+        }
+
+    def forget(self, plugin: str) -> None:
+        self._timings.pop(plugin, None)
+        self._errors.pop(plugin, None)
+
+    def timings(self, plugin: str) -> Dict[str, float]:
+        return dict(self._timings.get(plugin, {}))
+
+    def last_error(self, plugin: str) -> Optional[Dict[str, Any]]:
+        error = self._errors.get(plugin)
+        return dict(error) if error else None
+
+
+def state_snapshot(instance: Any, include_content: bool = False) -> Dict[str, Any]:
+    """Public attributes of a plugin instance: type (and size) by default,
+    plus a truncated repr when ``include_content``."""
+    snapshot = {}
+    for key, value in sorted(vars(instance).items()):
+        if key.startswith("_") or key in BASE_ATTRIBUTES:
+            continue
+        entry: Dict[str, Any] = {"type": type(value).__name__}
+        try:
+            entry["size"] = len(value)
+        except TypeError:
+            pass
+        if include_content:
+            try:
+                text = repr(value)
+            except Exception as e:
+                text = f"<unrepresentable: {e}>"
+            entry["value"] = text[:MAX_VALUE_CHARS]
+        snapshot[key] = entry
+    return snapshot
@@ -0,0 +1,245 @@
+"""
+Plugin Filesystem Guard - Audit and Restrict Plugin Writes
+
+Uses a Python audit hook (PEP 578) to see every filesystem write made in
+the sidecar process. Writes are attributed to a plugin when one of the
+plugin's own source files is on the call stack, and each plugin's
+accessed paths are recorded for ``plugins.get_fs_access``.
+
+Writes outside the vault are logged as warnings. With ``restrictPluginFs``
+enabled they are blocked: the plugin gets a ``PermissionError``. Paths a
+plugin has been granted (``plugins.<name>.fsGrants`` in ``.vault.json``)
+are always allowed.
+"""
+
+import os
+import sys
+import threading
+import time
+from pathlib import Path
+from typing import Any, Dict, Iterable, List, Optional, Tuple
+
+from loguru import logger
+
+logger = logger.bind(name=__name__)
+
+# Distinct paths remembered per plugin
+MAX_RECORDED_PATHS = 500
+
+_WRITE_FLAGS = os.O_WRONLY | os.O_RDWR | os.O_APPEND | os.O_CREAT | os.O_TRUNC
+
+# Audit events that modify the filesystem, mapped to the argument
+# positions holding affected paths
+_WRITE_EVENTS: Dict[str, Tuple[int, ...]] = {
+    "os.remove": (0,),
+    "os.rename": (0, 1),
+    "os.rmdir": (0,),
+    "os.mkdir": (0,),
+    "os.truncate": (0,),
+    "os.symlink": (1,),
+    "os.link": (1,),
+    "os.chmod": (0,),
+    "os.utime": (0,),
+    "shutil.rmtree": (0,),
+    "shutil.copyfile": (1,),
+    "shutil.copytree": (1,),
+    "shutil.move": (1,),
+}
+
+_active_guard: Optional["PluginFsGuard"] = None
+_hook_installed = False
+_local = threading.local()
+
+
+def _audit_hook(event: str, args: Tuple[Any, ...]) -> None:
+    guard = _active_guard
+    if guard is None or getattr(_local, "busy", False):
+        return
+    if event != "open" and event not in _WRITE_EVENTS:
+        return
+
+    _local.busy = True
+    try:
+        guard.observe(event, args)
+    finally:
+        _local.busy = False
+
+
+def _as_path(value: Any) -> Optional[str]:
+    if isinstance(value, (str, bytes, os.PathLike)):
+        path = os.fsdecode(value)
+        return path or None
+    return None  # file descriptors and the like
+
+
+def _is_write_open(args: Tuple[Any, ...]) -> bool:
+    mode = args[1] if len(args) > 1 else None
+    flags = args[2] if len(args) > 2 else 0
+    if isinstance(mode, str):
+        return any(ch in mode for ch in "wax+")
+    return isinstance(flags, int) and bool(flags & _WRITE_FLAGS)
+
+
+class PluginFsGuard:
+    """Per-vault record of plugin writes, optionally enforcing vault confinement."""
+
+    def __init__(self, vault_path: Path, restrict: bool = False):
+        self.vault_path = Path(vault_path).resolve()
+        self.plugins_dir = self.vault_path / "plugins"
+        # Directories holding one folder per plugin, for attributing writes
+        self.plugin_roots: List[Path] = [self.plugins_dir]
+        self.restrict = restrict
+        self._grants: Dict[str, List[Path]] = {}
+        self._accesses: Dict[str, Dict[str, Dict[str, Any]]] = {}
+        self._plugin_by_file: Dict[str, Optional[str]] = {}
+        self._lock = threading.Lock()
+
+    def install(self) -> None:
+        """Make this the guard consulted by the (process-wide) audit hook."""
+        global _active_guard, _hook_installed
+        _active_guard = self
+        if not _hook_installed:
+            sys.addaudithook(_audit_hook)
+            _hook_installed = True
+
+    def uninstall(self) -> None:
+        global _active_guard
+        if _active_guard is self:
+            _active_guard = None
+
+    def add_plugin_root(self, root: Path) -> None:
+        """Attribute code under ``root/<plugin>/`` to that plugin, too."""
+        root = Path(root).resolve()
+        if root not in self.plugin_roots:
+            self.plugin_roots.append(root)
+            self._plugin_by_file.clear()
+
+    def grant(self, plugin_name: str, paths: Iterable[Any]) -> None:
+        """Allow a plugin to write under `paths` (resolved against the vault)."""
+        resolved = []
+        for raw in paths or []:
+            if not isinstance(raw, str) or not raw.strip():
+                continue
+            path = Path(os.path.expanduser(raw))
+            if not path.is_absolute():
+                path = self.vault_path / path
+            resolved.append(path.resolve())
+        self._grants[plugin_name] = resolved
+
+    def get_access(self, plugin_name: str) -> Dict[str, Any]:
+        with self._lock:
+            accesses = sorted(
+                self._accesses.get(plugin_name, {}).values(),
+                key=lambda a: a["last"],
+                reverse=True,
+            )
+        return {
+            "plugin": plugin_name,
+            "restricted": self.restrict,
+            "granted": [str(p) for p in self._grants.get(plugin_name, [])],
+            "outside_vault": sum(1 for a in accesses if not a["inside_vault"]),
+            "accesses": accesses,
+        }
+
+    def is_allowed(self, plugin_name: str, path: Path) -> bool:
+        if self._within(path, self.vault_path):
+            return True
+        return any(self._within(path, grant) for grant in self._grants.get(plugin_name, []))
+
+    def observe(self, event: str, args: Tuple[Any, ...]) -> None:
+        if event == "open":
+            if not _is_write_open(args):
+                return
+            positions: Tuple[int, ...] = (0,)
+        else:
+            positions = _WRITE_EVENTS[event]
+
+        plugin_name = self._calling_plugin()
+        if plugin_name is None:
+            return
+
+        for position in positions:
+            raw = _as_path(args[position]) if position < len(args) else None
+            if raw is None:
+                continue
+            path = Path(os.path.abspath(raw))
+            try:
+                path = path.resolve()
+            except OSError:
+                pass
+            inside = self._within(path, self.vault_path)
+            allowed = self.is_allowed(plugin_name, path)
+            blocked = self.restrict and not allowed
+            first = self._record(plugin_name, event, path, inside, blocked)
+
+            if not allowed and first:
+                logger.warning(
+                    f"Plugin '{plugin_name}' wrote outside the vault ({event}): {path}"
+                    + (" [blocked]" if blocked else "")
+                )
+            if blocked:
+                raise PermissionError(
+                    f"Plugin '{plugin_name}' may not write outside the vault: {path}"
+                )
+
+    def _record(self, plugin_name: str, event: str, path: Path, inside: bool, blocked: bool) -> bool:
+        """Record an access; True the first time this path is seen for the plugin."""
+        key = str(path)
+        with self._lock:
+            paths = self._accesses.setdefault(plugin_name, {})
+            entry = paths.get(key)
+            if entry is None:
+                if len(paths) >= MAX_RECORDED_PATHS:
+                    return False
+                entry = paths[key] = {
+                    "path": key,
+                    "operations": [],
+                    "inside_vault": inside,
+                    "blocked": 0,
+                    "count": 0,
+                    "first": time.time(),
+                }
+                first = True
+            else:
+                first = False
+            if event not in entry["operations"]:
+                entry["operations"].append(event)
+            entry["count"] += 1
+            entry["blocked"] += int(blocked)
+            entry["last"] = time.time()
+        return first
+
+    def _calling_plugin(self) -> Optional[str]:
+        frame = sys._getframe(2)
+        while frame is not None:
+            filename = frame.f_code.co_filename
+            plugin_name = self._plugin_by_file.get(filename, "")
+            if plugin_name == "":
+                plugin_name = self._plugin_for(filename)
+                self._plugin_by_file[filename] = plugin_name
+            if plugin_name:
+                return plugin_name
+            frame = frame.f_back
+        return None
+
+    def _plugin_for(self, filename: str) -> Optional[str]:
+        try:
+            path = Path(filename).resolve()
+        except OSError:
+            return None
+        for root in self.plugin_roots:
+            try:
+                relative = path.relative_to(root)
+            except ValueError:
+                continue
+            if len(relative.parts) > 1:
+                return relative.parts[0]
+        return None
+
+    @staticmethod
+    def _within(path: Path, root: Path) -> bool:
+        try:
+            path.relative_to(root)
+            return True
+        except ValueError:
+            return False
@@ -0,0 +1,115 @@
+"""
+Provider Keys - Which Providers Need a Key, and Who Needs Them
+
+Cross-references the API keys in storage with the providers the vault
+actually uses: the providers of the models configured for each category
+(used by the core chat pipeline) and the providers plugins declare in
+``plugin.json``::
+
+    {
+        "providers": ["openai"],
+        "model_categories": ["fast"]
+    }
+
+``model_categories`` resolves through the vault's category configuration,
+so a plugin that asks for the "fast" model needs whichever provider serves
+it. Local providers (Ollama) never need a key.
+"""
+
+import json
+from pathlib import Path
+from typing import Any, Callable, Dict, Iterable, List, Mapping, Optional, Set
+
+from .model_ids import infer_provider, split_model_id
+
+# The user of category models outside any plugin
+CORE_USER = "core"
+# Providers that run locally and take no API key
+KEYLESS_PROVIDERS = {"ollama"}
+
+
+def read_plugin_manifest(plugin_dir: Path) -> Dict[str, Any]:
+    """``plugin.json`` of a plugin, or an empty dict."""
+    try:
+        manifest = json.loads((Path(plugin_dir) / "plugin.json").read_text(encoding="utf-8"))
+    except (OSError, json.JSONDecodeError):
+        return {}
+    return manifest if isinstance(manifest, dict) else {}
+
+
+def provider_of_model(model_id: Optional[str]) -> Optional[str]:
+    if not model_id:
+        return None
+    prefix, bare = split_model_id(model_id)
+    return prefix or infer_provider(bare)
+
+
+def plugin_providers(
+    manifest: Mapping[str, Any],
+    model_for_category: Callable[[str], Optional[str]],
+) -> Set[str]:
+    """Providers a plugin declares, directly or through model categories."""
+    providers: Set[str] = set()
+    for provider in manifest.get("providers") or []:
+        if isinstance(provider, str) and provider:
+            providers.add(provider.lower())
+    for category in manifest.get("model_categories") or []:
+        if not isinstance(category, str):
+            continue
+        provider = provider_of_model(model_for_category(category))
+        if provider:
+            providers.add(provider)
+    return providers
+
+
+def key_status(
+    needs: Mapping[str, Iterable[str]],
+    configured: Iterable[str],
+    known_providers: Mapping[str, Mapping[str, Any]],
+    environment: Mapping[str, str],
+) -> List[Dict[str, Any]]:
+    """
+    One entry per provider that has a key or is needed:
+    ``{provider, name, key_present, key_source, used_by, missing}``.
+
+    A key set in the environment (``OPENAI_API_KEY``...) counts as present,
+    since LiteLLM picks it up even when it is not in storage.
+    """
+    configured = set(configured)
+    providers = (set(needs) | configured) - KEYLESS_PROVIDERS
+    entries = []
+    for provider in sorted(providers):
+        info = known_providers.get(provider, {})
+        env_var = info.get("env_var")
+        if provider in configured:
+            source = "keyring"
+        elif env_var and environment.get(env_var):
+            source = "environment"
+        else:
+            source = None
+        used_by = sorted(needs.get(provider, []))
+        entries.append({
+            "provider": provider,
+            "name": info.get("name", provider),
+            "key_present": source is not None,
+            "key_source": source,
+            "used_by": used_by,
+            "missing": source is None and bool(used_by),
+            "supported": provider in known_providers,
+        })
+    return entries
+
+
+def missing_key_messages(entries: Iterable[Mapping[str, Any]]) -> List[str]:
+    """'Plugin X needs an OpenAI key you haven't set' for each gap."""
+    messages = []
+    for entry in entries:
+        if not entry["missing"]:
+            continue
+        users = ", ".join(
+            "Chat" if user == CORE_USER else f"Plugin '{user}'" for user in entry["used_by"]
+        )
+        verb = "needs" if len(entry["used_by"]) == 1 else "need"
+        article = "an" if entry["name"][:1].lower() in "aeiou" else "a"
+        messages.append(f"{users} {verb} {article} {entry['name']} API key you haven't set")
+    return messages
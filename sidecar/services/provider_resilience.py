@@ -0,0 +1,185 @@
+"""
+Provider Resilience - Retries and Circuit Breaking
+
+Wraps provider calls with a retry policy (exponential backoff) and a
+per-provider circuit breaker. After ``failure_threshold`` consecutive
+failures the circuit opens and calls fail fast until ``cooldown_seconds``
+have passed; the next call is then let through as a probe (half-open)
+and closes the circuit again on success.
+
+Configured from ``.vault.json`` under ``llm.resilience``.
+"""
+
+import asyncio
+import time
+from dataclasses import dataclass, asdict, field, fields
+from typing import Any, Awaitable, Callable, Dict, Optional, TypeVar
+
+from loguru import logger
+
+T = TypeVar("T")
+
+CLOSED = "closed"
+OPEN = "open"
+HALF_OPEN = "half_open"
+
+
+class CircuitOpenError(Exception):
+    """Raised instead of calling a provider whose circuit is open."""
+
+    def __init__(self, provider: str, retry_after: float):
+        self.provider = provider
+        self.retry_after = retry_after
+        super().__init__(
+            f"Provider '{provider}' is temporarily disabled after repeated failures; "
+            f"retry in {retry_after:.0f}s or reset the circuit"
+        )
+
+
+@dataclass
+class ResilienceSettings:
+    """Retry and circuit-breaker settings shared by all providers."""
+    retries: int = 2
+    backoff_seconds: float = 1.0
+    backoff_max_seconds: float = 10.0
+    failure_threshold: int = 5
+    cooldown_seconds: float = 60.0
+
+    @classmethod
+    def from_config(cls, config: Optional[Dict[str, Any]]) -> "ResilienceSettings":
+        """Build settings from a config dict, ignoring unknown keys."""
+        known = {f.name for f in fields(cls)}
+        settings = cls(**{k: v for k, v in (config or {}).items() if k in known})
+        settings.validate()
+        return settings
+
+    def validate(self) -> None:
+        if self.retries < 0:
+            raise ValueError("retries must be >= 0")
+        if self.backoff_seconds < 0 or self.backoff_max_seconds < 0:
+            raise ValueError("backoff must be >= 0")
+        if self.failure_threshold < 1:
+            raise ValueError("failure_threshold must be >= 1")
+        if self.cooldown_seconds < 0:
+            raise ValueError("cooldown_seconds must be >= 0")
+
+    def to_dict(self) -> Dict[str, Any]:
+        return asdict(self)
+
+
+@dataclass
+class _Circuit:
+    consecutive_failures: int = 0
+    opened_at: Optional[float] = None
+    probing: bool = False
+    last_error: Optional[str] = None
+    total_failures: int = 0
+    short_circuited: int = 0
+
+
+@dataclass
+class ProviderResilience:
+    """Retry policy plus one circuit breaker per provider."""
+    settings: ResilienceSettings = field(default_factory=ResilienceSettings)
+    _circuits: Dict[str, _Circuit] = field(default_factory=dict)
+
+    def _circuit(self, provider: str) -> _Circuit:
+        return self._circuits.setdefault(provider, _Circuit())
+
+    def _state(self, circuit: _Circuit) -> str:
+        if circuit.opened_at is None:
+            return CLOSED
+        if time.monotonic() - circuit.opened_at >= self.settings.cooldown_seconds:
+            return HALF_OPEN
+        return OPEN
+
+    def get_state(self, provider: str) -> Dict[str, Any]:
+        """Breaker state for a provider, suitable for returning over RPC."""
+        circuit = self._circuit(provider)
+        state = self._state(circuit)
+        retry_after = 0.0
+        if state == OPEN:
+            retry_after = self.settings.cooldown_seconds - (time.monotonic() - circuit.opened_at)
+        return {
+            "provider": provider,
+            "state": state,
+            "consecutive_failures": circuit.consecutive_failures,
+            "failure_threshold": self.settings.failure_threshold,
+            "retry_after_seconds": max(0.0, retry_after),
+            "total_failures": circuit.total_failures,
+            "short_circuited": circuit.short_circuited,
+            "last_error": circuit.last_error,
+        }
+
+    def reset(self, provider: str) -> None:
+        """Manually close a provider's circuit."""
+        self._circuits[provider] = _Circuit()
+        logger.info(f"Circuit for provider '{provider}' reset")
+
+    def before_call(self, provider: str) -> None:
+        """Raise CircuitOpenError if calls to ``provider`` should fail fast."""
+        circuit = self._circuit(provider)
+        state = self._state(circuit)
+        if state == OPEN or (state == HALF_OPEN and circuit.probing):
+            circuit.short_circuited += 1
+            retry_after = self.settings.cooldown_seconds - (time.monotonic() - circuit.opened_at)
+            raise CircuitOpenError(provider, max(0.0, retry_after))
+        if state == HALF_OPEN:
+            circuit.probing = True
+
+    def record_success(self, provider: str) -> None:
+        circuit = self._circuit(provider)
+        if circuit.opened_at is not None:
+            logger.info(f"Circuit for provider '{provider}' closed after successful probe")
+        circuit.consecutive_failures = 0
+        circuit.opened_at = None
+        circuit.probing = False
+
+    def record_failure(self, provider: str, error: Exception) -> None:
+        circuit = self._circuit(provider)
+        circuit.consecutive_failures += 1
+        circuit.total_failures += 1
+        circuit.last_error = str(error)
+        circuit.probing = False
+
+        if circuit.consecutive_failures >= self.settings.failure_threshold:
+            if circuit.opened_at is None:
+                logger.warning(
+                    f"Circuit for provider '{provider}' opened after "
+                    f"{circuit.consecutive_failures} consecutive failures"
+                )
+            # A failed half-open probe restarts the cooldown
+            circuit.opened_at = time.monotonic()
+
+    def backoff(self, attempt: int) -> float:
+        """Delay before retry number ``attempt`` (1-based)."""
+        delay = self.settings.backoff_seconds * (2 ** (attempt - 1))
+        return min(delay, self.settings.backoff_max_seconds)
+
+    async def call(self, provider: str, fn: Callable[[], Awaitable[T]]) -> T:
+        """Run ``fn`` with retries, honouring the provider's circuit."""
+        attempt = 0
+        while True:
+            self.before_call(provider)
+            try:
+                result = await fn()
+            except asyncio.CancelledError:
+                raise
+            except Exception as e:
+                self.record_failure(provider, e)
+                attempt += 1
+                if attempt > self.settings.retries:
+                    raise
+                delay = self.backoff(attempt)
+                logger.warning(
+                    f"Call to '{provider}' failed ({e}); retry {attempt}/{self.settings.retries} in {delay:.1f}s"
+                )
+                await asyncio.sleep(delay)
+            else:
+                self.record_success(provider)
+                return result
+
+
+def provider_of(litellm_model: str) -> str:
+    """Provider name from a LiteLLM ``provider/model`` string."""
+    return litellm_model.split("/", 1)[0] if "/" in litellm_model else "unknown"
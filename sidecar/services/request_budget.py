@@ -0,0 +1,100 @@
+"""
+Request Budget - App-wide Cap on Concurrent Provider Requests
+
+The Rust host owns a single budget shared by every window's sidecar. Before
+calling a provider, a sidecar asks for a permit with a ``REQUEST_PERMIT``
+event and waits until the host grants it through ``budget.grant``; the
+permit is handed back with ``RELEASE_PERMIT`` when the call ends, whether
+it succeeded, failed or was cancelled.
+
+Without a host to coordinate with (``--request-budget`` not passed) permits
+are granted immediately.
+
+Plugins that call providers themselves should do so inside a permit::
+
+    from sidecar.services.request_budget import get_request_budget
+
+    async with get_request_budget().permit("openai"):
+        ...
+"""
+
+import asyncio
+import contextlib
+import uuid
+from typing import Any, AsyncIterator, Callable, Dict, Optional
+
+from loguru import logger
+
+from .. import constants
+
+logger = logger.bind(name=__name__)
+
+# How long to wait for the host before calling the provider anyway, so an
+# unresponsive host cannot stall every request
+ACQUIRE_TIMEOUT = 300.0
+
+Sender = Callable[[str, Dict[str, Any]], None]
+
+
+class RequestBudget:
+    """Permits for provider requests, granted by the Rust host."""
+
+    def __init__(self, send: Optional[Sender] = None, acquire_timeout: float = ACQUIRE_TIMEOUT):
+        self._send = send
+        self.acquire_timeout = acquire_timeout
+        self._waiting: Dict[str, asyncio.Future] = {}
+
+    @property
+    def enabled(self) -> bool:
+        return self._send is not None
+
+    @contextlib.asynccontextmanager
+    async def permit(self, provider: str = "") -> AsyncIterator[None]:
+        """Hold one permit for the duration of the block."""
+        if self._send is None:
+            yield
+            return
+
+        lease_id = uuid.uuid4().hex
+        granted = asyncio.get_running_loop().create_future()
+        self._waiting[lease_id] = granted
+        try:
+            self._send(constants.EventType.REQUEST_PERMIT, {"lease_id": lease_id, "provider": provider})
+            try:
+                await asyncio.wait_for(asyncio.shield(granted), self.acquire_timeout)
+            except asyncio.TimeoutError:
+                logger.warning(
+                    f"No request permit for {provider or 'provider'} after "
+                    f"{self.acquire_timeout:.0f}s; proceeding without one"
+                )
+            yield
+        finally:
+            self._waiting.pop(lease_id, None)
+            self._send(constants.EventType.RELEASE_PERMIT, {"lease_id": lease_id})
+
+    def grant(self, lease_id: str) -> bool:
+        """Wake the request waiting on ``lease_id``; False if none is."""
+        granted = self._waiting.get(lease_id)
+        if granted is None or granted.done():
+            return False
+        granted.set_result(True)
+        return True
+
+    @property
+    def waiting(self) -> int:
+        return sum(1 for future in self._waiting.values() if not future.done())
+
+
+_request_budget = RequestBudget()
+
+
+def get_request_budget() -> RequestBudget:
+    """The sidecar's request budget client."""
+    return _request_budget
+
+
+def configure_request_budget(send: Optional[Sender]) -> RequestBudget:
+    """Coordinate permits through ``send``, or grant them locally if None."""
+    global _request_budget
+    _request_budget = RequestBudget(send)
+    return _request_budget
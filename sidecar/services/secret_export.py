@@ -0,0 +1,228 @@
+"""
+Secret Export - Move Stored API Keys Between Machines
+
+Exports the keys held by ``KeyringService`` in the platform's own secret
+format where one exists, so they can be managed with the OS tooling:
+
+- macOS: a standalone, password-protected keychain file (``.keychain-db``)
+  that Keychain Access can open or merge.
+
+Windows Credential Manager and the Linux Secret Service have no file
+export format, so on those platforms (or when asked) keys are written as
+an encrypted blob: AES-256-GCM with a key derived from the passphrase via
+scrypt. Plaintext never touches the disk in either format.
+"""
+
+import base64
+import json
+import os
+import shutil
+import subprocess
+import sys
+from pathlib import Path
+from typing import Any, Dict, Optional
+
+from loguru import logger
+
+from .keyring_service import KeyringService, PROVIDERS, SERVICE_NAME
+
+logger = logger.bind(name=__name__)
+
+FORMAT_AUTO = "auto"
+FORMAT_MACOS_KEYCHAIN = "macos-keychain"
+FORMAT_ENCRYPTED_BLOB = "encrypted-blob"
+
+BLOB_MAGIC = "tailor-secrets"
+BLOB_VERSION = 1
+MIN_PASSPHRASE_LEN = 8
+
+# scrypt cost parameters: ~64 MiB, well under a second on current hardware
+_SCRYPT_N = 2 ** 16
+_SCRYPT_R = 8
+_SCRYPT_P = 1
+
+
+class SecretExportError(Exception):
+    """Raised when secrets cannot be exported or imported."""
+
+
+def native_format() -> Optional[str]:
+    """The OS-native export format available here, if any."""
+    if sys.platform == "darwin" and shutil.which("security"):
+        return FORMAT_MACOS_KEYCHAIN
+    return None
+
+
+def export_secrets(
+    keyring_service: KeyringService,
+    path: Path,
+    passphrase: str,
+    fmt: str = FORMAT_AUTO,
+) -> Dict[str, Any]:
+    """Write every stored API key to ``path`` protected by ``passphrase``."""
+    _check_passphrase(passphrase)
+    path = Path(path)
+    if path.exists():
+        raise SecretExportError(f"Refusing to overwrite existing file: {path}")
+
+    secrets = {
+        provider: key
+        for provider in keyring_service.list_configured_providers()
+        if (key := keyring_service.get_api_key(provider))
+    }
+
+    note = None
+    if fmt == FORMAT_AUTO:
+        fmt = native_format() or FORMAT_ENCRYPTED_BLOB
+        if fmt == FORMAT_ENCRYPTED_BLOB:
+            note = (
+                f"{sys.platform} has no native secret export format; "
+                "wrote an encrypted blob instead"
+            )
+    elif fmt == FORMAT_MACOS_KEYCHAIN and native_format() != fmt:
+        raise SecretExportError("macOS keychain export is only available on macOS")
+
+    if fmt == FORMAT_MACOS_KEYCHAIN:
+        _export_keychain(path, passphrase, secrets)
+    elif fmt == FORMAT_ENCRYPTED_BLOB:
+        path.parent.mkdir(parents=True, exist_ok=True)
+        path.write_text(json.dumps(encrypt_blob(secrets, passphrase), indent=2))
+    else:
+        raise SecretExportError(f"Unknown export format: {fmt}")
+
+    logger.info(f"Exported {len(secrets)} API key(s) as {fmt}")
+    return {
+        "format": fmt,
+        "path": str(path),
+        "providers": sorted(secrets),
+        "note": note,
+    }
+
+
+def import_secrets(keyring_service: KeyringService, path: Path, passphrase: str) -> Dict[str, Any]:
+    """Store the keys from an export made by ``export_secrets``."""
+    path = Path(path)
+    if not path.is_file():
+        raise SecretExportError(f"Export file not found: {path}")
+
+    blob = _read_blob(path)
+    if blob is not None:
+        fmt = FORMAT_ENCRYPTED_BLOB
+        secrets = decrypt_blob(blob, passphrase)
+    elif native_format() == FORMAT_MACOS_KEYCHAIN:
+        fmt = FORMAT_MACOS_KEYCHAIN
+        secrets = _import_keychain(path, passphrase)
+    else:
+        raise SecretExportError("Unrecognised export file")
+
+    imported, skipped = [], []
+    for provider, key in secrets.items():
+        if provider in PROVIDERS and keyring_service.store_api_key(provider, key):
+            imported.append(provider)
+        else:
+            skipped.append(provider)
+
+    logger.info(f"Imported {len(imported)} API key(s) from {fmt}")
+    return {"format": fmt, "imported": sorted(imported), "skipped": sorted(skipped)}
+
+
+def encrypt_blob(secrets: Dict[str, str], passphrase: str) -> Dict[str, Any]:
+    from cryptography.hazmat.primitives.ciphers.aead import AESGCM
+
+    salt = os.urandom(16)
+    nonce = os.urandom(12)
+    key = _derive_key(passphrase, salt, _SCRYPT_N, _SCRYPT_R, _SCRYPT_P)
+    ciphertext = AESGCM(key).encrypt(nonce, json.dumps(secrets).encode(), BLOB_MAGIC.encode())
+    return {
+        "format": BLOB_MAGIC,
+        "version": BLOB_VERSION,
+        "kdf": {"name": "scrypt", "n": _SCRYPT_N, "r": _SCRYPT_R, "p": _SCRYPT_P,
+                "salt": _b64(salt)},
+        "nonce": _b64(nonce),
+        "ciphertext": _b64(ciphertext),
+    }
+
+
+def decrypt_blob(blob: Dict[str, Any], passphrase: str) -> Dict[str, str]:
+    from cryptography.exceptions import InvalidTag
+    from cryptography.hazmat.primitives.ciphers.aead import AESGCM
+
+    if blob.get("version") != BLOB_VERSION:
+        raise SecretExportError(f"Unsupported export version: {blob.get('version')}")
+    try:
+        kdf = blob["kdf"]
+        key = _derive_key(passphrase, base64.b64decode(kdf["salt"]), kdf["n"], kdf["r"], kdf["p"])
+        plaintext = AESGCM(key).decrypt(
+            base64.b64decode(blob["nonce"]),
+            base64.b64decode(blob["ciphertext"]),
+            BLOB_MAGIC.encode(),
+        )
+    except InvalidTag:
+        raise SecretExportError("Wrong passphrase or corrupted export")
+    except (KeyError, TypeError, ValueError) as e:
+        raise SecretExportError(f"Malformed export file: {e}")
+    return json.loads(plaintext)
+
+
+def _derive_key(passphrase: str, salt: bytes, n: int, r: int, p: int) -> bytes:
+    import hashlib
+    return hashlib.scrypt(
+        passphrase.encode(), salt=salt, n=n, r=r, p=p, maxmem=128 * n * r * 2, dklen=32
+    )
+
+
+def _check_passphrase(passphrase: str) -> None:
+    if len(passphrase or "") < MIN_PASSPHRASE_LEN:
+        raise SecretExportError(f"Passphrase must be at least {MIN_PASSPHRASE_LEN} characters")
+
+
+def _read_blob(path: Path) -> Optional[Dict[str, Any]]:
+    try:
+        data = json.loads(path.read_text())
+    except (UnicodeDecodeError, ValueError):
+        return None
+    return data if isinstance(data, dict) and data.get("format") == BLOB_MAGIC else None
+
+
+def _b64(data: bytes) -> str:
+    return base64.b64encode(data).decode()
+
+
+def _security(*args: str) -> str:
+    result = subprocess.run(["security", *args], capture_output=True, text=True)
+    if result.returncode != 0:
+        raise SecretExportError(f"security {args[0]} failed: {result.stderr.strip()}")
+    return result.stdout
+
+
+def _export_keychain(path: Path, passphrase: str, secrets: Dict[str, str]) -> None:
+    if path.suffix != ".keychain-db":
+        raise SecretExportError("macOS keychain exports must end in .keychain-db")
+    path.parent.mkdir(parents=True, exist_ok=True)
+    _security("create-keychain", "-p", passphrase, str(path))
+    try:
+        for provider, key in secrets.items():
+            # The keychain file is encrypted; the value only passes through argv
+            _security("add-generic-password", "-s", SERVICE_NAME, "-a", provider,
+                      "-w", key, str(path))
+    except SecretExportError:
+        path.unlink(missing_ok=True)
+        raise
+    finally:
+        subprocess.run(["security", "lock-keychain", str(path)], capture_output=True)
+
+
+def _import_keychain(path: Path, passphrase: str) -> Dict[str, str]:
+    _security("unlock-keychain", "-p", passphrase, str(path))
+    secrets = {}
+    try:
+        for provider in PROVIDERS:
+            try:
+                secrets[provider] = _security(
+                    "find-generic-password", "-s", SERVICE_NAME, "-a", provider, "-w", str(path)
+                ).rstrip("\n")
+            except SecretExportError:
+                continue  # not in this export
+    finally:
+        subprocess.run(["security", "lock-keychain", str(path)], capture_output=True)
+    return secrets
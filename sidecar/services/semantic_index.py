@@ -0,0 +1,193 @@
+"""
+Semantic Index - Embedding Vectors for Conversation Search
+
+Embeds every conversation message and keeps the vectors under
+``.tailor/embeddings/``: one file per conversation plus ``index.json``
+recording the embedding model and a hash of each indexed message.
+
+Building is incremental: messages whose hash is unchanged keep their
+vector, so only new or edited messages are embedded. Each conversation is
+written as soon as it is done, so an interrupted build resumes where it
+stopped. Switching the embedding model discards the old vectors, since
+vectors from different models are not comparable.
+"""
+
+import hashlib
+import json
+import math
+import os
+from pathlib import Path
+from typing import Any, Awaitable, Callable, Dict, List
+
+from loguru import logger
+
+logger = logger.bind(name=__name__)
+
+EMBEDDINGS_DIR = "embeddings"
+INDEX_FILE = "index.json"
+CONVERSATIONS_DIR = "conversations"
+# Messages sent to the embedding provider per request
+EMBED_BATCH_SIZE = 64
+# Characters of a message kept in search results
+SNIPPET_CHARS = 200
+
+Embedder = Callable[[List[str]], Awaitable[List[List[float]]]]
+
+
+class SemanticIndexError(Exception):
+    """Raised when the index cannot be built or searched."""
+
+
+def _hash(text: str) -> str:
+    return hashlib.sha256(text.encode("utf-8")).hexdigest()[:16]
+
+
+def _cosine(a: List[float], b: List[float]) -> float:
+    dot = sum(x * y for x, y in zip(a, b))
+    norm = math.sqrt(sum(x * x for x in a)) * math.sqrt(sum(y * y for y in b))
+    return dot / norm if norm else 0.0
+
+
+def _write_json(path: Path, data: Any) -> None:
+    path.parent.mkdir(parents=True, exist_ok=True)
+    tmp = path.with_suffix(".json.tmp")
+    tmp.write_text(json.dumps(data), encoding="utf-8")
+    os.replace(tmp, path)
+
+
+class SemanticIndex:
+    """Per-vault store of message embeddings."""
+
+    def __init__(self, vault_path: Path):
+        self.vault_path = Path(vault_path)
+        self.dir = self.vault_path / ".tailor" / EMBEDDINGS_DIR
+        self.index_path = self.dir / INDEX_FILE
+
+    def _load_index(self) -> Dict[str, Any]:
+        try:
+            data = json.loads(self.index_path.read_text(encoding="utf-8"))
+            if isinstance(data, dict) and isinstance(data.get("conversations"), dict):
+                return data
+        except FileNotFoundError:
+            pass
+        except Exception as e:
+            logger.warning(f"Rebuilding unreadable semantic index: {e}")
+        return {"model": None, "conversations": {}}
+
+    def _vectors_path(self, conversation_id: str) -> Path:
+        return self.dir / f"{conversation_id}.json"
+
+    def _load_vectors(self, conversation_id: str) -> List[Dict[str, Any]]:
+        try:
+            return json.loads(self._vectors_path(conversation_id).read_text(encoding="utf-8"))
+        except (OSError, ValueError):
+            return []
+
+    def _conversations(self) -> Dict[str, List[Dict[str, Any]]]:
+        folder = self.vault_path / CONVERSATIONS_DIR
+        conversations = {}
+        if not folder.is_dir():
+            return conversations
+        for path in sorted(folder.glob("*.json")):
+            try:
+                data = json.loads(path.read_text(encoding="utf-8"))
+            except (OSError, ValueError):
+                continue
+            messages = data.get("messages") if isinstance(data, dict) else None
+            if isinstance(messages, list):
+                conversations[path.stem] = messages
+        return conversations
+
+    async def build(self, model: str, embed: Embedder) -> Dict[str, Any]:
+        """Embed new and changed messages; returns build counts."""
+        index = self._load_index()
+        if index.get("model") != model:
+            if index.get("model"):
+                logger.info(f"Embedding model changed to {model}; re-embedding everything")
+            for conversation_id in index["conversations"]:
+                self._vectors_path(conversation_id).unlink(missing_ok=True)
+            index = {"model": model, "conversations": {}}
+
+        conversations = self._conversations()
+        stats = {"conversations": len(conversations), "embedded": 0, "reused": 0, "removed": 0}
+
+        for conversation_id in list(index["conversations"]):
+            if conversation_id not in conversations:
+                self._vectors_path(conversation_id).unlink(missing_ok=True)
+                del index["conversations"][conversation_id]
+                stats["removed"] += 1
+
+        for conversation_id, messages in conversations.items():
+            indexed = conversation_id in index["conversations"]
+            stored = self._load_vectors(conversation_id) if indexed else []
+            known = {entry["hash"]: entry["vector"] for entry in stored}
+
+            entries, pending = [], []
+            for position, message in enumerate(messages):
+                content = message.get("content") if isinstance(message, dict) else None
+                if not isinstance(content, str) or not content.strip():
+                    continue
+                digest = _hash(content)
+                entry = {
+                    "index": position,
+                    "role": message.get("role"),
+                    "hash": digest,
+                    "snippet": content[:SNIPPET_CHARS],
+                    "vector": known.get(digest),
+                }
+                entries.append(entry)
+                if entry["vector"] is None:
+                    pending.append(entry)
+
+            for start in range(0, len(pending), EMBED_BATCH_SIZE):
+                batch = pending[start:start + EMBED_BATCH_SIZE]
+                texts = [messages[e["index"]]["content"] for e in batch]
+                vectors = await embed(texts)
+                if len(vectors) != len(batch):
+                    raise SemanticIndexError("Embedding provider returned the wrong number of vectors")
+                for entry, vector in zip(batch, vectors):
+                    entry["vector"] = vector
+
+            stats["embedded"] += len(pending)
+            stats["reused"] += len(entries) - len(pending)
+            if pending or not indexed or [e["hash"] for e in stored] != [e["hash"] for e in entries]:
+                _write_json(self._vectors_path(conversation_id), entries)
+                index["conversations"][conversation_id] = {"messages": len(entries)}
+                # Saved per conversation so an interrupted build resumes here
+                _write_json(self.index_path, index)
+
+        _write_json(self.index_path, index)
+        return stats
+
+    def search(self, model: str, query_vector: List[float], top_k: int = 10) -> Dict[str, Any]:
+        """Nearest messages to ``query_vector`` and their best conversations."""
+        index = self._load_index()
+        if not index["conversations"]:
+            raise SemanticIndexError("The semantic index is empty; build it first")
+        if index.get("model") != model:
+            raise SemanticIndexError(
+                f"The semantic index was built with {index.get('model')}; rebuild it for {model}"
+            )
+
+        scored = []
+        for conversation_id in index["conversations"]:
+            for entry in self._load_vectors(conversation_id):
+                if entry.get("vector"):
+                    scored.append({
+                        "conversation_id": conversation_id,
+                        "message_index": entry["index"],
+                        "role": entry.get("role"),
+                        "snippet": entry.get("snippet", ""),
+                        "score": round(_cosine(query_vector, entry["vector"]), 6),
+                    })
+        scored.sort(key=lambda m: m["score"], reverse=True)
+        messages = scored[:max(1, top_k)]
+
+        best: Dict[str, Dict[str, Any]] = {}
+        for match in messages:
+            best.setdefault(match["conversation_id"], {
+                "conversation_id": match["conversation_id"],
+                "score": match["score"],
+                "message_index": match["message_index"],
+            })
+        return {"messages": messages, "conversations": list(best.values())}
@@ -0,0 +1,66 @@
+"""
+Streaming - Incremental Output for Host-Requested Streams
+
+A request sent by ``stream_from_sidecar`` carries a ``stream_channel``
+param. The WebSocket server strips it and runs the command inside that
+channel; anything the command passes to ``emit_chunk`` goes to the host as
+a ``STREAM_CHUNK`` event, which the host re-emits to the frontend as
+``sidecar://stream/{channel_id}``. When the command returns, a final chunk
+(``final: true``) carries its result, ending the stream.
+
+Outside a stream ``emit_chunk`` does nothing, so commands can stream
+unconditionally::
+
+    from sidecar.services.streaming import emit_chunk
+
+    for token in tokens:
+        emit_chunk({"token": token})
+"""
+
+import contextlib
+import contextvars
+from typing import Any, Callable, Dict, Iterator, Optional
+
+from .. import constants
+
+# Param naming the channel a request streams to
+STREAM_CHANNEL_PARAM = "stream_channel"
+
+Sender = Callable[[str, Dict[str, Any]], None]
+
+_channel: contextvars.ContextVar[Optional[str]] = contextvars.ContextVar("stream_channel", default=None)
+_send: Optional[Sender] = None
+
+
+def configure_stream_sender(send: Optional[Sender]) -> None:
+    """Deliver chunks through ``send`` (the host event sender)."""
+    global _send
+    _send = send
+
+
+def current_channel() -> Optional[str]:
+    """Channel of the request being handled, if it is streamed."""
+    return _channel.get()
+
+
+@contextlib.contextmanager
+def stream_channel(channel_id: Optional[str]) -> Iterator[None]:
+    """Run the enclosed command with its chunks going to ``channel_id``."""
+    token = _channel.set(channel_id)
+    try:
+        yield
+    finally:
+        _channel.reset(token)
+
+
+def emit_chunk(data: Any, final: bool = False) -> bool:
+    """Send one chunk on the current channel; False when not streaming."""
+    channel_id = _channel.get()
+    if channel_id is None or _send is None:
+        return False
+    _send(constants.EventType.STREAM_CHUNK, {
+        "channel_id": channel_id,
+        "data": data,
+        "final": final,
+    })
+    return True
@@ -0,0 +1,143 @@
+"""
+Usage Tracker - Per-Provider Request, Token and Cost Totals
+
+Counts requests, tokens and estimated cost per provider for the current
+billing period and remembers the latest rate-limit headers each provider
+returned. Totals are persisted to ``.tailor/usage.json`` so they survive
+restarts, and reset when a new period starts.
+
+The period starts on ``llm.usage.reset_day`` (day of the month, default 1)
+in ``.vault.json``.
+"""
+
+import json
+import os
+import threading
+from datetime import datetime, timezone
+from pathlib import Path
+from typing import Any, Dict, Optional
+
+from loguru import logger
+
+logger = logger.bind(name=__name__)
+
+USAGE_FILE = "usage.json"
+# Header prefixes providers use for rate-limit information
+_RATE_LIMIT_PREFIXES = ("x-ratelimit-", "anthropic-ratelimit-", "retry-after")
+# LiteLLM prefixes raw provider headers with this
+_LITELLM_HEADER_PREFIX = "llm_provider-"
+
+
+def period_start(now: datetime, reset_day: int) -> datetime:
+    """Start of the billing period containing ``now``."""
+    day = min(reset_day, 28)
+    start = now.replace(day=day, hour=0, minute=0, second=0, microsecond=0)
+    if start > now:
+        year, month = (now.year, now.month - 1) if now.month > 1 else (now.year - 1, 12)
+        start = start.replace(year=year, month=month)
+    return start
+
+
+def next_period_start(start: datetime) -> datetime:
+    year, month = (start.year, start.month + 1) if start.month < 12 else (start.year + 1, 1)
+    return start.replace(year=year, month=month)
+
+
+def extract_rate_limits(response: Any) -> Dict[str, str]:
+    """Rate-limit headers from a LiteLLM response (or stream), if it kept any."""
+    hidden = getattr(response, "_hidden_params", None) or {}
+    headers = hidden.get("additional_headers") or getattr(response, "_response_headers", None) or {}
+    limits = {}
+    for key, value in dict(headers).items():
+        name = str(key).lower()
+        if name.startswith(_LITELLM_HEADER_PREFIX):
+            name = name[len(_LITELLM_HEADER_PREFIX):]
+        if name.startswith(_RATE_LIMIT_PREFIXES):
+            limits[name] = str(value)
+    return limits
+
+
+class UsageTracker:
+    """Persisted per-provider usage for the current billing period."""
+
+    def __init__(self, vault_path: Path, reset_day: int = 1):
+        self.path = Path(vault_path) / ".tailor" / USAGE_FILE
+        self.reset_day = max(1, min(int(reset_day), 28))
+        self._lock = threading.Lock()
+        self._data = self._load()
+
+    def record(
+        self,
+        provider: str,
+        usage: Optional[Dict[str, int]] = None,
+        cost: Optional[float] = None,
+        rate_limits: Optional[Dict[str, str]] = None,
+    ) -> None:
+        usage = usage or {}
+        with self._lock:
+            self._roll_period()
+            entry = self._data["providers"].setdefault(provider, {
+                "requests": 0,
+                "prompt_tokens": 0,
+                "completion_tokens": 0,
+                "tokens": 0,
+                "estimated_cost": 0.0,
+                "rate_limits": {},
+            })
+            entry["requests"] += 1
+            entry["prompt_tokens"] += int(usage.get("prompt_tokens") or 0)
+            entry["completion_tokens"] += int(usage.get("completion_tokens") or 0)
+            entry["tokens"] += int(usage.get("total_tokens") or 0)
+            if cost:
+                entry["estimated_cost"] = round(entry["estimated_cost"] + cost, 6)
+            if rate_limits:
+                entry["rate_limits"] = rate_limits
+            entry["last_request"] = _now().isoformat()
+            self._save()
+
+    def snapshot(self) -> Dict[str, Any]:
+        with self._lock:
+            if self._roll_period():
+                self._save()
+            start = datetime.fromisoformat(self._data["period_start"])
+            window_reset = next_period_start(start).isoformat()
+            return {
+                "period_start": self._data["period_start"],
+                "window_reset": window_reset,
+                "providers": {
+                    provider: {**entry, "window_reset": window_reset}
+                    for provider, entry in self._data["providers"].items()
+                },
+            }
+
+    def _roll_period(self) -> bool:
+        """Start a fresh period if the stored one has ended."""
+        current = period_start(_now(), self.reset_day).isoformat()
+        if self._data.get("period_start") == current:
+            return False
+        self._data = {"period_start": current, "providers": {}}
+        return True
+
+    def _load(self) -> Dict[str, Any]:
+        try:
+            data = json.loads(self.path.read_text(encoding="utf-8"))
+            if isinstance(data, dict) and isinstance(data.get("providers"), dict):
+                return data
+        except FileNotFoundError:
+            pass
+        except Exception as e:
+            logger.warning(f"Ignoring unreadable usage file {self.path}: {e}")
+        return {"period_start": period_start(_now(), self.reset_day).isoformat(), "providers": {}}
+
+    def _save(self) -> None:
+        try:
+            self.path.parent.mkdir(parents=True, exist_ok=True)
+            tmp = self.path.with_suffix(".json.tmp")
+            tmp.write_text(json.dumps(self._data, indent=2), encoding="utf-8")
+            os.replace(tmp, self.path)
+        except Exception as e:
+            logger.warning(f"Failed to save provider usage: {e}")
+
+
+def _now() -> datetime:
+    return datetime.now(timezone.utc)
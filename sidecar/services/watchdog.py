@@ -0,0 +1,111 @@
+"""
+Loop Watchdog - Evidence for Diagnosing a Hung Sidecar
+
+When a plugin blocks the event loop, the sidecar can no longer answer any
+request, including questions about what went wrong. The watchdog keeps the
+evidence outside the loop instead:
+
+- a heartbeat task stamps the time on every loop iteration it gets;
+- plugin callbacks and commands are tracked while they run;
+- a daemon thread (which keeps running while the loop is blocked) writes
+  both to ``.tailor/sidecar-watchdog.json`` every second.
+
+The Rust side reads that file when pings stop being answered, to tell a
+blocked loop from a dead process and name the likely culprit plugin.
+"""
+
+import asyncio
+import itertools
+import json
+import os
+import threading
+import time
+from contextlib import contextmanager
+from pathlib import Path
+from typing import Any, Dict, Iterator, Optional
+
+from loguru import logger
+
+logger = logger.bind(name=__name__)
+
+WATCHDOG_FILE = "sidecar-watchdog.json"
+HEARTBEAT_INTERVAL = 0.5
+WRITE_INTERVAL = 1.0
+
+
+class LoopWatchdog:
+    """Tracks loop liveness and running plugin callbacks for a sidecar."""
+
+    def __init__(self, vault_path: Path):
+        self.path = Path(vault_path) / ".tailor" / WATCHDOG_FILE
+        self.last_tick = time.monotonic()
+        self._active: Dict[int, Dict[str, Any]] = {}
+        self._last_entered: Optional[Dict[str, Any]] = None
+        self._tokens = itertools.count(1)
+        self._lock = threading.Lock()
+        self._stop = threading.Event()
+        self._thread: Optional[threading.Thread] = None
+
+    @contextmanager
+    def track(self, plugin: Optional[str], callback: str) -> Iterator[None]:
+        """Record that ``plugin`` is running ``callback`` for the duration."""
+        if not plugin:
+            yield
+            return
+        token = next(self._tokens)
+        entry = {"plugin": plugin, "callback": callback, "since": time.time()}
+        with self._lock:
+            self._active[token] = entry
+            self._last_entered = entry
+        try:
+            yield
+        finally:
+            with self._lock:
+                self._active.pop(token, None)
+
+    async def heartbeat(self) -> None:
+        """Stamp the loop as alive; runs for the sidecar's lifetime."""
+        while not self._stop.is_set():
+            self.last_tick = time.monotonic()
+            await asyncio.sleep(HEARTBEAT_INTERVAL)
+
+    def start(self) -> None:
+        if self._thread is None:
+            self._thread = threading.Thread(target=self._writer, name="loop-watchdog", daemon=True)
+            self._thread.start()
+
+    def stop(self) -> None:
+        self._stop.set()
+
+    def snapshot(self) -> Dict[str, Any]:
+        now = time.time()
+        with self._lock:
+            active = sorted(self._active.values(), key=lambda e: e["since"])
+            last = dict(self._last_entered) if self._last_entered else None
+        return {
+            "pid": os.getpid(),
+            "written_at": now,
+            "loop_lag_seconds": round(time.monotonic() - self.last_tick, 3),
+            "active_callbacks": [
+                {**entry, "running_for_seconds": round(now - entry["since"], 3)}
+                for entry in active
+            ],
+            "last_entered": last,
+        }
+
+    def _writer(self) -> None:
+        while not self._stop.wait(WRITE_INTERVAL):
+            try:
+                self.path.parent.mkdir(parents=True, exist_ok=True)
+                tmp = self.path.with_suffix(".json.tmp")
+                tmp.write_text(json.dumps(self.snapshot()), encoding="utf-8")
+                os.replace(tmp, self.path)
+            except Exception as e:
+                logger.debug(f"Watchdog write failed: {e}")
+
+
+def plugin_of_handler(handler: Any) -> Optional[str]:
+    """Name of the plugin a bound handler belongs to, if it is a plugin method."""
+    owner = getattr(handler, "__self__", None)
+    name = getattr(owner, "name", None)
+    return name if isinstance(name, str) else None
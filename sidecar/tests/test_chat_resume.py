@@ -0,0 +1,49 @@
+import pytest
+from unittest.mock import AsyncMock, MagicMock
+
+from sidecar.vault_brain import VaultBrain
+
+
+def _brain():
+    brain = MagicMock()
+    brain._stream_chat_response = AsyncMock(return_value={"status": "success"})
+    return brain
+
+
+@pytest.mark.asyncio
+async def test_resume_streams_continuation_of_partial():
+    brain = _brain()
+    history = [{"role": "user", "content": "hi"}, {"role": "assistant", "content": "hello"}]
+
+    result = await VaultBrain.chat_resume(
+        brain,
+        chat_id="chat_1",
+        message_index=3,
+        prompt="tell me a story",
+        partial="Once upon",
+        history=history,
+    )
+
+    assert result["status"] == "success"
+    kwargs = brain._stream_chat_response.await_args.kwargs
+    assert kwargs["chat_id"] == "chat_1"
+    assert kwargs["history"][-2:] == [
+        {"role": "user", "content": "tell me a story"},
+        {"role": "assistant", "content": "Once upon"},
+    ]
+    assert kwargs["resume"] == {
+        "message_index": 3,
+        "partial": "Once upon",
+        "prompt": "tell me a story",
+        "history": history,
+    }
+
+
+@pytest.mark.asyncio
+async def test_resume_requires_target_message():
+    brain = _brain()
+
+    result = await VaultBrain.chat_resume(brain, chat_id="chat_1", prompt="hi")
+
+    assert result["status"] == "error"
+    brain._stream_chat_response.assert_not_awaited()
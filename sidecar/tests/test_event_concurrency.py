@@ -0,0 +1,80 @@
+import pytest
+import asyncio
+from sidecar.event_bus import EventBus, ConcurrencyLimiter, EventChannels
+
+
+@pytest.mark.asyncio
+async def test_limit_one_runs_handlers_serially():
+    bus = EventBus()
+    bus.limiter = ConcurrencyLimiter(1)
+
+    running = 0
+    peak = 0
+
+    async def handler():
+        nonlocal running, peak
+        running += 1
+        peak = max(peak, running)
+        await asyncio.sleep(0.01)
+        running -= 1
+
+    for _ in range(3):
+        bus.subscribe("test.event", handler)
+
+    await bus.publish("test.event")
+
+    assert peak == 1
+    assert bus.limiter.in_flight == 0
+
+
+@pytest.mark.asyncio
+async def test_nested_publish_does_not_deadlock():
+    bus = EventBus()
+    bus.limiter = ConcurrencyLimiter(1)
+    calls = []
+
+    async def inner():
+        calls.append("inner")
+
+    async def outer():
+        calls.append("outer")
+        await bus.publish("test.inner")
+
+    bus.subscribe("test.outer", outer)
+    bus.subscribe("test.inner", inner)
+
+    await asyncio.wait_for(bus.publish("test.outer"), timeout=1)
+
+    assert calls == ["outer", "inner"]
+
+
+@pytest.mark.asyncio
+async def test_set_limit_rejects_zero():
+    limiter = ConcurrencyLimiter()
+    with pytest.raises(ValueError):
+        await limiter.set_limit(0)
+    assert limiter.limit is None
+
+
+def test_muted_channel_is_suppressed():
+    channels = EventChannels()
+    assert channels.allow("progress")
+
+    channels.set_enabled("progress", False)
+    assert not channels.allow("progress")
+
+    [entry] = channels.snapshot(subscribers=2)
+    assert entry["channel"] == "progress"
+    assert entry["enabled"] is False
+    assert entry["subscribers"] == 0
+    assert (entry["emitted"], entry["suppressed"]) == (1, 1)
+
+    channels.set_enabled("progress", True)
+    assert channels.allow("progress")
+
+
+def test_channels_default_enabled():
+    channels = EventChannels(muted=["noisy"])
+    assert channels.allow("chat.token")
+    assert not channels.allow("noisy")
+    assert channels.muted == ["noisy"]
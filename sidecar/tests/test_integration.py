@@ -134,6 +134,69 @@ class TestIntegration:
         
         assert plugin.is_loaded is False
 
+    async def test_reload_plugin_picks_up_changes(self, integration_vault, mock_ws_server):
+        """Reloading re-imports main.py and swaps in the new commands."""
+        brain = VaultBrain(integration_vault, mock_ws_server)
+        await brain.initialize()
+        old_instance = brain.plugins["integration_test_plugin"]
+
+        main_file = integration_vault / "plugins" / "integration_test_plugin" / "main.py"
+        main_file.write_text(TEST_PLUGIN_CODE.replace("test.emit", "test.shout"), encoding="utf-8")
+        result = await brain.reload_plugin(plugin="integration_test_plugin")
+
+        assert result["status"] == "success"
+        assert result["reloaded"] is True
+        assert result["commands"] == ["test.echo", "test.shout"]
+        assert "test.emit" not in brain.commands
+        assert old_instance.is_loaded is False
+        assert brain.plugins["integration_test_plugin"] is not old_instance
+
+    async def test_reload_plugin_reports_syntax_error(self, integration_vault, mock_ws_server):
+        """A broken main.py comes back as a traceback; fixing it loads the plugin fresh."""
+        brain = VaultBrain(integration_vault, mock_ws_server)
+        await brain.initialize()
+
+        main_file = integration_vault / "plugins" / "integration_test_plugin" / "main.py"
+        main_file.write_text("class Plugin(:\n", encoding="utf-8")
+        result = await brain.reload_plugin(plugin="integration_test_plugin")
+
+        assert result["status"] == "error"
+        assert "SyntaxError" in result["traceback"]
+        assert "integration_test_plugin" not in brain.plugins
+        assert "test.echo" not in brain.commands
+
+        main_file.write_text(TEST_PLUGIN_CODE, encoding="utf-8")
+        result = await brain.reload_plugin(plugin="integration_test_plugin")
+        assert result["status"] == "success"
+        assert result["reloaded"] is False
+        assert "test.echo" in brain.commands
+
+    async def test_unload_then_enable_plugin_live(self, integration_vault, mock_ws_server):
+        """Unloading drops the plugin's commands; enabling it in .vault.json and reloading brings it back."""
+        brain = VaultBrain(integration_vault, mock_ws_server)
+        await brain.initialize()
+        instance = brain.plugins["integration_test_plugin"]
+
+        (integration_vault / ".vault.json").write_text(
+            json.dumps({"plugins": {"integration_test_plugin": {"enabled": False}}}), encoding="utf-8"
+        )
+        result = await brain.unload_plugin(plugin="integration_test_plugin")
+        assert result["unloaded"] is True
+        assert instance.is_loaded is False
+        assert "test.echo" not in brain.commands
+        assert (await brain.unload_plugin(plugin="integration_test_plugin"))["unloaded"] is False
+
+        result = await brain.reload_plugin(plugin="integration_test_plugin")
+        assert result["status"] == "error"
+        assert "disabled" in result["error"]
+
+        (integration_vault / ".vault.json").write_text(
+            json.dumps({"plugins": {"integration_test_plugin": {"enabled": True}}}), encoding="utf-8"
+        )
+        result = await brain.reload_plugin(plugin="integration_test_plugin")
+        assert result["status"] == "success"
+        assert "test.echo" in brain.commands
+
     async def test_real_example_vault(self, mock_ws_server):
         """Verify we can load the actual example-vault plugins."""
         # This assumes example-vault is at ../example-vault relative to sidecar
@@ -0,0 +1,44 @@
+from typing import Any, Dict, List, Optional
+
+from sidecar import utils
+from sidecar.decorators import command
+
+
+class FakePlugin:
+    @command("fake.create", "fake")
+    async def create(self, chat_id: str, message_index: int = -1, name: str = None,
+                     tags: Optional[List[str]] = None, **kwargs) -> Dict[str, Any]:
+        return {}
+
+    async def untyped(self, value, other=1):
+        return {}
+
+    async def loose(self, payload: Any = None):
+        return {}
+
+
+def _params(description):
+    return {p["name"]: p for p in description["params"]}
+
+
+def test_describes_types_and_requiredness():
+    description = utils.describe_handler(FakePlugin().create)
+    params = _params(description)
+
+    assert list(params) == ["chat_id", "message_index", "name", "tags"]
+    assert params["chat_id"]["required"] and params["chat_id"]["type"] == "string"
+    assert params["message_index"]["type"] == "integer" and not params["message_index"]["required"]
+    assert params["name"]["nullable"]
+    assert params["tags"]["type"] == "array" and params["tags"]["nullable"]
+    assert description["accepts_extra"] and description["annotated"]
+
+
+def test_unannotated_handlers_are_flagged():
+    description = utils.describe_handler(FakePlugin().untyped)
+    assert not description["annotated"]
+    assert not description["accepts_extra"]
+    assert _params(description)["value"]["required"]
+
+
+def test_non_json_annotations_are_unchecked():
+    assert _params(utils.describe_handler(FakePlugin().loose))["payload"]["type"] is None
@@ -0,0 +1,53 @@
+from sidecar.services.model_ids import catalog_from_litellm, normalize_model_id
+
+CATALOG = {
+    "openai": {"gpt-4o", "gpt-4o-mini", "text-embedding-3-small"},
+    "anthropic": {"claude-3-5-sonnet-20241022"},
+    "ollama": {"llama3.1:8b", "qwen2:latest"},
+}
+
+
+def test_known_id_is_canonical_and_case_insensitive():
+    check = normalize_model_id("openai", "GPT-4o", CATALOG)
+    assert check.valid
+    assert check.canonical == "gpt-4o"
+    assert check.warning is None
+
+
+def test_provider_prefix_and_inferred_provider():
+    assert normalize_model_id(None, "openai/gpt-4o-mini", CATALOG).canonical == "gpt-4o-mini"
+    assert normalize_model_id("", "claude-3-5-sonnet-20241022", CATALOG).provider == "anthropic"
+
+
+def test_typo_suggests_close_matches():
+    check = normalize_model_id("openai", "gpt-4o-mnii", CATALOG)
+    assert not check.valid
+    assert check.suggestions[0] == "gpt-4o-mini"
+    assert "did you mean 'gpt-4o-mini'" in check.error()
+
+
+def test_ollama_ids_are_prefixed_and_default_to_latest():
+    assert normalize_model_id("ollama", "llama3.1:8b", CATALOG).canonical == "ollama/llama3.1:8b"
+    assert normalize_model_id("ollama", "qwen2", CATALOG).canonical == "ollama/qwen2:latest"
+
+
+def test_unknown_provider_passes_through_with_warning():
+    check = normalize_model_id("openrouter", "meta/llama-3-70b", CATALOG)
+    assert check.valid
+    assert check.canonical == "meta/llama-3-70b"
+    assert "Unknown provider" in check.warning
+
+
+def test_known_provider_without_model_list_is_unchecked():
+    check = normalize_model_id("groq", "llama3-70b-8192", CATALOG, known_providers=["groq"])
+    assert check.canonical == "llama3-70b-8192"
+    assert check.warning
+
+
+def test_catalog_from_litellm_groups_by_provider():
+    catalog = catalog_from_litellm({
+        "gpt-4o": {"litellm_provider": "openai"},
+        "gemini/gemini-1.5-pro": {"litellm_provider": "gemini"},
+        "sample_spec": {},
+    })
+    assert catalog == {"openai": {"gpt-4o"}, "google": {"gemini-1.5-pro"}}
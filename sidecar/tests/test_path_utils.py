@@ -82,3 +82,41 @@ class TestValidatePluginStructure:
         
         # Should still validate successfully
         utils.validate_plugin_structure(plugin_dir)
+
+
+@pytest.mark.unit
+class TestPythonRequires:
+    """Test plugin python_requires compatibility checks."""
+
+    def test_specifier_matching(self):
+        """Test common specifier forms against fixed versions."""
+        assert utils.python_version_satisfies(">=3.10", "3.12.1")
+        assert not utils.python_version_satisfies(">=3.13", "3.12.1")
+        assert utils.python_version_satisfies(">=3.9,<3.13", "3.12.0")
+        assert not utils.python_version_satisfies(">=3.9,<3.12", "3.12.0")
+        assert utils.python_version_satisfies("==3.12.*", "3.12.4")
+        assert not utils.python_version_satisfies("!=3.12.*", "3.12.4")
+        assert utils.python_version_satisfies("~=3.10", "3.12.0")
+        assert not utils.python_version_satisfies("~=3.10.2", "3.11.0")
+
+    def test_invalid_specifier(self):
+        """Test malformed specifiers are rejected."""
+        with pytest.raises(ValueError):
+            utils.python_version_satisfies("3.10", "3.12.0")
+
+    def test_incompatible_plugin_refused(self, tmp_path):
+        """Test a plugin requiring an impossible Python fails to load."""
+        plugin_dir = tmp_path / "future_plugin"
+        plugin_dir.mkdir()
+        (plugin_dir / "plugin.json").write_text('{"python_requires": ">=99"}')
+
+        with pytest.raises(exceptions.PluginLoadError, match="Requires Python >=99"):
+            utils.check_plugin_python_requires(plugin_dir)
+
+    def test_plugin_without_requirement(self, tmp_path):
+        """Test plugins without python_requires are accepted."""
+        plugin_dir = tmp_path / "plain_plugin"
+        plugin_dir.mkdir()
+        (plugin_dir / "plugin.json").write_text('{"name": "plain"}')
+
+        utils.check_plugin_python_requires(plugin_dir)
@@ -0,0 +1,75 @@
+from types import SimpleNamespace
+
+import pytest
+
+from sidecar.services.plugin_diagnostics import PluginDiagnostics, state_snapshot
+from sidecar.vault_brain import VaultBrain
+
+
+class FakePlugin:
+    def __init__(self):
+        self.name = "notes"
+        self.config = {"enabled": True, "api_key": "sk-secret"}
+        self.recent_notes = ["Dear diary"]
+        self.counter = 3
+        self._private = "hidden"
+
+
+def test_record_error_keeps_traceback():
+    diagnostics = PluginDiagnostics()
+    try:
+        raise ValueError("bad config")
+    except ValueError as e:
+        diagnostics.record_error("notes", "load", e)
+
+    error = diagnostics.last_error("notes")
+    assert error["where"] == "load"
+    assert error["error"] == "bad config"
+    assert "ValueError" in error["traceback"]
+
+
+def test_state_snapshot_hides_content_by_default():
+    snapshot = state_snapshot(FakePlugin())
+    assert snapshot == {
+        "counter": {"type": "int"},
+        "recent_notes": {"type": "list", "size": 1},
+    }
+
+    full = state_snapshot(FakePlugin(), include_content=True)
+    assert full["recent_notes"]["value"] == "['Dear diary']"
+
+
+@pytest.mark.asyncio
+async def test_support_info_for_loaded_and_failed_plugins():
+    diagnostics = PluginDiagnostics()
+    diagnostics.record_timing("notes", "load", 12.5)
+    diagnostics.record_error("broken", "load", "No 'Plugin' class found")
+    brain = SimpleNamespace(
+        plugins={"notes": FakePlugin()},
+        diagnostics=diagnostics,
+        config={"plugins": {"broken": {"enabled": True}}},
+    )
+
+    info = await VaultBrain.get_plugin_support_info(brain, plugin="notes")
+    assert info["status"] == "success" and info["loaded"]
+    assert info["timings"] == {"load_ms": 12.5}
+    assert "value" not in info["state"]["recent_notes"]
+
+    failed = await VaultBrain.get_plugin_support_info(brain, plugin="broken")
+    assert not failed["loaded"]
+    assert failed["config"] == {"enabled": True}
+    assert failed["last_error"]["error"] == "No 'Plugin' class found"
+
+    missing = await VaultBrain.get_plugin_support_info(brain, plugin="absent")
+    assert missing["status"] == "error"
+
+
+def test_handler_errors_are_recorded():
+    brain = SimpleNamespace(diagnostics=PluginDiagnostics(), _lifecycle=lambda *a, **kw: None)
+    VaultBrain._observe_handler(
+        brain, "notes", "memory.saved", "finished",
+        {"error": "boom", "traceback": "Traceback ...", "duration_ms": 1.0},
+    )
+    error = brain.diagnostics.last_error("notes")
+    assert error["where"] == "event memory.saved"
+    assert error["traceback"] == "Traceback ..."
@@ -0,0 +1,106 @@
+import importlib.util
+
+import pytest
+from sidecar.services.plugin_fs_guard import PluginFsGuard
+
+
+PLUGIN_SOURCE = '''
+from pathlib import Path
+
+def write(path):
+    Path(path).write_text("data")
+'''
+
+
+def load_plugin(vault, name="scribbler", plugins_dir=None):
+    plugin_dir = (plugins_dir or vault / "plugins") / name
+    plugin_dir.mkdir(parents=True)
+    main_file = plugin_dir / "main.py"
+    main_file.write_text(PLUGIN_SOURCE)
+    spec = importlib.util.spec_from_file_location(name, main_file)
+    module = importlib.util.module_from_spec(spec)
+    spec.loader.exec_module(module)
+    return module
+
+
+def make_vault(tmp_path):
+    vault = tmp_path / "vault"
+    vault.mkdir()
+    outside = tmp_path / "outside"
+    outside.mkdir()
+    return vault, outside
+
+
+def test_records_writes_inside_and_outside(tmp_path):
+    vault, outside = make_vault(tmp_path)
+    plugin = load_plugin(vault)
+    guard = PluginFsGuard(vault)
+    guard.install()
+    try:
+        plugin.write(vault / "notes.txt")
+        plugin.write(outside / "stray.txt")
+    finally:
+        guard.uninstall()
+
+    access = guard.get_access("scribbler")
+    paths = {a["path"]: a for a in access["accesses"]}
+    assert str((vault / "notes.txt").resolve()) in paths
+    assert access["outside_vault"] == 1
+    assert (outside / "stray.txt").exists()
+
+
+def test_restrict_blocks_outside_writes(tmp_path):
+    vault, outside = make_vault(tmp_path)
+    plugin = load_plugin(vault)
+    guard = PluginFsGuard(vault, restrict=True)
+    guard.install()
+    try:
+        plugin.write(vault / "ok.txt")
+        with pytest.raises(PermissionError):
+            plugin.write(outside / "blocked.txt")
+    finally:
+        guard.uninstall()
+
+    assert (vault / "ok.txt").exists()
+    assert not (outside / "blocked.txt").exists()
+
+
+def test_granted_paths_are_allowed(tmp_path):
+    vault, outside = make_vault(tmp_path)
+    plugin = load_plugin(vault)
+    guard = PluginFsGuard(vault, restrict=True)
+    guard.grant("scribbler", [str(outside)])
+    guard.install()
+    try:
+        plugin.write(outside / "granted.txt")
+    finally:
+        guard.uninstall()
+
+    assert (outside / "granted.txt").exists()
+
+
+def test_non_plugin_writes_are_ignored(tmp_path):
+    vault, outside = make_vault(tmp_path)
+    guard = PluginFsGuard(vault, restrict=True)
+    guard.install()
+    try:
+        (outside / "core.txt").write_text("core")
+    finally:
+        guard.uninstall()
+
+    assert guard.get_access("scribbler")["accesses"] == []
+
+
+def test_shared_plugin_writes_are_attributed(tmp_path):
+    vault, outside = make_vault(tmp_path)
+    shared = tmp_path / "shared-plugins"
+    plugin = load_plugin(vault, plugins_dir=shared)
+    guard = PluginFsGuard(vault)
+    guard.add_plugin_root(shared)
+    guard.install()
+    try:
+        plugin.write(outside / "stray.txt")
+    finally:
+        guard.uninstall()
+
+    assert guard.get_access("scribbler")["outside_vault"] == 1
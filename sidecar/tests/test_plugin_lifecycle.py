@@ -0,0 +1,72 @@
+from types import SimpleNamespace
+
+import pytest
+
+from sidecar import constants
+from sidecar.event_bus import EventBus
+from sidecar.vault_brain import VaultBrain
+
+
+class FakePlugin:
+    name = "ticker"
+
+    async def on_tick(self):
+        pass
+
+    async def on_broken(self):
+        raise RuntimeError("boom")
+
+
+def _brain(enabled=True):
+    emitted = []
+    brain = SimpleNamespace(
+        lifecycle_events=enabled,
+        emit_to_frontend=lambda event_type, data: emitted.append((event_type, data)),
+    )
+    brain._lifecycle = lambda *a, **kw: VaultBrain._lifecycle(brain, *a, **kw)
+    return brain, emitted
+
+
+@pytest.mark.asyncio
+async def test_observer_sees_plugin_handlers_only():
+    bus = EventBus()
+    seen = []
+    bus.observer = lambda plugin, event, phase, details: seen.append((plugin, event, phase, details))
+    plugin = FakePlugin()
+
+    async def anonymous():
+        pass
+
+    bus.subscribe("test.event", plugin.on_broken)
+    bus.subscribe("test.event", anonymous)
+    await bus.publish("test.event", sequential=True)
+
+    assert [(p, e, ph) for p, e, ph, _ in seen] == [
+        ("ticker", "test.event", "started"),
+        ("ticker", "test.event", "finished"),
+    ]
+    assert seen[1][3]["error"] == "boom"
+    assert seen[1][3]["duration_ms"] >= 0
+
+
+def test_lifecycle_events_emit_only_when_enabled():
+    brain, emitted = _brain()
+    VaultBrain._lifecycle(brain, "ticker", "loaded")
+    VaultBrain._lifecycle(brain, None, "command_received", command="system.ping")
+
+    assert len(emitted) == 1
+    event_type, data = emitted[0]
+    assert event_type == constants.EventType.PLUGIN_LIFECYCLE
+    assert data["plugin"] == "ticker" and data["phase"] == "loaded"
+
+    quiet, quiet_emitted = _brain(enabled=False)
+    VaultBrain._lifecycle(quiet, "ticker", "loaded")
+    assert quiet_emitted == []
+
+
+def test_only_ticks_are_traced_from_handlers():
+    brain, emitted = _brain()
+    VaultBrain._observe_handler(brain, "ticker", constants.CoreEvents.TICK, "started", {})
+    VaultBrain._observe_handler(brain, "ticker", "memory.saved", "started", {})
+
+    assert [data["phase"] for _, data in emitted] == ["tick_started"]
@@ -0,0 +1,73 @@
+import pytest
+
+from sidecar.plugin_validator import validate_plugin
+
+VALID_PLUGIN = """
+from sidecar.api.plugin_base import PluginBase
+
+class Plugin(PluginBase):
+    def register_commands(self):
+        pass
+
+    async def on_load(self):
+        print("loading")
+"""
+
+
+@pytest.fixture
+def plugin_dir(tmp_path):
+    plugin_dir = tmp_path / "vault" / "plugins" / "checked"
+    plugin_dir.mkdir(parents=True)
+    return plugin_dir
+
+
+def _checks(result):
+    return {check["name"]: check["passed"] for check in result["checks"]}
+
+
+def test_valid_plugin_passes_every_check(plugin_dir, capsys):
+    (plugin_dir / "main.py").write_text(VALID_PLUGIN + 'print("imported")\n', encoding="utf-8")
+
+    result = validate_plugin(plugin_dir)
+
+    assert result["valid"] is True
+    assert result["traceback"] is None
+    assert set(_checks(result).values()) == {True}
+    assert "imported" not in capsys.readouterr().out
+
+
+def test_syntax_error_returns_traceback(plugin_dir):
+    (plugin_dir / "main.py").write_text("class Plugin(:\n", encoding="utf-8")
+
+    result = validate_plugin(plugin_dir)
+
+    assert result["valid"] is False
+    assert _checks(result) == {"main_file": True, "import": False}
+    assert "SyntaxError" in result["traceback"]
+
+
+def test_plugin_must_subclass_plugin_base(plugin_dir):
+    (plugin_dir / "main.py").write_text("class Plugin:\n    pass\n", encoding="utf-8")
+
+    result = validate_plugin(plugin_dir)
+
+    assert result["valid"] is False
+    assert _checks(result)["subclasses_plugin_base"] is False
+
+
+def test_contract_problems_are_each_reported(plugin_dir):
+    (plugin_dir / "main.py").write_text(
+        "from sidecar.api.plugin_base import PluginBase\n"
+        "class Plugin(PluginBase):\n"
+        "    def __init__(self, emitter, brain):\n"
+        "        pass\n"
+        "    def on_tick(self):\n"
+        "        pass\n",
+        encoding="utf-8",
+    )
+
+    checks = _checks(validate_plugin(plugin_dir))
+
+    assert checks["constructor"] is False
+    assert checks["required_methods"] is False
+    assert checks["async_hooks"] is False
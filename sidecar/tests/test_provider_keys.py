@@ -0,0 +1,44 @@
+import json
+
+from sidecar.services.keyring_service import PROVIDERS
+from sidecar.services.provider_keys import (
+    key_status,
+    missing_key_messages,
+    plugin_providers,
+    read_plugin_manifest,
+)
+
+CATEGORIES = {"fast": "groq/llama-3.1-8b-instant", "local": "ollama/llama3.1:8b"}
+
+
+def test_manifest_providers_and_categories_resolve():
+    manifest = {"providers": ["OpenAI"], "model_categories": ["fast", "local", "unset"]}
+    assert plugin_providers(manifest, CATEGORIES.get) == {"openai", "groq", "ollama"}
+
+
+def test_read_plugin_manifest_tolerates_missing_or_bad_json(tmp_path):
+    assert read_plugin_manifest(tmp_path) == {}
+    (tmp_path / "plugin.json").write_text("[1, 2]")
+    assert read_plugin_manifest(tmp_path) == {}
+    (tmp_path / "plugin.json").write_text(json.dumps({"providers": ["anthropic"]}))
+    assert read_plugin_manifest(tmp_path) == {"providers": ["anthropic"]}
+
+
+def test_status_flags_needed_providers_without_keys():
+    needs = {"openai": {"summarizer"}, "anthropic": {"core"}, "ollama": {"core"}}
+    entries = key_status(needs, ["anthropic", "mistral"], PROVIDERS, {})
+    by_provider = {e["provider"]: e for e in entries}
+
+    assert "ollama" not in by_provider
+    assert by_provider["openai"]["missing"]
+    assert by_provider["openai"]["used_by"] == ["summarizer"]
+    assert by_provider["anthropic"]["key_source"] == "keyring"
+    # A stored key nobody uses is reported, but not as missing
+    assert by_provider["mistral"]["key_present"] and by_provider["mistral"]["used_by"] == []
+    assert missing_key_messages(entries) == ["Plugin 'summarizer' needs an OpenAI API key you haven't set"]
+
+
+def test_environment_key_counts_as_present():
+    entries = key_status({"groq": {"core"}}, [], PROVIDERS, {"GROQ_API_KEY": "gsk-test"})
+    assert entries[0]["key_source"] == "environment"
+    assert not entries[0]["missing"]
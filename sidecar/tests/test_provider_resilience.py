@@ -0,0 +1,77 @@
+import pytest
+from sidecar.services.provider_resilience import (
+    CircuitOpenError,
+    ProviderResilience,
+    ResilienceSettings,
+    provider_of,
+)
+
+
+def make(**overrides):
+    settings = ResilienceSettings(backoff_seconds=0, backoff_max_seconds=0, **overrides)
+    return ProviderResilience(settings)
+
+
+@pytest.mark.asyncio
+async def test_retries_then_succeeds():
+    resilience = make(retries=2)
+    attempts = 0
+
+    async def flaky():
+        nonlocal attempts
+        attempts += 1
+        if attempts < 3:
+            raise RuntimeError("boom")
+        return "ok"
+
+    assert await resilience.call("openai", flaky) == "ok"
+    assert attempts == 3
+    assert resilience.get_state("openai")["state"] == "closed"
+
+
+@pytest.mark.asyncio
+async def test_circuit_opens_and_short_circuits():
+    resilience = make(retries=0, failure_threshold=2, cooldown_seconds=60)
+
+    async def failing():
+        raise RuntimeError("down")
+
+    for _ in range(2):
+        with pytest.raises(RuntimeError):
+            await resilience.call("groq", failing)
+
+    assert resilience.get_state("groq")["state"] == "open"
+    with pytest.raises(CircuitOpenError):
+        await resilience.call("groq", failing)
+    assert resilience.get_state("groq")["short_circuited"] == 1
+
+    resilience.reset("groq")
+    assert resilience.get_state("groq")["state"] == "closed"
+
+
+@pytest.mark.asyncio
+async def test_half_open_probe_closes_circuit():
+    resilience = make(retries=0, failure_threshold=1, cooldown_seconds=0)
+
+    async def failing():
+        raise RuntimeError("down")
+
+    async def healthy():
+        return 42
+
+    with pytest.raises(RuntimeError):
+        await resilience.call("anthropic", failing)
+    assert resilience.get_state("anthropic")["state"] == "half_open"
+
+    assert await resilience.call("anthropic", healthy) == 42
+    assert resilience.get_state("anthropic")["state"] == "closed"
+
+
+def test_invalid_settings_rejected():
+    with pytest.raises(ValueError):
+        ResilienceSettings.from_config({"failure_threshold": 0})
+
+
+def test_provider_of():
+    assert provider_of("openai/gpt-4o") == "openai"
+    assert provider_of("mystery") == "unknown"
@@ -0,0 +1,72 @@
+import asyncio
+
+import pytest
+
+from sidecar import constants
+from sidecar.services.request_budget import RequestBudget
+
+
+@pytest.mark.asyncio
+async def test_without_host_permits_are_immediate():
+    budget = RequestBudget()
+    async with budget.permit("openai"):
+        pass
+    assert not budget.enabled
+
+
+@pytest.mark.asyncio
+async def test_permit_waits_for_grant_and_is_released_on_error():
+    sent = []
+    budget = RequestBudget(send=lambda event_type, data: sent.append((event_type, data)))
+    entered = asyncio.Event()
+
+    async def call():
+        async with budget.permit("openai"):
+            entered.set()
+            raise RuntimeError("provider failed")
+
+    task = asyncio.create_task(call())
+    await asyncio.sleep(0)
+    assert sent[0][0] == constants.EventType.REQUEST_PERMIT
+    assert sent[0][1]["provider"] == "openai"
+    assert not entered.is_set()
+    assert budget.waiting == 1
+
+    lease_id = sent[0][1]["lease_id"]
+    assert budget.grant(lease_id)
+    with pytest.raises(RuntimeError):
+        await task
+
+    assert entered.is_set()
+    assert sent[-1] == (constants.EventType.RELEASE_PERMIT, {"lease_id": lease_id})
+    assert not budget.grant(lease_id)
+
+
+@pytest.mark.asyncio
+async def test_unanswered_request_proceeds_after_timeout():
+    sent = []
+    budget = RequestBudget(send=lambda event_type, data: sent.append((event_type, data)), acquire_timeout=0.01)
+    async with budget.permit("groq"):
+        pass
+    assert [event_type for event_type, _ in sent] == [
+        constants.EventType.REQUEST_PERMIT,
+        constants.EventType.RELEASE_PERMIT,
+    ]
+
+
+@pytest.mark.asyncio
+async def test_cancelled_waiter_releases_its_request():
+    sent = []
+    budget = RequestBudget(send=lambda event_type, data: sent.append((event_type, data)))
+
+    async def call():
+        async with budget.permit("openai"):
+            pass
+
+    task = asyncio.create_task(call())
+    await asyncio.sleep(0)
+    task.cancel()
+    with pytest.raises(asyncio.CancelledError):
+        await task
+    assert sent[-1][0] == constants.EventType.RELEASE_PERMIT
+    assert budget.waiting == 0
@@ -0,0 +1,61 @@
+import json
+
+import pytest
+
+from sidecar.services import secret_export
+from sidecar.services.secret_export import SecretExportError
+
+
+class FakeKeyring:
+    def __init__(self, secrets=None):
+        self.secrets = dict(secrets or {})
+
+    def list_configured_providers(self):
+        return list(self.secrets)
+
+    def get_api_key(self, provider):
+        return self.secrets.get(provider)
+
+    def store_api_key(self, provider, api_key):
+        self.secrets[provider] = api_key
+        return True
+
+
+@pytest.fixture(autouse=True)
+def no_native_format(monkeypatch):
+    monkeypatch.setattr(secret_export, "native_format", lambda: None)
+
+
+def test_blob_round_trip_never_writes_plaintext(tmp_path):
+    source = FakeKeyring({"openai": "sk-test-openai-key-1234567890"})
+    target = tmp_path / "keys.json"
+
+    result = secret_export.export_secrets(source, target, "correct horse")
+
+    assert result["format"] == secret_export.FORMAT_ENCRYPTED_BLOB
+    assert result["note"]
+    assert "sk-test" not in target.read_text()
+    assert json.loads(target.read_text())["format"] == secret_export.BLOB_MAGIC
+
+    dest = FakeKeyring()
+    imported = secret_export.import_secrets(dest, target, "correct horse")
+    assert imported["imported"] == ["openai"]
+    assert dest.secrets == source.secrets
+
+
+def test_wrong_passphrase_is_rejected(tmp_path):
+    target = tmp_path / "keys.json"
+    secret_export.export_secrets(FakeKeyring({"groq": "gsk_abc"}), target, "correct horse")
+
+    with pytest.raises(SecretExportError, match="Wrong passphrase"):
+        secret_export.import_secrets(FakeKeyring(), target, "battery staple")
+
+
+def test_export_refuses_short_passphrase_and_overwrite(tmp_path):
+    target = tmp_path / "keys.json"
+    with pytest.raises(SecretExportError, match="at least"):
+        secret_export.export_secrets(FakeKeyring(), target, "short")
+
+    target.write_text("{}")
+    with pytest.raises(SecretExportError, match="overwrite"):
+        secret_export.export_secrets(FakeKeyring(), target, "correct horse")
@@ -0,0 +1,104 @@
+import json
+
+import pytest
+
+from sidecar.services.semantic_index import SemanticIndex, SemanticIndexError
+
+
+def _write_conversation(vault, conversation_id, contents):
+    folder = vault / "conversations"
+    folder.mkdir(parents=True, exist_ok=True)
+    messages = [
+        {"role": "user" if i % 2 == 0 else "assistant", "content": text}
+        for i, text in enumerate(contents)
+    ]
+    (folder / f"{conversation_id}.json").write_text(json.dumps({"messages": messages}))
+
+
+class FakeEmbedder:
+    """Maps text onto a vector per keyword it mentions."""
+
+    KEYWORDS = ["cat", "dog", "rust"]
+
+    def __init__(self):
+        self.calls = []
+
+    async def __call__(self, texts):
+        self.calls.append(list(texts))
+        return [[float(k in t.lower()) for k in self.KEYWORDS] for t in texts]
+
+
+@pytest.mark.asyncio
+async def test_build_is_incremental(tmp_path):
+    _write_conversation(tmp_path, "pets", ["I have a cat", "Cats are nice"])
+    _write_conversation(tmp_path, "code", ["Rust borrow checker"])
+    index = SemanticIndex(tmp_path)
+    embed = FakeEmbedder()
+
+    stats = await index.build("openai/text-embedding-3-small", embed)
+    assert stats["embedded"] == 3 and stats["reused"] == 0
+    assert (tmp_path / ".tailor" / "embeddings" / "index.json").exists()
+
+    embed.calls.clear()
+    stats = await index.build("openai/text-embedding-3-small", embed)
+    assert stats["embedded"] == 0 and stats["reused"] == 3
+    assert embed.calls == []
+
+    _write_conversation(tmp_path, "pets", ["I have a cat", "Actually a dog"])
+    stats = await index.build("openai/text-embedding-3-small", embed)
+    assert stats["embedded"] == 1
+    assert embed.calls == [["Actually a dog"]]
+
+
+@pytest.mark.asyncio
+async def test_model_change_and_removal_rebuild(tmp_path):
+    _write_conversation(tmp_path, "pets", ["I have a cat"])
+    _write_conversation(tmp_path, "code", ["Rust borrow checker"])
+    index = SemanticIndex(tmp_path)
+    await index.build("model-a", FakeEmbedder())
+
+    (tmp_path / "conversations" / "code.json").unlink()
+    stats = await index.build("model-b", FakeEmbedder())
+    assert stats["embedded"] == 1
+    assert not (tmp_path / ".tailor" / "embeddings" / "code.json").exists()
+
+
+@pytest.mark.asyncio
+async def test_interrupted_build_resumes(tmp_path):
+    _write_conversation(tmp_path, "a", ["cat"])
+    _write_conversation(tmp_path, "b", ["dog"])
+    index = SemanticIndex(tmp_path)
+
+    async def failing(texts):
+        if texts == ["dog"]:
+            raise RuntimeError("provider down")
+        return [[1.0, 0.0, 0.0]]
+
+    with pytest.raises(RuntimeError):
+        await index.build("model-a", failing)
+
+    embed = FakeEmbedder()
+    stats = await index.build("model-a", embed)
+    assert embed.calls == [["dog"]]
+    assert stats["reused"] == 1
+
+
+@pytest.mark.asyncio
+async def test_search_ranks_by_similarity(tmp_path):
+    _write_conversation(tmp_path, "pets", ["I have a cat", "and a dog"])
+    _write_conversation(tmp_path, "code", ["Rust borrow checker"])
+    index = SemanticIndex(tmp_path)
+    await index.build("model-a", FakeEmbedder())
+
+    results = index.search("model-a", [0.0, 0.0, 1.0], top_k=2)
+    assert results["messages"][0]["conversation_id"] == "code"
+    assert results["messages"][0]["score"] == 1.0
+    assert results["conversations"][0]["conversation_id"] == "code"
+
+    with pytest.raises(SemanticIndexError):
+        index.search("model-b", [0.0, 0.0, 1.0])
+
+
+def test_search_empty_index(tmp_path):
+    with pytest.raises(SemanticIndexError):
+        SemanticIndex(tmp_path).search("model-a", [1.0])
@@ -0,0 +1,31 @@
+import json
+from types import SimpleNamespace
+
+from sidecar.vault_brain import VaultBrain
+
+
+def _brain(tmp_path, default=None):
+    config = {"llm": {"system_prompt": default}} if default else {}
+    return SimpleNamespace(vault_path=tmp_path, config=config)
+
+
+def _write_conversation(tmp_path, chat_id, **fields):
+    folder = tmp_path / "conversations"
+    folder.mkdir(exist_ok=True)
+    (folder / f"{chat_id}.json").write_text(json.dumps({"id": chat_id, "messages": [], **fields}))
+
+
+def test_conversation_prompt_overrides_vault_default(tmp_path):
+    brain = _brain(tmp_path, default="Be terse.")
+    _write_conversation(tmp_path, "chat_1", system_prompt="You are a pirate.")
+
+    assert VaultBrain._system_prompt_for(brain, "chat_1") == "You are a pirate."
+
+
+def test_falls_back_to_vault_default(tmp_path):
+    brain = _brain(tmp_path, default="Be terse.")
+    _write_conversation(tmp_path, "chat_2")
+
+    assert VaultBrain._system_prompt_for(brain, "chat_2") == "Be terse."
+    assert VaultBrain._system_prompt_for(brain, "../escape") == "Be terse."
+    assert VaultBrain._system_prompt_for(_brain(tmp_path), None) is None
@@ -0,0 +1,48 @@
+import json
+from datetime import datetime, timezone
+from types import SimpleNamespace
+
+from sidecar.services.usage_tracker import (
+    UsageTracker,
+    extract_rate_limits,
+    next_period_start,
+    period_start,
+)
+
+
+def test_usage_accumulates_and_persists(tmp_path):
+    tracker = UsageTracker(tmp_path)
+    tracker.record("openai", {"prompt_tokens": 10, "completion_tokens": 5, "total_tokens": 15}, 0.002)
+    tracker.record("openai", {"total_tokens": 5}, None, {"x-ratelimit-remaining-requests": "99"})
+
+    reloaded = UsageTracker(tmp_path).snapshot()
+    openai = reloaded["providers"]["openai"]
+    assert openai["requests"] == 2
+    assert openai["tokens"] == 20
+    assert openai["estimated_cost"] == 0.002
+    assert openai["rate_limits"] == {"x-ratelimit-remaining-requests": "99"}
+    assert openai["window_reset"] == reloaded["window_reset"]
+    assert (tmp_path / ".tailor" / "usage.json").exists()
+
+
+def test_counters_reset_in_a_new_period(tmp_path):
+    stale = {"period_start": "2000-01-01T00:00:00+00:00", "providers": {"groq": {"requests": 7}}}
+    (tmp_path / ".tailor").mkdir()
+    (tmp_path / ".tailor" / "usage.json").write_text(json.dumps(stale))
+
+    assert UsageTracker(tmp_path).snapshot()["providers"] == {}
+
+
+def test_period_boundaries():
+    now = datetime(2026, 1, 3, 12, tzinfo=timezone.utc)
+    start = period_start(now, reset_day=15)
+    assert start == datetime(2025, 12, 15, tzinfo=timezone.utc)
+    assert next_period_start(start) == datetime(2026, 1, 15, tzinfo=timezone.utc)
+
+
+def test_extract_rate_limits_strips_litellm_prefix():
+    response = SimpleNamespace(_hidden_params={"additional_headers": {
+        "llm_provider-x-ratelimit-remaining-tokens": "1000",
+        "content-type": "application/json",
+    }})
+    assert extract_rate_limits(response) == {"x-ratelimit-remaining-tokens": "1000"}
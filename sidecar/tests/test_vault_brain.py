@@ -299,3 +299,28 @@ class TestCommandRegistry:
         assert len(brain.events._subscribers["test.evt"]) == 0
 
 
+
+    @pytest.mark.asyncio
+    async def test_apply_config_live(self, brain):
+        """Live-applicable settings change in place; others need a restart."""
+        result = await brain.apply_config(config={
+            "tickInterval": 2,
+            "restrictPluginFs": True,
+            "mutedEventChannels": ["system:tick"],
+            "isolatePlugins": True,
+        })
+
+        assert sorted(result["applied"]) == ["mutedEventChannels", "restrictPluginFs", "tickInterval"]
+        assert result["requires_restart"] == ["isolatePlugins"]
+        assert brain.tick_interval == 2.0
+        assert brain.fs_guard.restrict is True
+        assert not brain.event_channels.is_enabled("system:tick")
+
+    @pytest.mark.asyncio
+    async def test_apply_config_rejects_bad_values(self, brain):
+        """Invalid values are reported per key and leave settings unchanged."""
+        result = await brain.apply_config(config={"tickInterval": 0, "logLevel": "LOUD"})
+
+        assert result["status"] == "error"
+        assert set(result["errors"]) == {"tickInterval", "logLevel"}
+        assert brain.tick_interval == constants.DEFAULT_TICK_INTERVAL
@@ -0,0 +1,69 @@
+import asyncio
+import json
+import time
+
+import pytest
+
+from sidecar.event_bus import EventBus
+from sidecar.services.watchdog import LoopWatchdog, WATCHDOG_FILE, plugin_of_handler
+
+
+class FakePlugin:
+    name = "slow_plugin"
+
+    def __init__(self, watchdog):
+        self.watchdog = watchdog
+        self.seen = None
+
+    async def on_event(self):
+        self.seen = self.watchdog.snapshot()
+
+
+def test_track_records_active_and_last_callback(tmp_path):
+    watchdog = LoopWatchdog(tmp_path)
+    with watchdog.track("memory", "memory.save"):
+        active = watchdog.snapshot()["active_callbacks"]
+        assert [(a["plugin"], a["callback"]) for a in active] == [("memory", "memory.save")]
+
+    snapshot = watchdog.snapshot()
+    assert snapshot["active_callbacks"] == []
+    assert snapshot["last_entered"]["plugin"] == "memory"
+
+
+def test_untracked_without_plugin(tmp_path):
+    watchdog = LoopWatchdog(tmp_path)
+    with watchdog.track(None, "system.ping"):
+        pass
+    assert watchdog.snapshot()["last_entered"] is None
+
+
+@pytest.mark.asyncio
+async def test_event_bus_attributes_handler_to_plugin(tmp_path):
+    watchdog = LoopWatchdog(tmp_path)
+    bus = EventBus()
+    bus.watchdog = watchdog
+    plugin = FakePlugin(watchdog)
+    bus.subscribe("test.event", plugin.on_event)
+
+    await bus.publish("test.event")
+
+    assert plugin_of_handler(plugin.on_event) == "slow_plugin"
+    assert plugin.seen["active_callbacks"][0]["plugin"] == "slow_plugin"
+    assert plugin.seen["active_callbacks"][0]["callback"] == "test.event"
+
+
+def test_writer_thread_reports_blocked_loop(tmp_path):
+    watchdog = LoopWatchdog(tmp_path)
+    watchdog.last_tick = time.monotonic() - 30
+    watchdog.start()
+    try:
+        path = tmp_path / ".tailor" / WATCHDOG_FILE
+        deadline = time.time() + 5
+        while not path.exists() and time.time() < deadline:
+            time.sleep(0.1)
+        data = json.loads(path.read_text())
+    finally:
+        watchdog.stop()
+
+    assert data["loop_lag_seconds"] >= 30
+    assert data["pid"] > 0
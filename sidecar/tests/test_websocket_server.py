@@ -148,3 +148,109 @@ class TestWebSocketServer:
         # Should have set connection, then cleared it in finally block
         assert server.connection is None
 
+
+    @pytest.mark.asyncio
+    async def test_cancel_request_aborts_in_flight_command(self, server):
+        """A cancel_request notification stops the running command and answers it."""
+        started = asyncio.Event()
+
+        async def slow_command(method, **params):
+            started.set()
+            await asyncio.sleep(30)
+            return {"status": "ok"}
+
+        mock_brain = MagicMock()
+        mock_brain.execute_command = slow_command
+
+        with patch.dict('sys.modules', {'sidecar.vault_brain': MagicMock(VaultBrain=MagicMock(get=MagicMock(return_value=mock_brain)))}):
+            server.connection = Mock()
+            server.connection.send = AsyncMock()
+
+            request = utils.build_request("llm.slow", request_id="rust_7")
+            task = asyncio.create_task(server.handle_message(json.dumps(request)))
+            await started.wait()
+
+            cancel = {"jsonrpc": "2.0", "method": constants.CANCEL_REQUEST_METHOD, "params": {"id": "rust_7"}}
+            await server.handle_message(json.dumps(cancel))
+            await asyncio.wait_for(task, 1)
+
+            server.connection.send.assert_called_once()
+            response = json.loads(server.connection.send.call_args[0][0])
+            assert response["id"] == "rust_7"
+            assert response["error"]["code"] == constants.JSONRPC_REQUEST_CANCELLED
+            assert server.in_flight == {}
+
+    @pytest.mark.asyncio
+    async def test_cancel_request_unknown_id(self, server):
+        """Cancelling a request that already finished is a no-op."""
+        server.connection = Mock()
+        server.connection.send = AsyncMock()
+
+        assert server.cancel_request("rust_404") is False
+        cancel = {"jsonrpc": "2.0", "method": constants.CANCEL_REQUEST_METHOD, "params": {"id": "rust_404"}}
+        await server.handle_message(json.dumps(cancel))
+
+        server.connection.send.assert_not_called()
+
+    @pytest.mark.asyncio
+    async def test_drain_reports_requests_still_running_and_rejects_new_ones(self, server):
+        """drain() waits for in-flight work, names what outlasted it, and turns new requests away."""
+        started = asyncio.Event()
+
+        async def slow_command(method, **params):
+            started.set()
+            await asyncio.sleep(30)
+            return {"status": "ok"}
+
+        mock_brain = MagicMock()
+        mock_brain.execute_command = slow_command
+
+        with patch.dict('sys.modules', {'sidecar.vault_brain': MagicMock(VaultBrain=MagicMock(get=MagicMock(return_value=mock_brain)))}):
+            server.connection = Mock()
+            server.connection.send = AsyncMock()
+
+            request = utils.build_request("llm.slow", request_id="rust_8")
+            task = asyncio.create_task(server.handle_message(json.dumps(request)))
+            await started.wait()
+
+            assert await server.drain(0.05) == ["llm.slow"]
+
+            late = utils.build_request("llm.slow", request_id="rust_9")
+            await server.handle_message(json.dumps(late))
+            response = json.loads(server.connection.send.call_args[0][0])
+            assert response["id"] == "rust_9"
+            assert response["error"]["code"] == constants.JSONRPC_SHUTTING_DOWN
+
+            task.cancel()
+
+    @pytest.mark.asyncio
+    async def test_streamed_request_emits_chunks_then_final_result(self, server):
+        """A request with stream_channel sends its chunks and result on that channel."""
+        from sidecar.services import streaming
+
+        sent = []
+        streaming.configure_stream_sender(lambda event_type, data: sent.append((event_type, data)))
+
+        async def streaming_command(method, **params):
+            assert streaming.STREAM_CHANNEL_PARAM not in params
+            streaming.emit_chunk({"token": "Hel"})
+            streaming.emit_chunk({"token": "lo"})
+            return {"status": "success"}
+
+        mock_brain = MagicMock()
+        mock_brain.execute_command = streaming_command
+
+        try:
+            with patch.dict('sys.modules', {'sidecar.vault_brain': MagicMock(VaultBrain=MagicMock(get=MagicMock(return_value=mock_brain)))}):
+                server.connection = Mock()
+                server.connection.send = AsyncMock()
+
+                request = utils.build_request("llm.stream", {"stream_channel": "ch1"}, request_id="rust_10")
+                await server.handle_message(json.dumps(request))
+        finally:
+            streaming.configure_stream_sender(None)
+
+        assert [data["data"] for _, data in sent] == [{"token": "Hel"}, {"token": "lo"}, {"status": "success"}]
+        assert [data["final"] for _, data in sent] == [False, False, True]
+        assert all(event == constants.EventType.STREAM_CHUNK and data["channel_id"] == "ch1" for event, data in sent)
+        assert streaming.emit_chunk({"token": "late"}) is False
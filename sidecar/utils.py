@@ -6,11 +6,13 @@ Includes:
 - Logging Configuration
 - JSON-RPC Utilities
 - Path Utilities
+- Command Introspection
 - ID Generation
 """
 
-from typing import Any, Dict, Optional, List
+from typing import Any, Callable, Dict, Optional, List, Tuple
 from pathlib import Path
+import inspect
 import os
 import sys
 import time
@@ -18,6 +20,8 @@ import json
 
 import random
 import string
+import types
+import typing
 from . import constants
 from . import exceptions
 
@@ -27,6 +31,11 @@ from . import exceptions
 
 from loguru import logger
 
+LOG_LEVELS = ("TRACE", "DEBUG", "INFO", "SUCCESS", "WARNING", "ERROR", "CRITICAL")
+
+# File handler target, kept so the level can be changed later
+_log_file: Optional[Path] = None
+
 def configure_logging(
     level: Optional[str] = None,
     log_file: Optional[Path] = None,
@@ -35,6 +44,9 @@ def configure_logging(
     """
     Configure logging using Loguru.
     """
+    global _log_file
+    _log_file = log_file
+
     # Remove default handler
     logger.remove()
     
@@ -85,6 +97,17 @@ def configure_logging(
     logger.info(f"Logging configured at {log_level} level")
 
 
+def set_log_level(level: str) -> str:
+    """Re-create the log handlers at ``level``; returns the normalized level."""
+    log_level = str(level).strip().upper()
+    if log_level == "WARN":
+        log_level = "WARNING"
+    if log_level not in LOG_LEVELS:
+        raise ValueError(f"Unknown log level '{level}'; expected one of {', '.join(LOG_LEVELS)}")
+    configure_logging(level=log_level, log_file=_log_file)
+    return log_level
+
+
 
 
 
@@ -287,6 +310,166 @@ def validate_plugin_structure(plugin_dir: Path) -> None:
             f"{constants.PLUGIN_MAIN_FILE} is not a file"
         )
 
+def _version_tuple(version: str) -> List[int]:
+    parts = []
+    for piece in version.strip().split("."):
+        digits = ""
+        for ch in piece:
+            if not ch.isdigit():
+                break
+            digits += ch
+        if not digits:
+            break
+        parts.append(int(digits))
+    if not parts:
+        raise ValueError(f"Invalid version: {version!r}")
+    return parts
+
+
+def python_version_satisfies(spec: str, version: Optional[str] = None) -> bool:
+    """
+    Check a ``python_requires`` specifier (e.g. ``">=3.10,<3.14"``) against
+    a version, defaulting to the running interpreter.
+
+    Supports ``>=``, ``<=``, ``>``, ``<``, ``==``, ``!=`` (with ``.*``) and ``~=``.
+    Raises ValueError for a malformed specifier.
+    """
+    if version is None:
+        version = ".".join(str(part) for part in sys.version_info[:3])
+    current = _version_tuple(version)
+
+    for clause in spec.split(","):
+        clause = clause.strip()
+        if not clause:
+            continue
+        op = next((o for o in ("~=", "==", "!=", ">=", "<=", ">", "<") if clause.startswith(o)), None)
+        if op is None:
+            raise ValueError(f"Invalid python_requires clause: {clause!r}")
+        target_text = clause[len(op):].strip()
+
+        if target_text.endswith(".*") and op in ("==", "!="):
+            prefix = _version_tuple(target_text[:-2])
+            matches = current[:len(prefix)] == prefix
+            if matches != (op == "=="):
+                return False
+            continue
+
+        target = _version_tuple(target_text)
+        width = max(len(current), len(target))
+        lhs = current + [0] * (width - len(current))
+        rhs = target + [0] * (width - len(target))
+
+        if op == "~=":
+            if len(target) < 2:
+                raise ValueError(f"~= needs at least two version parts: {clause!r}")
+            ok = lhs >= rhs and current[:len(target) - 1] == target[:-1]
+        else:
+            ok = {
+                "==": lhs == rhs,
+                "!=": lhs != rhs,
+                ">=": lhs >= rhs,
+                "<=": lhs <= rhs,
+                ">": lhs > rhs,
+                "<": lhs < rhs,
+            }[op]
+        if not ok:
+            return False
+
+    return True
+
+
+def check_plugin_python_requires(plugin_dir: Path) -> None:
+    """Refuse a plugin whose plugin.json ``python_requires`` excludes this interpreter."""
+    manifest_file = plugin_dir / "plugin.json"
+    if not manifest_file.exists():
+        return
+    try:
+        manifest = json.loads(manifest_file.read_text(encoding="utf-8"))
+    except (OSError, json.JSONDecodeError):
+        return
+
+    required = manifest.get("python_requires") if isinstance(manifest, dict) else None
+    if not required:
+        return
+
+    current = ".".join(str(part) for part in sys.version_info[:3])
+    try:
+        compatible = python_version_satisfies(str(required), current)
+    except ValueError as e:
+        raise exceptions.PluginLoadError(plugin_dir.name, f"Invalid python_requires: {e}")
+    if not compatible:
+        raise exceptions.PluginLoadError(
+            plugin_dir.name,
+            f"Requires Python {required}, but the sidecar runs Python {current}"
+        )
+
+# =============================================================================
+# Command Introspection
+# =============================================================================
+
+# JSON types for the annotations commands commonly use; others go unchecked
+_JSON_TYPES = {
+    str: "string",
+    int: "integer",
+    float: "number",
+    bool: "boolean",
+    list: "array",
+    dict: "object",
+}
+
+
+def _json_type(annotation: Any) -> Tuple[Optional[str], bool]:
+    """JSON type name for an annotation and whether it allows null."""
+    if annotation is None:
+        return None, False
+    origin = typing.get_origin(annotation)
+    if origin in (typing.Union, getattr(types, "UnionType", None)):
+        args = typing.get_args(annotation)
+        non_null = [a for a in args if a is not type(None)]
+        nullable = len(non_null) < len(args)
+        if len(non_null) == 1:
+            return _json_type(non_null[0])[0], nullable
+        return None, nullable
+    return _JSON_TYPES.get(origin or annotation), False
+
+
+def describe_handler(handler: Callable) -> Dict[str, Any]:
+    """
+    Describe the parameters a command handler accepts.
+
+    Each parameter has a JSON ``type`` (None when unannotated or not a
+    JSON type), whether it is ``required`` and whether it is ``nullable``.
+    ``annotated`` is False when no parameter has a type annotation.
+    """
+    target = inspect.unwrap(getattr(handler, "__func__", handler))
+    try:
+        hints = typing.get_type_hints(target)
+    except Exception:
+        hints = getattr(target, "__annotations__", {})
+
+    params = []
+    accepts_extra = False
+    for param in inspect.signature(handler).parameters.values():
+        if param.kind == param.VAR_KEYWORD:
+            accepts_extra = True
+            continue
+        if param.kind in (param.VAR_POSITIONAL, param.POSITIONAL_ONLY):
+            continue
+        json_type, nullable = _json_type(hints.get(param.name))
+        params.append({
+            "name": param.name,
+            "type": json_type,
+            "annotated": param.name in hints,
+            "required": param.default is param.empty,
+            "nullable": nullable or param.default is None,
+        })
+
+    return {
+        "params": params,
+        "accepts_extra": accepts_extra,
+        "annotated": any(p["annotated"] for p in params),
+    }
+
 def ensure_directory(path: Path, create: bool = True) -> Path:
     """Ensure a directory exists, optionally creating it."""
     resolved = path.resolve()
@@ -6,10 +6,14 @@ Acts as the central Event/Command hub.
 """
 
 import asyncio
+import os
 import json
+import re
 import importlib.util
 import time
 import inspect
+import sys
+import traceback
 from pathlib import Path
 from typing import Dict, Any, Optional, Callable, Awaitable, List
 from collections import defaultdict
@@ -22,8 +26,16 @@ from .decorators import command, on_event
 from .pipeline import DefaultPipeline, GraphPipeline, PipelineConfig
 from .plugin_installer import PluginInstaller
 from .services.keyring_service import get_keyring_service, KeyringService, PROVIDERS
+from .services import provider_keys
 from .services.llm_service import get_llm_service, LLMService, reset_llm_service
-from .event_bus import EventBus
+from .services.provider_resilience import CircuitOpenError, ResilienceSettings
+from .services.plugin_fs_guard import PluginFsGuard
+from .services.watchdog import LoopWatchdog
+from .services.semantic_index import SemanticIndex, SemanticIndexError
+from .services.plugin_diagnostics import PluginDiagnostics, state_snapshot
+from .services.request_budget import configure_request_budget
+from .services.streaming import configure_stream_sender, emit_chunk
+from .event_bus import EventBus, ConcurrencyLimiter, EventChannels
 
 # Local import avoids circular dependency in type checking if used carefully
 # from .api.plugin_base import PluginBase
@@ -61,10 +73,38 @@ class VaultBrain:
             raise RuntimeError("VaultBrain has not been initialized yet.")
         return cls._instance
 
-    def __init__(self, vault_path: Path, ws_server: Any):
+    def __init__(
+        self,
+        vault_path: Path,
+        ws_server: Any,
+        plugin_concurrency: Optional[int] = None,
+        muted_event_channels: Optional[List[str]] = None,
+        restrict_plugin_fs: bool = False,
+        only_plugins: Optional[List[str]] = None,
+        lifecycle_events: bool = False,
+        request_budget: bool = False,
+        shared_plugins_dir: Optional[Path] = None,
+        tick_interval: Optional[float] = None,
+    ):
         """
         Initialize VaultBrain instance.
         
+        Args:
+            vault_path: Path to the vault root
+            ws_server: WebSocket server used to reach the frontend
+            plugin_concurrency: Max concurrently running plugin callbacks
+                (None for unlimited)
+            muted_event_channels: Event channels not to emit to the frontend
+            restrict_plugin_fs: Block plugin writes outside the vault
+                (they are always recorded)
+            only_plugins: Load just these plugins (None loads every enabled
+                plugin). Used by per-plugin worker processes.
+            lifecycle_events: Emit PLUGIN_LIFECYCLE trace events (debug)
+            request_budget: Ask the host for a permit before provider calls
+            shared_plugins_dir: Plugins shared across vaults; a vault loads
+                the ones named in its ``sharedPlugins`` list
+            tick_interval: Seconds between TICK events (None for the default)
+        
         Note: Heavy initialization happens in self.initialize()
         """
         # Prevent re-initialization if already initialized
@@ -75,10 +115,18 @@ class VaultBrain:
         self.vault_path = utils.validate_vault_path(vault_path)
         
         self.plugins: Dict[str, Any] = {}
+        # Long-running plugin operations by id (latest checkpoint of each)
+        self.operations: Dict[str, Dict[str, Any]] = {}
         self.commands: Dict[str, Dict[str, Any]] = {}
         
         # Internal Event Bus
         self.events = EventBus()
+        self.events.limiter = ConcurrencyLimiter(plugin_concurrency)
+        # Evidence for diagnosing a blocked loop, written from a thread
+        self.watchdog = LoopWatchdog(self.vault_path)
+        self.events.watchdog = self.watchdog
+        # Frontend event channels (trigger_event types) and which are muted
+        self.event_channels = EventChannels(muted_event_channels)
         # Deprecated: direct access to subscribers, kept for safety if needed but ideally unused
         # self.subscribers is now managed by self.events
         
@@ -92,6 +140,30 @@ class VaultBrain:
         
         # Plugin Installer
         self.plugin_installer = PluginInstaller(self.vault_path)
+
+        # Records (and optionally blocks) plugin writes outside the vault
+        self.fs_guard = PluginFsGuard(self.vault_path, restrict=restrict_plugin_fs)
+
+        self.only_plugins = set(only_plugins) if only_plugins is not None else None
+
+        self.shared_plugins_dir = Path(shared_plugins_dir) if shared_plugins_dir else None
+        if self.shared_plugins_dir:
+            self.fs_guard.add_plugin_root(self.shared_plugins_dir)
+
+        # Init timings and last errors for plugin support bundles
+        self.diagnostics = PluginDiagnostics()
+
+        # Fine-grained plugin trace for developers; off unless asked for
+        self.lifecycle_events = lifecycle_events
+        self.events.observer = self._observe_handler
+
+        # Provider calls wait for a permit from the host's app-wide budget
+        self.request_budget = configure_request_budget(self._send_to_host if request_budget else None)
+        # Chunks of host-requested streams (stream_from_sidecar)
+        configure_stream_sender(self._send_to_host)
+
+        # Read on every tick, so system.apply_config can change it live
+        self.tick_interval = tick_interval or constants.DEFAULT_TICK_INTERVAL
         
         self._initialized = True
         logger.info(f"VaultBrain Singleton created for: {self.vault_path}")
@@ -137,6 +209,7 @@ class VaultBrain:
         
         # Initialize Keyring Service and set API key env vars
         self._keyring = get_keyring_service()
+        self._keyring.heal_provider_keys()
         self._keyring.set_env_vars()
         
         # Initialize LLM Service
@@ -164,6 +237,7 @@ class VaultBrain:
         # - CoreEvents.FILE_DELETED
 
         # Load Plugins (Phase 1: Discovery & Registration)
+        self.fs_guard.install()
         self._load_plugins()
         
         logger.info(
@@ -178,6 +252,7 @@ class VaultBrain:
         self._register_decorated_handlers()
         
         await self.publish(constants.CoreEvents.ALL_PLUGINS_LOADED)
+        self._emit_provider_key_status()
         logger.info("VaultBrain fully initialized and ready.")
 
     def _register_decorated_handlers(self) -> None:
@@ -207,9 +282,12 @@ class VaultBrain:
         for name, plugin in list(self.plugins.items())[::-1]:
             try:
                 await plugin.on_unload()
+                self._lifecycle(name, "unloaded")
             except Exception as e:
                 logger.error(f"Error unloading plugin {name}: {e}")
-                
+        
+        self.fs_guard.uninstall()
+        self.watchdog.stop()
         logger.info("VaultBrain shutdown complete.")
 
     # =========================================================================
@@ -222,103 +300,149 @@ class VaultBrain:
         Instantiates plugins and calls register_commands().
         Side-effect free (no active code execution).
         """
-        plugins_dir = utils.get_plugins_dir(self.vault_path)
-    
-        if not plugins_dir:
-            logger.info("No plugins found in vault")
-            return
-    
-        logger.debug(f"Scanning plugins directory: {plugins_dir}")
-    
-        plugin_dirs = []
-        for item in plugins_dir.iterdir():
-            if item.is_file():
-                continue
-            if item.name.startswith(('.', '_')):
-                continue
-        
-            main_file = item / constants.PLUGIN_MAIN_FILE
-            if main_file.exists():
-                plugin_dirs.append(item)
-
-        plugin_dirs = sorted(plugin_dirs, key=lambda p: p.name)
-    
+        plugin_dirs = self._discover_plugin_dirs()
         if not plugin_dirs:
             logger.info("No plugins found in vault")
             return
         
-        loaded_count = 0
         for plugin_dir in plugin_dirs:
             plugin_name = plugin_dir.name
-            # 1. Load defaults from settings.json (if exists)
-            defaults = {}
-            settings_path = plugin_dir / "settings.json"
-            if settings_path.exists():
-                try:
-                    defaults = json.loads(settings_path.read_text(encoding="utf-8"))
-                except Exception as e:
-                    logger.error(f"Failed to load settings.json for plugin '{plugin_name}': {e}")
-            
-            # 2. Get Overrides from .vault.json (Global Config)
-            # Structure: { "plugins": { "plugin_name": { "enabled": true, "param": 123 } } }
-            vault_apps_config = self.config.get("plugins", {})
-            # Handle both "plugins.plugin_name" direct object OR "plugins.enabled" list style legacy
-            # We assume dictionary structure for overrides: "plugins": { "explorer": {...} }
+            final_config = self._plugin_config(plugin_dir)
             
-            overrides = vault_apps_config.get(plugin_name, {})
-            if not isinstance(overrides, dict):
-                # Fallback if config is malformed or just a list
-                overrides = {}
-
-            # 3. Merge Configs (Override > Default)
-            final_config = defaults.copy()
-            final_config.update(overrides)
-            
-            # 4. Check Enablement (Default to False if not present in either)
-            is_enabled = final_config.get("enabled", False)
-            
-            if not is_enabled:
+            # Check Enablement (Default to False if not present in either)
+            if not final_config.get("enabled", False):
                 logger.debug(f"Plugin '{plugin_name}' is disabled, skipping")
                 continue
-            
+
+            if self.only_plugins is not None and plugin_name not in self.only_plugins:
+                logger.debug(f"Plugin '{plugin_name}' runs in another process, skipping")
+                continue
+
             try:
-                utils.validate_plugin_structure(plugin_dir)
-                
-                # Load module
-                main_file = plugin_dir / "main.py"
-                spec = importlib.util.spec_from_file_location(plugin_name, main_file)
-                if not spec or not spec.loader:
-                    raise exceptions.PluginLoadError(plugin_name, "Failed to create module spec")
-                
-                module = importlib.util.module_from_spec(spec)
-                spec.loader.exec_module(module)
-                
-                if not hasattr(module, constants.PLUGIN_CLASS_NAME):
-                    raise exceptions.PluginLoadError(plugin_name, f"No '{constants.PLUGIN_CLASS_NAME}' class found")
-                
-                # Instantiate (Fresh Init, no args passed mostly)
-                plugin_class = getattr(module, constants.PLUGIN_CLASS_NAME)
-                
-                # Pass resolved config to plugin
-                plugin = plugin_class(
-                    plugin_dir=plugin_dir,
-                    vault_path=self.vault_path,
-                    config=final_config
-                )
-                
-                # Phase 1: Register commands and hooks
-                plugin.register_commands()
-                
-                # Call register_hooks if plugin has it
-                if hasattr(plugin, 'register_hooks'):
-                    plugin.register_hooks()
-                
-                self.plugins[plugin_name] = plugin
-                logger.info(f"Plugin '{plugin_name}' loaded. Config: {final_config}")
-            
+                self._instantiate_plugin(plugin_dir, final_config)
             except Exception as e:
+                self.diagnostics.record_error(plugin_name, "load", e)
                 logger.exception(f"Failed to load plugin '{plugin_name}': {e}")
 
+    def _discover_plugin_dirs(self) -> List[Path]:
+        """Plugin folders with a main.py: the vault's own, then listed shared ones."""
+        plugins_dir = utils.get_plugins_dir(self.vault_path)
+    
+        plugin_dirs = []
+        if plugins_dir:
+            logger.debug(f"Scanning plugins directory: {plugins_dir}")
+            for item in plugins_dir.iterdir():
+                if item.is_file():
+                    continue
+                if item.name.startswith(('.', '_')):
+                    continue
+            
+                main_file = item / constants.PLUGIN_MAIN_FILE
+                if main_file.exists():
+                    plugin_dirs.append(item)
+
+        plugin_dirs.extend(self._shared_plugin_dirs({p.name for p in plugin_dirs}))
+        return sorted(plugin_dirs, key=lambda p: p.name)
+
+    def _plugin_config(self, plugin_dir: Path) -> Dict[str, Any]:
+        """A plugin's settings.json defaults overlaid with its .vault.json overrides."""
+        plugin_name = plugin_dir.name
+        # 1. Load defaults from settings.json (if exists)
+        defaults = {}
+        settings_path = plugin_dir / "settings.json"
+        if settings_path.exists():
+            try:
+                defaults = json.loads(settings_path.read_text(encoding="utf-8"))
+            except Exception as e:
+                logger.error(f"Failed to load settings.json for plugin '{plugin_name}': {e}")
+        
+        # 2. Get Overrides from .vault.json (Global Config)
+        # Structure: { "plugins": { "plugin_name": { "enabled": true, "param": 123 } } }
+        vault_apps_config = self.config.get("plugins", {})
+        # Handle both "plugins.plugin_name" direct object OR "plugins.enabled" list style legacy
+        # We assume dictionary structure for overrides: "plugins": { "explorer": {...} }
+        
+        overrides = vault_apps_config.get(plugin_name, {})
+        if not isinstance(overrides, dict):
+            # Fallback if config is malformed or just a list
+            overrides = {}
+
+        # 3. Merge Configs (Override > Default)
+        final_config = defaults.copy()
+        final_config.update(overrides)
+        return final_config
+
+    def _instantiate_plugin(self, plugin_dir: Path, final_config: Dict[str, Any]) -> Any:
+        """Import a plugin's main.py, create its Plugin and register it; raises on failure."""
+        plugin_name = plugin_dir.name
+        # Paths outside the vault this plugin may write to
+        self.fs_guard.grant(plugin_name, final_config.get("fsGrants", []))
+        
+        started = time.monotonic()
+        utils.validate_plugin_structure(plugin_dir)
+        utils.check_plugin_python_requires(plugin_dir)
+        
+        # Load module
+        main_file = plugin_dir / "main.py"
+        spec = importlib.util.spec_from_file_location(plugin_name, main_file)
+        if not spec or not spec.loader:
+            raise exceptions.PluginLoadError(plugin_name, "Failed to create module spec")
+        
+        module = importlib.util.module_from_spec(spec)
+        spec.loader.exec_module(module)
+        
+        if not hasattr(module, constants.PLUGIN_CLASS_NAME):
+            raise exceptions.PluginLoadError(plugin_name, f"No '{constants.PLUGIN_CLASS_NAME}' class found")
+        
+        # Instantiate (Fresh Init, no args passed mostly)
+        plugin_class = getattr(module, constants.PLUGIN_CLASS_NAME)
+        
+        # Pass resolved config to plugin
+        plugin = plugin_class(
+            plugin_dir=plugin_dir,
+            vault_path=self.vault_path,
+            config=final_config
+        )
+        
+        # Phase 1: Register commands and hooks
+        plugin.register_commands()
+        
+        # Call register_hooks if plugin has it
+        if hasattr(plugin, 'register_hooks'):
+            plugin.register_hooks()
+        
+        self.plugins[plugin_name] = plugin
+        self.diagnostics.record_timing(plugin_name, "load", _elapsed_ms(started))
+        logger.info(f"Plugin '{plugin_name}' loaded. Config: {final_config}")
+        return plugin
+
+    def _shared_plugin_dirs(self, local: set) -> List[Path]:
+        """
+        Shared plugins named in ``sharedPlugins``, except those the vault
+        overrides with a local plugin of the same name.
+        """
+        names = self.config.get(constants.SHARED_PLUGINS_SETTING) or []
+        if not isinstance(names, list) or not names:
+            return []
+        if not self.shared_plugins_dir:
+            logger.warning(f"Vault lists shared plugins {names} but no shared plugins directory is configured")
+            return []
+
+        dirs = []
+        for name in names:
+            if not isinstance(name, str) or not name or name.startswith(('.', '_')) or "/" in name or "\\" in name:
+                logger.warning(f"Ignoring invalid shared plugin name: {name!r}")
+                continue
+            if name in local:
+                logger.info(f"Local plugin '{name}' overrides the shared plugin of the same name")
+                continue
+            plugin_dir = self.shared_plugins_dir / name
+            if not (plugin_dir / constants.PLUGIN_MAIN_FILE).exists():
+                logger.warning(f"Shared plugin '{name}' not found in {self.shared_plugins_dir}")
+                continue
+            dirs.append(plugin_dir)
+        return dirs
+
     async def _activate_plugins(self):
         """
         Phase 2: Activation.
@@ -326,8 +450,10 @@ class VaultBrain:
         """
         logger.info("Activating plugins (calling on_load)...")
         for plugin_name, plugin in self.plugins.items():
+            started = time.monotonic()
             try:
                 await plugin.on_load()
+                self.diagnostics.record_timing(plugin_name, "activate", _elapsed_ms(started))
                 
                 # Auto-subscribe to TICK if plugin overrides on_tick
                 # We check if the method is different from the base class implementation
@@ -340,8 +466,10 @@ class VaultBrain:
                 self.subscribe(constants.CoreEvents.TICK, plugin.on_tick)
                 
                 # Announce plugin loaded
+                self._lifecycle(plugin_name, "loaded")
                 await self.publish(constants.CoreEvents.PLUGIN_LOADED, plugin_name=plugin_name)
             except Exception as e:
+                self.diagnostics.record_error(plugin_name, "activate", e)
                 logger.exception(f"Error activating plugin '{plugin_name}': {e}")
 
     # =========================================================================
@@ -393,16 +521,26 @@ class VaultBrain:
         This method checks both locations for backward compatibility.
         """
         handler = None
+        owner = None
         
         if command_id in self.commands:
             handler = self.commands[command_id]["handler"]
+            owner = self.commands[command_id].get("plugin")
         
         if handler is None:
             all_commands = list(self.commands.keys())
             raise exceptions.CommandNotFoundError(command_id, all_commands)
         
+        plugin = owner if owner != constants.CORE_PLUGIN_NAME else None
+        self._lifecycle(plugin, "command_received", command=command_id)
+        started = time.monotonic()
         try:
-            result = await handler(**kwargs)
+            with self.watchdog.track(plugin, command_id):
+                result = await handler(**kwargs)
+            self._lifecycle(
+                plugin, "command_completed",
+                command=command_id, status="success", duration_ms=_elapsed_ms(started),
+            )
             
             # Emit command executed event (fire and forget)
             asyncio.create_task(self.publish(
@@ -413,9 +551,32 @@ class VaultBrain:
             ))
             return result
         except Exception as e:
+            self._lifecycle(
+                plugin, "command_completed",
+                command=command_id, status="error", error=str(e), duration_ms=_elapsed_ms(started),
+            )
+            if plugin:
+                self.diagnostics.record_error(plugin, f"command {command_id}", e)
             logger.exception(f"Command '{command_id}' failed: {e}")
             raise exceptions.CommandExecutionError(command_id, e)
 
+    def _lifecycle(self, plugin: Optional[str], phase: str, **details: Any) -> None:
+        """Emit a PLUGIN_LIFECYCLE trace event when lifecycle events are on."""
+        if not self.lifecycle_events or not plugin:
+            return
+        self.emit_to_frontend(
+            constants.EventType.PLUGIN_LIFECYCLE,
+            {"plugin": plugin, "phase": phase, "timestamp": time.time(), **details},
+        )
+
+    def _observe_handler(self, plugin: str, event: str, phase: str, details: Dict[str, Any]) -> None:
+        tb = details.pop("traceback", None)
+        if details.get("error"):
+            self.diagnostics.record_error(plugin, f"event {event}", details["error"], tb)
+        # Only ticks are traced; other event handlers would drown the log
+        if event == constants.CoreEvents.TICK:
+            self._lifecycle(plugin, f"tick_{phase}", **details)
+
     @command("system.client_ready", constants.CORE_PLUGIN_NAME)
     async def _client_ready_handler(self, **kwargs):
         """Handle client ready signal."""
@@ -463,6 +624,401 @@ class VaultBrain:
             "plugins": list(self.plugins.keys())
         }
 
+    @command("system.drain", constants.CORE_PLUGIN_NAME)
+    async def drain(self, timeout_ms: int = 10000, **kwargs) -> Dict[str, Any]:
+        """
+        Stop taking requests and wait up to ``timeout_ms`` for those in
+        flight, before the host shuts the sidecar down.
+        """
+        pending = await self.ws_server.drain(timeout_ms / 1000) if self.ws_server else []
+        busy = sorted({
+            entry["plugin"] for entry in self.watchdog.snapshot()["active_callbacks"]
+        })
+        return {
+            "status": "success",
+            "drained": not pending,
+            "pending": pending,
+            "busy_plugins": busy,
+        }
+
+    @command("system.shutdown", constants.CORE_PLUGIN_NAME)
+    async def shutdown_sidecar(self, **kwargs) -> Dict[str, Any]:
+        """Unload plugins so they can flush, then exit the process."""
+        asyncio.create_task(self._shutdown_and_exit())
+        return {"status": "success"}
+
+    async def _shutdown_and_exit(self) -> None:
+        # Give the acknowledgement a moment to go out first
+        await asyncio.sleep(0.1)
+        try:
+            await self.shutdown()
+        except Exception as e:
+            logger.error(f"Error during shutdown: {e}")
+        os._exit(0)
+
+    @command("system.get_plugin_concurrency", constants.CORE_PLUGIN_NAME)
+    async def get_plugin_concurrency(self, **kwargs) -> Dict[str, Any]:
+        """Report the plugin callback limit and current usage."""
+        limiter = self.events.limiter
+        return {
+            "status": "success",
+            "limit": limiter.limit,
+            "in_flight": limiter.in_flight,
+            "waiting": limiter.waiting,
+        }
+
+    @command("system.set_plugin_concurrency", constants.CORE_PLUGIN_NAME)
+    async def set_plugin_concurrency(self, limit: Optional[int] = None, **kwargs) -> Dict[str, Any]:
+        """Change the plugin callback limit live (None for unlimited, 1 for serial)."""
+        try:
+            await self.events.limiter.set_limit(limit)
+        except ValueError as e:
+            return {"status": "error", "error": str(e)}
+        logger.info(f"Plugin concurrency limit set to {limit}")
+        return await self.get_plugin_concurrency()
+
+    @command("system.apply_config", constants.CORE_PLUGIN_NAME)
+    async def apply_config(self, config: Optional[Dict[str, Any]] = None, **kwargs) -> Dict[str, Any]:
+        """
+        Apply a settings delta without restarting.
+
+        Keys are vault setting names (``logLevel``, ``tickInterval``,
+        ``maxConcurrentPluginCallbacks``, ``mutedEventChannels``,
+        ``restrictPluginFs``, ``debugPluginLifecycle``,
+        ``providerResilience``). Keys this process cannot change while
+        running come back in ``requires_restart``; rejected values in
+        ``errors``.
+        """
+        appliers = {
+            "logLevel": self._apply_log_level,
+            "tickInterval": self._apply_tick_interval,
+            "maxConcurrentPluginCallbacks": self._apply_plugin_concurrency,
+            "mutedEventChannels": self._apply_muted_channels,
+            "restrictPluginFs": self._apply_restrict_fs,
+            "debugPluginLifecycle": self._apply_lifecycle_events,
+            "providerResilience": self._apply_resilience,
+        }
+        applied: List[str] = []
+        requires_restart: List[str] = []
+        errors: Dict[str, str] = {}
+        for key, value in (config or {}).items():
+            apply = appliers.get(key)
+            if apply is None:
+                requires_restart.append(key)
+                continue
+            try:
+                await apply(value)
+            except (TypeError, ValueError) as e:
+                errors[key] = str(e)
+                continue
+            applied.append(key)
+
+        if applied:
+            logger.info(f"Applied config live: {', '.join(applied)}")
+        return {
+            "status": "error" if errors and not applied else "success",
+            "applied": applied,
+            "requires_restart": requires_restart,
+            "errors": errors,
+        }
+
+    async def _apply_log_level(self, value: Any) -> None:
+        utils.set_log_level(value)
+
+    async def _apply_tick_interval(self, value: Any) -> None:
+        if isinstance(value, bool) or not isinstance(value, (int, float)):
+            raise TypeError("tickInterval must be a number of seconds")
+        if value < constants.MIN_TICK_INTERVAL:
+            raise ValueError(f"tickInterval must be at least {constants.MIN_TICK_INTERVAL}s")
+        self.tick_interval = float(value)
+
+    async def _apply_plugin_concurrency(self, value: Any) -> None:
+        if value is not None and (isinstance(value, bool) or not isinstance(value, int)):
+            raise TypeError("maxConcurrentPluginCallbacks must be a whole number or null")
+        await self.events.limiter.set_limit(value)
+
+    async def _apply_muted_channels(self, value: Any) -> None:
+        if not isinstance(value, list) or not all(isinstance(c, str) for c in value):
+            raise TypeError("mutedEventChannels must be a list of channel names")
+        for channel in set(self.event_channels.muted) - set(value):
+            self.event_channels.set_enabled(channel, True)
+        for channel in value:
+            self.event_channels.set_enabled(channel, False)
+
+    async def _apply_restrict_fs(self, value: Any) -> None:
+        if not isinstance(value, bool):
+            raise TypeError("restrictPluginFs must be true or false")
+        self.fs_guard.restrict = value
+
+    async def _apply_lifecycle_events(self, value: Any) -> None:
+        if not isinstance(value, bool):
+            raise TypeError("debugPluginLifecycle must be true or false")
+        self.lifecycle_events = value
+
+    async def _apply_resilience(self, value: Any) -> None:
+        if not isinstance(value, dict):
+            raise TypeError("providerResilience must be an object")
+        result = await self.configure_provider_resilience(settings=value)
+        if result.get("status") != "success":
+            raise ValueError(result.get("error", "invalid resilience settings"))
+
+    @command("events.list_subscriptions", constants.CORE_PLUGIN_NAME)
+    async def list_event_subscriptions(self, **kwargs) -> Dict[str, Any]:
+        """Frontend event channels seen so far, with delivery counts."""
+        subscribers = len(self.ws_server.connections) if self.ws_server else 0
+        return {
+            "status": "success",
+            "subscribers": subscribers,
+            "channels": self.event_channels.snapshot(subscribers),
+        }
+
+    @command("events.set_subscription", constants.CORE_PLUGIN_NAME)
+    async def set_event_subscription(self, channel: str = "", enabled: bool = True, **kwargs) -> Dict[str, Any]:
+        """Mute or unmute a frontend event channel at the source."""
+        if not channel:
+            return {"status": "error", "error": "channel is required"}
+        self.event_channels.set_enabled(channel, bool(enabled))
+        logger.info(f"Event channel '{channel}' {'enabled' if enabled else 'muted'}")
+        return {"status": "success", "channel": channel, "enabled": bool(enabled), "muted": self.event_channels.muted}
+
+    @command("plugins.get_fs_access", constants.CORE_PLUGIN_NAME)
+    async def get_plugin_fs_access(self, plugin: str = "", **kwargs) -> Dict[str, Any]:
+        """Paths a plugin has written to, flagging those outside the vault."""
+        if not plugin:
+            return {"status": "error", "error": "plugin is required"}
+        return {"status": "success", **self.fs_guard.get_access(plugin)}
+
+    @command("plugins.get_support_info", constants.CORE_PLUGIN_NAME)
+    async def get_plugin_support_info(
+        self, plugin: str = "", include_content: bool = False, **kwargs
+    ) -> Dict[str, Any]:
+        """Config, init timings, last error and a state snapshot for one plugin.
+
+        The snapshot has attribute types only unless ``include_content``.
+        """
+        if not plugin:
+            return {"status": "error", "error": "plugin is required"}
+        instance = self.plugins.get(plugin)
+        last_error = self.diagnostics.last_error(plugin)
+        if instance is None and last_error is None:
+            return {"status": "error", "error": f"Plugin not loaded: {plugin}"}
+
+        if instance is not None:
+            config = instance.config
+        else:
+            config = self.config.get("plugins", {}).get(plugin, {})
+        return {
+            "status": "success",
+            "plugin": plugin,
+            "loaded": instance is not None,
+            "config": config,
+            "timings": self.diagnostics.timings(plugin),
+            "last_error": last_error,
+            "state": state_snapshot(instance, include_content) if instance is not None else {},
+        }
+
+    # =========================================================================
+    # Long-running Operations (checkpoint / resume)
+    # =========================================================================
+
+    def record_checkpoint(
+        self,
+        plugin_name: str,
+        operation_id: str,
+        checkpoint: Any,
+        progress: Optional[float] = None,
+        message: Optional[str] = None,
+        status: str = "running",
+    ) -> None:
+        """Store an operation's latest checkpoint and broadcast it.
+
+        The Rust side persists these events so ``operations.resume`` can be
+        driven after a sidecar or app restart.
+        """
+        operation = {
+            "operation_id": operation_id,
+            "plugin": plugin_name,
+            "vault_path": str(self.vault_path),
+            "checkpoint": checkpoint,
+            "progress": progress,
+            "message": message,
+            "status": status,
+            "updated": time.time(),
+        }
+        self.operations[operation_id] = operation
+        self.emit_to_frontend("operation.checkpoint", operation)
+
+    @command("operations.get_checkpoint", constants.CORE_PLUGIN_NAME)
+    async def get_operation_checkpoint(self, operation_id: str = "", **kwargs) -> Dict[str, Any]:
+        """Latest checkpoint reported for an operation in this sidecar session."""
+        operation = self.operations.get(operation_id)
+        if operation is None:
+            return {"status": "error", "error": f"Unknown operation: {operation_id}"}
+        return {"status": "success", "operation": operation}
+
+    @command("operations.resume", constants.CORE_PLUGIN_NAME)
+    async def resume_operation(
+        self,
+        operation_id: str = "",
+        plugin: str = "",
+        checkpoint: Any = None,
+        **kwargs
+    ) -> Dict[str, Any]:
+        """Hand a saved checkpoint back to its plugin and continue in the background."""
+        if not operation_id or not plugin:
+            return {"status": "error", "error": "operation_id and plugin are required"}
+
+        from .api.plugin_base import PluginBase
+
+        instance = self.plugins.get(plugin)
+        if instance is None:
+            return {"status": "error", "error": f"Plugin not loaded: {plugin}"}
+        if type(instance).resume_operation is PluginBase.resume_operation:
+            return {"status": "error", "error": f"Plugin '{plugin}' does not support resuming operations"}
+
+        async def run() -> None:
+            try:
+                await instance.resume_operation(operation_id, checkpoint)
+            except Exception as e:
+                logger.exception(f"Resuming operation '{operation_id}' failed: {e}")
+                self.record_checkpoint(plugin, operation_id, checkpoint, message=str(e), status="failed")
+
+        self.record_checkpoint(plugin, operation_id, checkpoint, message="Resuming")
+        asyncio.create_task(run())
+        return {"status": "success", "operation_id": operation_id, "resumed": True}
+
+    @command("system.list_commands", constants.CORE_PLUGIN_NAME)
+    async def list_commands(self, **kwargs) -> Dict[str, Any]:
+        """Registered command ids mapped to the plugin that owns them."""
+        return {
+            "status": "success",
+            "commands": {
+                command_id: info.get("plugin")
+                for command_id, info in self.commands.items()
+            },
+        }
+
+    @command("system.describe_plugin_methods", constants.CORE_PLUGIN_NAME)
+    async def describe_plugin_methods(self, plugin: str = "", **kwargs) -> Dict[str, Any]:
+        """Parameter signatures of the commands a loaded plugin registered."""
+        instance = self.plugins.get(plugin)
+        if instance is None:
+            return {"status": "error", "error": f"Plugin not loaded: {plugin}"}
+        return {
+            "status": "success",
+            "plugin": plugin,
+            # Changes whenever the plugin is loaded again
+            "instance_id": str(id(instance)),
+            "methods": {
+                command_id: utils.describe_handler(info["handler"])
+                for command_id, info in self.commands.items()
+                if info.get("plugin") == plugin
+            },
+        }
+
+    @command("plugins.reload", constants.CORE_PLUGIN_NAME)
+    async def reload_plugin(self, plugin: str = "", **kwargs) -> Dict[str, Any]:
+        """
+        Re-import one plugin's main.py and replace its instance, leaving the
+        rest of the vault running. A plugin that isn't loaded (e.g. its last
+        load failed) is loaded fresh. On failure the import traceback is
+        returned so syntax errors show up immediately.
+        """
+        plugin_dir = next((d for d in self._discover_plugin_dirs() if d.name == plugin), None)
+        if plugin_dir is None:
+            return {"status": "error", "plugin": plugin, "valid": False, "error": f"Plugin not found: {plugin}"}
+
+        # The host may have just enabled it in .vault.json
+        self.config = self._load_config()
+        final_config = self._plugin_config(plugin_dir)
+        if not final_config.get("enabled", False):
+            return {"status": "error", "plugin": plugin, "valid": True, "error": f"Plugin '{plugin}' is disabled"}
+
+        was_loaded = plugin in self.plugins
+        if was_loaded:
+            await self._unload_plugin(plugin)
+        # Helper modules the plugin imported would otherwise be reused as-is
+        self._purge_plugin_modules(plugin_dir)
+
+        try:
+            utils.validate_plugin_structure(plugin_dir)
+        except exceptions.PluginLoadError as e:
+            return {"status": "error", "plugin": plugin, "valid": False, "reloaded": was_loaded, "error": str(e)}
+
+        try:
+            instance = self._instantiate_plugin(plugin_dir, final_config)
+            await instance.on_load()
+            self.subscribe(constants.CoreEvents.TICK, instance.on_tick)
+        except Exception as e:
+            self._forget_plugin_handlers(plugin, self.plugins.pop(plugin, None))
+            self.diagnostics.record_error(plugin, "load", e)
+            logger.exception(f"Failed to reload plugin '{plugin}': {e}")
+            return {
+                "status": "error",
+                "plugin": plugin,
+                "valid": True,
+                "reloaded": was_loaded,
+                "error": str(e),
+                "traceback": traceback.format_exc(),
+            }
+
+        self._lifecycle(plugin, "loaded")
+        await self.publish(constants.CoreEvents.PLUGIN_LOADED, plugin_name=plugin)
+        logger.info(f"Plugin '{plugin}' {'reloaded' if was_loaded else 'loaded'}")
+        return {
+            "status": "success",
+            "plugin": plugin,
+            "valid": True,
+            "reloaded": was_loaded,
+            "commands": sorted(c for c, info in self.commands.items() if info.get("plugin") == plugin),
+        }
+
+    @command("plugins.unload", constants.CORE_PLUGIN_NAME)
+    async def unload_plugin(self, plugin: str = "", **kwargs) -> Dict[str, Any]:
+        """
+        Stop one loaded plugin (on_unload, then drop its commands and
+        handlers) without touching its files, before it is disabled or
+        uninstalled. `unloaded` is False if it wasn't loaded.
+        """
+        self.config = self._load_config()
+        if plugin not in self.plugins:
+            return {"status": "success", "plugin": plugin, "unloaded": False}
+        await self._unload_plugin(plugin)
+        await self.publish(constants.CoreEvents.PLUGIN_UNLOADED, plugin_name=plugin)
+        logger.info(f"Plugin '{plugin}' unloaded")
+        return {"status": "success", "plugin": plugin, "unloaded": True}
+
+    async def _unload_plugin(self, plugin_name: str) -> None:
+        """Unload one plugin and drop its commands and event handlers."""
+        instance = self.plugins.pop(plugin_name)
+        try:
+            await instance.on_unload()
+        except Exception as e:
+            logger.error(f"Error unloading plugin {plugin_name}: {e}")
+        self._forget_plugin_handlers(plugin_name, instance)
+        self._lifecycle(plugin_name, "unloaded")
+
+    @staticmethod
+    def _purge_plugin_modules(plugin_dir: Path) -> None:
+        """Drop modules imported from the plugin's folder so they are re-imported."""
+        root = plugin_dir.resolve()
+        for name, module in list(sys.modules.items()):
+            module_file = getattr(module, "__file__", None)
+            if module_file and Path(module_file).resolve().is_relative_to(root):
+                del sys.modules[name]
+
+    def _forget_plugin_handlers(self, plugin_name: str, instance: Any = None) -> None:
+        """Remove a plugin's commands and, given its instance, its event handlers."""
+        for command_id in [c for c, info in self.commands.items() if info.get("plugin") == plugin_name]:
+            self.unregister_command(command_id)
+        if instance is not None:
+            self.events.unsubscribe_owner(instance)
+
+    @command("system.ping", constants.CORE_PLUGIN_NAME)
+    async def ping(self, **kwargs) -> Dict[str, Any]:
+        """Liveness check used by the Rust side."""
+        return {"status": "ok", "timestamp": time.time()}
+
 
 
     # =========================================================================
@@ -491,6 +1047,7 @@ class VaultBrain:
             # Update environment variable
             self._keyring.set_env_vars()
             logger.info(f"Stored API key for {provider}")
+            self._emit_provider_key_status()
             return {"status": "success", "provider": provider}
         else:
             return {"status": "error", "error": "Failed to store API key"}
@@ -507,11 +1064,45 @@ class VaultBrain:
             return {"status": "error", "error": "provider is required"}
         
         success = self._keyring.delete_api_key(provider)
+        if success:
+            self._keyring.set_env_vars()
+            self._emit_provider_key_status()
         return {
             "status": "success" if success else "error",
             "provider": provider
         }
 
+    @command("settings.export_secrets", constants.CORE_PLUGIN_NAME)
+    async def export_secrets(
+        self, path: str = "", passphrase: str = "", format: str = "auto", **kwargs
+    ) -> Dict[str, Any]:
+        """Export stored API keys in the OS-native format, or an encrypted blob."""
+        from .services import secret_export
+
+        if not path:
+            return {"status": "error", "error": "path is required"}
+        try:
+            result = secret_export.export_secrets(self._keyring, Path(path), passphrase, format)
+        except secret_export.SecretExportError as e:
+            return {"status": "error", "error": str(e)}
+        return {"status": "success", **result}
+
+    @command("settings.import_secrets", constants.CORE_PLUGIN_NAME)
+    async def import_secrets(self, path: str = "", passphrase: str = "", **kwargs) -> Dict[str, Any]:
+        """Import API keys from a file written by ``settings.export_secrets``."""
+        from .services import secret_export
+
+        if not path:
+            return {"status": "error", "error": "path is required"}
+        try:
+            result = secret_export.import_secrets(self._keyring, Path(path), passphrase)
+        except secret_export.SecretExportError as e:
+            return {"status": "error", "error": str(e)}
+        if result["imported"]:
+            self._keyring.set_env_vars()
+            self._emit_provider_key_status()
+        return {"status": "success", **result}
+
     @command("settings.list_providers", constants.CORE_PLUGIN_NAME)
     async def list_providers(self, **kwargs) -> Dict[str, Any]:
         """List all providers and their configuration status."""
@@ -520,6 +1111,42 @@ class VaultBrain:
             "providers": self._keyring.get_provider_status()
         }
 
+    @command("settings.get_provider_key_status", constants.CORE_PLUGIN_NAME)
+    async def get_provider_key_status(self, **kwargs) -> Dict[str, Any]:
+        """
+        Stored keys against the providers the vault uses: the models set for
+        each category and the providers loaded plugins declare. Keys stored
+        under an alias ("gemini") are moved to their provider id first.
+        """
+        healed = self._keyring.heal_provider_keys()
+        return {"status": "success", **self._provider_key_status(), "healed": healed}
+
+    def _provider_key_status(self) -> Dict[str, Any]:
+        needs: Dict[str, set] = {}
+        for model in self._llm_service.get_category_config().values():
+            provider = provider_keys.provider_of_model(model)
+            if provider:
+                needs.setdefault(provider, set()).add(provider_keys.CORE_USER)
+        for name, plugin in self.plugins.items():
+            manifest = provider_keys.read_plugin_manifest(plugin.plugin_dir)
+            for provider in provider_keys.plugin_providers(manifest, self._llm_service.get_model_for_category):
+                needs.setdefault(provider, set()).add(name)
+
+        entries = provider_keys.key_status(
+            needs, self._keyring.list_configured_providers(), PROVIDERS, os.environ
+        )
+        return {
+            "providers": entries,
+            "missing": provider_keys.missing_key_messages(entries),
+        }
+
+    def _emit_provider_key_status(self) -> None:
+        """Tell the frontend the key status changed, after keys or plugins did."""
+        try:
+            self.emit_to_frontend(constants.EventType.PROVIDER_KEY_STATUS, self._provider_key_status())
+        except Exception as e:
+            logger.warning(f"Failed to compute provider key status: {e}")
+
     @command("settings.verify_api_key", constants.CORE_PLUGIN_NAME)
     async def verify_api_key(self, provider: str = "", **kwargs) -> Dict[str, Any]:
         """Verify an API key by making a test request."""
@@ -532,12 +1159,118 @@ class VaultBrain:
             return {"status": "error", "error": "provider is required"}
         
         result = await self._keyring.verify_api_key(provider)
+        circuit = self._llm_service.resilience.get_state(provider)
+        if circuit["state"] != "closed":
+            result.setdefault(
+                "warning",
+                f"Generations for '{provider}' are paused by the circuit breaker "
+                f"after {circuit['consecutive_failures']} consecutive failures"
+            )
         return {
             "status": "success" if result.get("valid") else "error",
             "provider": provider,
+            "circuit": circuit,
             **result
         }
 
+    @command("settings.get_provider_usage", constants.CORE_PLUGIN_NAME)
+    async def get_provider_usage(self, **kwargs) -> Dict[str, Any]:
+        """Requests, tokens, estimated cost and rate limits per provider this period."""
+        return {"status": "success", **self._llm_service.usage.snapshot()}
+
+    @command("settings.get_provider_circuit_state", constants.CORE_PLUGIN_NAME)
+    async def get_provider_circuit_state(self, provider: str = "", **kwargs) -> Dict[str, Any]:
+        """Circuit breaker state (closed/open/half_open) for a provider."""
+        if not provider:
+            return {"status": "error", "error": "provider is required"}
+        return {
+            "status": "success",
+            "circuit": self._llm_service.resilience.get_state(provider),
+            "settings": self._llm_service.resilience.settings.to_dict(),
+        }
+
+    @command("settings.reset_provider_circuit", constants.CORE_PLUGIN_NAME)
+    async def reset_provider_circuit(self, provider: str = "", **kwargs) -> Dict[str, Any]:
+        """Manually close a provider's circuit so calls go through again."""
+        if not provider:
+            return {"status": "error", "error": "provider is required"}
+        self._llm_service.resilience.reset(provider)
+        return {
+            "status": "success",
+            "circuit": self._llm_service.resilience.get_state(provider),
+        }
+
+    @command("settings.configure_provider_resilience", constants.CORE_PLUGIN_NAME)
+    async def configure_provider_resilience(self, settings: Optional[Dict[str, Any]] = None, **kwargs) -> Dict[str, Any]:
+        """Update retry/backoff/circuit settings live and save them to .vault.json."""
+        current = self._llm_service.resilience.settings.to_dict()
+        try:
+            updated = ResilienceSettings.from_config({**current, **(settings or {})})
+        except (TypeError, ValueError) as e:
+            return {"status": "error", "error": str(e)}
+
+        try:
+            config_path = utils.get_vault_config_path(self.vault_path)
+            config = self._load_config()
+            config.setdefault("llm", {})["resilience"] = updated.to_dict()
+            with open(config_path, "w", encoding="utf-8") as f:
+                json.dump(config, f, indent=4)
+            self.config = config
+        except Exception as e:
+            logger.error(f"Failed to save resilience settings: {e}")
+            return {"status": "error", "error": str(e)}
+
+        self._llm_service.resilience.settings = updated
+        logger.info(f"Provider resilience settings updated: {updated.to_dict()}")
+        return {"status": "success", "settings": updated.to_dict()}
+
+    def _semantic_unavailable(self, reason: str) -> Dict[str, Any]:
+        return {
+            "status": "error",
+            "code": "semantic_unavailable",
+            "error": f"Semantic search unavailable: {reason}",
+        }
+
+    @command("search.build_semantic_index", constants.CORE_PLUGIN_NAME)
+    async def build_semantic_index(self, **kwargs) -> Dict[str, Any]:
+        """Embed new and changed conversation messages into .tailor/embeddings/."""
+        model = self._llm_service.get_model_for_category("embedding")
+        if not model:
+            return self._semantic_unavailable("no embedding model is configured")
+
+        try:
+            stats = await SemanticIndex(self.vault_path).build(
+                model, lambda texts: self._llm_service.embed(texts, model=model)
+            )
+        except SemanticIndexError as e:
+            return {"status": "error", "error": str(e)}
+        except Exception as e:
+            logger.error(f"Semantic index build failed: {e}")
+            return self._semantic_unavailable(str(e))
+
+        logger.info(f"Semantic index built with {model}: {stats}")
+        return {"status": "success", "model": model, **stats}
+
+    @command("search.semantic_search", constants.CORE_PLUGIN_NAME)
+    async def semantic_search(self, query: str = "", top_k: int = 10, **kwargs) -> Dict[str, Any]:
+        """Messages and conversations closest in meaning to ``query``."""
+        if not query.strip():
+            return {"status": "error", "error": "query is required"}
+        model = self._llm_service.get_model_for_category("embedding")
+        if not model:
+            return self._semantic_unavailable("no embedding model is configured")
+
+        try:
+            query_vector = (await self._llm_service.embed([query], model=model))[0]
+        except Exception as e:
+            return self._semantic_unavailable(str(e))
+
+        try:
+            results = SemanticIndex(self.vault_path).search(model, query_vector, top_k)
+        except SemanticIndexError as e:
+            return {"status": "error", "error": str(e)}
+        return {"status": "success", "model": model, **results}
+
 
 
     @command("settings.get_available_models", constants.CORE_PLUGIN_NAME)
@@ -564,6 +1297,18 @@ class VaultBrain:
             "models": result
         }
 
+    @command("settings.normalize_model_id", constants.CORE_PLUGIN_NAME)
+    async def normalize_model_id(self, provider: str = "", model: str = "", **kwargs) -> Dict[str, Any]:
+        """Validate a model id and return its canonical form or close matches."""
+        if not model:
+            return {"status": "error", "error": "model is required"}
+
+        check = await self._llm_service.normalize_model_id(provider or None, model)
+        result = {"status": "success", **check.to_dict()}
+        if not check.valid:
+            result["error"] = check.error()
+        return result
+
     @command("settings.get_model_categories", constants.CORE_PLUGIN_NAME)
     async def get_model_categories(self, **kwargs) -> Dict[str, Any]:
         """Get current category configuration and category metadata."""
@@ -585,6 +1330,11 @@ class VaultBrain:
         
         if not category or not model:
             return {"status": "error", "error": "category and model are required"}
+
+        check = await self._llm_service.normalize_model_id(kwargs.get("provider"), model)
+        if not check.valid:
+            return {"status": "error", "error": check.error(), "suggestions": check.suggestions}
+        model = check.canonical
         
         # Update in-memory
         self._llm_service.set_category_model(category, model)
@@ -607,6 +1357,7 @@ class VaultBrain:
             self.config = config
             
             logger.info(f"Set model for category '{category}': {model}")
+            self._emit_provider_key_status()
             return {
                 "status": "success",
                 "category": category,
@@ -711,6 +1462,9 @@ class VaultBrain:
                 metadata = {}
                 if chat_id:
                     metadata["chat_id"] = chat_id
+                system_prompt = self._system_prompt_for(chat_id)
+                if system_prompt:
+                    metadata["system_prompt"] = system_prompt
                     
                 context = await self.pipeline.run(
                     message=message,
@@ -725,6 +1479,13 @@ class VaultBrain:
                     "model": context.metadata.get("model", "unknown"),
                     "usage": context.metadata.get("usage", {})
                 }
+        except CircuitOpenError as e:
+            logger.warning(f"Chat blocked by circuit breaker: {e}")
+            return {
+                "status": "error",
+                "error": str(e),
+                "circuit": self._llm_service.resilience.get_state(e.provider),
+            }
         except Exception as e:
             logger.error(f"Chat error: {e}")
             return {"status": "error", "error": str(e)}
@@ -735,7 +1496,8 @@ class VaultBrain:
         history: List[Dict[str, str]],
         category: str,
         stream_id: str,
-        chat_id: str = None
+        chat_id: str = None,
+        resume: Optional[Dict[str, Any]] = None,
     ) -> Dict[str, Any]:
         """
         Stream chat response tokens via WebSocket events.
@@ -744,23 +1506,37 @@ class VaultBrain:
             - CHAT_STREAM_START: When streaming begins
             - CHAT_TOKEN: For each token received
             - CHAT_STREAM_END: When streaming completes (includes full response)
+
+        The start event carries ``chat_id`` and ``vault_path`` so the Rust side
+        can record the reply as an incomplete message until the stream ends.
+        ``resume`` (``message_index``, ``partial``, ``prompt``, ``history``)
+        marks a continuation of an interrupted reply; tokens then extend
+        ``partial``.
         """
         full_response = ""
         
         try:
             # Emit stream start event
-            self.emit_to_frontend(
-                constants.EventType.CHAT_STREAM_START,
-                {
-                    "stream_id": stream_id,
-                    "message": message
+            start = {
+                "stream_id": stream_id,
+                "message": message,
+                "chat_id": chat_id,
+                "vault_path": str(self.vault_path),
+            }
+            if resume:
+                start["resume"] = {
+                    "message_index": resume["message_index"],
+                    "partial": resume["partial"],
                 }
-            )
+            self.emit_to_frontend(constants.EventType.CHAT_STREAM_START, start)
             
             # Prepare metadata for pipeline
             metadata = {}
             if chat_id:
                 metadata["chat_id"] = chat_id
+            system_prompt = self._system_prompt_for(chat_id)
+            if system_prompt:
+                metadata["system_prompt"] = system_prompt
             
             # Use pipeline's stream_run method
             async for token in self.pipeline.stream_run(
@@ -779,6 +1555,8 @@ class VaultBrain:
                         "accumulated": full_response
                     }
                 )
+                # Also on the host stream channel, when called through one
+                emit_chunk({"stream_id": stream_id, "token": token})
             
             # Emit stream end event with full response
             self.emit_to_frontend(
@@ -795,12 +1573,21 @@ class VaultBrain:
             from .pipeline.types import PipelineContext
             from .pipeline.events import PipelineEvents
             
-            ctx = PipelineContext(
-                message=message,
-                original_message=message,
-                history=history or [],
-                response=full_response
-            )
+            if resume:
+                # Record the original exchange, not the continuation prompt
+                ctx = PipelineContext(
+                    message=resume["prompt"],
+                    original_message=resume["prompt"],
+                    history=resume["history"],
+                    response=resume["partial"] + full_response
+                )
+            else:
+                ctx = PipelineContext(
+                    message=message,
+                    original_message=message,
+                    history=history or [],
+                    response=full_response
+                )
             
             if chat_id:
                 ctx.metadata["chat_id"] = chat_id
@@ -836,6 +1623,50 @@ class VaultBrain:
             }
 
 
+    @command("chat.resume", constants.CORE_PLUGIN_NAME)
+    async def chat_resume(
+        self,
+        chat_id: str = "",
+        message_index: int = -1,
+        prompt: str = "",
+        partial: str = "",
+        history: List[Dict[str, str]] = None,
+        category: str = "fast",
+        **kwargs
+    ) -> Dict[str, Any]:
+        """
+        Continue an interrupted streamed reply.
+
+        ``history`` is the conversation before ``prompt``; ``partial`` is the
+        reply text committed before the interruption. New tokens are streamed
+        as a continuation of it.
+        """
+        if not chat_id or not prompt or message_index < 0:
+            return {"status": "error", "error": "chat_id, prompt and message_index are required"}
+
+        resumed_history = list(history or [])
+        resumed_history.append({"role": "user", "content": prompt})
+        if partial:
+            resumed_history.append({"role": "assistant", "content": partial})
+        continuation = (
+            "Your previous reply was cut off. Continue it exactly where it stopped, "
+            "without repeating any of it."
+        )
+
+        return await self._stream_chat_response(
+            message=continuation,
+            history=resumed_history,
+            category=category,
+            stream_id=utils.generate_id("stream_"),
+            chat_id=chat_id,
+            resume={
+                "message_index": message_index,
+                "partial": partial,
+                "prompt": prompt,
+                "history": list(history or []),
+            },
+        )
+
     @command("plugins.install", constants.CORE_PLUGIN_NAME)
     async def install_plugin(self, download_url: str = "", repo_url: str = "", plugin_id: str = "", **kwargs) -> Dict[str, Any]:
         # Backward compatibility: Check if args are inside 'p' or 'params' dict in kwargs
@@ -1013,6 +1844,7 @@ class VaultBrain:
             
             # 8. Announce ready
             await self.publish(constants.CoreEvents.ALL_PLUGINS_LOADED)
+            self._emit_provider_key_status()
             
             logger.info(f"Vault restarted: {len(self.plugins)} plugins loaded")
             
@@ -1062,6 +1894,8 @@ class VaultBrain:
         """
         Send a raw event to the Frontend via WebSocket.
         """
+        if not self.event_channels.allow(event_type):
+            return
         if not self.is_client_connected:
             logger.debug(f"Skipping '{event_type}': Client not connected")
             return
@@ -1079,6 +1913,25 @@ class VaultBrain:
         )
         self.ws_server.send_to_rust(msg)
 
+    def _send_to_host(self, event_type: str, data: Dict[str, Any]) -> None:
+        """Send an event meant for the Rust host itself, which is never
+        muted and does not wait for the frontend to be ready."""
+        self.ws_server.send_to_rust(utils.build_request(
+            method="trigger_event",
+            params={
+                "event_type": event_type,
+                "scope": constants.EventScope.WINDOW,
+                "data": data,
+                "timestamp": time.time(),
+            },
+            request_id=utils.generate_id("evt_"),
+        ))
+
+    @command("budget.grant", constants.CORE_PLUGIN_NAME)
+    async def grant_request_permit(self, lease_id: str = "", **kwargs) -> Dict[str, Any]:
+        """Called by the host when a requested permit is granted."""
+        return {"status": "success", "granted": self.request_budget.grant(lease_id)}
+
 
 
 
@@ -1086,6 +1939,25 @@ class VaultBrain:
     # Config & Utils
     # =========================================================================
 
+    def _system_prompt_for(self, chat_id: Optional[str]) -> Optional[str]:
+        """
+        System prompt for a conversation: its own ``system_prompt`` if set,
+        else the vault default (``llm.system_prompt`` in .vault.json).
+        """
+        if chat_id and re.fullmatch(r"[A-Za-z0-9_-]+", chat_id):
+            path = self.vault_path / "conversations" / f"{chat_id}.json"
+            try:
+                prompt = json.loads(path.read_text(encoding="utf-8")).get("system_prompt")
+                if isinstance(prompt, str) and prompt.strip():
+                    return prompt
+            except FileNotFoundError:
+                pass
+            except Exception as e:
+                logger.warning(f"Could not read system prompt for conversation {chat_id}: {e}")
+
+        prompt = self.config.get("llm", {}).get("system_prompt")
+        return prompt if isinstance(prompt, str) and prompt.strip() else None
+
     def _load_config(self) -> Dict[str, Any]:
         """Load .vault.json."""
         config_file = utils.get_vault_config_path(self.vault_path)
@@ -1104,8 +1976,11 @@ class VaultBrain:
     async def tick_loop(self) -> None:
         logger.info("Starting tick loop...")
         while True:
-            await asyncio.sleep(constants.DEFAULT_TICK_INTERVAL)
+            await asyncio.sleep(self.tick_interval)
             await self.publish(constants.CoreEvents.TICK)
 
     # Removed explicit _tick_plugins iteration
 
+
+def _elapsed_ms(started: float) -> float:
+    return round((time.monotonic() - started) * 1000, 1)
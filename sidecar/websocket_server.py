@@ -7,7 +7,8 @@ manages command/event exchange using JSON-RPC 2.0 protocol.
 
 import asyncio
 import json
-from typing import Optional, Dict, Any, Callable, Awaitable
+import traceback
+from typing import Optional, Dict, Any, Callable, Awaitable, List
 import websockets
 from websockets.exceptions import ConnectionClosed
 import inspect
@@ -15,6 +16,7 @@ from loguru import logger
 from . import utils
 from . import constants
 from . import exceptions
+from .services import streaming
 
 
 # Type alias for command handlers
@@ -47,9 +49,17 @@ class WebSocketServer:
         self.port = port
         self.host = host
         self.connection: Optional[Any] = None
+        # All live clients: the vault window plus Rust-side command clients
+        self.connections: set = set()
         self.message_queue: asyncio.Queue = asyncio.Queue()
         self.pending_messages: list[Dict[str, Any]] = []
         self.brain = None  # Will be set by VaultBrain after initialization
+        # Requests being executed, by JSON-RPC id, so they can be cancelled
+        self.in_flight: Dict[Any, asyncio.Task] = {}
+        self._in_flight_methods: Dict[Any, str] = {}
+        self._cancelled: set = set()
+        # Set by drain(): only DRAIN_EXEMPT_METHODS are served from then on
+        self.draining = False
         
         logger.info(f"WebSocket server initialized on {host}:{port}")
     
@@ -89,10 +99,16 @@ class WebSocketServer:
         client_addr = websocket.remote_address
         logger.info(f"Client connected from {client_addr}")
         self.connection = websocket
+        self.connections.add(websocket)
         
+        tasks: set = set()
         try:
+            # Each message runs as its own task, so a slow request doesn't
+            # hold up the rest (or a cancellation aimed at it)
             async for message in websocket:
-                await self.handle_message(message)
+                task = asyncio.create_task(self.handle_message(message, websocket))
+                tasks.add(task)
+                task.add_done_callback(tasks.discard)
         
         except ConnectionClosed as e:
             logger.info(f"Client disconnected: {e.code} - {e.reason}")
@@ -101,10 +117,16 @@ class WebSocketServer:
             logger.exception(f"WebSocket error: {e}")
         
         finally:
-            self.connection = None
+            # Nobody is left to answer
+            for task in tasks:
+                task.cancel()
+            self.connections.discard(websocket)
+            if self.connection is websocket:
+                # Fall back to any remaining client so events keep flowing
+                self.connection = next(iter(self.connections), None)
             logger.debug("Connection closed")
     
-    async def handle_message(self, message: str) -> None:
+    async def handle_message(self, message: str, websocket: Optional[Any] = None) -> None:
         """
         Handle incoming message from Rust.
         
@@ -113,6 +135,8 @@ class WebSocketServer:
         
         Args:
             message: JSON-RPC message string
+            websocket: Connection the message arrived on; the response is
+                sent back on it (defaults to the primary connection)
         """
         request_id: Optional[str] = None
         
@@ -140,14 +164,40 @@ class WebSocketServer:
                 logger.error(f"Message missing method: {data}")
                 return
             
+            if method == constants.CANCEL_REQUEST_METHOD and request_id is None:
+                self.cancel_request(params.get("id"))
+                return
+            
             logger.debug(f"Received command: {method}")
+
+            if self.draining and method not in constants.DRAIN_EXEMPT_METHODS:
+                if request_id is not None:
+                    await self.reply(websocket, utils.build_error(
+                        constants.JSONRPC_SHUTTING_DOWN,
+                        "Sidecar is shutting down",
+                        data={"method": method},
+                        request_id=request_id,
+                    ))
+                return
             
+            if request_id is not None:
+                self.in_flight[request_id] = asyncio.current_task()
+                self._in_flight_methods[request_id] = method
+            channel_id = params.pop(streaming.STREAM_CHANNEL_PARAM, None) if isinstance(params, dict) else None
             try:
-                result = await self._execute_request(method, params, request_id)
+                try:
+                    with streaming.stream_channel(channel_id):
+                        result = await self._execute_request(method, params, request_id)
+                        # Ends the stream; queued after every chunk it sent
+                        streaming.emit_chunk(result, final=True)
+                finally:
+                    # Only the work itself is cancellable, never the reply
+                    self.in_flight.pop(request_id, None)
+                    self._in_flight_methods.pop(request_id, None)
                 
                 # Send success response
                 response = utils.build_response(result, request_id=request_id)
-                await self.send(response)
+                await self.reply(websocket, response)
                 logger.debug(f"Command '{method}' executed successfully")
                 
             except exceptions.MethodNotFoundError:
@@ -156,7 +206,18 @@ class WebSocketServer:
                     method=method,
                     request_id=request_id,
                 )
-                await self.send(error_response)
+                await self.reply(websocket, error_response)
+                
+            except asyncio.CancelledError:
+                if request_id not in self._cancelled:
+                    raise
+                logger.info(f"Command '{method}' cancelled ({request_id})")
+                await self.reply(websocket, utils.build_error(
+                    constants.JSONRPC_REQUEST_CANCELLED,
+                    f"Request {request_id} was cancelled",
+                    data={"method": method},
+                    request_id=request_id,
+                ))
                 
             except Exception as e:
                 logger.exception(f"Execution error for '{method}': {e}")
@@ -165,10 +226,14 @@ class WebSocketServer:
                     details={
                         "method": method,
                         "error_type": type(e).__name__,
+                        "traceback": traceback.format_exc(),
                     },
                     request_id=request_id,
                 )
-                await self.send(error_response)
+                await self.reply(websocket, error_response)
+            
+            finally:
+                self._cancelled.discard(request_id)
         
         except exceptions.WebSocketMessageError as e:
             logger.error(f"Message handling error: {e.message}")
@@ -180,6 +245,38 @@ class WebSocketServer:
             logger.exception(f"Unexpected error handling message: {e}")
             self.close()
 
+    async def drain(self, timeout: float) -> List[str]:
+        """
+        Stop taking new requests and wait up to ``timeout`` seconds for the
+        ones in flight. Returns the methods of those still running.
+        """
+        self.draining = True
+        current = asyncio.current_task()
+        running = {
+            request_id: task for request_id, task in self.in_flight.items()
+            if task is not current and not task.done()
+        }
+        if running:
+            logger.info(f"Draining {len(running)} in-flight request(s)")
+            await asyncio.wait(running.values(), timeout=timeout)
+        return sorted(
+            self._in_flight_methods.get(request_id, "?")
+            for request_id, task in running.items() if not task.done()
+        )
+
+    def cancel_request(self, request_id: Any) -> bool:
+        """
+        Abort the in-flight request with ``request_id``; it is answered with
+        a cancellation error. False if no such request is running.
+        """
+        task = self.in_flight.get(request_id)
+        if task is None or task.done():
+            logger.debug(f"Nothing to cancel for request {request_id}")
+            return False
+        self._cancelled.add(request_id)
+        task.cancel()
+        return True
+
     async def _execute_request(self, method: str, params: Dict[str, Any], request_id: Optional[str]) -> Any:
         """
         Execute the requested method via VaultBrain.
@@ -194,20 +291,45 @@ class WebSocketServer:
         except exceptions.CommandNotFoundError:
             raise exceptions.MethodNotFoundError(method)
             
+    async def reply(self, websocket: Optional[Any], data: Dict[str, Any]) -> None:
+        """
+        Send a response on the connection the request arrived on.
+        
+        Args:
+            websocket: Originating connection (None uses the primary connection)
+            data: Response data (will be JSON encoded)
+        """
+        if websocket is None:
+            websocket = self.connection
+        if websocket is None:
+            logger.warning("No active connection, cannot send response")
+            return
+        try:
+            await websocket.send(json.dumps(data))
+            logger.debug(f"Sent response: {data.get('id')}")
+        except Exception as e:
+            logger.exception(f"Reply error: {e}")
+
     async def send(self, data: Dict[str, Any]) -> None:
         """
         Send message to Rust.
         
+        Events are broadcast to every connected client.
+        
         Args:
             data: Message data (will be JSON encoded)
         """
         if self.is_connected():
-            try:
-                await self.connection.send(json.dumps(data))
-                logger.debug(f"Sent message: {data.get('method', 'response')}")
-            except Exception as e:
-                logger.exception(f"Send error: {e}")
-                self.close()
+            targets = list(self.connections) or [self.connection]
+            payload = json.dumps(data)
+            for target in targets:
+                try:
+                    await target.send(payload)
+                except Exception as e:
+                    logger.exception(f"Send error: {e}")
+                    if target is self.connection:
+                        self.close()
+            logger.debug(f"Sent message: {data.get('method', 'response')}")
         else:
             logger.warning("No active connection, cannot send message")
     
@@ -0,0 +1,348 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use anyhow::{anyhow, Context, Result};
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use serde::Serialize;
+
+use crate::fs_utils::atomic_write;
+
+/// Keychain service the keys are stored under; the sidecar's keyring
+/// service uses the same one, so it sees keys saved here
+const KEYRING_SERVICE: &str = "tailor-ai";
+/// Names (never values) of the keys saved, since keychains can't be listed
+const KEY_INDEX_FILE: &str = "api-keys.json";
+/// Encrypted store used when no keychain is available, and its key
+const FALLBACK_STORE_FILE: &str = "api-keys.enc";
+const FALLBACK_KEY_FILE: &str = "api-keys.key";
+const NONCE_LEN: usize = 12;
+
+/// Expected shape of a provider's API keys
+struct KeyFormat {
+    provider: &'static str,
+    prefix: &'static str,
+    min_len: usize,
+    max_len: usize,
+}
+
+// Lengths are deliberately loose: providers change formats (e.g. OpenAI
+// project keys), so a mismatch only produces a warning.
+const KEY_FORMATS: &[KeyFormat] = &[
+    KeyFormat { provider: "openai", prefix: "sk-", min_len: 40, max_len: 256 },
+    KeyFormat { provider: "anthropic", prefix: "sk-ant-", min_len: 40, max_len: 256 },
+    KeyFormat { provider: "google", prefix: "AIza", min_len: 39, max_len: 39 },
+    KeyFormat { provider: "groq", prefix: "gsk_", min_len: 40, max_len: 128 },
+    KeyFormat { provider: "mistral", prefix: "", min_len: 32, max_len: 64 },
+];
+
+#[derive(Debug, Serialize)]
+pub struct KeyValidation {
+    /// The key with surrounding whitespace removed
+    pub key: String,
+    /// Set when the key doesn't look like the named provider's format
+    pub warning: Option<String>,
+}
+
+/// Trim an API key and check it against the provider's known format.
+///
+/// Only an empty key is an error; format mismatches return a warning so an
+/// unusual but valid key can still be saved. Unknown providers are just trimmed.
+pub fn validate_api_key(provider: &str, key: &str) -> Result<KeyValidation, String> {
+    let key = key.trim().to_string();
+    if key.is_empty() {
+        return Err("API key is empty".to_string());
+    }
+
+    let mut problems = Vec::new();
+
+    if key.chars().any(char::is_whitespace) {
+        problems.push("contains whitespace".to_string());
+    }
+
+    let provider_lower = provider.trim().to_ascii_lowercase();
+    if let Some(format) = KEY_FORMATS.iter().find(|f| f.provider == provider_lower) {
+        if !key.starts_with(format.prefix) {
+            problems.push(format!("expected to start with '{}'", format.prefix));
+        }
+        if key.len() < format.min_len || key.len() > format.max_len {
+            if format.min_len == format.max_len {
+                problems.push(format!("expected {} characters, got {}", format.min_len, key.len()));
+            } else {
+                problems.push(format!(
+                    "expected {}-{} characters, got {}",
+                    format.min_len, format.max_len, key.len()
+                ));
+            }
+        }
+    }
+
+    let warning = if problems.is_empty() {
+        None
+    } else {
+        Some(format!(
+            "This doesn't look like a valid {} key: {}",
+            provider,
+            problems.join(", ")
+        ))
+    };
+
+    Ok(KeyValidation { key, warning })
+}
+
+/// Where a key was put (or found)
+#[derive(Debug, Clone, Serialize)]
+pub struct KeyStorage {
+    /// "keychain" (the OS credential store) or "encrypted_file"
+    pub backend: String,
+    /// The encrypted file, for "encrypted_file"
+    pub path: Option<String>,
+}
+
+/// API keys in the OS keychain, or in an encrypted file in the app config
+/// dir where the platform has no usable keychain. The file's key sits next
+/// to it (owner-only on Unix), which keeps the keys out of plain sight and
+/// out of backups of the store alone, not away from local users.
+pub struct ApiKeyStore {
+    config_dir: PathBuf,
+}
+
+impl ApiKeyStore {
+    pub fn new(config_dir: &Path) -> Self {
+        Self { config_dir: config_dir.to_path_buf() }
+    }
+
+    pub fn save(&self, name: &str, value: &str) -> Result<KeyStorage> {
+        let storage = match keychain_entry(name).and_then(|entry| entry.set_password(value).map_err(|e| anyhow!(e))) {
+            Ok(()) => {
+                // A copy left in the file from an earlier fallback would go stale
+                self.remove_from_file(name)?;
+                self.keychain_storage()
+            }
+            Err(e) => {
+                eprintln!("Warning: OS keychain unavailable ({}); using encrypted file", e);
+                let mut keys = self.read_file()?;
+                keys.insert(name.to_string(), value.to_string());
+                self.write_file(&keys)?;
+                self.file_storage()
+            }
+        };
+        self.update_index(|names| {
+            names.insert(name.to_string());
+        })?;
+        Ok(storage)
+    }
+
+    /// The stored secret, from the keychain first
+    pub fn get(&self, name: &str) -> Result<Option<(String, KeyStorage)>> {
+        if let Ok(entry) = keychain_entry(name) {
+            match entry.get_password() {
+                Ok(value) => return Ok(Some((value, self.keychain_storage()))),
+                Err(keyring::Error::NoEntry) => {}
+                Err(e) => eprintln!("Warning: OS keychain unavailable ({}); checking encrypted file", e),
+            }
+        }
+        Ok(self.read_file()?.remove(name).map(|value| (value, self.file_storage())))
+    }
+
+    /// Remove the key wherever it is; false if it was not stored
+    pub fn delete(&self, name: &str) -> Result<bool> {
+        let in_keychain = match keychain_entry(name).map(|entry| entry.delete_credential()) {
+            Ok(Ok(())) => true,
+            // No keychain here means the key can only be in the file
+            Ok(Err(keyring::Error::NoEntry | keyring::Error::NoStorageAccess(_) | keyring::Error::PlatformFailure(_)))
+            | Err(_) => false,
+            Ok(Err(e)) => return Err(anyhow!(e).context("Failed to delete API key from keychain")),
+        };
+        let in_file = self.remove_from_file(name)?;
+        self.update_index(|names| {
+            names.remove(name);
+        })?;
+        Ok(in_keychain || in_file)
+    }
+
+    /// Names of the stored keys: those saved here plus any of `known`
+    /// (such as keys the sidecar saved) found in the keychain
+    pub fn names(&self, known: &[&str]) -> Result<Vec<String>> {
+        let mut candidates = self.read_index();
+        candidates.extend(known.iter().map(|name| name.to_string()));
+        let in_file = self.read_file()?;
+        Ok(candidates.into_iter()
+            .filter(|name| {
+                in_file.contains_key(name)
+                    || keychain_entry(name).is_ok_and(|entry| entry.get_password().is_ok())
+            })
+            .collect())
+    }
+
+    /// Where a new key would be stored right now
+    pub fn storage(&self) -> KeyStorage {
+        let probe = keychain_entry("__tailor_probe__").map(|entry| entry.get_password());
+        match probe {
+            Ok(Ok(_)) | Ok(Err(keyring::Error::NoEntry)) => self.keychain_storage(),
+            _ => self.file_storage(),
+        }
+    }
+
+    fn keychain_storage(&self) -> KeyStorage {
+        KeyStorage { backend: "keychain".to_string(), path: None }
+    }
+
+    fn file_storage(&self) -> KeyStorage {
+        KeyStorage {
+            backend: "encrypted_file".to_string(),
+            path: Some(self.config_dir.join(FALLBACK_STORE_FILE).to_string_lossy().to_string()),
+        }
+    }
+
+    fn read_index(&self) -> BTreeSet<String> {
+        fs::read_to_string(self.config_dir.join(KEY_INDEX_FILE))
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    fn update_index(&self, change: impl FnOnce(&mut BTreeSet<String>)) -> Result<()> {
+        let mut names = self.read_index();
+        change(&mut names);
+        fs::create_dir_all(&self.config_dir)?;
+        atomic_write(&self.config_dir.join(KEY_INDEX_FILE), serde_json::to_string_pretty(&names)?.as_bytes())
+            .context("Failed to write API key index")
+    }
+
+    fn remove_from_file(&self, name: &str) -> Result<bool> {
+        let mut keys = self.read_file()?;
+        if keys.remove(name).is_none() {
+            return Ok(false);
+        }
+        self.write_file(&keys)?;
+        Ok(true)
+    }
+
+    fn read_file(&self) -> Result<BTreeMap<String, String>> {
+        let contents = match fs::read(self.config_dir.join(FALLBACK_STORE_FILE)) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(BTreeMap::new()),
+            Err(e) => return Err(anyhow!(e).context("Failed to read encrypted API key file")),
+        };
+        if contents.len() < NONCE_LEN {
+            anyhow::bail!("Encrypted API key file is truncated");
+        }
+        let (nonce, ciphertext) = contents.split_at(NONCE_LEN);
+        let plaintext = self.cipher()?
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| anyhow!("Encrypted API key file could not be decrypted"))?;
+        serde_json::from_slice(&plaintext).context("Encrypted API key file is corrupt")
+    }
+
+    fn write_file(&self, keys: &BTreeMap<String, String>) -> Result<()> {
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = self.cipher()?
+            .encrypt(&nonce, serde_json::to_vec(keys)?.as_slice())
+            .map_err(|_| anyhow!("Failed to encrypt API keys"))?;
+        let mut contents = nonce.to_vec();
+        contents.extend(ciphertext);
+        fs::create_dir_all(&self.config_dir)?;
+        atomic_write(&self.config_dir.join(FALLBACK_STORE_FILE), &contents)
+            .context("Failed to write encrypted API key file")
+    }
+
+    /// Cipher for the fallback file, creating its key on first use. Only a
+    /// missing key is replaced: a new key can't decrypt the keys already stored.
+    fn cipher(&self) -> Result<ChaCha20Poly1305> {
+        let key_path = self.config_dir.join(FALLBACK_KEY_FILE);
+        let key = match fs::read(&key_path) {
+            Ok(bytes) if bytes.len() == 32 => *Key::from_slice(&bytes),
+            Ok(_) => anyhow::bail!("API key file key at {} is invalid", key_path.display()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                let key = ChaCha20Poly1305::generate_key(&mut OsRng);
+                fs::create_dir_all(&self.config_dir)?;
+                // Owner-only from the start, never readable by others even briefly
+                let mut options = fs::OpenOptions::new();
+                options.write(true).create_new(true);
+                #[cfg(unix)]
+                {
+                    use std::os::unix::fs::OpenOptionsExt;
+                    options.mode(0o600);
+                }
+                let mut file = options.open(&key_path).context("Failed to create API key file key")?;
+                file.write_all(key.as_slice())
+                    .and_then(|()| file.sync_all())
+                    .context("Failed to write API key file key")?;
+                key
+            }
+            Err(e) => {
+                return Err(anyhow!(e).context(format!("Failed to read API key file key at {}", key_path.display())));
+            }
+        };
+        Ok(ChaCha20Poly1305::new(&key))
+    }
+}
+
+fn keychain_entry(name: &str) -> Result<keyring::Entry> {
+    keyring::Entry::new(KEYRING_SERVICE, name).map_err(|e| anyhow!(e))
+}
+
+/// Providers whose keys may have been saved by the sidecar
+pub fn known_providers() -> Vec<&'static str> {
+    KEY_FORMATS.iter().map(|f| f.provider).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The keychain is the user's real one, so these exercise the encrypted
+    // file the store falls back to
+    fn temp_store() -> ApiKeyStore {
+        ApiKeyStore::new(&std::env::temp_dir().join(format!("tailor-keys-{}", uuid::Uuid::new_v4())))
+    }
+
+    #[test]
+    fn file_keys_are_stored_loaded_and_deleted() {
+        let store = temp_store();
+        let mut keys = BTreeMap::new();
+        keys.insert("openai".to_string(), "sk-one".to_string());
+        keys.insert("groq".to_string(), "gsk_two".to_string());
+        store.write_file(&keys).unwrap();
+
+        let stored = fs::read(store.config_dir.join(FALLBACK_STORE_FILE)).unwrap();
+        assert!(!String::from_utf8_lossy(&stored).contains("sk-one"));
+        assert_eq!(store.read_file().unwrap(), keys);
+
+        assert!(store.remove_from_file("openai").unwrap());
+        assert!(!store.remove_from_file("openai").unwrap());
+        assert_eq!(store.read_file().unwrap().into_keys().collect::<Vec<_>>(), ["groq"]);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(store.config_dir.join(FALLBACK_KEY_FILE)).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        fs::remove_dir_all(&store.config_dir).unwrap();
+    }
+
+    #[test]
+    fn an_unreadable_key_file_is_an_error_and_is_not_replaced() {
+        let store = temp_store();
+        let mut keys = BTreeMap::new();
+        keys.insert("openai".to_string(), "sk-one".to_string());
+        store.write_file(&keys).unwrap();
+        let key_path = store.config_dir.join(FALLBACK_KEY_FILE);
+        let key = fs::read(&key_path).unwrap();
+
+        // A directory can't be read as a file, whoever runs the test
+        fs::rename(&key_path, store.config_dir.join("key.bak")).unwrap();
+        fs::create_dir(&key_path).unwrap();
+        assert!(store.read_file().is_err());
+        assert!(store.write_file(&BTreeMap::new()).is_err());
+        assert!(key_path.is_dir());
+
+        fs::remove_dir(&key_path).unwrap();
+        fs::write(&key_path, &key).unwrap();
+        assert_eq!(store.read_file().unwrap(), keys);
+        fs::remove_dir_all(&store.config_dir).unwrap();
+    }
+}
@@ -0,0 +1,249 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use serde::{Deserialize, Serialize};
+use anyhow::{Result, Context};
+
+use crate::fs_utils::dir_size;
+
+/// Kind of tailor-managed artifact living outside a vault directory
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ArtifactKind {
+    Venv,
+    Cache,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrphanedArtifact {
+    /// Stable identifier used by `clean_orphaned_artifacts` ("venv:<vault_id>")
+    pub id: String,
+    pub kind: ArtifactKind,
+    pub vault_id: String,
+    pub path: String,
+    pub size_bytes: u64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct CleanupFailure {
+    pub id: String,
+    pub error: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct CleanupReport {
+    pub removed: Vec<String>,
+    pub failed: Vec<CleanupFailure>,
+    pub reclaimed_bytes: u64,
+}
+
+/// Marker file written into every artifact directory tailor creates, so we
+/// never touch a directory we did not create ourselves.
+pub const ARTIFACT_MARKER: &str = ".tailor-artifact";
+
+/// App data and cache dirs the per-vault artifacts live under
+static ARTIFACT_DIRS: OnceLock<(PathBuf, PathBuf)> = OnceLock::new();
+
+/// Set where per-vault venvs and caches are created; called once at startup
+pub fn set_artifact_dirs(app_data_dir: PathBuf, app_cache_dir: PathBuf) {
+    let _ = ARTIFACT_DIRS.set((app_data_dir, app_cache_dir));
+}
+
+/// The `id` from a vault's `.vault.json`, when it is usable as a directory name
+pub fn vault_id(vault: &Path) -> Option<String> {
+    let contents = fs::read_to_string(vault.join(".vault.json")).ok()?;
+    let config: serde_json::Value = serde_json::from_str(&contents).ok()?;
+    config.get("id")
+        .and_then(|v| v.as_str())
+        .filter(|id| !id.is_empty() && !id.starts_with('.') && !id.contains(['/', '\\']))
+        .map(str::to_string)
+}
+
+pub struct ArtifactScanner;
+
+impl ArtifactScanner {
+    /// Where the vault's dependencies are installed, if its venv can live
+    /// under the app data dir
+    pub fn vault_venv(vault: &Path) -> Option<PathBuf> {
+        let (app_data_dir, _) = ARTIFACT_DIRS.get()?;
+        Some(Self::venvs_root(app_data_dir).join(vault_id(vault)?))
+    }
+
+    /// The vault's cache directory, created if needed; None when the vault
+    /// has no usable id
+    pub fn vault_cache(vault: &Path) -> Option<PathBuf> {
+        let (_, app_cache_dir) = ARTIFACT_DIRS.get()?;
+        let cache = Self::caches_root(app_cache_dir).join(vault_id(vault)?);
+        match Self::create_artifact_dir(&cache) {
+            Ok(()) => Some(cache),
+            Err(e) => {
+                eprintln!("Warning: Failed to create vault cache {}: {}", cache.display(), e);
+                None
+            }
+        }
+    }
+
+    /// Create `dir` and mark it as made by tailor, so it can be found (and
+    /// removed) once its vault is gone
+    pub fn create_artifact_dir(dir: &Path) -> Result<()> {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+        let marker = dir.join(ARTIFACT_MARKER);
+        if !marker.is_file() {
+            fs::write(&marker, b"")
+                .with_context(|| format!("Failed to write {}", marker.display()))?;
+        }
+        Ok(())
+    }
+
+    /// Directory holding per-vault virtual environments (`<root>/<vault_id>`)
+    pub fn venvs_root(app_data_dir: &Path) -> PathBuf {
+        app_data_dir.join("venvs")
+    }
+
+    /// Directory holding per-vault caches (`<root>/<vault_id>`)
+    pub fn caches_root(app_cache_dir: &Path) -> PathBuf {
+        app_cache_dir.join("vaults")
+    }
+
+    /// Find venvs whose vault id is not in `known_vault_ids`
+    pub fn find_orphaned_venvs(
+        app_data_dir: &Path,
+        known_vault_ids: &HashSet<String>,
+    ) -> Result<Vec<OrphanedArtifact>> {
+        Self::scan(&Self::venvs_root(app_data_dir), ArtifactKind::Venv, known_vault_ids)
+    }
+
+    /// Find caches whose vault id is not in `known_vault_ids`
+    pub fn find_orphaned_caches(
+        app_cache_dir: &Path,
+        known_vault_ids: &HashSet<String>,
+    ) -> Result<Vec<OrphanedArtifact>> {
+        Self::scan(&Self::caches_root(app_cache_dir), ArtifactKind::Cache, known_vault_ids)
+    }
+
+    /// Remove the requested artifacts.
+    ///
+    /// Only ids present in `orphans` (a fresh scan) are removed, so a stale id
+    /// from the UI can never delete something that has since become owned.
+    pub fn clean(ids: &[String], orphans: &[OrphanedArtifact]) -> CleanupReport {
+        let mut report = CleanupReport::default();
+
+        for id in ids {
+            let Some(artifact) = orphans.iter().find(|a| &a.id == id) else {
+                report.failed.push(CleanupFailure {
+                    id: id.clone(),
+                    error: "Not an orphaned tailor artifact".to_string(),
+                });
+                continue;
+            };
+
+            match fs::remove_dir_all(&artifact.path) {
+                Ok(()) => {
+                    println!("Removed orphaned {:?} artifact: {}", artifact.kind, artifact.path);
+                    report.reclaimed_bytes += artifact.size_bytes;
+                    report.removed.push(id.clone());
+                }
+                Err(e) => report.failed.push(CleanupFailure {
+                    id: id.clone(),
+                    error: format!("Failed to remove {}: {}", artifact.path, e),
+                }),
+            }
+        }
+
+        report
+    }
+
+    fn scan(
+        root: &Path,
+        kind: ArtifactKind,
+        known_vault_ids: &HashSet<String>,
+    ) -> Result<Vec<OrphanedArtifact>> {
+        let mut orphans = Vec::new();
+
+        if !root.exists() {
+            return Ok(orphans);
+        }
+
+        let entries = fs::read_dir(root)
+            .with_context(|| format!("Failed to read {}", root.display()))?;
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+            // Skip symlinks entirely: a link may point into user data
+            let Ok(file_type) = entry.file_type() else { continue };
+            if !file_type.is_dir() {
+                continue;
+            }
+            if !path.join(ARTIFACT_MARKER).is_file() {
+                continue;
+            }
+
+            let vault_id = entry.file_name().to_string_lossy().to_string();
+            if known_vault_ids.contains(&vault_id) {
+                continue;
+            }
+
+            let prefix = match kind {
+                ArtifactKind::Venv => "venv",
+                ArtifactKind::Cache => "cache",
+            };
+
+            orphans.push(OrphanedArtifact {
+                id: format!("{}:{}", prefix, vault_id),
+                kind,
+                vault_id,
+                size_bytes: dir_size(&path),
+                path: path.to_string_lossy().to_string(),
+            });
+        }
+
+        Ok(orphans)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_root(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("tailor-{}-{}", name, uuid::Uuid::new_v4().simple()))
+    }
+
+    #[test]
+    fn finds_only_marked_artifacts_of_unknown_vaults() {
+        let app_data_dir = temp_root("artifacts");
+        let venvs = ArtifactScanner::venvs_root(&app_data_dir);
+        ArtifactScanner::create_artifact_dir(&venvs.join("kept")).unwrap();
+        ArtifactScanner::create_artifact_dir(&venvs.join("gone")).unwrap();
+        fs::write(venvs.join("gone").join("module.py"), b"x = 1").unwrap();
+        fs::create_dir_all(venvs.join("unmarked")).unwrap();
+
+        let known = HashSet::from(["kept".to_string()]);
+        let orphans = ArtifactScanner::find_orphaned_venvs(&app_data_dir, &known).unwrap();
+
+        assert_eq!(orphans.len(), 1);
+        assert_eq!(orphans[0].id, "venv:gone");
+        assert!(orphans[0].size_bytes > 0);
+
+        let report = ArtifactScanner::clean(&["venv:gone".to_string(), "venv:unmarked".to_string()], &orphans);
+        assert_eq!(report.removed, ["venv:gone"]);
+        assert_eq!(report.failed.len(), 1);
+        assert!(!venvs.join("gone").exists());
+        assert!(venvs.join("unmarked").exists());
+
+        let _ = fs::remove_dir_all(&app_data_dir);
+    }
+
+    #[test]
+    fn vault_id_must_be_a_plain_directory_name() {
+        let vault = temp_root("vault-id");
+        fs::create_dir_all(&vault).unwrap();
+        for (id, usable) in [("abc-123", true), ("../escape", false), ("a/b", false), ("", false)] {
+            fs::write(vault.join(".vault.json"), serde_json::json!({ "id": id }).to_string()).unwrap();
+            assert_eq!(vault_id(&vault).is_some(), usable, "{:?}", id);
+        }
+        let _ = fs::remove_dir_all(&vault);
+    }
+}
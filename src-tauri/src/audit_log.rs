@@ -0,0 +1,201 @@
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use serde::{Deserialize, Serialize};
+
+use crate::redact::{redact_json, redact_text};
+
+/// Audit log file, under `~/.tailor/`
+const AUDIT_LOG_FILE: &str = "audit.jsonl";
+/// Rotate the log once it grows past this size
+const MAX_AUDIT_FILE_BYTES: u64 = 5 * 1024 * 1024;
+/// Params larger than this (serialized) are recorded as a size only
+const MAX_PARAMS_BYTES: usize = 4 * 1024;
+/// Strings inside params are cut to this many characters
+const MAX_STRING_CHARS: usize = 256;
+/// Cap on entries returned by a single query
+const MAX_QUERY_LIMIT: usize = 1000;
+
+/// One recorded command invocation, already redacted
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub timestamp: String,
+    pub window_label: String,
+    pub method: String,
+    pub params: serde_json::Value,
+    /// "ok", "error" or "cancelled"
+    pub status: String,
+    pub duration_ms: u64,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, Deserialize)]
+pub struct AuditQuery {
+    /// Only entries whose method contains this text
+    pub method: Option<String>,
+    pub window_label: Option<String>,
+    pub status: Option<String>,
+    /// RFC 3339 bounds on the entry timestamp (inclusive)
+    pub since: Option<String>,
+    pub until: Option<String>,
+    pub limit: Option<usize>,
+}
+
+/// Opt-in record of every command routed to a sidecar. Off unless the
+/// `auditLog` global setting is true; a failed write is reported and
+/// dropped, never surfaced to the command being recorded.
+pub struct AuditLog {
+    enabled: AtomicBool,
+    log_file: Option<PathBuf>,
+    write_lock: Mutex<()>,
+}
+
+impl AuditLog {
+    pub fn new(home_dir: Option<&Path>, enabled: bool) -> Self {
+        Self {
+            enabled: AtomicBool::new(enabled),
+            log_file: home_dir.map(|home| home.join(".tailor").join(AUDIT_LOG_FILE)),
+            write_lock: Mutex::new(()),
+        }
+    }
+
+    pub fn set_enabled(&self, enabled: bool) {
+        if self.enabled.swap(enabled, Ordering::Relaxed) != enabled {
+            println!("Audit log {}", if enabled { "enabled" } else { "disabled" });
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    pub fn record(
+        &self,
+        window_label: &str,
+        method: &str,
+        params: &serde_json::Value,
+        status: &str,
+        duration_ms: u64,
+        error: Option<&str>,
+    ) {
+        if !self.is_enabled() {
+            return;
+        }
+        let Some(log_file) = &self.log_file else { return };
+
+        let entry = AuditEntry {
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            window_label: window_label.to_string(),
+            method: method.to_string(),
+            params: audit_params(params),
+            status: status.to_string(),
+            duration_ms,
+            error: error.map(|e| truncate(&redact_text(e), MAX_STRING_CHARS)),
+        };
+
+        let _guard = self.write_lock.lock().unwrap_or_else(|e| e.into_inner());
+        if let Err(e) = append(log_file, &entry) {
+            eprintln!("Warning: Failed to write audit log entry: {}", e);
+        }
+    }
+
+    /// Entries matching `query`, newest first
+    pub fn query(&self, query: &AuditQuery) -> Vec<AuditEntry> {
+        let Some(log_file) = &self.log_file else { return Vec::new() };
+        let limit = query.limit.unwrap_or(200).clamp(1, MAX_QUERY_LIMIT);
+        let since = query.since.as_deref().and_then(parse_time);
+        let until = query.until.as_deref().and_then(parse_time);
+
+        let matches = |entry: &AuditEntry| {
+            let at = parse_time(&entry.timestamp);
+            query.method.as_deref().map_or(true, |m| entry.method.contains(m))
+                && query.window_label.as_deref().map_or(true, |w| entry.window_label == w)
+                && query.status.as_deref().map_or(true, |s| entry.status == s)
+                && since.map_or(true, |since| at.is_some_and(|at| at >= since))
+                && until.map_or(true, |until| at.is_some_and(|at| at <= until))
+        };
+
+        let _guard = self.write_lock.lock().unwrap_or_else(|e| e.into_inner());
+        let mut entries: Vec<AuditEntry> = [rotated_file(log_file), log_file.clone()]
+            .iter()
+            .filter_map(|path| fs::File::open(path).ok())
+            .flat_map(|file| BufReader::new(file).lines().map_while(Result::ok))
+            .filter_map(|line| serde_json::from_str::<AuditEntry>(&line).ok())
+            .filter(|entry| matches(entry))
+            .collect();
+
+        entries.reverse();
+        entries.truncate(limit);
+        entries
+    }
+
+    /// Delete the log and its rotated copy; returns how many entries went
+    pub fn clear(&self) -> std::io::Result<usize> {
+        let Some(log_file) = &self.log_file else { return Ok(0) };
+        let _guard = self.write_lock.lock().unwrap_or_else(|e| e.into_inner());
+
+        let mut removed = 0;
+        for path in [rotated_file(log_file), log_file.clone()] {
+            let Ok(file) = fs::File::open(&path) else { continue };
+            removed += BufReader::new(file).lines().map_while(Result::ok).count();
+            fs::remove_file(&path)?;
+        }
+        Ok(removed)
+    }
+}
+
+fn append(log_file: &Path, entry: &AuditEntry) -> std::io::Result<()> {
+    if let Some(dir) = log_file.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    if fs::metadata(log_file).is_ok_and(|m| m.len() > MAX_AUDIT_FILE_BYTES) {
+        fs::rename(log_file, rotated_file(log_file))?;
+    }
+
+    let line = serde_json::to_string(entry)?;
+    let mut file = OpenOptions::new().create(true).append(true).open(log_file)?;
+    writeln!(file, "{}", line)
+}
+
+fn rotated_file(log_file: &Path) -> PathBuf {
+    log_file.with_extension("jsonl.1")
+}
+
+/// Redacted params with long strings cut short; a payload still too large
+/// after that is replaced by its size
+fn audit_params(params: &serde_json::Value) -> serde_json::Value {
+    let mut params = params.clone();
+    redact_json(&mut params);
+    truncate_strings(&mut params);
+
+    let size = serde_json::to_string(&params).map(|s| s.len()).unwrap_or(0);
+    if size > MAX_PARAMS_BYTES {
+        serde_json::json!({ "truncated": true, "bytes": size })
+    } else {
+        params
+    }
+}
+
+fn truncate_strings(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(map) => map.values_mut().for_each(truncate_strings),
+        serde_json::Value::Array(items) => items.iter_mut().for_each(truncate_strings),
+        serde_json::Value::String(text) => *text = truncate(text, MAX_STRING_CHARS),
+        _ => {}
+    }
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some((cut, _)) => format!("{}… ({} chars)", &text[..cut], text.chars().count()),
+        None => text.to_string(),
+    }
+}
+
+fn parse_time(value: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    chrono::DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|t| t.with_timezone(&chrono::Utc))
+}
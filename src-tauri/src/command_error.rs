@@ -0,0 +1,121 @@
+use std::borrow::Cow;
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+
+/// Error returned by every IPC command. The frontend receives
+/// `{ code, message }`: `code` is stable and meant for deciding how to
+/// recover (retry, ask for a key, reinstall), `message` is for display.
+/// `TOO_MANY_WINDOWS` also carries `open` and `max`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CommandError {
+    /// Arguments or settings the command can't accept
+    Validation(String),
+    /// A vault, plugin, conversation or other named thing doesn't exist
+    NotFound(String),
+    /// Python packages or tools the vault needs couldn't be installed or found
+    DependencyFailed(String),
+    /// The window's sidecar isn't running, can't be reached or keeps crashing
+    SidecarUnavailable(String),
+    /// Reading or writing files failed
+    Io(String),
+    /// Credentials are missing or were refused
+    AuthRequired(String),
+    /// A remote host couldn't be reached
+    Network(String),
+    /// The request was cancelled before it finished
+    Cancelled(String),
+    /// `maxOpenVaults` windows are already open or opening
+    TooManyWindows { open: usize, max: usize },
+    /// Anything else
+    Internal(String),
+}
+
+impl CommandError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Validation(_) => "VALIDATION",
+            Self::NotFound(_) => "NOT_FOUND",
+            Self::DependencyFailed(_) => "DEPENDENCY_FAILED",
+            Self::SidecarUnavailable(_) => "SIDECAR_UNAVAILABLE",
+            Self::Io(_) => "IO",
+            Self::AuthRequired(_) => "AUTH_REQUIRED",
+            Self::Network(_) => "NETWORK",
+            Self::Cancelled(_) => "CANCELLED",
+            Self::TooManyWindows { .. } => "TOO_MANY_WINDOWS",
+            Self::Internal(_) => "INTERNAL",
+        }
+    }
+
+    pub fn message(&self) -> Cow<'_, str> {
+        match self {
+            Self::TooManyWindows { open, max } => Cow::Owned(format!(
+                "{} of {} allowed vault windows are open or opening; close one or retry with closeLeastRecent",
+                open, max
+            )),
+            Self::Validation(m)
+            | Self::NotFound(m)
+            | Self::DependencyFailed(m)
+            | Self::SidecarUnavailable(m)
+            | Self::Io(m)
+            | Self::AuthRequired(m)
+            | Self::Network(m)
+            | Self::Cancelled(m)
+            | Self::Internal(m) => Cow::Borrowed(m),
+        }
+    }
+}
+
+impl std::fmt::Display for CommandError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message())
+    }
+}
+
+impl std::error::Error for CommandError {}
+
+impl Serialize for CommandError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let fields = if matches!(self, Self::TooManyWindows { .. }) { 4 } else { 2 };
+        let mut error = serializer.serialize_struct("CommandError", fields)?;
+        error.serialize_field("code", self.code())?;
+        error.serialize_field("message", &self.message())?;
+        if let Self::TooManyWindows { open, max } = self {
+            error.serialize_field("open", open)?;
+            error.serialize_field("max", max)?;
+        }
+        error.end()
+    }
+}
+
+impl From<std::io::Error> for CommandError {
+    fn from(error: std::io::Error) -> Self {
+        Self::Io(error.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn io_errors_are_io_and_keep_their_message() {
+        let error = CommandError::from(std::io::Error::new(std::io::ErrorKind::PermissionDenied, "read-only file system"));
+        assert_eq!(error.code(), "IO");
+        assert_eq!(error.to_string(), "read-only file system");
+    }
+
+    #[test]
+    fn serializes_code_and_message() {
+        let json = serde_json::to_value(CommandError::NotFound("plugin 'x' is not installed".to_string())).unwrap();
+        assert_eq!(json, serde_json::json!({ "code": "NOT_FOUND", "message": "plugin 'x' is not installed" }));
+    }
+
+    #[test]
+    fn too_many_windows_carries_the_counts() {
+        let error = CommandError::TooManyWindows { open: 3, max: 3 };
+        assert_eq!(error.code(), "TOO_MANY_WINDOWS");
+        let json = serde_json::to_value(&error).unwrap();
+        assert_eq!(json["open"], 3);
+        assert_eq!(json["max"], 3);
+        assert!(json["message"].as_str().unwrap().starts_with("3 of 3 allowed"));
+    }
+}
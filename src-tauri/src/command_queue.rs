@@ -0,0 +1,137 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use tokio::sync::oneshot;
+use anyhow::Result;
+
+/// Vault setting holding the max number of in-flight frontend commands
+pub const MAX_IN_FLIGHT_COMMANDS_SETTING: &str = "maxInFlightCommands";
+
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct CommandQueueDepth {
+    pub queued: usize,
+    pub in_flight: usize,
+    /// None means unlimited (the queue is a pass-through)
+    pub max_in_flight: Option<usize>,
+}
+
+#[derive(Default)]
+struct WindowQueue {
+    max_in_flight: Option<usize>,
+    in_flight: usize,
+    waiting: VecDeque<oneshot::Sender<QueuePermit>>,
+}
+
+impl WindowQueue {
+    fn has_capacity(&self) -> bool {
+        self.max_in_flight.map_or(true, |max| self.in_flight < max)
+    }
+}
+
+/// Per-window FIFO throttle for outgoing sidecar commands.
+///
+/// Windows without a limit never wait, so the default behaves exactly
+/// like sending directly.
+#[derive(Default)]
+pub struct CommandQueue {
+    windows: Mutex<HashMap<String, WindowQueue>>,
+}
+
+/// Held for the duration of a command; releasing it dispatches the next
+/// queued command for the window
+pub struct QueuePermit {
+    queue: Arc<CommandQueue>,
+    window_label: String,
+}
+
+impl Drop for QueuePermit {
+    fn drop(&mut self) {
+        self.queue.release(&self.window_label);
+    }
+}
+
+impl CommandQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Wait for a slot, in arrival order. Resolves immediately when the
+    /// window is unlimited or below its limit with nothing queued.
+    pub async fn acquire(self: &Arc<Self>, window_label: &str) -> Result<QueuePermit> {
+        let receiver = {
+            let mut windows = self.windows.lock().unwrap();
+            let queue = windows.entry(window_label.to_string()).or_default();
+            if queue.waiting.is_empty() && queue.has_capacity() {
+                queue.in_flight += 1;
+                return Ok(self.permit(window_label));
+            }
+            let (sender, receiver) = oneshot::channel();
+            queue.waiting.push_back(sender);
+            receiver
+        };
+
+        receiver
+            .await
+            .map_err(|_| anyhow::anyhow!("Command queue closed for window: {}", window_label))
+    }
+
+    /// Change a window's limit; None removes it. Raising the limit
+    /// dispatches queued commands right away.
+    pub fn set_limit(self: &Arc<Self>, window_label: &str, max_in_flight: Option<usize>) {
+        self.windows.lock().unwrap()
+            .entry(window_label.to_string())
+            .or_default()
+            .max_in_flight = max_in_flight;
+        self.dispatch(window_label);
+    }
+
+    pub fn depth(&self, window_label: &str) -> CommandQueueDepth {
+        self.windows.lock().unwrap()
+            .get(window_label)
+            .map(|queue| CommandQueueDepth {
+                queued: queue.waiting.len(),
+                in_flight: queue.in_flight,
+                max_in_flight: queue.max_in_flight,
+            })
+            .unwrap_or_default()
+    }
+
+    /// Drop a closed window's queue; anything still waiting fails
+    pub fn remove(&self, window_label: &str) {
+        self.windows.lock().unwrap().remove(window_label);
+    }
+
+    fn permit(self: &Arc<Self>, window_label: &str) -> QueuePermit {
+        QueuePermit {
+            queue: self.clone(),
+            window_label: window_label.to_string(),
+        }
+    }
+
+    fn release(self: &Arc<Self>, window_label: &str) {
+        if let Some(queue) = self.windows.lock().unwrap().get_mut(window_label) {
+            queue.in_flight = queue.in_flight.saturating_sub(1);
+        }
+        self.dispatch(window_label);
+    }
+
+    /// Hand free slots to waiters in order. Permits are sent outside the
+    /// lock: a waiter that gave up returns its permit, whose drop
+    /// releases the slot again.
+    fn dispatch(self: &Arc<Self>, window_label: &str) {
+        let mut ready = Vec::new();
+        {
+            let mut windows = self.windows.lock().unwrap();
+            let Some(queue) = windows.get_mut(window_label) else { return };
+            while queue.has_capacity() {
+                let Some(sender) = queue.waiting.pop_front() else { break };
+                queue.in_flight += 1;
+                ready.push(sender);
+            }
+        }
+
+        for sender in ready {
+            let _ = sender.send(self.permit(window_label));
+        }
+    }
+}
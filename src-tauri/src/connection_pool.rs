@@ -0,0 +1,675 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::Duration;
+use futures::{SinkExt, StreamExt};
+use serde::Serialize;
+use tokio::sync::{broadcast, mpsc, oneshot, Mutex};
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+use anyhow::{Result, Context};
+
+use crate::command_error::CommandError;
+use crate::sidecar_client::{SidecarClient, DEFAULT_REQUEST_TIMEOUT};
+
+/// How many settled request ids to remember for duplicate detection
+const SETTLED_ID_CAPACITY: usize = 512;
+/// Prefix of JSON-RPC ids issued by the pool; the suffix is a sequence number
+const ID_PREFIX: &str = "rust_";
+/// Notification telling the sidecar to abort the request with `params.id`
+const CANCEL_METHOD: &str = "cancel_request";
+/// Buffered sidecar notifications per subscriber before the oldest are dropped
+const NOTIFICATION_BUFFER: usize = 256;
+/// How long a freshly spawned sidecar gets to start accepting connections
+const READY_TIMEOUT: Duration = Duration::from_secs(30);
+/// JSON-RPC error codes the sidecar answers with (see sidecar/constants.py)
+const JSONRPC_INVALID_REQUEST: i64 = -32600;
+const JSONRPC_METHOD_NOT_FOUND: i64 = -32601;
+const JSONRPC_INVALID_PARAMS: i64 = -32602;
+const JSONRPC_REQUEST_CANCELLED: i64 = -32800;
+const JSONRPC_SHUTTING_DOWN: i64 = -32801;
+
+type Reply = Result<serde_json::Value>;
+
+/// A JSON-RPC error response from the sidecar; recoverable from the
+/// `anyhow::Error` returned by `request` via `downcast_ref`
+#[derive(Debug, Clone)]
+pub struct SidecarRpcError {
+    pub code: i64,
+    pub message: String,
+    /// Extra detail from the sidecar (error type, traceback)
+    pub data: Option<serde_json::Value>,
+}
+
+impl std::fmt::Display for SidecarRpcError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Sidecar error {}: {}", self.code, self.message)
+    }
+}
+
+impl std::error::Error for SidecarRpcError {}
+
+/// A failed `request` as a `CommandError`. A sidecar that answered and
+/// rejected the request is classified by JSON-RPC code; failing to reach it
+/// at all (connecting, timeouts, a closed socket) is `SidecarUnavailable`.
+pub fn command_error(error: &anyhow::Error) -> CommandError {
+    if let Some(cancelled) = error.downcast_ref::<RequestCancelled>() {
+        return CommandError::Cancelled(cancelled.to_string());
+    }
+    let Some(rpc) = error.downcast_ref::<SidecarRpcError>() else {
+        return CommandError::SidecarUnavailable(format!("Sidecar request failed: {:#}", error));
+    };
+    let message = rpc.message.clone();
+    match rpc.code {
+        JSONRPC_INVALID_REQUEST | JSONRPC_INVALID_PARAMS => CommandError::Validation(message),
+        JSONRPC_METHOD_NOT_FOUND => CommandError::NotFound(message),
+        JSONRPC_REQUEST_CANCELLED => CommandError::Cancelled(message),
+        JSONRPC_SHUTTING_DOWN => CommandError::SidecarUnavailable(message),
+        _ => CommandError::Internal(message),
+    }
+}
+
+/// A request failed locally by `cancel`; recoverable via `downcast_ref`
+#[derive(Debug, Clone)]
+pub struct RequestCancelled {
+    pub id: String,
+}
+
+impl std::fmt::Display for RequestCancelled {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "request {} was cancelled", self.id)
+    }
+}
+
+impl std::error::Error for RequestCancelled {}
+
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct ConnectionDiagnostics {
+    pub connected: bool,
+    pub pending: usize,
+    pub requests_sent: u64,
+    pub responses_received: u64,
+    /// Responses whose id matched no pending request, or arrived twice
+    pub correlation_anomalies: u64,
+    pub reconnects: u64,
+    pub last_anomaly: Option<String>,
+}
+
+/// Pending requests plus recently settled ids for one live connection
+#[derive(Default)]
+struct Correlation {
+    pending: HashMap<u64, oneshot::Sender<Reply>>,
+    /// Recently settled ids, oldest first, with whether a response arrived
+    settled: VecDeque<(u64, Settled)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Settled {
+    Answered,
+    /// Timed out or failed locally; one late response is still expected
+    Abandoned,
+}
+
+impl Correlation {
+    fn settle(&mut self, seq: u64, how: Settled) {
+        if self.settled.len() >= SETTLED_ID_CAPACITY {
+            self.settled.pop_front();
+        }
+        self.settled.push_back((seq, how));
+    }
+
+    fn fail_all(&mut self, reason: &str) {
+        let failed: Vec<_> = self.pending.drain().collect();
+        for (seq, sender) in failed {
+            let _ = sender.send(Err(anyhow::anyhow!(reason.to_string())));
+            self.settle(seq, Settled::Abandoned);
+        }
+    }
+}
+
+#[derive(Clone)]
+struct Connection {
+    outgoing: mpsc::UnboundedSender<Message>,
+    alive: Arc<AtomicBool>,
+    correlation: Arc<StdMutex<Correlation>>,
+}
+
+/// An id handed out by `reserve_id`, so its request can be cancelled while
+/// it is still queued as well as once it is in flight
+struct Reservation {
+    owner: String,
+    cancel_tx: Option<oneshot::Sender<()>>,
+    cancel_rx: Option<oneshot::Receiver<()>>,
+}
+
+/// Forgets a reservation once its request finishes or is dropped
+struct ReservationGuard<'a> {
+    reservations: &'a StdMutex<HashMap<String, Reservation>>,
+    id: &'a str,
+}
+
+impl Drop for ReservationGuard<'_> {
+    fn drop(&mut self) {
+        ConnectionPool::lock(self.reservations).remove(self.id);
+    }
+}
+
+#[derive(Default)]
+struct PortState {
+    connection: Option<Connection>,
+    /// Survives reconnects so counters cover the sidecar's whole lifetime
+    stats: Arc<StdMutex<ConnectionDiagnostics>>,
+    /// Set by `disconnect`, so a request that already looked the port up
+    /// doesn't reconnect to a sidecar that is going away
+    closed: bool,
+}
+
+/// One persistent WebSocket per sidecar, multiplexing JSON-RPC requests by id.
+///
+/// The reader task sanity-checks every response id against the pending set.
+/// A response for an unknown or already-settled id means correlation can no
+/// longer be trusted, so every in-flight request is failed and the socket is
+/// dropped; the next request reconnects with a clean slate.
+///
+/// Notifications (`trigger_event` frames) are not correlated; they are
+/// republished to subscribers along with the port they arrived on.
+pub struct ConnectionPool {
+    /// Each port's state is locked on its own, so connecting to one sidecar
+    /// never holds up requests to another
+    ports: StdMutex<HashMap<u16, Arc<Mutex<PortState>>>>,
+    next_seq: AtomicU64,
+    notifications: broadcast::Sender<(u16, serde_json::Value)>,
+    reservations: StdMutex<HashMap<String, Reservation>>,
+}
+
+impl Default for ConnectionPool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ConnectionPool {
+    pub fn new() -> Self {
+        let (notifications, _) = broadcast::channel(NOTIFICATION_BUFFER);
+        Self {
+            ports: StdMutex::new(HashMap::new()),
+            next_seq: AtomicU64::new(0),
+            notifications,
+            reservations: StdMutex::new(HashMap::new()),
+        }
+    }
+
+    /// Receive the params of every `trigger_event` notification from any sidecar
+    pub fn subscribe(&self) -> broadcast::Receiver<(u16, serde_json::Value)> {
+        self.notifications.subscribe()
+    }
+
+    /// Open the pooled connection for `port` now rather than on first request,
+    /// so notifications are received from the start
+    pub async fn ensure_connected(&self, port: u16, timeout: Duration) -> Result<()> {
+        self.live_connection(port, timeout).await?;
+        Ok(())
+    }
+
+    /// Connect to a freshly spawned sidecar in the background once it is up,
+    /// so its notifications (such as operation checkpoints) are recorded
+    /// even before the first request
+    pub fn connect_when_ready(self: &Arc<Self>, port: u16) {
+        let pool = self.clone();
+        tauri::async_runtime::spawn(async move {
+            let connected = match SidecarClient::wait_until_ready(port, READY_TIMEOUT).await {
+                Ok(()) => pool.ensure_connected(port, DEFAULT_REQUEST_TIMEOUT).await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = connected {
+                eprintln!("Warning: Sidecar on port {} not connected: {}", port, e);
+            }
+        });
+    }
+
+    fn port_state(&self, port: u16) -> Arc<Mutex<PortState>> {
+        Self::lock(&self.ports).entry(port).or_default().clone()
+    }
+
+    /// The port's connection and counters, (re)connecting first if its
+    /// connection is gone. Only this port is locked while connecting, so
+    /// concurrent requests to it wait for that one connect rather than
+    /// each opening a socket.
+    async fn live_connection(
+        &self,
+        port: u16,
+        timeout: Duration,
+    ) -> Result<(Connection, Arc<StdMutex<ConnectionDiagnostics>>)> {
+        let state = self.port_state(port);
+        let mut entry = state.lock().await;
+        if entry.closed {
+            anyhow::bail!("Sidecar connection closed");
+        }
+
+        let alive = entry.connection.as_ref()
+            .is_some_and(|c| c.alive.load(Ordering::SeqCst));
+        if !alive {
+            let reconnecting = entry.connection.is_some();
+            let connection = Self::connect(
+                port,
+                entry.stats.clone(),
+                self.notifications.clone(),
+                timeout,
+            )
+            .await?;
+            entry.connection = Some(connection);
+            if reconnecting {
+                Self::lock(&entry.stats).reconnects += 1;
+                println!("Reconnected to sidecar on port {}", port);
+            }
+        }
+
+        let connection = entry.connection.clone().expect("connection just established");
+        Ok((connection, entry.stats.clone()))
+    }
+
+    /// Send a request over the pooled connection for `port`
+    pub async fn request(
+        &self,
+        port: u16,
+        method: &str,
+        params: serde_json::Value,
+        timeout: Duration,
+    ) -> Result<serde_json::Value> {
+        let seq = self.next_seq.fetch_add(1, Ordering::SeqCst) + 1;
+        self.send_request(port, seq, method, params, timeout, None).await
+    }
+
+    /// Allocate the JSON-RPC id for a request `owner` will send later with
+    /// `request_reserved`, so it can be cancelled by id in the meantime
+    pub fn reserve_id(&self, owner: &str) -> String {
+        let seq = self.next_seq.fetch_add(1, Ordering::SeqCst) + 1;
+        let id = format!("{}{}", ID_PREFIX, seq);
+        let (cancel_tx, cancel_rx) = oneshot::channel();
+        Self::lock(&self.reservations).insert(id.clone(), Reservation {
+            owner: owner.to_string(),
+            cancel_tx: Some(cancel_tx),
+            cancel_rx: Some(cancel_rx),
+        });
+        id
+    }
+
+    /// `request` under an id from `reserve_id`
+    pub async fn request_reserved(
+        &self,
+        port: u16,
+        id: &str,
+        method: &str,
+        params: serde_json::Value,
+        timeout: Duration,
+    ) -> Result<serde_json::Value> {
+        let _guard = ReservationGuard { reservations: &self.reservations, id };
+        let seq = id.strip_prefix(ID_PREFIX)
+            .and_then(|s| s.parse::<u64>().ok())
+            .with_context(|| format!("Not a pool request id: {}", id))?;
+        let cancelled = Self::lock(&self.reservations)
+            .get_mut(id)
+            .and_then(|reservation| reservation.cancel_rx.take())
+            .with_context(|| format!("Request id {} is not reserved", id))?;
+        self.send_request(port, seq, method, params, timeout, Some(cancelled)).await
+    }
+
+    /// Forget a reserved id whose request was never sent
+    pub fn release_id(&self, id: &str) {
+        Self::lock(&self.reservations).remove(id);
+    }
+
+    /// Cancel a reserved request of `owner`'s. A request still waiting to be
+    /// sent fails as soon as it is; one in flight fails immediately and the
+    /// sidecar is told to abort it. False if no such request is pending.
+    pub fn cancel(&self, owner: &str, id: &str) -> bool {
+        let mut reservations = Self::lock(&self.reservations);
+        let Some(reservation) = reservations.get_mut(id).filter(|r| r.owner == owner) else {
+            return false;
+        };
+        match reservation.cancel_tx.take() {
+            Some(cancel_tx) => cancel_tx.send(()).is_ok(),
+            None => false,
+        }
+    }
+
+    async fn send_request(
+        &self,
+        port: u16,
+        seq: u64,
+        method: &str,
+        params: serde_json::Value,
+        timeout: Duration,
+        mut cancelled: Option<oneshot::Receiver<()>>,
+    ) -> Result<serde_json::Value> {
+        let id = format!("{}{}", ID_PREFIX, seq);
+        // Cancelled while queued: never send it
+        if let Some(rx) = cancelled.as_mut() {
+            if rx.try_recv().is_ok() {
+                return Err(anyhow::Error::new(RequestCancelled { id }));
+            }
+        }
+        let (reply_tx, reply_rx) = oneshot::channel();
+
+        let (connection, stats) = self.live_connection(port, timeout).await?;
+        let Connection { outgoing, correlation, .. } = connection;
+        Self::lock(&correlation).pending.insert(seq, reply_tx);
+
+        let message = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": method,
+            "params": params,
+        });
+
+        if outgoing.send(Message::Text(message.to_string())).is_err() {
+            Self::lock(&correlation).pending.remove(&seq);
+            anyhow::bail!("Sidecar connection is closed");
+        }
+        Self::lock(&stats).requests_sent += 1;
+
+        let cancel_signal = async {
+            let requested = match cancelled {
+                Some(rx) => rx.await.is_ok(),
+                None => false,
+            };
+            if !requested {
+                std::future::pending::<()>().await;
+            }
+        };
+
+        tokio::select! {
+            reply = tokio::time::timeout(timeout, reply_rx) => match reply {
+                Ok(Ok(Ok(value))) => Ok(value),
+                Ok(Ok(Err(e))) => Err(e),
+                Ok(Err(_)) => anyhow::bail!("Sidecar connection dropped before responding"),
+                Err(_) => {
+                    // Remember the id so a late response isn't mistaken for corruption
+                    let mut correlation = Self::lock(&correlation);
+                    correlation.pending.remove(&seq);
+                    correlation.settle(seq, Settled::Abandoned);
+                    anyhow::bail!("Timed out waiting for '{}' response", method)
+                }
+            },
+            _ = cancel_signal => {
+                {
+                    let mut correlation = Self::lock(&correlation);
+                    correlation.pending.remove(&seq);
+                    // The sidecar still answers, usually with a cancellation error
+                    correlation.settle(seq, Settled::Abandoned);
+                }
+                let notification = serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "method": CANCEL_METHOD,
+                    "params": { "id": id },
+                });
+                let _ = outgoing.send(Message::Text(notification.to_string()));
+                Err(anyhow::Error::new(RequestCancelled { id }))
+            }
+        }
+    }
+
+    /// Counters for the pooled connection to `port`
+    pub async fn diagnostics(&self, port: u16) -> ConnectionDiagnostics {
+        let Some(state) = Self::lock(&self.ports).get(&port).cloned() else {
+            return ConnectionDiagnostics::default();
+        };
+        let entry = state.lock().await;
+
+        let mut diagnostics = Self::lock(&entry.stats).clone();
+        if let Some(connection) = &entry.connection {
+            diagnostics.connected = connection.alive.load(Ordering::SeqCst);
+            diagnostics.pending = Self::lock(&connection.correlation).pending.len();
+        }
+        diagnostics
+    }
+
+    /// Drop the connection (and its counters) for a sidecar that is going away
+    pub async fn disconnect(&self, port: u16) {
+        let removed = Self::lock(&self.ports).remove(&port);
+        if let Some(state) = removed {
+            let mut entry = state.lock().await;
+            entry.closed = true;
+            if let Some(connection) = entry.connection.take() {
+                connection.alive.store(false, Ordering::SeqCst);
+                Self::lock(&connection.correlation).fail_all("Sidecar connection closed");
+                let _ = connection.outgoing.send(Message::Close(None));
+            }
+        }
+    }
+
+    async fn connect(
+        port: u16,
+        stats: Arc<StdMutex<ConnectionDiagnostics>>,
+        notifications: broadcast::Sender<(u16, serde_json::Value)>,
+        timeout: Duration,
+    ) -> Result<Connection> {
+        let url = format!("ws://127.0.0.1:{}", port);
+        let (ws, _) = tokio::time::timeout(timeout, connect_async(&url))
+            .await
+            .context("Timed out connecting to sidecar")?
+            .with_context(|| format!("Failed to connect to {}", url))?;
+
+        let (mut sink, mut stream) = ws.split();
+        let (outgoing, mut outgoing_rx) = mpsc::unbounded_channel::<Message>();
+        let alive = Arc::new(AtomicBool::new(true));
+        let correlation = Arc::new(StdMutex::new(Correlation::default()));
+
+        // Writer: forwards queued frames to the socket
+        let writer_alive = alive.clone();
+        tokio::spawn(async move {
+            while let Some(message) = outgoing_rx.recv().await {
+                let closing = matches!(message, Message::Close(_));
+                if sink.send(message).await.is_err() || closing {
+                    break;
+                }
+            }
+            writer_alive.store(false, Ordering::SeqCst);
+            let _ = sink.close().await;
+        });
+
+        // Reader: routes responses to their pending request by id
+        let reader_alive = alive.clone();
+        let reader_correlation = correlation.clone();
+        let reader_outgoing = outgoing.clone();
+        tokio::spawn(async move {
+            while let Some(frame) = stream.next().await {
+                let Ok(Message::Text(text)) = frame else {
+                    if frame.is_err() {
+                        break;
+                    }
+                    continue;
+                };
+                let Ok(data) = serde_json::from_str::<serde_json::Value>(&text) else {
+                    continue;
+                };
+                // Notifications (events) carry a method; only responses are correlated
+                if let Some(method) = data.get("method") {
+                    if method == "trigger_event" {
+                        let params = data.get("params").cloned().unwrap_or(serde_json::Value::Null);
+                        let _ = notifications.send((port, params));
+                    }
+                    continue;
+                }
+
+                if let Err(anomaly) = Self::deliver(&reader_correlation, &stats, &data) {
+                    eprintln!("Sidecar correlation anomaly on port {}: {}", port, anomaly);
+                    {
+                        let mut stats = Self::lock(&stats);
+                        stats.correlation_anomalies += 1;
+                        stats.last_anomaly = Some(anomaly);
+                    }
+                    reader_alive.store(false, Ordering::SeqCst);
+                    Self::lock(&reader_correlation)
+                        .fail_all("Sidecar connection resynced after a response correlation error; please retry");
+                    let _ = reader_outgoing.send(Message::Close(None));
+                    return;
+                }
+            }
+
+            reader_alive.store(false, Ordering::SeqCst);
+            Self::lock(&reader_correlation).fail_all("Sidecar connection closed");
+        });
+
+        Ok(Connection { outgoing, alive, correlation })
+    }
+
+    /// Hand a response to its waiting request, or describe why it can't be trusted
+    fn deliver(
+        correlation: &StdMutex<Correlation>,
+        stats: &StdMutex<ConnectionDiagnostics>,
+        data: &serde_json::Value,
+    ) -> std::result::Result<(), String> {
+        let id = data.get("id").and_then(|v| v.as_str()).unwrap_or_default();
+        let seq = id.strip_prefix(ID_PREFIX)
+            .and_then(|s| s.parse::<u64>().ok())
+            .ok_or_else(|| format!("response with foreign id {:?}", data.get("id")))?;
+
+        let mut correlation = Self::lock(correlation);
+        let Some(sender) = correlation.pending.remove(&seq) else {
+            return match correlation.settled.iter_mut().find(|(s, _)| *s == seq) {
+                // A late reply to a timed-out request is expected once; drop it quietly
+                Some(entry) if entry.1 == Settled::Abandoned => {
+                    entry.1 = Settled::Answered;
+                    Ok(())
+                }
+                Some(_) => Err(format!("duplicate response for id {}", id)),
+                None => Err(format!("response for unknown id {}", id)),
+            };
+        };
+        correlation.settle(seq, Settled::Answered);
+        drop(correlation);
+
+        Self::lock(stats).responses_received += 1;
+
+        let reply = match data.get("error") {
+            Some(error) => Err(anyhow::Error::new(SidecarRpcError {
+                code: error.get("code").and_then(|c| c.as_i64()).unwrap_or(0),
+                message: error.get("message")
+                    .and_then(|m| m.as_str())
+                    .unwrap_or("Unknown error")
+                    .to_string(),
+                data: error.get("data").cloned(),
+            })),
+            None => Ok(data.get("result").cloned().unwrap_or(serde_json::Value::Null)),
+        };
+        let _ = sender.send(reply);
+        Ok(())
+    }
+
+    fn lock<T>(mutex: &StdMutex<T>) -> std::sync::MutexGuard<'_, T> {
+        mutex.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rpc(code: i64, message: &str) -> anyhow::Error {
+        anyhow::Error::new(SidecarRpcError { code, message: message.to_string(), data: None })
+    }
+
+    #[test]
+    fn rejections_are_typed_by_json_rpc_code() {
+        let cases = [
+            (JSONRPC_INVALID_PARAMS, "VALIDATION"),
+            (JSONRPC_INVALID_REQUEST, "VALIDATION"),
+            (JSONRPC_METHOD_NOT_FOUND, "NOT_FOUND"),
+            (JSONRPC_REQUEST_CANCELLED, "CANCELLED"),
+            (JSONRPC_SHUTTING_DOWN, "SIDECAR_UNAVAILABLE"),
+            (-32603, "INTERNAL"),
+            (1, "INTERNAL"),
+        ];
+        for (code, expected) in cases {
+            assert_eq!(command_error(&rpc(code, "rejected")).code(), expected, "{}", code);
+        }
+        assert_eq!(command_error(&rpc(JSONRPC_METHOD_NOT_FOUND, "Method not found: x")).message(), "Method not found: x");
+    }
+
+    /// A WebSocket JSON-RPC server answering every request, counting the
+    /// connections it accepts; `delay` slows each handshake down
+    async fn serve(accepted: Arc<AtomicU64>, delay: Duration) -> u16 {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            while let Ok((socket, _)) = listener.accept().await {
+                accepted.fetch_add(1, Ordering::SeqCst);
+                tokio::spawn(async move {
+                    tokio::time::sleep(delay).await;
+                    let mut ws = tokio_tungstenite::accept_async(socket).await.unwrap();
+                    while let Some(Ok(Message::Text(text))) = ws.next().await {
+                        let request: serde_json::Value = serde_json::from_str(&text).unwrap();
+                        let reply = serde_json::json!({ "jsonrpc": "2.0", "id": request["id"], "result": "pong" });
+                        let _ = ws.send(Message::Text(reply.to_string())).await;
+                    }
+                });
+            }
+        });
+        port
+    }
+
+    #[tokio::test]
+    async fn a_hung_sidecar_does_not_block_other_ports() {
+        // Accepts connections but never completes the handshake
+        let hung = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let hung_port = hung.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let mut held = Vec::new();
+            while let Ok((socket, _)) = hung.accept().await {
+                held.push(socket);
+            }
+        });
+        let live_port = serve(Arc::new(AtomicU64::new(0)), Duration::ZERO).await;
+
+        let pool = Arc::new(ConnectionPool::new());
+        let stuck = {
+            let pool = pool.clone();
+            tokio::spawn(async move {
+                pool.request(hung_port, "system.ping", serde_json::json!({}), Duration::from_secs(30)).await
+            })
+        };
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let reply = tokio::time::timeout(
+            Duration::from_secs(2),
+            pool.request(live_port, "system.ping", serde_json::json!({}), Duration::from_secs(5)),
+        ).await;
+        assert_eq!(reply.expect("blocked behind the hung port").unwrap(), "pong");
+        stuck.abort();
+    }
+
+    #[tokio::test]
+    async fn concurrent_requests_share_one_connect() {
+        let accepted = Arc::new(AtomicU64::new(0));
+        let port = serve(accepted.clone(), Duration::from_millis(100)).await;
+        let pool = ConnectionPool::new();
+
+        let (first, second) = tokio::join!(
+            pool.request(port, "system.ping", serde_json::json!({}), Duration::from_secs(5)),
+            pool.request(port, "system.ping", serde_json::json!({}), Duration::from_secs(5)),
+        );
+        assert_eq!(first.unwrap(), "pong");
+        assert_eq!(second.unwrap(), "pong");
+        assert_eq!(accepted.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn a_disconnected_port_is_not_reconnected_by_a_waiting_request() {
+        let port = serve(Arc::new(AtomicU64::new(0)), Duration::ZERO).await;
+        let pool = ConnectionPool::new();
+        pool.ensure_connected(port, Duration::from_secs(5)).await.unwrap();
+
+        let state = pool.port_state(port);
+        pool.disconnect(port).await;
+        assert!(state.lock().await.closed);
+        assert!(!pool.diagnostics(port).await.connected);
+    }
+
+    #[test]
+    fn transport_failures_are_sidecar_unavailable() {
+        let error = anyhow::anyhow!("Sidecar connection is closed");
+        assert_eq!(command_error(&error).code(), "SIDECAR_UNAVAILABLE");
+        let error = anyhow::Error::new(RequestCancelled { id: "rust_7".to_string() });
+        assert_eq!(command_error(&error).code(), "CANCELLED");
+    }
+}
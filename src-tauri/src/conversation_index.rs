@@ -0,0 +1,508 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use serde::{Deserialize, Serialize};
+use anyhow::{Result, Context};
+
+use crate::conversations::{self, Conversation};
+use crate::fs_utils::atomic_write;
+
+/// Search index for a vault's conversations, under `.tailor/`
+pub const INDEX_FILE: &str = "conversation-index.json";
+/// Present while the index is known to be behind the conversation files
+const DIRTY_MARKER: &str = "conversation-index.dirty";
+/// Bump when the entry format changes; older indexes are rebuilt
+const INDEX_VERSION: u32 = 2;
+/// Words shorter than this are not indexed
+const MIN_TERM_LEN: usize = 2;
+/// Characters of context kept on each side of a match in a search snippet
+const SNIPPET_CONTEXT_CHARS: usize = 60;
+/// Results returned when the filters don't set a limit
+const DEFAULT_SEARCH_LIMIT: usize = 50;
+
+/// Serializes read-modify-write cycles on index files
+static INDEX_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexEntry {
+    pub id: String,
+    pub title: String,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub created: Option<String>,
+    #[serde(default)]
+    pub updated: Option<String>,
+    pub message_count: usize,
+    /// Lowercased words from the title and message bodies
+    pub terms: BTreeSet<String>,
+    /// Modification time (ms) of the file when it was indexed
+    pub indexed_mtime_ms: Option<i64>,
+    /// Size of the file when it was indexed, to catch edits within one mtime tick
+    pub indexed_size: Option<u64>,
+    /// Name of the file within the conversations directory
+    pub file: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ConversationIndex {
+    pub version: u32,
+    pub updated: Option<String>,
+    pub entries: BTreeMap<String, IndexEntry>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct IndexFreshness {
+    pub indexed: usize,
+    pub on_disk: usize,
+    /// A write to the index failed; the next read rebuilds it
+    pub dirty: bool,
+    /// Conversations changed on disk since they were indexed
+    pub stale: Vec<String>,
+    /// Conversations on disk the index doesn't know about
+    pub missing: Vec<String>,
+    /// Index entries whose conversation file is gone
+    pub orphaned: Vec<String>,
+    pub updated: Option<String>,
+}
+
+fn index_dir(vault_path: &Path) -> PathBuf {
+    vault_path.join(".tailor")
+}
+
+fn index_path(vault_path: &Path) -> PathBuf {
+    index_dir(vault_path).join(INDEX_FILE)
+}
+
+fn dirty_path(vault_path: &Path) -> PathBuf {
+    index_dir(vault_path).join(DIRTY_MARKER)
+}
+
+/// Build an index entry for a conversation as stored at `file`
+pub fn entry_for(conversation: &Conversation, file: Option<&Path>) -> IndexEntry {
+    let mut terms = tokenize(&conversation.title);
+    for message in &conversation.messages {
+        terms.extend(tokenize(&message.content));
+    }
+
+    IndexEntry {
+        id: conversation.id.clone(),
+        title: conversation.title.clone(),
+        model: conversation.model.clone(),
+        created: conversation.created.clone(),
+        updated: conversation.updated.clone(),
+        message_count: conversation.messages.len(),
+        terms,
+        indexed_mtime_ms: file.and_then(modified_ms),
+        indexed_size: file.and_then(|f| fs::metadata(f).ok()).map(|m| m.len()),
+        file: file.and_then(|f| f.file_name()).map(|n| n.to_string_lossy().to_string()),
+    }
+}
+
+/// Whether `entry` still describes the file at `path`
+fn is_current(entry: &IndexEntry, path: &Path) -> bool {
+    let size = fs::metadata(path).ok().map(|m| m.len());
+    entry.indexed_mtime_ms.is_some()
+        && entry.indexed_mtime_ms == modified_ms(path)
+        && entry.indexed_size == size
+}
+
+/// Lowercased alphanumeric words of at least `MIN_TERM_LEN` characters
+pub fn tokenize(text: &str) -> BTreeSet<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| word.chars().count() >= MIN_TERM_LEN)
+        .map(|word| word.to_lowercase())
+        .collect()
+}
+
+/// Reflect a conversation that was just written. Failures are logged and
+/// mark the index dirty instead of failing the caller's write.
+pub fn record_write(vault_path: &Path, conversation: &Conversation) {
+    let file = conversations::conversation_path(vault_path, &conversation.id).ok();
+    let entry = entry_for(conversation, file.as_deref());
+    update_quietly(vault_path, |index| {
+        index.entries.insert(entry.id.clone(), entry);
+    });
+}
+
+/// Reflect a conversation that was just deleted, with the same failure
+/// handling as `record_write`
+pub fn record_delete(vault_path: &Path, conversation_id: &str) {
+    update_quietly(vault_path, |index| {
+        index.entries.remove(conversation_id);
+    });
+}
+
+/// Mark the index for a lazy rebuild, e.g. after bulk changes
+pub fn mark_dirty(vault_path: &Path) {
+    if let Err(e) = atomic_write(&dirty_path(vault_path), b"") {
+        eprintln!("Warning: Failed to mark conversation index dirty: {}", e);
+    }
+}
+
+/// Load the index, rebuilding it first if it is dirty, missing or outdated.
+/// Otherwise conversations changed on disk since they were indexed (by the
+/// sidecar, sync or by hand) are reindexed and deleted ones dropped.
+pub fn load(vault_path: &Path) -> Result<ConversationIndex> {
+    let _guard = lock_index();
+
+    if !dirty_path(vault_path).exists() {
+        if let Some(index) = read_index(vault_path) {
+            return refresh_locked(vault_path, index);
+        }
+    }
+    rebuild_locked(vault_path)
+}
+
+/// The index lock; a panic while it was held leaves nothing half-written
+/// (writes are atomic), so a poisoned lock is still good to use
+fn lock_index() -> std::sync::MutexGuard<'static, ()> {
+    INDEX_LOCK.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Bring `index` up to date with the files on disk, reading only the ones
+/// that changed
+fn refresh_locked(vault_path: &Path, mut index: ConversationIndex) -> Result<ConversationIndex> {
+    let files: HashMap<String, PathBuf> = conversations::conversation_files(vault_path)
+        .into_iter()
+        .filter_map(|path| Some((path.file_name()?.to_string_lossy().to_string(), path)))
+        .collect();
+
+    let before = index.entries.len();
+    index.entries.retain(|_, entry| {
+        entry.file.as_ref()
+            .and_then(|file| files.get(file))
+            .is_some_and(|path| is_current(entry, path))
+    });
+    let mut changed = index.entries.len() != before;
+
+    let indexed: BTreeSet<String> = index.entries.values().filter_map(|e| e.file.clone()).collect();
+    for (file, path) in &files {
+        if indexed.contains(file) {
+            continue;
+        }
+        let Ok(text) = fs::read_to_string(path) else { continue };
+        let Ok(conversation) = conversations::parse_conversation(&text) else { continue };
+        // Another file claiming the id keeps its entry until the duplicate is resolved
+        if index.entries.contains_key(&conversation.id) {
+            continue;
+        }
+        index.entries.insert(conversation.id.clone(), entry_for(&conversation, Some(path)));
+        changed = true;
+    }
+
+    if changed {
+        write_index(vault_path, &mut index)?;
+    }
+    Ok(index)
+}
+
+/// Compare the index with the conversation files without rebuilding it
+pub fn freshness(vault_path: &Path) -> IndexFreshness {
+    let _guard = lock_index();
+
+    let dirty = dirty_path(vault_path).exists();
+    let index = read_index(vault_path).unwrap_or_default();
+
+    let mut on_disk = BTreeMap::new();
+    for path in conversations::conversation_files(vault_path) {
+        if let Some(id) = path.file_stem().map(|s| s.to_string_lossy().to_string()) {
+            on_disk.insert(id, path);
+        }
+    }
+
+    let mut stale = Vec::new();
+    let mut missing = Vec::new();
+    for (id, path) in &on_disk {
+        match index.entries.get(id) {
+            Some(entry) if is_current(entry, path) => {}
+            Some(_) => stale.push(id.clone()),
+            None => missing.push(id.clone()),
+        }
+    }
+    let orphaned = index.entries.keys()
+        .filter(|id| !on_disk.contains_key(*id))
+        .cloned()
+        .collect();
+
+    IndexFreshness {
+        indexed: index.entries.len(),
+        on_disk: on_disk.len(),
+        dirty,
+        stale,
+        missing,
+        orphaned,
+        updated: index.updated,
+    }
+}
+
+#[derive(Debug, Default, Clone, Deserialize)]
+pub struct SearchFilters {
+    /// RFC 3339 bounds on the last update (or creation) time, inclusive
+    pub since: Option<String>,
+    pub until: Option<String>,
+    /// Only conversations with this model (case-insensitive)
+    pub model: Option<String>,
+    pub limit: Option<usize>,
+}
+
+/// A matching conversation, without its messages
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchHit {
+    pub id: String,
+    pub title: String,
+    /// The matched text with some context, or the first message when the
+    /// query is empty
+    pub snippet: Option<String>,
+    /// Last update, else creation time
+    pub timestamp: Option<String>,
+    pub model: Option<String>,
+    pub message_count: usize,
+}
+
+/// Conversations whose title or messages contain `query` (case-insensitive),
+/// within `filters`. The index narrows the candidates to those containing
+/// every query word, so only they are read from disk. Title matches come
+/// first, then the most recently updated.
+pub fn search(vault_path: &Path, query: &str, filters: &SearchFilters) -> Result<Vec<SearchHit>> {
+    let index = load(vault_path)?;
+    let needle = query.trim().to_lowercase();
+    let words = tokenize(&needle);
+    let since = filters.since.as_deref().and_then(parse_time);
+    let until = filters.until.as_deref().and_then(parse_time);
+    let model = filters.model.as_deref().map(str::to_lowercase).filter(|m| !m.is_empty());
+
+    let candidates = index.entries.values().filter(|entry| {
+        let at = entry.updated.as_deref().or(entry.created.as_deref()).and_then(parse_time);
+        model.as_ref().map_or(true, |m| entry.model.as_deref().is_some_and(|em| em.to_lowercase() == *m))
+            && since.map_or(true, |since| at.is_some_and(|at| at >= since))
+            && until.map_or(true, |until| at.is_some_and(|at| at <= until))
+            && words.iter().all(|word| entry.terms.iter().any(|term| term.contains(word.as_str())))
+    });
+
+    let mut hits: Vec<(bool, SearchHit)> = candidates
+        .filter_map(|entry| {
+            let conversation = conversations::load_conversation(vault_path, &entry.id).ok()?;
+            let title_match = !needle.is_empty() && conversation.title.to_lowercase().contains(&needle);
+            let snippet = if needle.is_empty() {
+                conversation.messages.first().map(|m| snippet(&m.content, ""))
+            } else {
+                let body = conversation.messages.iter()
+                    .find(|m| m.content.to_lowercase().contains(&needle))
+                    .map(|m| snippet(&m.content, &needle));
+                // The index can lag the file; drop conversations that no longer match
+                if body.is_none() && !title_match {
+                    return None;
+                }
+                body
+            };
+            Some((title_match, SearchHit {
+                timestamp: conversation.updated.clone().or(conversation.created.clone()),
+                message_count: conversation.messages.len(),
+                id: conversation.id,
+                title: conversation.title,
+                model: conversation.model,
+                snippet,
+            }))
+        })
+        .collect();
+
+    hits.sort_by(|(a_title, a), (b_title, b)| {
+        b_title.cmp(a_title).then_with(|| {
+            let time = |hit: &SearchHit| hit.timestamp.as_deref().and_then(parse_time);
+            time(b).cmp(&time(a))
+        })
+    });
+    hits.truncate(filters.limit.unwrap_or(DEFAULT_SEARCH_LIMIT));
+    Ok(hits.into_iter().map(|(_, hit)| hit).collect())
+}
+
+/// `text` around the first occurrence of `needle` (the start when empty),
+/// on one line
+fn snippet(text: &str, needle: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let lower: Vec<char> = text.to_lowercase().chars().collect();
+    let needle: Vec<char> = needle.chars().collect();
+    // Lowercasing can change the length; only trust positions when it didn't
+    let at = if needle.is_empty() || lower.len() != chars.len() {
+        0
+    } else {
+        lower.windows(needle.len()).position(|w| w == needle.as_slice()).unwrap_or(0)
+    };
+
+    let start = at.saturating_sub(SNIPPET_CONTEXT_CHARS);
+    let end = (at + needle.len() + SNIPPET_CONTEXT_CHARS).min(chars.len());
+    let body: String = chars[start..end].iter().collect();
+    let body = body.split_whitespace().collect::<Vec<_>>().join(" ");
+    format!(
+        "{}{}{}",
+        if start > 0 { "…" } else { "" },
+        body,
+        if end < chars.len() { "…" } else { "" },
+    )
+}
+
+fn parse_time(value: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    chrono::DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|t| t.with_timezone(&chrono::Utc))
+}
+
+fn update_quietly(vault_path: &Path, change: impl FnOnce(&mut ConversationIndex)) {
+    let _guard = lock_index();
+
+    // A dirty index is rebuilt wholesale on the next read; patching it is pointless
+    if dirty_path(vault_path).exists() {
+        return;
+    }
+
+    let result = match read_index(vault_path) {
+        Some(mut index) => {
+            change(&mut index);
+            write_index(vault_path, &mut index)
+        }
+        // No usable index yet; the change is already on disk, so a full build covers it
+        None => rebuild_locked(vault_path).map(|_| ()),
+    };
+
+    if let Err(e) = result {
+        eprintln!("Warning: Conversation index update failed, will rebuild lazily: {}", e);
+        mark_dirty(vault_path);
+    }
+}
+
+fn rebuild_locked(vault_path: &Path) -> Result<ConversationIndex> {
+    let mut index = ConversationIndex { version: INDEX_VERSION, ..Default::default() };
+    for path in conversations::conversation_files(vault_path) {
+        let Ok(text) = fs::read_to_string(&path) else { continue };
+        let Ok(conversation) = conversations::parse_conversation(&text) else { continue };
+        index.entries.insert(conversation.id.clone(), entry_for(&conversation, Some(&path)));
+    }
+
+    write_index(vault_path, &mut index)?;
+    let marker = dirty_path(vault_path);
+    if marker.exists() {
+        fs::remove_file(&marker)
+            .with_context(|| format!("Failed to clear {}", marker.display()))?;
+    }
+    Ok(index)
+}
+
+fn read_index(vault_path: &Path) -> Option<ConversationIndex> {
+    let contents = fs::read_to_string(index_path(vault_path)).ok()?;
+    let index: ConversationIndex = serde_json::from_str(&contents).ok()?;
+    (index.version == INDEX_VERSION).then_some(index)
+}
+
+fn write_index(vault_path: &Path, index: &mut ConversationIndex) -> Result<()> {
+    index.updated = Some(chrono::Utc::now().to_rfc3339());
+    atomic_write(&index_path(vault_path), serde_json::to_string(index)?.as_bytes())
+}
+
+fn modified_ms(path: &Path) -> Option<i64> {
+    fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .map(|t| chrono::DateTime::<chrono::Utc>::from(t).timestamp_millis())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_vault() -> PathBuf {
+        let vault = std::env::temp_dir().join(format!("tailor-index-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(conversations::conversations_dir(&vault)).unwrap();
+        vault
+    }
+
+    fn write(vault: &Path, id: &str, content: &str) -> Conversation {
+        let conversation: Conversation = serde_json::from_value(serde_json::json!({
+            "id": id,
+            "title": format!("Chat {}", id),
+            "messages": [{ "role": "user", "content": content }],
+        })).unwrap();
+        conversations::write_conversation_raw(vault, id, &serde_json::to_string(&conversation).unwrap()).unwrap();
+        conversation
+    }
+
+    fn ids(vault: &Path, query: &str) -> Vec<String> {
+        let mut ids: Vec<String> = search(vault, query, &SearchFilters::default())
+            .unwrap()
+            .into_iter()
+            .map(|hit| hit.id)
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn a_missing_or_dirty_index_is_rebuilt() {
+        let vault = temp_vault();
+        write(&vault, "a", "tomatoes and basil");
+        write(&vault, "b", "train timetables");
+
+        assert_eq!(ids(&vault, "basil"), ["a"]);
+        assert!(index_path(&vault).exists());
+
+        fs::write(index_path(&vault), b"{ not json").unwrap();
+        assert_eq!(ids(&vault, "timetables"), ["b"]);
+
+        mark_dirty(&vault);
+        assert_eq!(load(&vault).unwrap().entries.len(), 2);
+        assert!(!dirty_path(&vault).exists());
+        fs::remove_dir_all(&vault).unwrap();
+    }
+
+    #[test]
+    fn recorded_writes_update_the_index_in_place() {
+        let vault = temp_vault();
+        write(&vault, "a", "tomatoes");
+        load(&vault).unwrap();
+
+        let conversation = write(&vault, "a", "tomatoes and cucumbers");
+        record_write(&vault, &conversation);
+        assert!(read_index(&vault).unwrap().entries["a"].terms.contains("cucumbers"));
+        assert_eq!(ids(&vault, "cucumbers"), ["a"]);
+
+        conversations::delete_conversation(&vault, "a").unwrap();
+        record_delete(&vault, "a");
+        assert!(read_index(&vault).unwrap().entries.is_empty());
+        fs::remove_dir_all(&vault).unwrap();
+    }
+
+    #[test]
+    fn files_changed_outside_the_app_are_reindexed_on_load() {
+        let vault = temp_vault();
+        write(&vault, "a", "tomatoes");
+        write(&vault, "b", "basil");
+        load(&vault).unwrap();
+
+        // Written by the sidecar or sync, with nothing on the Rust side recording it
+        write(&vault, "a", "tomatoes and a much longer note about cucumbers");
+        write(&vault, "c", "carrots");
+        fs::remove_file(conversations::conversation_path(&vault, "b").unwrap()).unwrap();
+
+        assert_eq!(ids(&vault, "cucumbers"), ["a"]);
+        assert_eq!(ids(&vault, "carrots"), ["c"]);
+        let index = read_index(&vault).unwrap();
+        assert_eq!(index.entries.keys().collect::<Vec<_>>(), ["a", "c"]);
+        let freshness = freshness(&vault);
+        assert!(freshness.stale.is_empty() && freshness.missing.is_empty() && freshness.orphaned.is_empty());
+        fs::remove_dir_all(&vault).unwrap();
+    }
+
+    #[test]
+    fn a_panic_while_holding_the_lock_does_not_break_search() {
+        let vault = temp_vault();
+        write(&vault, "a", "tomatoes");
+        let _ = std::thread::spawn(|| {
+            let _guard = lock_index();
+            panic!("poison the index lock");
+        }).join();
+
+        assert_eq!(ids(&vault, "tomatoes"), ["a"]);
+        fs::remove_dir_all(&vault).unwrap();
+    }
+}
@@ -0,0 +1,616 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use serde::{Deserialize, Serialize};
+use anyhow::{Result, Context};
+
+use crate::fs_utils::atomic_write;
+
+/// Directory (relative to the vault root) holding one JSON file per conversation
+pub const CONVERSATIONS_DIR: &str = "conversations";
+/// Message field set to `false` while a reply is still streaming; absent means complete
+pub const COMPLETE_FIELD: &str = "complete";
+/// Message field holding how many characters of a streamed reply are on disk
+pub const COMMITTED_OFFSET_FIELD: &str = "committed_offset";
+/// Message field naming the sidecar stream that produced a reply
+pub const STREAM_ID_FIELD: &str = "stream_id";
+/// Conversation field overriding the vault's default system prompt
+pub const SYSTEM_PROMPT_FIELD: &str = "system_prompt";
+/// Longest system prompt accepted when the vault doesn't set `maxSystemPromptLength`
+pub const DEFAULT_MAX_SYSTEM_PROMPT_CHARS: usize = 8000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Message {
+    pub role: String,
+    pub content: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<String>,
+    /// Unknown keys are preserved so newer frontends don't lose data
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl Message {
+    /// False only for replies explicitly marked `complete: false`
+    pub fn is_complete(&self) -> bool {
+        self.extra.get(COMPLETE_FIELD).and_then(|v| v.as_bool()) != Some(false)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Conversation {
+    pub id: String,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub messages: Vec<Message>,
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// One lock per conversation file. Every write goes through it, so a
+/// command's update and the stream recorder's can't overwrite each other.
+static FILE_LOCKS: OnceLock<Mutex<HashMap<PathBuf, Arc<Mutex<()>>>>> = OnceLock::new();
+
+fn file_lock(path: &Path) -> Arc<Mutex<()>> {
+    FILE_LOCKS.get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .entry(path.to_path_buf())
+        .or_default()
+        .clone()
+}
+
+/// Run `write` holding the lock for the conversation file at `path`
+fn with_file_lock<T>(path: &Path, write: impl FnOnce() -> Result<T>) -> Result<T> {
+    let lock = file_lock(path);
+    let _held = lock.lock().unwrap_or_else(|e| e.into_inner());
+    write()
+}
+
+pub fn conversations_dir(vault_path: &Path) -> PathBuf {
+    vault_path.join(CONVERSATIONS_DIR)
+}
+
+/// Resolve the file for a conversation id, rejecting ids that could escape
+/// the conversations directory.
+pub fn conversation_path(vault_path: &Path, conversation_id: &str) -> Result<PathBuf> {
+    let valid = !conversation_id.is_empty()
+        && conversation_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        anyhow::bail!("Invalid conversation id: {:?}", conversation_id);
+    }
+    Ok(conversations_dir(vault_path).join(format!("{}.json", conversation_id)))
+}
+
+/// Parse and validate conversation JSON, reporting the error location on failure
+pub fn parse_conversation(text: &str) -> Result<Conversation, String> {
+    serde_json::from_str::<Conversation>(text).map_err(|e| {
+        format!("Invalid conversation JSON at line {}, column {}: {}", e.line(), e.column(), e)
+    })
+}
+
+/// Copy the current file to `.tailor/backups/conversations/<id>.<timestamp>.json`
+pub fn backup_conversation(vault_path: &Path, conversation_id: &str) -> Result<Option<PathBuf>> {
+    let source = conversation_path(vault_path, conversation_id)?;
+    if !source.exists() {
+        return Ok(None);
+    }
+
+    let backup_dir = vault_path.join(".tailor").join("backups").join(CONVERSATIONS_DIR);
+    fs::create_dir_all(&backup_dir)
+        .with_context(|| format!("Failed to create {}", backup_dir.display()))?;
+
+    let backup_path = backup_dir.join(format!(
+        "{}.{}.json",
+        conversation_id,
+        chrono::Utc::now().format("%Y%m%d%H%M%S%3f")
+    ));
+    fs::copy(&source, &backup_path)
+        .with_context(|| format!("Failed to back up {}", source.display()))?;
+    Ok(Some(backup_path))
+}
+
+/// Write raw conversation text atomically
+pub fn write_conversation_raw(vault_path: &Path, conversation_id: &str, text: &str) -> Result<()> {
+    let path = conversation_path(vault_path, conversation_id)?;
+    with_file_lock(&path, || atomic_write(&path, text.as_bytes()))
+}
+
+/// Every `*.json` file in the vault's conversations directory, sorted by name
+pub fn conversation_files(vault_path: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = fs::read_dir(conversations_dir(vault_path))
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "json"))
+                .collect()
+        })
+        .unwrap_or_default();
+    files.sort();
+    files
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConversationSummary {
+    pub id: String,
+    pub title: String,
+    pub updated: Option<String>,
+    pub message_count: usize,
+    /// File name within the conversations directory
+    pub file: String,
+    /// Another file in this vault claims the same id
+    pub duplicate: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IdReassignment {
+    pub file: String,
+    pub old_id: String,
+    pub new_id: String,
+}
+
+/// A parsed conversation file paired with where it lives
+struct StoredConversation {
+    path: PathBuf,
+    conversation: Conversation,
+}
+
+fn load_all(vault_path: &Path) -> Vec<StoredConversation> {
+    conversation_files(vault_path)
+        .into_iter()
+        .filter_map(|path| {
+            let text = fs::read_to_string(&path).ok()?;
+            let conversation = parse_conversation(&text).ok()?;
+            Some(StoredConversation { path, conversation })
+        })
+        .collect()
+}
+
+/// Summaries of every readable conversation, flagging ids claimed by more than one file
+pub fn list_conversations(vault_path: &Path) -> Vec<ConversationSummary> {
+    let stored = load_all(vault_path);
+
+    let mut counts = HashMap::new();
+    for item in &stored {
+        *counts.entry(item.conversation.id.clone()).or_insert(0usize) += 1;
+    }
+
+    stored
+        .into_iter()
+        .map(|item| ConversationSummary {
+            duplicate: counts.get(&item.conversation.id).copied().unwrap_or(0) > 1,
+            file: file_name(&item.path),
+            id: item.conversation.id,
+            title: item.conversation.title,
+            updated: item.conversation.updated,
+            message_count: item.conversation.messages.len(),
+        })
+        .collect()
+}
+
+/// Generate an id that no conversation in the vault uses yet
+fn fresh_id(taken: &HashSet<String>) -> String {
+    loop {
+        let id = format!("conv_{}", uuid::Uuid::new_v4().simple());
+        if !taken.contains(&id) {
+            return id;
+        }
+    }
+}
+
+/// Store a new conversation, refusing ids already claimed by any file.
+///
+/// An empty id is replaced with a fresh one.
+pub fn create_conversation(vault_path: &Path, mut conversation: Conversation) -> Result<Conversation> {
+    let taken: HashSet<String> =
+        load_all(vault_path).into_iter().map(|s| s.conversation.id).collect();
+
+    if conversation.id.is_empty() {
+        conversation.id = fresh_id(&taken);
+    } else if taken.contains(&conversation.id) {
+        anyhow::bail!("A conversation with id '{}' already exists", conversation.id);
+    }
+
+    let path = conversation_path(vault_path, &conversation.id)?;
+    with_file_lock(&path, || {
+        if path.exists() {
+            anyhow::bail!("Conversation file already exists: {}", path.display());
+        }
+
+        let now = chrono::Utc::now().to_rfc3339();
+        conversation.created.get_or_insert_with(|| now.clone());
+        conversation.updated.get_or_insert(now);
+
+        atomic_write(&path, serde_json::to_string_pretty(&conversation)?.as_bytes())?;
+        Ok(conversation)
+    })
+}
+
+/// Read and parse a stored conversation by id
+pub fn load_conversation(vault_path: &Path, conversation_id: &str) -> Result<Conversation> {
+    let path = conversation_path(vault_path, conversation_id)?;
+    if !path.exists() {
+        anyhow::bail!("Conversation not found: {}", conversation_id);
+    }
+    let text = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    parse_conversation(&text).map_err(anyhow::Error::msg)
+}
+
+/// Remove a stored conversation's file; false if there was none
+pub fn delete_conversation(vault_path: &Path, conversation_id: &str) -> Result<bool> {
+    let path = conversation_path(vault_path, conversation_id)?;
+    with_file_lock(&path, || {
+        if !path.is_file() {
+            return Ok(false);
+        }
+        fs::remove_file(&path).with_context(|| format!("Failed to delete {}", path.display()))?;
+        Ok(true)
+    })
+}
+
+/// Load a stored conversation, apply `change` and save it, all under the
+/// file's lock; returns the saved conversation
+pub fn update_conversation(
+    vault_path: &Path,
+    conversation_id: &str,
+    change: impl FnOnce(&mut Conversation) -> Result<()>,
+) -> Result<Conversation> {
+    let path = conversation_path(vault_path, conversation_id)?;
+    with_file_lock(&path, || {
+        let mut conversation = load_conversation(vault_path, conversation_id)?;
+        change(&mut conversation)?;
+        atomic_write(&path, serde_json::to_string_pretty(&conversation)?.as_bytes())?;
+        Ok(conversation)
+    })
+}
+
+/// Append a message to a stored conversation and bump its `updated` time
+pub fn append_message(vault_path: &Path, conversation_id: &str, mut message: Message) -> Result<Conversation> {
+    update_conversation(vault_path, conversation_id, |conversation| {
+        let now = chrono::Utc::now().to_rfc3339();
+        message.timestamp.get_or_insert_with(|| now.clone());
+        conversation.messages.push(message);
+        conversation.updated = Some(now);
+        Ok(())
+    })
+}
+
+/// Set (or with `None`, clear) a conversation's own system prompt
+pub fn set_system_prompt(vault_path: &Path, conversation_id: &str, prompt: Option<&str>) -> Result<Conversation> {
+    update_conversation(vault_path, conversation_id, |conversation| {
+        match prompt {
+            Some(prompt) => conversation.extra.insert(SYSTEM_PROMPT_FIELD.to_string(), prompt.into()),
+            None => conversation.extra.remove(SYSTEM_PROMPT_FIELD),
+        };
+        conversation.updated = Some(chrono::Utc::now().to_rfc3339());
+        Ok(())
+    })
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EffectiveSystemPrompt {
+    pub prompt: Option<String>,
+    /// "conversation", "vault", or "none" (the sidecar's built-in prompt)
+    pub source: &'static str,
+}
+
+/// The prompt the sidecar will use: the conversation's override, else the
+/// vault default (`llm.system_prompt` in `.vault.json`)
+pub fn effective_system_prompt(vault_config: &serde_json::Value, conversation: &Conversation) -> EffectiveSystemPrompt {
+    let non_empty = |v: Option<&serde_json::Value>| {
+        v.and_then(|v| v.as_str())
+            .filter(|p| !p.trim().is_empty())
+            .map(str::to_string)
+    };
+
+    if let Some(prompt) = non_empty(conversation.extra.get(SYSTEM_PROMPT_FIELD)) {
+        return EffectiveSystemPrompt { prompt: Some(prompt), source: "conversation" };
+    }
+    match non_empty(vault_config.get("llm").and_then(|llm| llm.get(SYSTEM_PROMPT_FIELD))) {
+        Some(prompt) => EffectiveSystemPrompt { prompt: Some(prompt), source: "vault" },
+        None => EffectiveSystemPrompt { prompt: None, source: "none" },
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IncompleteMessage {
+    pub index: usize,
+    pub role: String,
+    /// Text committed before the stream was interrupted
+    pub content: String,
+    pub committed_offset: usize,
+    pub stream_id: Option<String>,
+    pub timestamp: Option<String>,
+}
+
+/// Messages left `complete: false` by an interrupted stream
+pub fn incomplete_messages(vault_path: &Path, conversation_id: &str) -> Result<Vec<IncompleteMessage>> {
+    let conversation = load_conversation(vault_path, conversation_id)?;
+    Ok(conversation.messages
+        .iter()
+        .enumerate()
+        .filter(|(_, message)| !message.is_complete())
+        .map(|(index, message)| IncompleteMessage {
+            index,
+            role: message.role.clone(),
+            content: message.content.clone(),
+            committed_offset: message.extra.get(COMMITTED_OFFSET_FIELD)
+                .and_then(|v| v.as_u64())
+                .map(|offset| offset as usize)
+                .unwrap_or_else(|| message.content.chars().count()),
+            stream_id: message.extra.get(STREAM_ID_FIELD)
+                .and_then(|v| v.as_str())
+                .map(str::to_string),
+            timestamp: message.timestamp.clone(),
+        })
+        .collect())
+}
+
+/// Give every duplicate-id conversation except the newest a fresh id.
+///
+/// "Newest" is the latest `updated` (falling back to `created`, then file
+/// modification time). Reassigned files are renamed to `<new_id>.json` with
+/// their references to themselves rewritten. A reference from any other
+/// conversation follows the copy only when it names a message (`message_id`)
+/// that only the copy has; otherwise it keeps pointing at the survivor.
+/// Only the keys in `CONVERSATION_REFERENCE_KEYS` are treated as references.
+pub fn resolve_duplicate_ids(vault_path: &Path) -> Result<Vec<IdReassignment>> {
+    let stored = load_all(vault_path);
+    let mut taken: HashSet<String> =
+        stored.iter().map(|s| s.conversation.id.clone()).collect();
+
+    let mut groups: BTreeMap<String, Vec<StoredConversation>> = BTreeMap::new();
+    for item in stored {
+        groups.entry(item.conversation.id.clone()).or_default().push(item);
+    }
+
+    // Decide every new id first, so references between the files can be
+    // rewritten before anything is written
+    let mut moves: Vec<Reassignment> = Vec::new();
+    let mut items: Vec<(StoredConversation, Option<usize>)> = Vec::new();
+    let mut survivors: Vec<(String, PathBuf)> = Vec::new();
+    for (id, mut group) in groups {
+        if group.len() < 2 {
+            items.extend(group.into_iter().map(|item| (item, None)));
+            continue;
+        }
+
+        // Newest first
+        group.sort_by_key(|item| std::cmp::Reverse(recency_key(item)));
+        let messages: Vec<HashSet<String>> =
+            group.iter().map(|item| message_ids(&item.conversation)).collect();
+        for (position, item) in group.into_iter().enumerate() {
+            if position == 0 {
+                survivors.push((id.clone(), item.path.clone()));
+                items.push((item, None));
+                continue;
+            }
+            let new_id = fresh_id(&taken);
+            taken.insert(new_id.clone());
+            // Messages no other file with this id has
+            let unique = messages[position].iter()
+                .filter(|m| {
+                    messages.iter().enumerate().all(|(other, ids)| other == position || !ids.contains(*m))
+                })
+                .cloned()
+                .collect();
+            moves.push(Reassignment { old_id: id.clone(), new_id, unique_messages: unique });
+            items.push((item, Some(moves.len() - 1)));
+        }
+    }
+
+    let mut reassigned = Vec::new();
+    for (mut item, own_move) in items {
+        let mut changed = false;
+        for (index, reassignment) in moves.iter().enumerate() {
+            let follows = |message: Option<&str>| {
+                own_move == Some(index)
+                    || message.is_some_and(|m| reassignment.unique_messages.contains(m))
+            };
+            changed |= replace_references(
+                &mut item.conversation,
+                &reassignment.old_id,
+                &reassignment.new_id,
+                &follows,
+            );
+        }
+
+        let Some(index) = own_move else {
+            if changed {
+                with_file_lock(&item.path, || {
+                    atomic_write(&item.path, serde_json::to_string_pretty(&item.conversation)?.as_bytes())
+                })?;
+                println!("Updated references in conversation {} ({})", item.conversation.id, file_name(&item.path));
+            }
+            continue;
+        };
+
+        let reassignment = &moves[index];
+        item.conversation.id = reassignment.new_id.clone();
+        let target = conversation_path(vault_path, &reassignment.new_id)?;
+        with_file_lock(&target, || {
+            atomic_write(&target, serde_json::to_string_pretty(&item.conversation)?.as_bytes())
+        })?;
+        with_file_lock(&item.path, || {
+            fs::remove_file(&item.path).with_context(|| format!("Failed to remove {}", item.path.display()))
+        })?;
+
+        println!("Reassigned duplicate conversation {} -> {} ({})", reassignment.old_id, reassignment.new_id, file_name(&item.path));
+        reassigned.push(IdReassignment {
+            file: file_name(&target),
+            old_id: reassignment.old_id.clone(),
+            new_id: reassignment.new_id.clone(),
+        });
+    }
+
+    // A survivor may have been the copy with the odd file name
+    for (id, path) in survivors {
+        if let Ok(canonical) = conversation_path(vault_path, &id) {
+            if path != canonical && !canonical.exists() {
+                fs::rename(&path, &canonical)
+                    .with_context(|| format!("Failed to rename {}", path.display()))?;
+            }
+        }
+    }
+
+    Ok(reassigned)
+}
+
+/// A duplicate given a fresh id by `resolve_duplicate_ids`
+struct Reassignment {
+    old_id: String,
+    new_id: String,
+    /// Ids of messages found in this copy and no other file with `old_id`
+    unique_messages: HashSet<String>,
+}
+
+fn recency_key(item: &StoredConversation) -> (Option<chrono::DateTime<chrono::FixedOffset>>, std::time::SystemTime) {
+    let stamp = item.conversation.updated.as_deref()
+        .or(item.conversation.created.as_deref())
+        .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok());
+    let modified = fs::metadata(&item.path)
+        .and_then(|m| m.modified())
+        .unwrap_or(std::time::UNIX_EPOCH);
+    (stamp, modified)
+}
+
+/// Keys whose string value names a conversation, at any depth of a
+/// conversation or its messages (a branch's origin, a link to another chat)
+const CONVERSATION_REFERENCE_KEYS: &[&str] = &["conversation_id", "chat_id", "parent_id", "branch_of", "forked_from"];
+/// Key naming the message a reference points at, next to a conversation key
+const MESSAGE_REFERENCE_KEY: &str = "message_id";
+
+fn message_ids(conversation: &Conversation) -> HashSet<String> {
+    conversation.messages.iter()
+        .filter_map(|m| m.extra.get("id").and_then(|id| id.as_str()))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Point references to `old` at `new` where `follows` agrees, given the
+/// message the reference names (if any). True if anything changed.
+fn replace_references(
+    conversation: &mut Conversation,
+    old: &str,
+    new: &str,
+    follows: &dyn Fn(Option<&str>) -> bool,
+) -> bool {
+    fn walk(value: &mut serde_json::Value, old: &str, new: &str, follows: &dyn Fn(Option<&str>) -> bool) -> bool {
+        match value {
+            serde_json::Value::Array(items) => {
+                items.iter_mut().fold(false, |changed, v| walk(v, old, new, follows) | changed)
+            }
+            serde_json::Value::Object(map) => replace_in_map(map, old, new, follows),
+            _ => false,
+        }
+    }
+    fn replace_in_map(
+        map: &mut serde_json::Map<String, serde_json::Value>,
+        old: &str,
+        new: &str,
+        follows: &dyn Fn(Option<&str>) -> bool,
+    ) -> bool {
+        let message = map.get(MESSAGE_REFERENCE_KEY).and_then(|m| m.as_str()).map(str::to_string);
+        let mut changed = false;
+        for (key, value) in map.iter_mut() {
+            let is_reference = CONVERSATION_REFERENCE_KEYS.contains(&key.as_str())
+                && value.as_str() == Some(old);
+            if is_reference {
+                if follows(message.as_deref()) {
+                    *value = serde_json::Value::String(new.to_string());
+                    changed = true;
+                }
+            } else {
+                changed |= walk(value, old, new, follows);
+            }
+        }
+        changed
+    }
+
+    let mut changed = replace_in_map(&mut conversation.extra, old, new, follows);
+    for message in &mut conversation.messages {
+        changed |= replace_in_map(&mut message.extra, old, new, follows);
+    }
+    changed
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_vault() -> PathBuf {
+        let vault = std::env::temp_dir().join(format!("tailor-conversations-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(conversations_dir(&vault)).unwrap();
+        vault
+    }
+
+    fn write(vault: &Path, file: &str, conversation: serde_json::Value) {
+        fs::write(conversations_dir(vault).join(file), conversation.to_string()).unwrap();
+    }
+
+    fn read(vault: &Path, id: &str) -> serde_json::Value {
+        serde_json::from_str(&fs::read_to_string(conversation_path(vault, id).unwrap()).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn reassigned_duplicates_carry_their_cross_references_along() {
+        let vault = temp_vault();
+        write(&vault, "dup.json", serde_json::json!({
+            "id": "dup",
+            "updated": "2026-02-01T00:00:00Z",
+            "messages": [{ "role": "user", "content": "kept", "id": "m-kept" }],
+        }));
+        write(&vault, "dup-copy.json", serde_json::json!({
+            "id": "dup",
+            "updated": "2026-01-01T00:00:00Z",
+            "branch_of": "dup",
+            "note": "dup",
+            "messages": [{ "role": "user", "content": "copied", "id": "m-copy" }],
+        }));
+        write(&vault, "other.json", serde_json::json!({
+            "id": "other",
+            "note": "dup",
+            "links": [
+                { "conversation_id": "dup", "message_id": "m-copy" },
+                { "conversation_id": "dup", "message_id": "m-kept" },
+            ],
+            "messages": [{ "role": "user", "content": "see", "chat_id": "dup" }],
+        }));
+
+        let reassigned = resolve_duplicate_ids(&vault).unwrap();
+        assert_eq!(reassigned.len(), 1);
+        let new_id = &reassigned[0].new_id;
+        assert_eq!(reassigned[0].old_id, "dup");
+        assert!(!conversations_dir(&vault).join("dup-copy.json").exists());
+
+        let copy = read(&vault, new_id);
+        assert_eq!(copy["id"], *new_id);
+        assert_eq!(copy["branch_of"], *new_id);
+        assert_eq!(copy["note"], "dup");
+
+        let other = read(&vault, "other");
+        assert_eq!(other["links"][0]["conversation_id"], *new_id);
+        assert_eq!(other["links"][1]["conversation_id"], "dup");
+        assert_eq!(other["messages"][0]["chat_id"], "dup");
+        assert_eq!(other["note"], "dup");
+
+        assert_eq!(read(&vault, "dup")["messages"][0]["content"], "kept");
+        fs::remove_dir_all(&vault).unwrap();
+    }
+}
@@ -0,0 +1,223 @@
+use std::collections::{HashMap, VecDeque};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+
+use crate::command_error::CommandError;
+use crate::connection_pool::ConnectionPool;
+use crate::settings;
+use crate::sidecar_manager::SidecarManager;
+
+/// Vault setting choosing what happens when the sidecar crashes
+pub const CRASH_POLICY_SETTING: &str = "crashPolicy";
+/// How often sidecar processes are checked for an unexpected exit
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+/// First auto-restart delay; doubles with each recent crash
+const RESTART_BACKOFF_BASE: Duration = Duration::from_secs(1);
+const RESTART_BACKOFF_CAP: Duration = Duration::from_secs(30);
+/// Crashes within this many minutes count towards a crash loop
+const CRASH_LOOP_WINDOW_MINUTES: i64 = 10;
+/// Crashes within the window at which auto-restart gives up
+const CRASH_LOOP_THRESHOLD: usize = 5;
+/// Crashes remembered per window
+const CRASHES_PER_WINDOW: usize = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CrashPolicy {
+    /// Respawn right away, with backoff, until a crash loop is detected
+    Auto,
+    /// Emit `sidecar://crashed` and let the UI offer a restart
+    Prompt,
+    /// Do nothing
+    Off,
+}
+
+impl CrashPolicy {
+    pub fn for_vault(vault_path: &Path) -> Self {
+        settings::load_vault_settings(vault_path)
+            .ok()
+            .and_then(|s| s.get(CRASH_POLICY_SETTING).cloned())
+            .and_then(|v| serde_json::from_value(v).ok())
+            .unwrap_or(Self::Prompt)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CrashRecord {
+    pub window_label: String,
+    pub timestamp: DateTime<Utc>,
+    pub pid: u32,
+    pub exit_code: Option<i32>,
+    pub policy: CrashPolicy,
+    /// "restart_scheduled", "prompted", "ignored" or "crash_loop"
+    pub action: String,
+    /// Crashes within the crash-loop window, this one included
+    pub recent_crashes: usize,
+    pub restart_delay_ms: Option<u64>,
+    /// Port of the respawned sidecar, once an auto-restart succeeded
+    pub restarted_port: Option<u16>,
+    pub error: Option<String>,
+}
+
+/// Recent sidecar crashes, keyed by window
+#[derive(Default)]
+pub struct CrashHistory {
+    windows: Mutex<HashMap<String, VecDeque<CrashRecord>>>,
+}
+
+impl CrashHistory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Oldest first
+    pub fn history(&self, window_label: &str) -> Vec<CrashRecord> {
+        self.windows.lock().unwrap()
+            .get(window_label)
+            .map(|records| records.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    fn record(&self, record: CrashRecord) {
+        let mut windows = self.windows.lock().unwrap();
+        let records = windows.entry(record.window_label.clone()).or_default();
+        if records.len() == CRASHES_PER_WINDOW {
+            records.pop_front();
+        }
+        records.push_back(record);
+    }
+
+    fn recent_count(&self, window_label: &str, now: DateTime<Utc>) -> usize {
+        self.windows.lock().unwrap()
+            .get(window_label)
+            .map(|records| records.iter().filter(|r| now - r.timestamp <= chrono::Duration::minutes(CRASH_LOOP_WINDOW_MINUTES)).count())
+            .unwrap_or(0)
+    }
+
+    fn set_restart_outcome(&self, window_label: &str, pid: u32, outcome: &Result<u16, CommandError>) -> Option<CrashRecord> {
+        let mut windows = self.windows.lock().unwrap();
+        let record = windows.get_mut(window_label)?.iter_mut().rev().find(|r| r.pid == pid)?;
+        match outcome {
+            Ok(port) => record.restarted_port = Some(*port),
+            Err(e) => record.error = Some(e.to_string()),
+        }
+        Some(record.clone())
+    }
+}
+
+/// Restart delay after `recent_crashes` crashes in the window
+fn backoff(recent_crashes: usize) -> Duration {
+    let exponent = recent_crashes.saturating_sub(1).min(16) as u32;
+    (RESTART_BACKOFF_BASE * 2u32.pow(exponent)).min(RESTART_BACKOFF_CAP)
+}
+
+/// Watch every window's sidecar for an unexpected exit and apply the vault's
+/// `crashPolicy`. Emits `sidecar://crashed` for each crash,
+/// `sidecar://crash-loop` when auto-restart gives up, and
+/// `sidecar://restarted` once a respawned sidecar is registered under the
+/// same window label.
+pub fn spawn_crash_monitor(
+    app: AppHandle,
+    sidecar_manager: Arc<SidecarManager>,
+    pool: Arc<ConnectionPool>,
+    history: Arc<CrashHistory>,
+) {
+    tauri::async_runtime::spawn(async move {
+        // Pid of the exited process already handled per window, so a dead
+        // sidecar left in place (policy off/prompt, crash loop) counts once
+        let mut handled: HashMap<String, u32> = HashMap::new();
+        loop {
+            tokio::time::sleep(POLL_INTERVAL).await;
+
+            let labels = sidecar_manager.window_labels().await;
+            handled.retain(|label, _| labels.contains(label));
+
+            for label in labels {
+                let Some(status) = sidecar_manager.process_status(&label).await else { continue };
+                if status.running || handled.get(&label) == Some(&status.pid) {
+                    continue;
+                }
+                handled.insert(label.clone(), status.pid);
+
+                let now = Utc::now();
+                let policy = CrashPolicy::for_vault(Path::new(&status.vault_path));
+                let recent_crashes = history.recent_count(&label, now) + 1;
+                let (action, restart_delay) = match policy {
+                    CrashPolicy::Auto if recent_crashes >= CRASH_LOOP_THRESHOLD => ("crash_loop", None),
+                    CrashPolicy::Auto => ("restart_scheduled", Some(backoff(recent_crashes))),
+                    CrashPolicy::Prompt => ("prompted", None),
+                    CrashPolicy::Off => ("ignored", None),
+                };
+
+                let record = CrashRecord {
+                    window_label: label.clone(),
+                    timestamp: now,
+                    pid: status.pid,
+                    exit_code: status.exit_code,
+                    policy,
+                    action: action.to_string(),
+                    recent_crashes,
+                    restart_delay_ms: restart_delay.map(|d| d.as_millis() as u64),
+                    restarted_port: None,
+                    error: (action == "crash_loop").then(|| format!(
+                        "sidecar crashed {} times in {} minutes; auto-restart stopped",
+                        recent_crashes,
+                        CRASH_LOOP_WINDOW_MINUTES,
+                    )),
+                };
+                eprintln!(
+                    "Sidecar for window '{}' exited (code {:?}); policy {:?}, {}",
+                    label, status.exit_code, policy, action
+                );
+                history.record(record.clone());
+                if policy != CrashPolicy::Off {
+                    let _ = app.emit("sidecar://crashed", &record);
+                }
+                if action == "crash_loop" {
+                    let _ = app.emit("sidecar://crash-loop", &record);
+                }
+
+                if let Some(delay) = restart_delay {
+                    let app = app.clone();
+                    let sidecar_manager = sidecar_manager.clone();
+                    let pool = pool.clone();
+                    let history = history.clone();
+                    let old_port = status.ws_port;
+                    tauri::async_runtime::spawn(async move {
+                        tokio::time::sleep(delay).await;
+                        let outcome = restart(&sidecar_manager, &pool, &label, old_port).await;
+                        if let Err(e) = &outcome {
+                            eprintln!("Failed to restart sidecar for window '{}': {}", label, e);
+                        }
+                        if let Some(record) = history.set_restart_outcome(&label, status.pid, &outcome) {
+                            if outcome.is_ok() {
+                                let _ = app.emit("sidecar://restarted", &record);
+                            }
+                        }
+                    });
+                }
+            }
+        }
+    });
+}
+
+/// Respawn a window's sidecar and connect the pool to its new port
+pub async fn restart(
+    sidecar_manager: &SidecarManager,
+    pool: &Arc<ConnectionPool>,
+    window_label: &str,
+    old_port: u16,
+) -> Result<u16, CommandError> {
+    pool.disconnect(old_port).await;
+    let ws_port = sidecar_manager
+        .restart_sidecar(window_label)
+        .await
+        .map_err(|e| CommandError::SidecarUnavailable(format!("Failed to restart sidecar: {}", e)))?;
+    pool.connect_when_ready(ws_port);
+    println!("Sidecar restarted for window '{}' on port {}", window_label, ws_port);
+    Ok(ws_port)
+}
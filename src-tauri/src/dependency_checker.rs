@@ -1,27 +1,176 @@
-use std::path::PathBuf;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 use anyhow::{Result, Context};
+use serde::Serialize;
+
+use crate::artifact_scanner::ArtifactScanner;
+use crate::plugins;
+
+/// Directory (relative to the vault root) dependencies are installed into
+/// when the vault has no venv under the app data dir; the sidecar puts it
+/// on its import path
+pub const LIB_DIR: &str = "lib";
+const REQUIREMENTS_FILE: &str = "requirements.txt";
+
+/// One line of a requirements file
+#[derive(Debug, Clone, Serialize)]
+pub struct Requirement {
+    /// Normalized distribution name (lowercase, `-` separated)
+    pub name: String,
+    /// The line as written, e.g. "requests>=2.31"
+    pub spec: String,
+    /// Requirements file it came from, relative to the vault
+    pub source: String,
+}
+
+/// What the vault needs against what its lib dir has
+#[derive(Debug, Clone, Serialize)]
+pub struct DependencyStatus {
+    /// Nothing to install
+    pub satisfied: bool,
+    pub required: Vec<Requirement>,
+    pub missing: Vec<Requirement>,
+    pub lib_dir: String,
+}
+
+/// Progress of `check_and_install`, also emitted as `deps://progress`
+#[derive(Debug, Clone, Serialize)]
+pub struct DependencyProgress {
+    /// 1-based index of the package being installed; 0 while checking
+    pub step: usize,
+    pub total: usize,
+    /// "checking", "satisfied", "installing", "installed" or "failed"
+    pub status: &'static str,
+    pub package: Option<String>,
+    pub message: String,
+}
 
 pub struct DependencyChecker;
 
 impl DependencyChecker {
-    /// Check and install dependencies for a vault
-    pub async fn check_and_install(vault_path: &str) -> Result<()> {
-        // Dependency management is now handled by pixi at the project level.
-        // We no longer install per-vault requirements.txt.
-        println!("Skipping per-vault dependency check for: {} (handled by pixi)", vault_path);
+    /// Where the vault's dependencies are installed: its venv under the app
+    /// data dir, or `lib/` in vaults without an id
+    pub fn lib_dir(vault_path: &Path) -> PathBuf {
+        ArtifactScanner::vault_venv(vault_path).unwrap_or_else(|| vault_path.join(LIB_DIR))
+    }
+
+    /// Requirements of the vault (`plugins/requirements.txt` and each
+    /// plugin's own) and which of them its lib dir lacks. Packages installed
+    /// in `lib/` by older versions count too. Only presence is checked, not
+    /// versions.
+    pub fn check(vault_path: &Path) -> DependencyStatus {
+        let lib_dir = Self::lib_dir(vault_path);
+        let mut installed = installed_distributions(&lib_dir);
+        installed.extend(installed_distributions(&vault_path.join(LIB_DIR)));
+
+        let mut files = vec![vault_path.join(plugins::PLUGINS_DIR).join(REQUIREMENTS_FILE)];
+        files.extend(plugins::plugin_dirs(vault_path).into_iter().map(|dir| dir.join(REQUIREMENTS_FILE)));
+
+        let mut seen = HashSet::new();
+        let required: Vec<Requirement> = files.iter()
+            .flat_map(|file| parse_requirements(vault_path, file))
+            .filter(|req| seen.insert(req.name.clone()))
+            .collect();
+        let missing: Vec<Requirement> = required.iter()
+            .filter(|req| !installed.contains(&req.name))
+            .cloned()
+            .collect();
+
+        DependencyStatus {
+            satisfied: missing.is_empty(),
+            required,
+            missing,
+            lib_dir: lib_dir.to_string_lossy().to_string(),
+        }
+    }
+
+    /// Install whatever `check` finds missing into the lib dir, one package at a
+    /// time so `on_progress` can follow along. A satisfied vault returns
+    /// after a single "satisfied" report without running pip.
+    ///
+    /// On failure the error names the package, and says whether packages
+    /// installed earlier in the run were left in the lib dir.
+    pub async fn check_and_install(vault_path: &str, on_progress: impl Fn(DependencyProgress)) -> Result<()> {
+        let vault = PathBuf::from(vault_path);
+        on_progress(DependencyProgress {
+            step: 0,
+            total: 0,
+            status: "checking",
+            package: None,
+            message: "Checking dependencies".to_string(),
+        });
+
+        let status = Self::check(&vault);
+        let total = status.missing.len();
+        if status.satisfied {
+            on_progress(DependencyProgress {
+                step: 0,
+                total: 0,
+                status: "satisfied",
+                package: None,
+                message: "Dependencies already satisfied".to_string(),
+            });
+            return Ok(());
+        }
+
+        println!("Installing {} dependencies for: {}", total, vault_path);
+        let python = Self::get_python_executable()?;
+        let lib_dir = Self::lib_dir(&vault);
+        if ArtifactScanner::vault_venv(&vault).is_some() {
+            ArtifactScanner::create_artifact_dir(&lib_dir)?;
+        } else {
+            fs::create_dir_all(&lib_dir)
+                .with_context(|| format!("Failed to create {}", lib_dir.display()))?;
+        }
+
+        for (index, req) in status.missing.iter().enumerate() {
+            let step = index + 1;
+            on_progress(DependencyProgress {
+                step,
+                total,
+                status: "installing",
+                package: Some(req.name.clone()),
+                message: format!("Installing {} ({} of {})", req.spec, step, total),
+            });
+
+            if let Err(e) = pip_install(&python, &lib_dir, &req.spec).await {
+                let partial = if index > 0 {
+                    format!("{} package(s) installed before it were left in {}", index, lib_dir.display())
+                } else {
+                    "nothing was installed".to_string()
+                };
+                on_progress(DependencyProgress {
+                    step,
+                    total,
+                    status: "failed",
+                    package: Some(req.name.clone()),
+                    message: e.to_string(),
+                });
+                anyhow::bail!("Failed to install '{}' (from {}): {}; {}", req.spec, req.source, e, partial);
+            }
+
+            on_progress(DependencyProgress {
+                step,
+                total,
+                status: "installed",
+                package: Some(req.name.clone()),
+                message: format!("Installed {}", req.spec),
+            });
+        }
         Ok(())
     }
 
-    /// Get pip executable
-    fn get_pip_executable() -> Result<String> {
+    /// Python interpreter the sidecar runs under
+    pub fn get_python_executable() -> Result<String> {
         #[cfg(target_os = "windows")]
-        let pip_candidates = vec!["pip.exe", "pip3.exe"];
-        
+        let python_candidates = vec!["python.exe", "python3.exe"];
+
         #[cfg(not(target_os = "windows"))]
-        let pip_candidates = vec!["pip3", "pip"];
+        let python_candidates = vec!["python3", "python"];
 
-        for candidate in pip_candidates {
+        for candidate in python_candidates {
             if let Ok(output) = Command::new(candidate)
                 .arg("--version")
                 .output()
@@ -32,28 +181,89 @@ impl DependencyChecker {
             }
         }
 
-        anyhow::bail!("pip not found in PATH")
+        anyhow::bail!("Python not found in PATH")
+    }
+
+    /// Version reported by `python --version`, e.g. "3.12.1"
+    pub fn get_python_version(python_exe: &str) -> Result<String> {
+        let output = Command::new(python_exe)
+            .arg("--version")
+            .output()
+            .with_context(|| format!("Failed to run {}", python_exe))?;
+        // Python 2 and early 3.x print the version to stderr
+        let text = format!(
+            "{}{}",
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        );
+        text.split_whitespace()
+            .find(|word| word.starts_with(|c: char| c.is_ascii_digit()))
+            .map(str::to_string)
+            .with_context(|| format!("Unexpected --version output from {}: {}", python_exe, text.trim()))
     }
+}
 
-    /// Check if dependencies need updating
-    #[allow(dead_code)]
-    pub async fn needs_update(vault_path: &str) -> Result<bool> {
-        let vault_path = PathBuf::from(vault_path);
-        let requirements_file = vault_path.join("plugins").join("requirements.txt");
-        let lib_dir = vault_path.join("lib");
+async fn pip_install(python: &str, lib_dir: &Path, spec: &str) -> Result<()> {
+    let output = tokio::process::Command::new(python)
+        .args(["-m", "pip", "install", "--quiet", "--disable-pip-version-check", "--target"])
+        .arg(lib_dir)
+        .arg(spec)
+        .output()
+        .await
+        .with_context(|| format!("Failed to run pip with {}", python))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let reason = stderr.lines().rev().find(|line| !line.trim().is_empty()).unwrap_or("pip failed");
+        anyhow::bail!("{}", reason.trim());
+    }
+    Ok(())
+}
 
-        // If requirements.txt doesn't exist, no update needed
-        if !requirements_file.exists() {
-            return Ok(false);
-        }
+/// Requirements in `file`, skipping comments and pip options (`-r`, `-e`, ...)
+fn parse_requirements(vault_path: &Path, file: &Path) -> Vec<Requirement> {
+    let Ok(contents) = fs::read_to_string(file) else { return Vec::new() };
+    let source = file.strip_prefix(vault_path).unwrap_or(file).to_string_lossy().to_string();
+    contents.lines()
+        .map(|line| line.split('#').next().unwrap_or("").trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('-'))
+        .filter_map(|spec| {
+            let name = spec.split(|c: char| "<>=!~;[@ ".contains(c)).next()?;
+            (!name.is_empty()).then(|| Requirement {
+                name: normalize(name),
+                spec: spec.to_string(),
+                source: source.clone(),
+            })
+        })
+        .collect()
+}
 
-        // If lib directory doesn't exist, update needed
-        if !lib_dir.exists() {
-            return Ok(true);
-        }
+/// Distributions installed in `lib_dir`, from their metadata directories
+fn installed_distributions(lib_dir: &Path) -> HashSet<String> {
+    fs::read_dir(lib_dir)
+        .map(|entries| {
+            entries.flatten()
+                .filter_map(|entry| {
+                    let name = entry.file_name().to_string_lossy().to_string();
+                    let stem = name.strip_suffix(".dist-info").or_else(|| name.strip_suffix(".egg-info"))?;
+                    // `{name}-{version}`; names never contain `-` here
+                    Some(normalize(stem.split('-').next().unwrap_or(stem)))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
 
-        // Check modification times (simplified check)
-        // In production, you'd want to parse requirements.txt and check installed versions
-        Ok(false)
+/// PEP 503 name normalization
+fn normalize(name: &str) -> String {
+    let mut normalized = String::with_capacity(name.len());
+    for c in name.chars() {
+        if matches!(c, '-' | '_' | '.') {
+            if !normalized.ends_with('-') {
+                normalized.push('-');
+            }
+        } else {
+            normalized.push(c.to_ascii_lowercase());
+        }
     }
+    normalized
 }
@@ -0,0 +1,400 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::artifact_scanner::{self, ArtifactScanner};
+use crate::command_error::CommandError;
+use crate::fs_utils::dir_size;
+use crate::recents;
+use crate::settings;
+use crate::vault_excludes::ExcludeMatcher;
+
+/// Share of `maxTotalDiskBytes` past which the warning event fires
+const WARNING_RATIO: f64 = 0.9;
+/// How often the monitor re-measures while a cap is configured
+const CHECK_INTERVAL: Duration = Duration::from_secs(1800);
+pub const DISK_WARNING_EVENT: &str = "disk-usage://warning";
+
+/// Vault-relative folders counted apart from the vault's content. Backups
+/// and logs are not excluded from copies, so they are matched by location
+/// before the exclude rules are consulted.
+const BACKUP_DIRS: &[&str] = &[".tailor/backups"];
+const LOG_DIRS: &[&str] = &[".tailor/logs"];
+const TRASH_DIRS: &[&str] = &[".trash", ".tailor/trash"];
+const VENV_DIRS: &[&str] = &[".venv", "venv"];
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct VaultDiskUsage {
+    pub name: String,
+    pub path: String,
+    pub total_bytes: u64,
+    /// Files the vault's exclude rules keep, less backups and logs
+    pub content_bytes: u64,
+    /// The vault's own venv plus its venv under the app data directory
+    pub venv_bytes: u64,
+    /// Everything else the exclude rules drop, plus the vault's app cache
+    pub cache_bytes: u64,
+    pub backup_bytes: u64,
+    pub trash_bytes: u64,
+    pub log_bytes: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GlobalDiskUsage {
+    /// App caches not belonging to a registered vault
+    pub cache_bytes: u64,
+    /// Venvs under the app data directory not belonging to a registered vault
+    pub orphaned_venv_bytes: u64,
+    pub log_bytes: u64,
+    /// The rest of the app data directory: shared plugins, registry, settings
+    pub app_data_bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PruneSuggestion {
+    /// "backups", "cache", "trash" or "orphaned_venvs"
+    pub kind: String,
+    pub path: String,
+    pub bytes: u64,
+    pub description: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TotalDiskUsage {
+    pub total_bytes: u64,
+    pub vaults: Vec<VaultDiskUsage>,
+    pub global: GlobalDiskUsage,
+    /// `maxTotalDiskBytes`, if configured
+    pub limit_bytes: Option<u64>,
+    /// True once usage passes 90% of the limit
+    pub near_limit: bool,
+    /// Largest reclaimable items first
+    pub suggestions: Vec<PruneSuggestion>,
+}
+
+/// Directories the app keeps outside any vault
+pub struct AppDirs {
+    pub config: PathBuf,
+    pub data: PathBuf,
+    pub cache: PathBuf,
+    pub log: Option<PathBuf>,
+}
+
+impl AppDirs {
+    pub fn from_app(app: &AppHandle) -> Result<Self, CommandError> {
+        Ok(Self {
+            config: app.path().app_config_dir()
+                .map_err(|e| CommandError::Internal(format!("Failed to get app config directory: {}", e)))?,
+            data: app.path().app_data_dir()
+                .map_err(|e| CommandError::Internal(format!("Failed to get app data directory: {}", e)))?,
+            cache: app.path().app_cache_dir()
+                .map_err(|e| CommandError::Internal(format!("Failed to get app cache directory: {}", e)))?,
+            log: app.path().app_log_dir().ok(),
+        })
+    }
+}
+
+/// Measure every registered vault and the app's own directories
+pub fn measure(dirs: &AppDirs, limit_bytes: Option<u64>) -> TotalDiskUsage {
+    let venvs_root = ArtifactScanner::venvs_root(&dirs.data);
+    let caches_root = ArtifactScanner::caches_root(&dirs.cache);
+
+    let mut attributed = HashSet::new();
+    let mut vaults = Vec::new();
+    for item in recents::load(&dirs.config) {
+        let vault = PathBuf::from(&item.path);
+        if !vault.is_dir() {
+            continue;
+        }
+        let mut usage = measure_vault(&vault);
+        usage.name = item.name.clone();
+        usage.path = item.path.clone();
+        if let Some(id) = artifact_scanner::vault_id(&vault) {
+            usage.venv_bytes += dir_size(&venvs_root.join(&id));
+            usage.cache_bytes += dir_size(&caches_root.join(&id));
+            attributed.insert(id);
+        }
+        usage.total_bytes = usage.content_bytes + usage.venv_bytes + usage.cache_bytes
+            + usage.backup_bytes + usage.trash_bytes + usage.log_bytes;
+        vaults.push(usage);
+    }
+
+    let orphaned_venv_bytes = unattributed_size(&venvs_root, &attributed);
+    let venvs_total = dir_size(&venvs_root);
+    let log_bytes = dirs.log.as_deref().map(dir_size).unwrap_or(0);
+    let log_in_data = dirs.log.as_deref().is_some_and(|log| log.starts_with(&dirs.data));
+    let cache_in_data = dirs.cache.starts_with(&dirs.data);
+    let cache_total = dir_size(&dirs.cache);
+    let attributed_cache: u64 = attributed.iter().map(|id| dir_size(&caches_root.join(id))).sum();
+    let global = GlobalDiskUsage {
+        cache_bytes: cache_total.saturating_sub(attributed_cache),
+        orphaned_venv_bytes,
+        log_bytes,
+        app_data_bytes: dir_size(&dirs.data)
+            .saturating_sub(venvs_total)
+            .saturating_sub(if log_in_data { log_bytes } else { 0 })
+            .saturating_sub(if cache_in_data { cache_total } else { 0 }),
+    };
+
+    let total_bytes = vaults.iter().map(|v| v.total_bytes).sum::<u64>()
+        + global.cache_bytes + global.orphaned_venv_bytes + global.log_bytes + global.app_data_bytes;
+    let near_limit = limit_bytes.is_some_and(|limit| total_bytes as f64 >= limit as f64 * WARNING_RATIO);
+    let suggestions = suggestions(&vaults, &global, dirs);
+
+    TotalDiskUsage { total_bytes, vaults, global, limit_bytes, near_limit, suggestions }
+}
+
+/// Split a vault's files by kind, using the vault's exclude rules for what
+/// counts as content
+fn measure_vault(vault: &Path) -> VaultDiskUsage {
+    let matcher = ExcludeMatcher::load(vault);
+    let mut usage = VaultDiskUsage::default();
+    walk(vault, Path::new(""), &matcher, None, &mut usage);
+    usage
+}
+
+#[derive(Clone, Copy)]
+enum Bucket {
+    Content,
+    Venv,
+    Cache,
+    Backup,
+    Trash,
+    Log,
+}
+
+fn walk(root: &Path, rel: &Path, matcher: &ExcludeMatcher, bucket: Option<Bucket>, usage: &mut VaultDiskUsage) {
+    let Ok(entries) = fs::read_dir(root.join(rel)) else { return };
+    for entry in entries.flatten() {
+        let Ok(file_type) = entry.file_type() else { continue };
+        let child = rel.join(entry.file_name());
+        let bucket = bucket.unwrap_or_else(|| classify(&child, file_type.is_dir(), matcher));
+        if file_type.is_dir() {
+            // Below a classified directory everything shares its bucket
+            let inherited = match bucket {
+                Bucket::Content => None,
+                other => Some(other),
+            };
+            walk(root, &child, matcher, inherited, usage);
+        } else if file_type.is_file() {
+            let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+            *match bucket {
+                Bucket::Content => &mut usage.content_bytes,
+                Bucket::Venv => &mut usage.venv_bytes,
+                Bucket::Cache => &mut usage.cache_bytes,
+                Bucket::Backup => &mut usage.backup_bytes,
+                Bucket::Trash => &mut usage.trash_bytes,
+                Bucket::Log => &mut usage.log_bytes,
+            } += size;
+        }
+    }
+}
+
+fn classify(rel: &Path, is_dir: bool, matcher: &ExcludeMatcher) -> Bucket {
+    let is = |dirs: &[&str]| is_dir && dirs.iter().any(|d| rel == Path::new(d));
+    if is(BACKUP_DIRS) {
+        Bucket::Backup
+    } else if is(LOG_DIRS) {
+        Bucket::Log
+    } else if is(TRASH_DIRS) {
+        Bucket::Trash
+    } else if is(VENV_DIRS) {
+        Bucket::Venv
+    } else if matcher.is_excluded(rel, is_dir) {
+        Bucket::Cache
+    } else {
+        Bucket::Content
+    }
+}
+
+fn unattributed_size(root: &Path, attributed: &HashSet<String>) -> u64 {
+    let Ok(entries) = fs::read_dir(root) else { return 0 };
+    entries.flatten()
+        .filter(|entry| !attributed.contains(&entry.file_name().to_string_lossy().to_string()))
+        .map(|entry| match entry.file_type() {
+            Ok(t) if t.is_dir() => dir_size(&entry.path()),
+            _ => entry.metadata().map(|m| m.len()).unwrap_or(0),
+        })
+        .sum()
+}
+
+fn suggestions(vaults: &[VaultDiskUsage], global: &GlobalDiskUsage, dirs: &AppDirs) -> Vec<PruneSuggestion> {
+    let mut suggestions = Vec::new();
+    for vault in vaults {
+        let vault_dir = Path::new(&vault.path);
+        if vault.backup_bytes > 0 {
+            suggestions.push(PruneSuggestion {
+                kind: "backups".to_string(),
+                path: vault_dir.join(BACKUP_DIRS[0]).to_string_lossy().to_string(),
+                bytes: vault.backup_bytes,
+                description: format!("Delete old backups in '{}'", vault.name),
+            });
+        }
+        if vault.trash_bytes > 0 {
+            suggestions.push(PruneSuggestion {
+                kind: "trash".to_string(),
+                path: vault.path.clone(),
+                bytes: vault.trash_bytes,
+                description: format!("Empty the trash in '{}'", vault.name),
+            });
+        }
+        if vault.cache_bytes > 0 {
+            suggestions.push(PruneSuggestion {
+                kind: "cache".to_string(),
+                path: vault.path.clone(),
+                bytes: vault.cache_bytes,
+                description: format!("Clear caches of '{}'; they are rebuilt on demand", vault.name),
+            });
+        }
+    }
+    if global.orphaned_venv_bytes > 0 {
+        suggestions.push(PruneSuggestion {
+            kind: "orphaned_venvs".to_string(),
+            path: ArtifactScanner::venvs_root(&dirs.data).to_string_lossy().to_string(),
+            bytes: global.orphaned_venv_bytes,
+            description: "Remove venvs of vaults that are no longer registered (clean_orphaned_artifacts)".to_string(),
+        });
+    }
+    if global.cache_bytes > 0 {
+        suggestions.push(PruneSuggestion {
+            kind: "cache".to_string(),
+            path: dirs.cache.to_string_lossy().to_string(),
+            bytes: global.cache_bytes,
+            description: "Clear the app cache".to_string(),
+        });
+    }
+    suggestions.sort_by(|a, b| b.bytes.cmp(&a.bytes));
+    suggestions
+}
+
+/// Measure, and emit `disk-usage://warning` when usage nears the cap
+pub fn check(app: &AppHandle) -> Result<TotalDiskUsage, CommandError> {
+    let dirs = AppDirs::from_app(app)?;
+    let global_settings = settings::load_global_settings(&dirs.config)
+        .map_err(|e| CommandError::Io(format!("Failed to load global settings: {}", e)))?;
+    let usage = measure(&dirs, settings::max_total_disk_bytes(&global_settings));
+
+    if usage.near_limit {
+        eprintln!(
+            "Warning: Tailor data uses {} of {} allowed bytes",
+            usage.total_bytes,
+            usage.limit_bytes.unwrap_or_default(),
+        );
+        if let Err(e) = app.emit(DISK_WARNING_EVENT, &usage) {
+            eprintln!("Warning: Failed to emit disk usage warning: {}", e);
+        }
+    }
+    Ok(usage)
+}
+
+/// Re-check disk usage periodically while `maxTotalDiskBytes` is set
+pub fn spawn_disk_usage_monitor(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let capped = app.path().app_config_dir().ok()
+                .and_then(|dir| settings::load_global_settings(&dir).ok())
+                .and_then(|s| settings::max_total_disk_bytes(&s))
+                .is_some();
+            if capped {
+                let app = app.clone();
+                let _ = tauri::async_runtime::spawn_blocking(move || {
+                    if let Err(e) = check(&app) {
+                        eprintln!("Warning: Disk usage check failed: {}", e);
+                    }
+                }).await;
+            }
+            tokio::time::sleep(CHECK_INTERVAL).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(path: &Path, len: usize) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, vec![b'x'; len]).unwrap();
+    }
+
+    /// A registered vault with id "v1", app dirs holding its venv and cache,
+    /// and leftovers of an unregistered vault "gone"
+    fn tree() -> (PathBuf, AppDirs) {
+        let root = std::env::temp_dir().join(format!("tailor-disk-{}", uuid::Uuid::new_v4()));
+        let vault = root.join("vault");
+        fs::create_dir_all(&vault).unwrap();
+        fs::write(vault.join(".vault.json"), r#"{"id":"v1"}"#).unwrap();
+        file(&vault.join("notes.md"), 100);
+        file(&vault.join(".venv/lib/site.py"), 1000);
+        file(&vault.join("__pycache__/notes.pyc"), 50);
+        file(&vault.join(".tailor/backups/settings.json"), 300);
+        file(&vault.join(".tailor/logs/sidecar.log"), 70);
+        file(&vault.join(".trash/old.md"), 20);
+
+        let dirs = AppDirs {
+            config: root.join("config"),
+            data: root.join("data"),
+            cache: root.join("cache"),
+            log: Some(root.join("logs")),
+        };
+        file(&dirs.data.join("venvs/v1/pkg.py"), 400);
+        file(&dirs.data.join("venvs/gone/pkg.py"), 600);
+        file(&dirs.data.join("registry.json"), 10);
+        file(&dirs.cache.join("vaults/v1/index"), 80);
+        file(&dirs.cache.join("vaults/gone/index"), 90);
+        file(&dirs.cache.join("thumbnails"), 5);
+        file(&root.join("logs/app.log"), 7);
+        recents::save(&dirs.config, &[recents::VaultListItem {
+            name: "Notes".to_string(),
+            path: vault.to_string_lossy().to_string(),
+            created: None,
+            id: Some("v1".to_string()),
+        }]).unwrap();
+        (root, dirs)
+    }
+
+    #[test]
+    fn vault_files_and_app_dirs_are_attributed_to_the_vault() {
+        let (root, dirs) = tree();
+        let usage = measure(&dirs, None);
+        let vault = &usage.vaults[0];
+
+        let config_len = r#"{"id":"v1"}"#.len() as u64;
+        assert_eq!(vault.content_bytes, 100 + config_len);
+        assert_eq!(vault.venv_bytes, 1000 + 400);
+        assert_eq!(vault.cache_bytes, 50 + 80);
+        assert_eq!(vault.backup_bytes, 300);
+        assert_eq!(vault.log_bytes, 70);
+        assert_eq!(vault.trash_bytes, 20);
+        assert_eq!(vault.total_bytes, 100 + config_len + 1400 + 130 + 300 + 70 + 20);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn unattributed_app_data_is_counted_once_globally() {
+        let (root, dirs) = tree();
+        let usage = measure(&dirs, None);
+
+        assert_eq!(usage.global.orphaned_venv_bytes, 600);
+        assert_eq!(usage.global.cache_bytes, 90 + 5);
+        assert_eq!(usage.global.log_bytes, 7);
+        // Venvs are reported per vault or as orphaned, never again here
+        assert_eq!(usage.global.app_data_bytes, 10);
+        assert_eq!(usage.total_bytes, usage.vaults[0].total_bytes + 600 + 95 + 7 + 10);
+        assert!(usage.suggestions.iter().any(|s| s.kind == "orphaned_venvs" && s.bytes == 600));
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn the_limit_warning_starts_at_ninety_percent() {
+        let (root, dirs) = tree();
+        let total = measure(&dirs, None).total_bytes;
+
+        assert!(measure(&dirs, Some(total)).near_limit);
+        assert!(!measure(&dirs, Some(total * 2)).near_limit);
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,97 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use serde::Serialize;
+
+use crate::connection_pool::SidecarRpcError;
+use crate::redact::{redact_json, redact_text};
+
+/// Failures remembered per window
+const FAILURES_PER_WINDOW: usize = 20;
+
+/// A sidecar command that errored, already redacted for sharing
+#[derive(Debug, Clone, Serialize)]
+pub struct CommandFailure {
+    pub timestamp: String,
+    pub window_label: String,
+    pub method: String,
+    pub params: serde_json::Value,
+    pub error: String,
+    /// JSON-RPC error code, when the sidecar answered with an error
+    pub code: Option<i64>,
+    pub traceback: Option<String>,
+}
+
+/// Rolling buffer of recent command failures, keyed by window
+#[derive(Default)]
+pub struct FailureLog {
+    windows: Mutex<HashMap<String, VecDeque<CommandFailure>>>,
+}
+
+impl FailureLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a request that failed outright (transport or JSON-RPC error)
+    pub fn record_error(&self, window_label: &str, method: &str, params: &serde_json::Value, error: &anyhow::Error) {
+        let rpc = error.downcast_ref::<SidecarRpcError>();
+        let traceback = rpc
+            .and_then(|e| e.data.as_ref())
+            .and_then(|d| d.get("traceback"))
+            .and_then(|t| t.as_str())
+            .map(str::to_string);
+
+        self.push(window_label, method, params, error.to_string(), rpc.map(|e| e.code), traceback);
+    }
+
+    /// Record a command that returned `{"status": "error"}` in its result
+    pub fn record_result(&self, window_label: &str, method: &str, params: &serde_json::Value, result: &serde_json::Value) {
+        if result.get("status").and_then(|s| s.as_str()) != Some("error") {
+            return;
+        }
+        let error = result.get("error")
+            .or_else(|| result.get("message"))
+            .and_then(|e| e.as_str())
+            .unwrap_or("Command returned an error status")
+            .to_string();
+        let traceback = result.get("traceback").and_then(|t| t.as_str()).map(str::to_string);
+
+        self.push(window_label, method, params, error, None, traceback);
+    }
+
+    pub fn last(&self, window_label: &str) -> Option<CommandFailure> {
+        self.windows.lock().unwrap()
+            .get(window_label)
+            .and_then(|failures| failures.back().cloned())
+    }
+
+    fn push(
+        &self,
+        window_label: &str,
+        method: &str,
+        params: &serde_json::Value,
+        error: String,
+        code: Option<i64>,
+        traceback: Option<String>,
+    ) {
+        let mut params = params.clone();
+        redact_json(&mut params);
+
+        let failure = CommandFailure {
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            window_label: window_label.to_string(),
+            method: method.to_string(),
+            params,
+            error: redact_text(&error),
+            code,
+            traceback: traceback.map(|t| redact_text(&t)),
+        };
+
+        let mut windows = self.windows.lock().unwrap();
+        let failures = windows.entry(window_label.to_string()).or_default();
+        if failures.len() >= FAILURES_PER_WINDOW {
+            failures.pop_front();
+        }
+        failures.push_back(failure);
+    }
+}
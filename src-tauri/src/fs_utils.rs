@@ -0,0 +1,157 @@
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use anyhow::{Result, Context};
+
+use crate::command_error::CommandError;
+
+/// Write `contents` to `path` atomically (temp file in the same directory + rename).
+///
+/// Readers see either the old file or the new one, never a partial write.
+/// In vaults whose storage cannot replace a file by rename (see
+/// `storage_backend`), the temp file is copied over the target instead.
+pub fn atomic_write(path: &Path, contents: &[u8]) -> Result<()> {
+    let parent = path.parent()
+        .context("Target path has no parent directory")?;
+    fs::create_dir_all(parent)
+        .with_context(|| format!("Failed to create {}", parent.display()))?;
+
+    let file_name = path.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let tmp_path = parent.join(format!(".{}.{}.tmp", file_name, uuid::Uuid::new_v4()));
+
+    let copy_write = crate::storage_backend::needs_copy_write(path);
+    let result = (|| -> Result<()> {
+        let mut file = fs::File::create(&tmp_path)
+            .with_context(|| format!("Failed to create {}", tmp_path.display()))?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+        if copy_write {
+            // Not atomic, but an interrupted copy leaves the complete temp
+            // file behind to recover from
+            fs::copy(&tmp_path, path)
+                .with_context(|| format!("Failed to replace {}", path.display()))?;
+            fs::File::open(path)?.sync_all()?;
+            fs::remove_file(&tmp_path)?;
+        } else {
+            fs::rename(&tmp_path, path)
+                .with_context(|| format!("Failed to replace {}", path.display()))?;
+        }
+        Ok(())
+    })();
+
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+    result
+}
+
+/// Recursively copy a directory tree (symlinks are skipped)
+pub fn copy_dir_recursive(src: &Path, dst: &Path) -> Result<()> {
+    fs::create_dir_all(dst)
+        .with_context(|| format!("Failed to create {}", dst.display()))?;
+
+    for entry in fs::read_dir(src).with_context(|| format!("Failed to read {}", src.display()))? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        let target = dst.join(entry.file_name());
+
+        if file_type.is_dir() {
+            copy_dir_recursive(&entry.path(), &target)?;
+        } else if file_type.is_file() {
+            fs::copy(entry.path(), &target)
+                .with_context(|| format!("Failed to copy {}", entry.path().display()))?;
+        }
+    }
+    Ok(())
+}
+
+/// Recursively sum file sizes under `path` without following symlinks
+pub fn dir_size(path: &Path) -> u64 {
+    let mut total = 0;
+    if let Ok(entries) = fs::read_dir(path) {
+        for entry in entries.flatten() {
+            let Ok(metadata) = entry.metadata() else { continue };
+            if metadata.is_dir() {
+                total += dir_size(&entry.path());
+            } else if metadata.is_file() {
+                total += metadata.len();
+            }
+        }
+    }
+    total
+}
+
+/// Validate a path argument at a command boundary.
+///
+/// Rejects empty, whitespace-only, relative and NUL-containing paths with a
+/// `Validation` error naming `param`, so commands never fall back to the
+/// current directory.
+pub fn resolve_vault_path(param: &str, value: &str) -> Result<PathBuf, CommandError> {
+    if value.trim().is_empty() {
+        return Err(CommandError::Validation(format!("{} must not be empty", param)));
+    }
+    if value.contains('\0') {
+        return Err(CommandError::Validation(format!("{} contains a NUL character", param)));
+    }
+
+    let path = PathBuf::from(value);
+    if !path.is_absolute() {
+        return Err(CommandError::Validation(format!("{} must be an absolute path, got {:?}", param, value)));
+    }
+    Ok(path)
+}
+
+/// `resolve_vault_path`, canonicalized so every spelling of one vault
+/// (`..`, `.`, symlinks, trailing separators) names the same open
+pub fn vault_key(param: &str, value: &str) -> Result<PathBuf, CommandError> {
+    let path = resolve_vault_path(param, value)?;
+    Ok(fs::canonicalize(&path).unwrap_or(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn absolute_example() -> &'static str {
+        if cfg!(windows) { r"C:\Vaults\notes" } else { "/vaults/notes" }
+    }
+
+    #[test]
+    fn rejects_empty_path() {
+        let err = resolve_vault_path("vault_path", "").unwrap_err();
+        assert_eq!(err.code(), "VALIDATION");
+        assert!(err.message().contains("vault_path"));
+    }
+
+    #[test]
+    fn rejects_whitespace_path() {
+        let err = resolve_vault_path("vault_path", "   ").unwrap_err();
+        assert_eq!(err.code(), "VALIDATION");
+        assert!(err.message().contains("must not be empty"));
+    }
+
+    #[test]
+    fn rejects_relative_paths() {
+        for value in ["vault", "./vault", "../vault", "notes/vault"] {
+            let err = resolve_vault_path("vault_a", value).unwrap_err();
+            assert_eq!(err.code(), "VALIDATION", "{}", value);
+            assert!(err.message().contains("vault_a"), "{}", value);
+            assert!(err.message().contains("absolute"), "{}", value);
+        }
+    }
+
+    #[test]
+    fn rejects_nul_character() {
+        let err = resolve_vault_path("vault_path", "/vaults/no\0tes").unwrap_err();
+        assert_eq!(err.code(), "VALIDATION");
+        assert!(err.message().contains("NUL"));
+    }
+
+    #[test]
+    fn accepts_absolute_path() {
+        let path = resolve_vault_path("vault_path", absolute_example()).unwrap();
+        assert_eq!(path, PathBuf::from(absolute_example()));
+    }
+}
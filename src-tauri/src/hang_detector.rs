@@ -0,0 +1,217 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+
+use crate::sidecar_client::SidecarClient;
+use crate::sidecar_manager::SidecarManager;
+
+/// Written by the sidecar's watchdog thread, which keeps running while the
+/// event loop is blocked
+const WATCHDOG_FILE: &str = ".tailor/sidecar-watchdog.json";
+/// A ping slower than this counts as unanswered
+const PING_TIMEOUT: Duration = Duration::from_secs(5);
+/// How often the background watchdog pings each sidecar
+const WATCH_INTERVAL: Duration = Duration::from_secs(15);
+/// Consecutive unanswered pings before a hang is reported
+const MISSED_PINGS_BEFORE_REPORT: u32 = 2;
+/// Window over which CPU usage is sampled
+const CPU_SAMPLE: Duration = Duration::from_millis(500);
+/// CPU usage (percent of one core) at or above which a hung loop is spinning
+const SPINNING_CPU_PERCENT: f32 = 50.0;
+/// Loop lag beyond which the watchdog file shows the loop as blocked
+const BLOCKED_LOOP_LAG_SECS: f64 = 5.0;
+/// Watchdog files older than this were not written by a live thread
+const WATCHDOG_STALE_SECS: f64 = 10.0;
+
+/// Plugin callback the sidecar was running when it stopped answering
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HangCulprit {
+    pub plugin: String,
+    /// Event or command id the plugin was handling
+    pub callback: String,
+    /// Seconds spent in the callback, None when it had already returned
+    pub running_for_seconds: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HangDiagnosis {
+    pub window_label: String,
+    /// "healthy", "stuck", "spinning", "unresponsive" or "not_running"
+    pub state: String,
+    pub responsive: bool,
+    pub pid: Option<u32>,
+    pub cpu_percent: Option<f32>,
+    /// Seconds since the event loop last ran, from the watchdog file
+    pub loop_lag_seconds: Option<f64>,
+    pub culprit: Option<HangCulprit>,
+    pub suggestion: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct WatchdogFile {
+    written_at: f64,
+    loop_lag_seconds: f64,
+    #[serde(default)]
+    active_callbacks: Vec<WatchdogCallback>,
+    last_entered: Option<WatchdogCallback>,
+}
+
+#[derive(Debug, Deserialize)]
+struct WatchdogCallback {
+    plugin: String,
+    callback: String,
+    running_for_seconds: Option<f64>,
+}
+
+/// Ping the window's sidecar and, if it does not answer, work out whether
+/// its loop is stuck (idle CPU, e.g. a deadlock or blocking call) or
+/// spinning (busy CPU, e.g. an infinite loop), and which plugin was last
+/// running.
+pub async fn diagnose(sidecar_manager: &SidecarManager, window_label: &str) -> HangDiagnosis {
+    let mut diagnosis = HangDiagnosis {
+        window_label: window_label.to_string(),
+        state: "not_running".to_string(),
+        responsive: false,
+        pid: None,
+        cpu_percent: None,
+        loop_lag_seconds: None,
+        culprit: None,
+        suggestion: None,
+    };
+
+    let Some(status) = sidecar_manager.process_status(window_label).await else {
+        return diagnosis;
+    };
+    diagnosis.pid = Some(status.pid);
+    if !status.running {
+        diagnosis.suggestion = Some(match status.exit_code {
+            Some(code) => format!("The sidecar exited with code {}; reopen the vault", code),
+            None => "The sidecar exited; reopen the vault".to_string(),
+        });
+        return diagnosis;
+    }
+
+    let ping = SidecarClient::request(status.ws_port, "system.ping", serde_json::json!({}), PING_TIMEOUT).await;
+    if ping.is_ok() {
+        diagnosis.state = "healthy".to_string();
+        diagnosis.responsive = true;
+        return diagnosis;
+    }
+
+    let cpu = sample_cpu(status.pid).await;
+    let watchdog = read_watchdog(Path::new(&status.vault_path));
+    diagnosis.cpu_percent = cpu;
+    diagnosis.loop_lag_seconds = watchdog.as_ref().map(|w| w.loop_lag_seconds);
+
+    // A fresh watchdog file with no loop lag means the loop is fine and
+    // only the connection is failing
+    let loop_blocked = watchdog.as_ref().map_or(true, |w| w.loop_lag_seconds >= BLOCKED_LOOP_LAG_SECS);
+    diagnosis.state = match cpu {
+        _ if !loop_blocked => "unresponsive",
+        Some(percent) if percent >= SPINNING_CPU_PERCENT => "spinning",
+        Some(_) => "stuck",
+        None => "unresponsive",
+    }.to_string();
+
+    diagnosis.culprit = watchdog.and_then(|w| {
+        let WatchdogFile { mut active_callbacks, last_entered, .. } = w;
+        // Oldest still-running callback first; otherwise the last one entered
+        if active_callbacks.is_empty() {
+            last_entered.map(|c| HangCulprit { plugin: c.plugin, callback: c.callback, running_for_seconds: None })
+        } else {
+            let c = active_callbacks.remove(0);
+            Some(HangCulprit { plugin: c.plugin, callback: c.callback, running_for_seconds: c.running_for_seconds })
+        }
+    });
+
+    diagnosis.suggestion = Some(match &diagnosis.culprit {
+        Some(culprit) => format!(
+            "Plugin '{}' was handling '{}'; restart the vault and disable the plugin if it happens again",
+            culprit.plugin, culprit.callback
+        ),
+        None => "Restart the vault to recover the sidecar".to_string(),
+    });
+    diagnosis
+}
+
+/// The watchdog file, if a live watchdog thread wrote it recently
+fn read_watchdog(vault_path: &Path) -> Option<WatchdogFile> {
+    let contents = fs::read_to_string(vault_path.join(WATCHDOG_FILE)).ok()?;
+    let watchdog: WatchdogFile = serde_json::from_str(&contents).ok()?;
+    let now = chrono::Utc::now().timestamp_millis() as f64 / 1000.0;
+    (now - watchdog.written_at <= WATCHDOG_STALE_SECS).then_some(watchdog)
+}
+
+/// Plugins with callbacks running, per a fresh watchdog file
+pub fn busy_plugins(vault_path: &Path) -> Vec<String> {
+    read_watchdog(vault_path)
+        .map(|w| w.active_callbacks.into_iter().map(|c| c.plugin).collect())
+        .unwrap_or_default()
+}
+
+/// CPU usage of `pid` over a short sample, as a percentage of one core
+async fn sample_cpu(pid: u32) -> Option<f32> {
+    let pid = sysinfo::Pid::from_u32(pid);
+    let mut system = sysinfo::System::new();
+    system.refresh_process(pid);
+    tokio::time::sleep(CPU_SAMPLE.max(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL)).await;
+    system.refresh_process(pid);
+    system.process(pid).map(|process| process.cpu_usage())
+}
+
+/// Ping every sidecar periodically, recording answers as its `last_seen`
+/// heartbeat, and emit `sidecar-deadlock://{label}`
+/// with a diagnosis once a sidecar misses several pings in a row. Each hang
+/// is reported once, until the sidecar answers again.
+pub fn spawn_hang_watchdog(app: AppHandle, sidecar_manager: Arc<SidecarManager>) {
+    tauri::async_runtime::spawn(async move {
+        let mut missed: HashMap<String, u32> = HashMap::new();
+        let mut reported: HashSet<String> = HashSet::new();
+        loop {
+            tokio::time::sleep(WATCH_INTERVAL).await;
+
+            let labels = sidecar_manager.window_labels().await;
+            missed.retain(|label, _| labels.contains(label));
+            reported.retain(|label| labels.contains(label));
+
+            for label in labels {
+                let Some(status) = sidecar_manager.process_status(&label).await else { continue };
+                if !status.running {
+                    continue;
+                }
+                let ping = SidecarClient::request(status.ws_port, "system.ping", serde_json::json!({}), PING_TIMEOUT).await;
+                if ping.is_ok() {
+                    sidecar_manager.mark_seen(&label).await;
+                    missed.remove(&label);
+                    reported.remove(&label);
+                    continue;
+                }
+
+                let count = missed.entry(label.clone()).or_insert(0);
+                *count += 1;
+                if *count < MISSED_PINGS_BEFORE_REPORT || reported.contains(&label) {
+                    continue;
+                }
+
+                let diagnosis = diagnose(&sidecar_manager, &label).await;
+                if diagnosis.responsive || diagnosis.state == "not_running" {
+                    continue;
+                }
+                eprintln!(
+                    "Warning: Sidecar for {} is {} (culprit: {})",
+                    label,
+                    diagnosis.state,
+                    diagnosis.culprit.as_ref().map_or("unknown", |c| c.plugin.as_str()),
+                );
+                if let Err(e) = app.emit(&format!("sidecar-deadlock://{}", label), &diagnosis) {
+                    eprintln!("Warning: Failed to emit sidecar deadlock event: {}", e);
+                }
+                reported.insert(label);
+            }
+        }
+    });
+}
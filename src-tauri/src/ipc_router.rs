@@ -1,51 +1,352 @@
-use crate::{AppState, dependency_checker::DependencyChecker};
-use tauri::{AppHandle, State, Manager};
+use crate::{AppState, dependency_checker::{DependencyChecker, DependencyStatus}};
+use crate::sidecar_manager::{
+    PluginProcessInfo, ShutdownReport, TimeoutOverrides, DRAIN_TIMEOUT_SETTING, ISOLATE_PLUGINS_SETTING,
+    LOG_LEVEL_SETTING, MUTED_EVENT_CHANNELS_SETTING, PLUGIN_CONCURRENCY_SETTING, PLUGIN_LIFECYCLE_EVENTS_SETTING,
+    RESTRICT_PLUGIN_FS_SETTING, SHUTDOWN_TIMEOUT_SETTING, TICK_INTERVAL_SETTING,
+};
+use crate::artifact_scanner::{self, ArtifactScanner, CleanupReport, OrphanedArtifact};
+use crate::sidecar_client::{SidecarClient, DEFAULT_REQUEST_TIMEOUT};
+use crate::connection_pool::{self, ConnectionDiagnostics, RequestCancelled};
+use crate::command_queue::{CommandQueueDepth, MAX_IN_FLIGHT_COMMANDS_SETTING};
+use crate::scheduler::{ScheduledTask, ScheduledTaskKind};
+use crate::vault_diff::{self, VaultDiff};
+use crate::vault_files::{self, VaultFileListing, VaultFilePreview};
+use crate::vault_excludes::{self, VaultExcludes};
+use crate::operations::{self, OperationCheckpoint};
+use crate::settings_profiles::{self, SettingsProfile};
+use crate::plugins;
+use crate::plugin_validator::{self, PluginValidation};
+use crate::recents::{self, RecentsRepair, VaultListItem};
+use crate::registry;
+use crate::hang_detector::{self, HangDiagnosis};
+use crate::plugin_lifecycle::LifecycleEntry;
+use crate::method_signatures::{self, PluginSignatures};
+use crate::crash_monitor::{self, CrashPolicy, CrashRecord, CRASH_POLICY_SETTING};
+use crate::request_budget::RequestBudgetStatus;
+use crate::storage_backend::{self, StorageBackend};
+use crate::disk_usage::{self, TotalDiskUsage};
+use crate::python_compat::{self, PythonCompat};
+use crate::plugin_updater::{PluginUpdater, PluginUpdateOutcome};
+use crate::settings::{self, DEFAULT_VAULT_DIR_SETTING, REQUEST_BUDGET_SETTING};
+use crate::conversations;
+use crate::conversation_index;
+use crate::sidecar_logs::{LogEntry, LogPage, LogQuery};
+use crate::audit_log::{AuditEntry, AuditQuery};
+use crate::sidecar_streams::{self, STREAM_CHANNEL_PARAM};
+use crate::sidecar_batches::BATCH_CONCURRENCY;
+use crate::failures::CommandFailure;
+use crate::redact::{redact_json, redact_text};
+use crate::fs_utils::{atomic_write, resolve_vault_path, vault_key};
+use crate::api_keys;
+use crate::command_error::CommandError;
+use crate::plugin_installer;
+use crate::vault_migration::{MigrationReport, VaultMigrator, VAULT_CONFIG_VERSION};
+use crate::window_session::{self, WindowGeometry};
+use crate::window_manager::{SlotReservation, WindowManager};
+use tauri::{AppHandle, Emitter, State, Manager};
 use serde::{Deserialize, Serialize};
 use anyhow::Result;
-use std::path::PathBuf;
+use futures::StreamExt;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::fs;
+use std::time::{Duration, Instant};
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VaultInfo {
     pub window_label: String,
     pub vault_path: String,
     pub ws_port: u16,
+    /// Plugins touched by `autoUpdatePlugins` while opening
+    #[serde(default)]
+    pub plugin_updates: Vec<PluginUpdateOutcome>,
+    /// Dry-run migration plan when the vault uses a legacy layout, so the UI
+    /// can offer `migrate_vault`
+    #[serde(default)]
+    pub pending_migration: Option<MigrationReport>,
+    /// Enabled plugins whose `python_requires` excludes the sidecar's
+    /// interpreter; the sidecar refuses to load them
+    #[serde(default)]
+    pub incompatible_plugins: Vec<PythonCompat>,
+    /// Set when the vault sits on cloud-synced, network or FUSE storage
+    #[serde(default)]
+    pub storage_warning: Option<String>,
+    /// A scratch vault from `open_temp_vault`, deleted on close unless kept
+    #[serde(default)]
+    pub disposable: bool,
 }
 
-/// Open a new vault window
+/// Open a new vault window.
+///
+/// Repeated opens of a vault that is still opening (e.g. a double-click)
+/// wait for the first and return its result instead of opening it twice.
+/// A vault that is already open has its window focused and its existing
+/// `VaultInfo` returned.
 #[tauri::command]
 pub async fn open_vault(
     app: AppHandle,
     vault_path: String,
+    close_least_recent: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<VaultInfo, CommandError> {
+    let key = vault_key("vault_path", &vault_path)?;
+    state.vault_opens
+        .run(key, open_vault_window(&app, vault_path, close_least_recent, false, None, &state))
+        .await
+}
+
+/// Reopen the vault windows saved in the last session, at their saved size
+/// and position, through the same pipeline as `open_vault`. Vaults that
+/// are gone or fail to open are skipped with a warning; past
+/// `maxOpenVaults`, the least recently used are left closed.
+pub async fn restore_session(app: AppHandle) {
+    let Ok(config_dir) = app_config_dir(&app) else { return };
+    let mut windows = window_session::load(&config_dir);
+    if windows.is_empty() {
+        return;
+    }
+    let state = app.state::<AppState>();
+
+    // Under `maxOpenVaults`, reopen the most recently used vaults
+    let max = settings::load_global_settings(&config_dir)
+        .ok()
+        .and_then(|global_settings| settings::max_open_vaults(&global_settings));
+    if let Some(max) = max {
+        let room = max.saturating_sub(state.window_manager.lock().await.window_count());
+        let (kept, dropped) = window_session::most_recent(windows, room);
+        for window in dropped {
+            println!("Not restoring vault {}: maxOpenVaults ({}) reached", window.vault_path, max);
+        }
+        windows = kept;
+    }
+    if windows.is_empty() {
+        return;
+    }
+    println!("Restoring {} vault window(s) from the last session", windows.len());
+    for window in windows {
+        if !Path::new(&window.vault_path).is_dir() {
+            eprintln!("Warning: Skipping restore of missing vault {}", window.vault_path);
+            continue;
+        }
+        let Ok(key) = vault_key("vault_path", &window.vault_path) else {
+            eprintln!("Warning: Skipping restore of invalid vault path {}", window.vault_path);
+            continue;
+        };
+        let opened = state.vault_opens
+            .run(key, open_vault_window(&app, window.vault_path.clone(), None, false, window.geometry, &state))
+            .await;
+        if let Err(e) = opened {
+            eprintln!("Warning: Failed to restore vault {}: {}", window.vault_path, e);
+        }
+    }
+}
+
+/// Directory name prefix and vault name of scratch vaults
+const TEMP_VAULT_PREFIX: &str = "tailor-scratch-";
+const TEMP_VAULT_NAME: &str = "Scratch Pad";
+
+/// Open a scratch vault in a temporary directory. It is deleted when the
+/// window closes unless `close_vault` is asked to keep it, and stays out of
+/// recents until then.
+#[tauri::command]
+pub async fn open_temp_vault(
+    app: AppHandle,
+    close_least_recent: Option<bool>,
     state: State<'_, AppState>,
-) -> Result<VaultInfo, String> {
+) -> Result<VaultInfo, CommandError> {
+    let vault = std::env::temp_dir().join(format!("{}{}", TEMP_VAULT_PREFIX, uuid::Uuid::new_v4().simple()));
+    scaffold_vault(&vault, TEMP_VAULT_NAME)?;
+    let vault_path = vault.to_string_lossy().to_string();
+    println!("Created scratch vault at {}", vault_path);
+
+    // A failed open has already closed its window; only the directory is left
+    let opened = open_vault_window(&app, vault_path, close_least_recent, true, None, &state).await;
+    if opened.is_err() {
+        let _ = fs::remove_dir_all(&vault);
+    }
+    opened
+}
+
+async fn open_vault_window(
+    app: &AppHandle,
+    vault_path: String,
+    close_least_recent: Option<bool>,
+    disposable: bool,
+    geometry: Option<WindowGeometry>,
+    state: &State<'_, AppState>,
+) -> Result<VaultInfo, CommandError> {
+    let vault = resolve_vault_path("vault_path", &vault_path)?;
+
+    // A second window would run a second sidecar over the same vault files
+    let existing = state.window_manager.lock().await.find_window_by_vault(&vault);
+    if let Some(window_label) = existing {
+        return focus_vault_window(app, state, window_label).await;
+    }
+
     println!("Opening vault: {}", vault_path);
 
-    // Step 1: Check and install dependencies
-    DependencyChecker::check_and_install(&vault_path)
-        .await
-        .map_err(|e| format!("Failed to install dependencies: {}", e))?;
+    // Held until the window is registered; any failure before that frees it
+    let reservation = enforce_open_vault_limit(app, state, close_least_recent.unwrap_or(false)).await?;
 
-    // Step 2: Create window
-    let window_label = state.window_manager
-        .lock()
+    // Step 0: Detect legacy layouts; opening still proceeds
+    let pending_migration = VaultMigrator::migrate(&vault, true)
+        .ok()
+        .filter(|report| report.legacy);
+    if pending_migration.is_some() {
+        println!("Vault {} uses a legacy layout; migration available", vault_path);
+    }
+
+    // Step 0b: Adapt writes to the vault's storage and warn about risky locations
+    let storage = storage_backend::detect(&vault);
+    storage_backend::register(&vault, &storage);
+    if let Some(warning) = &storage.warning {
+        eprintln!("Warning: {}", warning);
+    }
+
+    // Step 1: Check and install dependencies, reporting each package
+    DependencyChecker::check_and_install(&vault_path, |progress| {
+        let _ = app.emit("deps://progress", serde_json::json!({
+            "vault_path": vault_path,
+            "step": progress.step,
+            "total": progress.total,
+            "status": progress.status,
+            "package": progress.package,
+            "message": progress.message,
+        }));
+    })
         .await
-        .create_vault_window(&app, vault_path.clone())
-        .map_err(|e| format!("Failed to create window: {}", e))?;
+        .map_err(|e| CommandError::DependencyFailed(format!("Failed to install dependencies: {}", e)))?;
 
-    // Step 3: Spawn sidecar
-    let ws_port = state.sidecar_manager
+    // Step 1b: Optionally update plugins before the sidecar loads them
+    let plugin_updates = auto_update_plugins(app, &vault_path).await;
+
+    // Step 1c: Report plugins the sidecar's Python can't run
+    let incompatible_plugins = incompatible_plugins(&vault_path);
+    for compat in &incompatible_plugins {
+        println!(
+            "Warning: Plugin '{}' requires Python {} but the sidecar uses {}; it will not load",
+            compat.plugin,
+            compat.required.as_deref().unwrap_or("?"),
+            compat.current,
+        );
+    }
+
+    // Step 2: Create window. It is built before taking the window manager
+    // lock: building runs on the main thread, which also takes that lock
+    let window_label = WindowManager::build_vault_window(app, &vault_path, geometry)
+        .map_err(|e| CommandError::Internal(format!("Failed to create window: {}", e)))?;
+    {
+        let mut window_manager = state.window_manager.lock().await;
+        window_manager.register_window(reservation, &window_label, vault_path.clone(), geometry);
+        if disposable {
+            window_manager.mark_disposable(&window_label);
+        }
+    }
+
+    // Step 2b: Restore the vault's command throttle, if any
+    let queue_limit = settings::load_vault_settings(&vault)
+        .ok()
+        .and_then(|config| config.get(MAX_IN_FLIGHT_COMMANDS_SETTING).and_then(|v| v.as_u64()))
+        .filter(|limit| *limit >= 1)
+        .map(|limit| limit as usize);
+    state.command_queue.set_limit(&window_label, queue_limit);
+
+    // Step 3: Spawn sidecar. Without one the window is useless, so take it
+    // back down rather than leave it open and counted against the cap
+    let ws_port = match state.sidecar_manager
         .spawn_sidecar(window_label.clone(), vault_path.clone())
         .await
-        .map_err(|e| format!("Failed to spawn sidecar: {}", e))?;
+    {
+        Ok(ws_port) => ws_port,
+        Err(e) => {
+            discard_vault_window(app, state, &window_label).await;
+            return Err(CommandError::SidecarUnavailable(format!("Failed to spawn sidecar: {}", e)));
+        }
+    };
 
     println!("Vault opened successfully: window={}, port={}", window_label, ws_port);
 
-    // Register vault in registry
-    let vault_path_buf = PathBuf::from(&vault_path);
-    let config_path = vault_path_buf.join(".vault.json");
+    // Connect the pool once the sidecar is up so its notifications (such as
+    // operation checkpoints) are recorded even before the first request
+    state.connection_pool.connect_when_ready(ws_port);
+    for worker in state.sidecar_manager.plugin_process_info(&window_label).await {
+        state.connection_pool.connect_when_ready(worker.ws_port);
+    }
+
+    // Register vault in registry; scratch vaults only once they are kept
+    if !disposable {
+        let vault_item = vault_list_item(&vault, &vault_path);
+        if let Err(e) = register_vault_in_registry(app, &vault_item).await {
+            eprintln!("Warning: Failed to register vault in registry: {}", e);
+        }
+    }
+
+    Ok(VaultInfo {
+        window_label,
+        vault_path,
+        ws_port,
+        plugin_updates,
+        pending_migration,
+        incompatible_plugins,
+        storage_warning: storage.warning,
+        disposable,
+    })
+}
+
+/// Untrack and close the window of a vault that failed to open
+async fn discard_vault_window(app: &AppHandle, state: &State<'_, AppState>, window_label: &str) {
+    state.command_queue.remove(window_label);
+    state.window_manager.lock().await.remove_window(window_label);
+    if let Some(window) = app.get_webview_window(window_label) {
+        let _ = window.close();
+    }
+}
+
+/// Raise the window already showing a vault and return its `VaultInfo`
+/// instead of opening the vault again
+async fn focus_vault_window(
+    app: &AppHandle,
+    state: &State<'_, AppState>,
+    window_label: String,
+) -> Result<VaultInfo, CommandError> {
+    println!("Vault already open in window {}; focusing it", window_label);
+    if let Some(window) = app.get_webview_window(&window_label) {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+
+    let (vault_path, disposable) = {
+        let mut window_manager = state.window_manager.lock().await;
+        window_manager.touch(&window_label);
+        let vault_path = window_manager
+            .get_vault_path(&window_label)
+            .ok_or_else(|| CommandError::NotFound(format!("Vault not found for window: {}", window_label)))?
+            .clone();
+        (vault_path, window_manager.is_disposable(&window_label))
+    };
+    let ws_port = state.sidecar_manager
+        .get_ws_port(&window_label)
+        .await
+        .ok_or_else(|| sidecar_not_found(&window_label))?;
+
+    Ok(VaultInfo {
+        window_label,
+        vault_path,
+        ws_port,
+        plugin_updates: Vec::new(),
+        pending_migration: None,
+        incompatible_plugins: Vec::new(),
+        storage_warning: None,
+        disposable,
+    })
+}
+
+/// Registry entry for a vault, named from its `.vault.json` when it has one
+fn vault_list_item(vault: &Path, vault_path: &str) -> VaultListItem {
+    let config_path = vault.join(".vault.json");
     
-    let mut name = vault_path_buf.file_name()
+    let mut name = vault.file_name()
         .map(|n| n.to_string_lossy().to_string())
         .unwrap_or_else(|| "Unknown Vault".to_string());
     let mut created = None;
@@ -62,472 +363,3135 @@ pub async fn open_vault(
             }
         }
     }
+    // Vaults made outside the app may not record when they were created
+    if created.is_none() {
+        created = fs::metadata(vault)
+            .and_then(|m| m.created().or_else(|_| m.modified()))
+            .ok()
+            .map(|time| chrono::DateTime::<chrono::Utc>::from(time).to_rfc3339());
+    }
 
-    let vault_item = VaultListItem {
+    VaultListItem {
         name,
-        path: vault_path.clone(),
+        path: vault_path.to_string(),
         created,
-    };
+        id: artifact_scanner::vault_id(vault),
+    }
+}
+
+/// Hold a window slot for a vault about to open. Past `maxOpenVaults` this
+/// refuses, or makes room by closing the least-recently-used window when
+/// the caller asks to.
+async fn enforce_open_vault_limit(
+    app: &AppHandle,
+    state: &State<'_, AppState>,
+    close_least_recent: bool,
+) -> Result<SlotReservation, CommandError> {
+    let global_settings = settings::load_global_settings(&app_config_dir(app)?)
+        .map_err(|e| CommandError::Io(format!("Failed to load global settings: {}", e)))?;
+    let max = settings::max_open_vaults(&global_settings);
 
-    if let Err(e) = register_vault_in_registry(&app, &vault_item).await {
-        println!("Warning: Failed to register vault in registry: {}", e);
+    // Another open may take the freed slot first; then evict again
+    loop {
+        let taken = match WindowManager::reserve_slot(&state.window_manager, max).await {
+            Ok(reservation) => return Ok(reservation),
+            Err(taken) => taken,
+        };
+        let limit = max.unwrap_or(taken);
+        let least_recent = state.window_manager.lock().await.least_recently_used();
+        match least_recent {
+            Some(label) if close_least_recent => {
+                println!("Open vault limit ({}) reached; closing least recently used window {}", limit, label);
+                let closed = shutdown_vault_window(state, &label, TimeoutOverrides::default()).await?;
+                if let Some(scratch) = closed.scratch {
+                    // Evicting is not the user closing it; leave the scratch vault
+                    // for the OS to clean up rather than deleting it unasked
+                    println!("Left scratch vault of evicted window at {}", scratch.display());
+                }
+                if let Some(window) = app.get_webview_window(&label) {
+                    let _ = window.close();
+                }
+            }
+            _ => return Err(CommandError::TooManyWindows { open: taken, max: limit }),
+        }
     }
+}
 
-    Ok(VaultInfo {
-        window_label,
-        vault_path,
-        ws_port,
+#[derive(Debug, Serialize)]
+pub struct OpenVaultCount {
+    pub count: usize,
+    /// The `maxOpenVaults` cap, or None when unlimited
+    pub max: Option<usize>,
+}
+
+/// Number of open vault windows and the configured cap
+#[tauri::command]
+pub async fn get_open_vault_count(
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<OpenVaultCount, CommandError> {
+    let global_settings = settings::load_global_settings(&app_config_dir(&app)?)
+        .map_err(|e| CommandError::Io(format!("Failed to load global settings: {}", e)))?;
+
+    Ok(OpenVaultCount {
+        count: state.window_manager.lock().await.window_count(),
+        max: settings::max_open_vaults(&global_settings),
     })
 }
 
-/// Send command to sidecar
+/// Apply plugin updates when the vault opts in via `autoUpdatePlugins`.
+///
+/// Honors `autoUpdateAllowList` (only these, when non-empty) and
+/// `autoUpdateDenyList`. Never fails: any problem leaves the installed
+/// version in place and is recorded in the returned outcomes.
+async fn auto_update_plugins(app: &AppHandle, vault_path: &str) -> Vec<PluginUpdateOutcome> {
+    let path = PathBuf::from(vault_path);
+    let vault_settings = match settings::load_vault_settings(&path) {
+        Ok(s) => s,
+        Err(e) => {
+            eprintln!("Warning: Skipping plugin auto-update, settings unreadable: {}", e);
+            return Vec::new();
+        }
+    };
+
+    if !settings::get_bool(&vault_settings, "autoUpdatePlugins", false) {
+        return Vec::new();
+    }
+
+    let allow = settings::get_string_list(&vault_settings, "autoUpdateAllowList");
+    let deny = settings::get_string_list(&vault_settings, "autoUpdateDenyList");
+
+    let candidates: Vec<String> = PluginUpdater::updatable_plugins(&path)
+        .into_iter()
+        .filter(|name| allow.is_empty() || allow.contains(name))
+        .filter(|name| !deny.contains(name))
+        .collect();
+
+    let total = candidates.len();
+    let mut outcomes = Vec::with_capacity(total);
+
+    for (index, plugin) in candidates.iter().enumerate() {
+        let _ = app.emit("plugin-update://progress", serde_json::json!({
+            "vault_path": vault_path,
+            "plugin": plugin,
+            "step": index + 1,
+            "total": total,
+            "message": format!("Checking {} for updates", plugin),
+        }));
+
+        let outcome = PluginUpdater::update_plugin(&path, plugin).await;
+        println!("Plugin auto-update '{}': {:?}", plugin, outcome.status);
+        outcomes.push(outcome);
+    }
+
+    if total > 0 {
+        let _ = app.emit("plugin-update://done", serde_json::json!({
+            "vault_path": vault_path,
+            "outcomes": outcomes,
+        }));
+    }
+
+    outcomes
+}
+
+/// Enabled plugins whose `python_requires` rules out the sidecar interpreter
+fn incompatible_plugins(vault_path: &str) -> Vec<PythonCompat> {
+    let vault_path = PathBuf::from(vault_path);
+    let Ok(current) = DependencyChecker::get_python_executable()
+        .and_then(|python| DependencyChecker::get_python_version(&python))
+    else {
+        return Vec::new();
+    };
+    let vault_config = plugins::read_vault_config(&vault_path);
+
+    plugins::plugin_dirs(&vault_path)
+        .into_iter()
+        .filter(|dir| plugins::is_enabled(&vault_config, dir))
+        .filter_map(|dir| {
+            let name = dir.file_name()?.to_string_lossy().to_string();
+            python_compat::check_plugin(&vault_path, &name, &current).ok()
+        })
+        .filter(|compat| !compat.compatible)
+        .collect()
+}
+
+/// Check a plugin's `python_requires` against the sidecar's interpreter
+#[tauri::command]
+pub async fn check_plugin_python_compat(vault_path: String, plugin_name: String) -> Result<PythonCompat, CommandError> {
+    let vault = resolve_vault_path("vault_path", &vault_path)?;
+    let current = DependencyChecker::get_python_executable()
+        .and_then(|python| DependencyChecker::get_python_version(&python))
+        .map_err(|e| CommandError::DependencyFailed(format!("Failed to detect Python version: {}", e)))?;
+
+    python_compat::check_plugin(&vault, &plugin_name, &current)
+        .map_err(|e| CommandError::Internal(format!("Failed to check plugin compatibility: {}", e)))
+}
+
+/// Send command to sidecar. The JSON-RPC id it goes out under is announced
+/// on `sidecar-request://{window_label}` (echoing the command's `tag`, if
+/// any) before it is queued, so the caller can `cancel_request` it.
 #[tauri::command]
 pub async fn send_to_sidecar(
+    app: AppHandle,
     window_label: String,
     command: serde_json::Value,
     state: State<'_, AppState>,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, CommandError> {
     println!("Sending command to sidecar '{}': {:?}", window_label, command);
 
-    // Get WebSocket port
-    let ws_port = state.sidecar_manager
-        .get_ws_port(&window_label)
-        .await
-        .ok_or_else(|| format!("Sidecar not found for window: {}", window_label))?;
+    let method = command.get("method")
+        .and_then(|m| m.as_str())
+        .ok_or_else(|| CommandError::Validation("Command is missing 'method'".to_string()))?;
+    let params = command.get("params").cloned().unwrap_or_else(|| serde_json::json!({}));
+
+    let request_id = state.connection_pool.reserve_id(&window_label);
+    let _ = app.emit(&format!("sidecar-request://{}", window_label), serde_json::json!({
+        "request_id": request_id,
+        "method": method,
+        "tag": command.get("tag"),
+    }));
 
-    // In a full implementation, you would:
-    // 1. Connect to WebSocket at ws://localhost:{ws_port}
-    // 2. Send JSON-RPC command
-    // 3. Wait for response
-    // For now, return a placeholder
+    let result = queued_sidecar_request(&state, &window_label, &command, method, params, &request_id).await;
+    state.connection_pool.release_id(&request_id);
+    result
+}
 
-    // TODO: Implement WebSocket client communication
-    println!("Would send to ws://localhost:{}", ws_port);
+/// Send `command` under a reserved id once the window's command queue has
+/// room for it
+async fn queued_sidecar_request(
+    state: &State<'_, AppState>,
+    window_label: &str,
+    command: &serde_json::Value,
+    method: &str,
+    params: serde_json::Value,
+    request_id: &str,
+) -> Result<serde_json::Value, CommandError> {
+    // High-priority commands (such as cancellations) skip the queue
+    let _permit = if is_high_priority(command, method) {
+        None
+    } else {
+        Some(state.command_queue
+            .acquire(window_label)
+            .await
+            .map_err(|e| CommandError::SidecarUnavailable(e.to_string()))?)
+    };
 
-    Ok(serde_json::json!({
-        "status": "pending",
-        "message": "WebSocket communication not yet implemented"
-    }))
+    route_sidecar_request(state, window_label, method, params, DEFAULT_REQUEST_TIMEOUT, Some(request_id)).await
 }
 
-/// Close a vault window and terminate its sidecar
+/// Send several commands (`{method, params}` each) to the sidecar at once,
+/// at most `BATCH_CONCURRENCY` in flight. Results come back in input order
+/// as `{status: "ok", result}` or, for a command that failed without
+/// stopping the others, `{status: "error", error: {code, message}}`. Each
+/// command's request id is announced like `send_to_sidecar`'s (with the
+/// `batch_id`); passing a `batch_id` lets `cancel_batch` cancel them all.
 #[tauri::command]
-pub async fn close_vault(
+pub async fn send_to_sidecar_batch(
+    app: AppHandle,
     window_label: String,
+    commands: Vec<serde_json::Value>,
+    batch_id: Option<String>,
     state: State<'_, AppState>,
-) -> Result<(), String> {
-    println!("Closing vault window: {}", window_label);
+) -> Result<Vec<serde_json::Value>, CommandError> {
+    if let Some(batch_id) = &batch_id {
+        if !sidecar_streams::is_valid_channel_id(batch_id) {
+            return Err(CommandError::Validation("batch_id must be non-empty and use only letters, digits, '-', '_' or ':'".to_string()));
+        }
+    }
+    println!("Sending batch of {} commands to sidecar '{}'", commands.len(), window_label);
 
-    // Step 1: Terminate sidecar
-    state.sidecar_manager
-        .terminate_sidecar(&window_label)
-        .await
-        .map_err(|e| format!("Failed to terminate sidecar: {}", e))?;
+    let request_ids: Vec<String> = commands.iter()
+        .map(|_| state.connection_pool.reserve_id(&window_label))
+        .collect();
+    if let Some(batch_id) = &batch_id {
+        if !state.batches.open(batch_id, &window_label, &request_ids) {
+            for request_id in &request_ids {
+                state.connection_pool.release_id(request_id);
+            }
+            return Err(CommandError::Validation(format!("batch '{}' is already in progress", batch_id)));
+        }
+    }
+    for (command, request_id) in commands.iter().zip(&request_ids) {
+        let _ = app.emit(&format!("sidecar-request://{}", window_label), serde_json::json!({
+            "request_id": request_id,
+            "method": command.get("method"),
+            "tag": command.get("tag"),
+            "batch_id": batch_id,
+        }));
+    }
 
-    // Step 2: Remove window from tracking
-    state.window_manager
-        .lock()
-        .await
-        .remove_window(&window_label);
+    let results = futures::stream::iter(commands.iter().zip(&request_ids))
+        .map(|(command, request_id)| {
+            let state = &state;
+            let window_label = &window_label;
+            async move {
+                let result = match command.get("method").and_then(|m| m.as_str()) {
+                    Some(method) => {
+                        let params = command.get("params").cloned().unwrap_or_else(|| serde_json::json!({}));
+                        queued_sidecar_request(state, window_label, command, method, params, request_id).await
+                    }
+                    None => Err(CommandError::Validation("Command is missing 'method'".to_string())),
+                };
+                state.connection_pool.release_id(request_id);
+                match result {
+                    Ok(result) => serde_json::json!({ "status": "ok", "result": result }),
+                    Err(error) => serde_json::json!({ "status": "error", "error": error }),
+                }
+            }
+        })
+        .buffered(BATCH_CONCURRENCY)
+        .collect::<Vec<_>>()
+        .await;
 
-    println!("Vault closed successfully: {}", window_label);
+    if let Some(batch_id) = &batch_id {
+        state.batches.close(batch_id);
+    }
+    Ok(results)
+}
 
-    Ok(())
+/// Cancel every unfinished command of a `send_to_sidecar_batch` call; those
+/// slots come back as `Cancelled` errors. False if there is no such batch.
+#[tauri::command]
+pub async fn cancel_batch(
+    window_label: String,
+    batch_id: String,
+    state: State<'_, AppState>,
+) -> Result<bool, CommandError> {
+    let Some(request_ids) = state.batches.request_ids(&window_label, &batch_id) else {
+        return Ok(false);
+    };
+    let cancelled = request_ids.iter()
+        .filter(|id| state.connection_pool.cancel(&window_label, id))
+        .count();
+    println!("Cancelled {} requests of batch {} for window '{}'", cancelled, batch_id, window_label);
+    Ok(true)
 }
 
-/// Get the current window's vault information
+/// Abort a `send_to_sidecar` request by the id announced for it. The pending
+/// call fails with a `Cancelled` error right away, and the sidecar is told
+/// to stop the work (a slow provider call, say). Returns false if the
+/// request already finished.
 #[tauri::command]
-pub async fn get_current_vault_info(
-    window: tauri::Window,
+pub async fn cancel_request(
+    window_label: String,
+    request_id: String,
     state: State<'_, AppState>,
-) -> Result<VaultInfo, String> {
-    let window_label = window.label().to_string();
-    
-    // Get vault path
-    let vault_path = state.window_manager
-        .lock()
-        .await
-        .get_vault_path(&window_label)
-        .ok_or_else(|| "Vault not found for this window".to_string())?
-        .clone();
-    
-    // Get WebSocket port
-    let ws_port = state.sidecar_manager
-        .get_ws_port(&window_label)
-        .await
-        .ok_or_else(|| "Sidecar not found for this window".to_string())?;
-    
-    Ok(VaultInfo {
-        window_label,
-        vault_path,
-        ws_port,
-    })
+) -> Result<bool, CommandError> {
+    let cancelled = state.connection_pool.cancel(&window_label, &request_id);
+    if cancelled {
+        println!("Cancelled request {} for window '{}'", request_id, window_label);
+    }
+    Ok(cancelled)
 }
 
+/// Limit for a whole `stream_from_sidecar` stream
+const STREAM_REQUEST_TIMEOUT: Duration = Duration::from_secs(600);
+/// How long the final chunk may trail the command's reply
+const FINAL_CHUNK_GRACE: Duration = Duration::from_secs(2);
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct VaultListItem {
-    pub name: String,
-    pub path: String,
-    pub created: Option<String>,
+#[derive(Debug, Serialize)]
+pub struct StreamHandle {
+    pub channel_id: String,
+    /// JSON-RPC id the command went out under
+    pub request_id: String,
 }
 
-/// List all known vaults
+/// Send `command` (`{method, params}`) to the sidecar and forward its
+/// partial output as `sidecar://stream/{channel_id}` events while it runs.
+/// Returns once the command is sent; `sidecar://stream/{channel_id}/done`
+/// follows with `{status, result, error}` when it finishes, fails or is
+/// cancelled with `cancel_stream`.
 #[tauri::command]
-pub async fn list_vaults(app: AppHandle) -> Result<Vec<VaultListItem>, String> {
-    let mut vaults = Vec::new();
-    
-    // Get app data directory
-    let app_data_dir = app.path().app_data_dir()
-        .map_err(|e| format!("Failed to get app data directory: {}", e))?;
-    
-    // Create app data directory if it doesn't exist
-    fs::create_dir_all(&app_data_dir)
-        .map_err(|e| format!("Failed to create app data directory: {}", e))?;
-    
-    let registry_path = app_data_dir.join("vaults.json");
-    
-    // If registry exists, load it
-    if registry_path.exists() {
-        if let Ok(contents) = fs::read_to_string(&registry_path) {
-                if let Ok(registry) = serde_json::from_str::<Vec<VaultListItem>>(&contents) {
-                    // Validate that vaults still exist and load info from .vault.json
-                    for mut vault in registry {
-                        let vault_path = PathBuf::from(&vault.path);
-                        if vault_path.exists() {
-                            // Try to load vault info from .vault.json
-                            let config_path = vault_path.join(".vault.json");
-                            if config_path.exists() {
-                                if let Ok(config_contents) = fs::read_to_string(&config_path) {
-                                    if let Ok(config) = serde_json::from_str::<serde_json::Value>(&config_contents) {
-                                        if let Some(name) = config.get("name").and_then(|v| v.as_str()) {
-                                            vault.name = name.to_string();
-                                        }
-                                        if let Some(created) = config.get("created").and_then(|v| v.as_str()) {
-                                            vault.created = Some(created.to_string());
-                                        }
-                                    }
-                                }
-                            }
-                            vaults.push(vault);
-                        }
+pub async fn stream_from_sidecar(
+    app: AppHandle,
+    window_label: String,
+    command: serde_json::Value,
+    channel_id: String,
+    state: State<'_, AppState>,
+) -> Result<StreamHandle, CommandError> {
+    if !sidecar_streams::is_valid_channel_id(&channel_id) {
+        return Err(CommandError::Validation("channel_id must be non-empty and use only letters, digits, '-', '_' or ':'".to_string()));
+    }
+    let method = command.get("method")
+        .and_then(|m| m.as_str())
+        .ok_or_else(|| CommandError::Validation("Command is missing 'method'".to_string()))?
+        .to_string();
+    let mut params = command.get("params").cloned().unwrap_or_else(|| serde_json::json!({}));
+    let Some(fields) = params.as_object_mut() else {
+        return Err(CommandError::Validation("params of a streamed command must be an object".to_string()));
+    };
+    fields.insert(STREAM_CHANNEL_PARAM.to_string(), serde_json::json!(channel_id));
+
+    let request_id = state.connection_pool.reserve_id(&window_label);
+    if !state.streams.open(&channel_id, &window_label, &request_id) {
+        state.connection_pool.release_id(&request_id);
+        return Err(CommandError::Validation(format!("stream channel '{}' is already in use", channel_id)));
+    }
+    // Subscribe before sending so no early chunk is missed
+    let events = state.connection_pool.subscribe();
+    println!("Streaming '{}' from sidecar '{}' on channel {}", method, window_label, channel_id);
+
+    let handle = StreamHandle { channel_id: channel_id.clone(), request_id: request_id.clone() };
+    tauri::async_runtime::spawn(async move {
+        let state = app.state::<AppState>();
+        let done = forward_stream(&app, &state, &window_label, &method, params, &channel_id, &request_id, events).await;
+        state.connection_pool.release_id(&request_id);
+        state.streams.close(&channel_id);
+        let _ = app.emit(&format!("sidecar://stream/{}/done", channel_id), done);
+    });
+    Ok(handle)
+}
+
+/// Run the streamed request, emitting its chunks until the final one (or
+/// the request failing); returns the `done` payload
+#[allow(clippy::too_many_arguments)]
+async fn forward_stream(
+    app: &AppHandle,
+    state: &State<'_, AppState>,
+    window_label: &str,
+    method: &str,
+    params: serde_json::Value,
+    channel_id: &str,
+    request_id: &str,
+    mut events: tokio::sync::broadcast::Receiver<(u16, serde_json::Value)>,
+) -> serde_json::Value {
+    let request = async {
+        let _permit = state.command_queue
+            .acquire(window_label)
+            .await
+            .map_err(|e| CommandError::SidecarUnavailable(e.to_string()))?;
+        route_sidecar_request(state, window_label, method, params, STREAM_REQUEST_TIMEOUT, Some(request_id)).await
+    };
+    tokio::pin!(request);
+
+    let event_name = format!("sidecar://stream/{}", channel_id);
+    let mut reply: Option<Result<serde_json::Value, CommandError>> = None;
+    let mut final_data: Option<serde_json::Value> = None;
+    let mut deadline: Option<tokio::time::Instant> = None;
+    while final_data.is_none() {
+        let grace_over = async move {
+            match deadline {
+                Some(deadline) => tokio::time::sleep_until(deadline).await,
+                None => std::future::pending().await,
+            }
+        };
+        tokio::select! {
+            result = &mut request, if reply.is_none() => {
+                let failed = result.is_err();
+                reply = Some(result);
+                if failed {
+                    break;
+                }
+                deadline = Some(tokio::time::Instant::now() + FINAL_CHUNK_GRACE);
+            }
+            event = events.recv() => match event {
+                Ok((_port, params)) => {
+                    let Some(chunk) = sidecar_streams::chunk_for(&params, channel_id) else { continue };
+                    if state.streams.is_cancelled(channel_id) {
+                        continue;
+                    }
+                    if chunk.is_final {
+                        final_data = Some(chunk.data);
+                    } else {
+                        let _ = app.emit(&event_name, chunk.data);
                     }
                 }
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                    eprintln!("Warning: Stream {} skipped {} events", channel_id, skipped);
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+            },
+            _ = grace_over => break,
         }
     }
-    
-    Ok(vaults)
+    // The final chunk can beat the reply; it is not long behind
+    if reply.is_none() && final_data.is_some() {
+        reply = tokio::time::timeout(FINAL_CHUNK_GRACE, &mut request).await.ok();
+    }
+
+    if state.streams.is_cancelled(channel_id) {
+        return serde_json::json!({ "status": "cancelled", "result": null, "error": null });
+    }
+    match reply {
+        Some(Err(error)) => serde_json::json!({
+            "status": "error",
+            "result": null,
+            "error": error.message(),
+            "code": error.code(),
+        }),
+        Some(Ok(result)) => serde_json::json!({ "status": "success", "result": final_data.unwrap_or(result), "error": null }),
+        None => serde_json::json!({ "status": "success", "result": final_data, "error": null }),
+    }
 }
 
-/// Get vault information
+/// Stop forwarding a stream from `stream_from_sidecar` and tell the sidecar
+/// to abort the command. False if there is no such stream.
 #[tauri::command]
-pub async fn get_vault_info(vault_path: String) -> Result<serde_json::Value, String> {
-    let path = PathBuf::from(&vault_path);
-    let config_path = path.join(".vault.json");
-    
-    if !config_path.exists() {
-        return Err("Vault config file not found".to_string());
-    }
-    
-    let contents = fs::read_to_string(&config_path)
-        .map_err(|e| format!("Failed to read vault config: {}", e))?;
-    
-    let config: serde_json::Value = serde_json::from_str(&contents)
-        .map_err(|e| format!("Failed to parse vault config: {}", e))?;
-    
-    Ok(config)
+pub async fn cancel_stream(
+    window_label: String,
+    channel_id: String,
+    state: State<'_, AppState>,
+) -> Result<bool, CommandError> {
+    let Some(request_id) = state.streams.cancel(&window_label, &channel_id) else {
+        return Ok(false);
+    };
+    state.connection_pool.cancel(&window_label, &request_id);
+    println!("Cancelled stream {} for window '{}'", channel_id, window_label);
+    Ok(true)
 }
 
-/// Update plugin configuration in .vault.json
+/// Call a plugin command after checking `args` against the handler's
+/// introspected signature, so a UI bug comes back as a precise
+/// `Validation` error instead of a `TypeError` from the sidecar. `method`
+/// is a full command id or a name under the plugin (`create_branch` for
+/// `memory.create_branch`). Methods without type annotations are sent as-is.
 #[tauri::command]
-pub async fn update_plugin_config(
-    vault_path: String,
-    plugin_id: String,
-    config: serde_json::Value,
-) -> Result<(), String> {
-    let path = PathBuf::from(&vault_path);
-    let config_path = path.join(".vault.json");
-    
-    // Read existing config
-    let contents = if config_path.exists() {
-        fs::read_to_string(&config_path)
-            .map_err(|e| format!("Failed to read vault config: {}", e))?
+pub async fn invoke_plugin_method(
+    window_label: String,
+    plugin_name: String,
+    method: String,
+    args: Option<serde_json::Value>,
+    state: State<'_, AppState>,
+) -> Result<serde_json::Value, CommandError> {
+    let signatures = plugin_signatures(&state, &window_label, &plugin_name).await?;
+    let command_id = if signatures.methods.contains_key(&method) {
+        method
     } else {
-        r#"{"plugins": {}}"#.to_string()
+        format!("{}.{}", plugin_name, method)
     };
-    
-    let mut vault_config: serde_json::Value = serde_json::from_str(&contents)
-        .map_err(|e| format!("Failed to parse vault config: {}", e))?;
-    
-    // Ensure plugins object exists
-    if vault_config.get("plugins").is_none() {
-        vault_config["plugins"] = serde_json::json!({});
+    let signature = signatures.methods.get(&command_id).ok_or_else(|| {
+        CommandError::Validation(format!("plugin '{}' has no method '{}'", plugin_name, command_id))
+    })?;
+
+    let args = match args.unwrap_or_else(|| serde_json::json!({})) {
+        serde_json::Value::Object(args) => args,
+        _ => return Err(CommandError::Validation("args must be an object".to_string())),
+    };
+    method_signatures::validate_args(&command_id, signature, &args)?;
+
+    let _permit = state.command_queue
+        .acquire(&window_label)
+        .await
+        .map_err(|e| CommandError::SidecarUnavailable(e.to_string()))?;
+    sidecar_request(&state, &window_label, &command_id, serde_json::Value::Object(args)).await
+}
+
+/// Introspected command signatures for a loaded plugin, cached until the
+/// process hosting it changes
+async fn plugin_signatures(
+    state: &State<'_, AppState>,
+    window_label: &str,
+    plugin: &str,
+) -> Result<PluginSignatures, CommandError> {
+    let (pid, ws_port) = state.sidecar_manager
+        .plugin_host(window_label, plugin)
+        .await
+        .ok_or_else(|| sidecar_not_found(window_label))?;
+    if let Some(cached) = state.signatures.get(window_label, plugin, pid) {
+        return Ok(cached);
     }
-    
-    // Get or create plugin config
-    if vault_config["plugins"].get(&plugin_id).is_none() {
-        vault_config["plugins"][&plugin_id] = serde_json::json!({});
+
+    let result = state.connection_pool
+        .request(ws_port, "system.describe_plugin_methods", serde_json::json!({ "plugin": plugin }), DEFAULT_REQUEST_TIMEOUT)
+        .await
+        .map_err(|e| connection_pool::command_error(&e))?;
+    if result.get("status").and_then(|s| s.as_str()) == Some("error") {
+        let error = result.get("error").and_then(|e| e.as_str()).unwrap_or("unknown error");
+        return Err(CommandError::Validation(error.to_string()));
     }
+    let signatures: PluginSignatures = serde_json::from_value(result)
+        .map_err(|e| CommandError::Internal(format!("Failed to parse signatures for plugin '{}': {}", plugin, e)))?;
+
+    state.signatures.insert(window_label, plugin, pid, signatures.clone());
+    Ok(signatures)
+}
+
+/// Commands marked `"priority": "high"`, and cancellations, bypass the
+/// per-window command queue
+fn is_high_priority(command: &serde_json::Value, method: &str) -> bool {
+    let marked = command.get("priority").and_then(|p| p.as_str()) == Some("high");
+    let cancel = method.rsplit('.').next().is_some_and(|name| name.starts_with("cancel"));
+    marked || cancel
+}
+
+/// Send a request to the sidecar owned by `window_label`, remembering
+/// failures for `capture_command_failure`
+async fn sidecar_request(
+    state: &State<'_, AppState>,
+    window_label: &str,
+    method: &str,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, CommandError> {
+    sidecar_request_with_timeout(state, window_label, method, params, DEFAULT_REQUEST_TIMEOUT).await
+}
+
+/// `sidecar_request` for calls that may outlast the default timeout
+async fn sidecar_request_with_timeout(
+    state: &State<'_, AppState>,
+    window_label: &str,
+    method: &str,
+    params: serde_json::Value,
+    timeout: Duration,
+) -> Result<serde_json::Value, CommandError> {
+    route_sidecar_request(state, window_label, method, params, timeout, None).await
+}
+
+/// `sidecar_request_with_timeout`, optionally under an id reserved with the
+/// connection pool so the request can be cancelled
+async fn route_sidecar_request(
+    state: &State<'_, AppState>,
+    window_label: &str,
+    method: &str,
+    params: serde_json::Value,
+    timeout: Duration,
+    request_id: Option<&str>,
+) -> Result<serde_json::Value, CommandError> {
+    let ws_port = match plugin_worker_port(state, window_label, method).await {
+        Some(port) => port,
+        None => state.sidecar_manager
+            .get_ws_port(window_label)
+            .await
+            .ok_or_else(|| sidecar_not_found(window_label))?,
+    };
+    state.window_manager.lock().await.touch(window_label);
+
+    let started = Instant::now();
+    let response = match request_id {
+        Some(id) => state.connection_pool.request_reserved(ws_port, id, method, params.clone(), timeout).await,
+        None => state.connection_pool.request(ws_port, method, params.clone(), timeout).await,
+    };
+    let elapsed_ms = started.elapsed().as_millis() as u64;
+    match response {
+        Ok(result) => {
+            state.failures.record_result(window_label, method, &params, &result);
+            let error = (result.get("status").and_then(|s| s.as_str()) == Some("error"))
+                .then(|| result.get("error").or_else(|| result.get("message")).and_then(|e| e.as_str()).unwrap_or(""));
+            let status = if error.is_some() { "error" } else { "ok" };
+            state.audit.record(window_label, method, &params, status, elapsed_ms, error);
+            Ok(result)
+        }
+        // Cancelling is not a failure worth capturing
+        Err(e) if e.downcast_ref::<RequestCancelled>().is_some() => {
+            state.audit.record(window_label, method, &params, "cancelled", elapsed_ms, None);
+            Err(connection_pool::command_error(&e))
+        }
+        Err(e) => {
+            state.failures.record_error(window_label, method, &params, &e);
+            state.audit.record(window_label, method, &params, "error", elapsed_ms, Some(&e.to_string()));
+            Err(connection_pool::command_error(&e))
+        }
+    }
+}
+
+/// Error for a window with no sidecar, e.g. one that was closed
+fn sidecar_not_found(window_label: &str) -> CommandError {
+    CommandError::SidecarUnavailable(format!("Sidecar not found for window: {}", window_label))
+}
+
+/// Owner the sidecar reports for its built-in commands
+const CORE_PLUGIN_NAME: &str = "core";
+/// Limit for asking a plugin worker which commands it registered
+const WORKER_QUERY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// With isolated plugins, the worker that registered `method`. Each worker
+/// is asked for its commands on first use; the rest go to the main sidecar.
+async fn plugin_worker_port(state: &State<'_, AppState>, window_label: &str, method: &str) -> Option<u16> {
+    if !state.sidecar_manager.has_workers(window_label).await {
+        return None;
+    }
+    if let Some(port) = state.sidecar_manager.route_command(window_label, method).await {
+        return Some(port);
+    }
+
+    for (plugin, port) in state.sidecar_manager.workers_without_commands(window_label).await {
+        match state.connection_pool
+            .request(port, "system.list_commands", serde_json::json!({}), WORKER_QUERY_TIMEOUT)
+            .await
+        {
+            Ok(result) => {
+                let commands = result.get("commands")
+                    .and_then(|c| c.as_object())
+                    .map(|commands| {
+                        commands.iter()
+                            .filter(|(_, owner)| owner.as_str() != Some(CORE_PLUGIN_NAME))
+                            .map(|(id, _)| id.clone())
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                state.sidecar_manager.set_worker_commands(window_label, &plugin, commands).await;
+            }
+            // Not up yet (or hung); asked again on the next unrouted command
+            Err(e) => eprintln!("Warning: Plugin worker '{}' did not list its commands: {}", plugin, e),
+        }
+    }
+    state.sidecar_manager.route_command(window_label, method).await
+}
+
+/// PID and health of each plugin worker when the vault runs plugins in
+/// isolated processes (`isolatePlugins`); empty in single-process mode
+#[tauri::command]
+pub async fn get_plugin_process_info(
+    window_label: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<PluginProcessInfo>, CommandError> {
+    let mut info = state.sidecar_manager.plugin_process_info(&window_label).await;
+    for worker in info.iter_mut().filter(|w| w.running) {
+        let ping = SidecarClient::request(worker.ws_port, "system.ping", serde_json::json!({}), WORKER_QUERY_TIMEOUT).await;
+        worker.responsive = Some(ping.is_ok());
+    }
+    Ok(info)
+}
+
+/// Whether a window's sidecar is answering and, if not, whether its event
+/// loop is stuck or spinning and which plugin was last running. The same
+/// diagnosis is pushed as `sidecar-deadlock://{window_label}` when the
+/// background watchdog notices a hang.
+#[tauri::command]
+pub async fn diagnose_sidecar_hang(
+    window_label: String,
+    state: State<'_, AppState>,
+) -> Result<HangDiagnosis, CommandError> {
+    let diagnosis = hang_detector::diagnose(&state.sidecar_manager, &window_label).await;
+    if diagnosis.state == "not_running" && diagnosis.pid.is_none() {
+        return Err(sidecar_not_found(&window_label));
+    }
+    Ok(diagnosis)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SidecarStatus {
+    pub window_label: String,
+    pub running: bool,
+    pub pid: u32,
+    pub port: u16,
+    pub exit_code: Option<i32>,
+    pub started_at: chrono::DateTime<chrono::Utc>,
+    /// Last answered heartbeat ping (sent every 15 seconds while it runs)
+    pub last_seen: Option<chrono::DateTime<chrono::Utc>>,
+    /// What happens if it exits: "auto" respawns it under the same window
+    /// label (emitting `sidecar://restarted`), "prompt" or "off" don't
+    pub crash_policy: CrashPolicy,
+}
+
+/// Whether a window's sidecar process is alive, its port, and when it last
+/// answered a heartbeat
+#[tauri::command]
+pub async fn get_sidecar_status(
+    window_label: String,
+    state: State<'_, AppState>,
+) -> Result<SidecarStatus, CommandError> {
+    let status = state.sidecar_manager
+        .process_status(&window_label)
+        .await
+        .ok_or_else(|| CommandError::NotFound(format!("no sidecar for window '{}'", window_label)))?;
+    Ok(SidecarStatus {
+        window_label,
+        running: status.running,
+        pid: status.pid,
+        port: status.ws_port,
+        exit_code: status.exit_code,
+        started_at: status.started_at,
+        last_seen: status.last_seen,
+        crash_policy: CrashPolicy::for_vault(Path::new(&status.vault_path)),
+    })
+}
+
+/// Sidecar crashes seen for a window, oldest first, with what the vault's
+/// `crashPolicy` ("auto", "prompt" or "off") did about each
+#[tauri::command]
+pub async fn get_crash_history(
+    window_label: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<CrashRecord>, CommandError> {
+    Ok(state.crashes.history(&window_label))
+}
+
+/// Respawn a window's sidecar under the same window label, e.g. from the
+/// button offered for `sidecar://crashed` under the "prompt" policy
+#[tauri::command]
+pub async fn restart_sidecar(
+    window_label: String,
+    state: State<'_, AppState>,
+) -> Result<u16, CommandError> {
+    let old_port = state.sidecar_manager
+        .get_ws_port(&window_label)
+        .await
+        .ok_or_else(|| sidecar_not_found(&window_label))?;
+    crash_monitor::restart(&state.sidecar_manager, &state.connection_pool, &window_label, old_port).await
+}
+
+/// Recent lifecycle trace (load, unload, ticks, commands) for one plugin,
+/// oldest first. Empty unless the vault has `debugPluginLifecycle` on.
+#[tauri::command]
+pub async fn get_plugin_lifecycle_history(
+    window_label: String,
+    plugin_name: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<LifecycleEntry>, CommandError> {
+    Ok(state.lifecycle.history(&window_label, &plugin_name))
+}
+
+/// Request/response counters for a window's pooled sidecar connection,
+/// including how many correlation anomalies forced a resync
+#[tauri::command]
+pub async fn get_connection_diagnostics(
+    window_label: String,
+    state: State<'_, AppState>,
+) -> Result<ConnectionDiagnostics, CommandError> {
+    let ws_port = state.sidecar_manager
+        .get_ws_port(&window_label)
+        .await
+        .ok_or_else(|| sidecar_not_found(&window_label))?;
+
+    Ok(state.connection_pool.diagnostics(ws_port).await)
+}
+
+/// Commands queued and in flight for a window, for a busy indicator
+#[tauri::command]
+pub async fn get_command_queue_depth(
+    window_label: String,
+    state: State<'_, AppState>,
+) -> Result<CommandQueueDepth, CommandError> {
+    Ok(state.command_queue.depth(&window_label))
+}
+
+/// Limit how many frontend commands a window may have in flight and
+/// persist it for the vault. Excess commands wait in order.
+///
+/// `max_in_flight` of `None` means unlimited, the default.
+#[tauri::command]
+pub async fn set_command_queue_limit(
+    window_label: String,
+    max_in_flight: Option<usize>,
+    state: State<'_, AppState>,
+) -> Result<CommandQueueDepth, CommandError> {
+    if max_in_flight == Some(0) {
+        return Err(CommandError::Validation("Max in-flight commands must be at least 1".to_string()));
+    }
+
+    let vault_path = state.window_manager
+        .lock()
+        .await
+        .get_vault_path(&window_label)
+        .cloned()
+        .ok_or_else(|| CommandError::NotFound(format!("Vault not found for window: {}", window_label)))?;
+    settings::save_vault_settings(
+        &PathBuf::from(&vault_path),
+        &serde_json::json!({ MAX_IN_FLIGHT_COMMANDS_SETTING: max_in_flight }),
+    )
+    .map_err(|e| CommandError::Io(format!("Failed to persist command queue limit: {}", e)))?;
+
+    state.command_queue.set_limit(&window_label, max_in_flight);
+    Ok(state.command_queue.depth(&window_label))
+}
+
+/// Get the plugin callback concurrency limit and in-flight count
+#[tauri::command]
+pub async fn get_plugin_concurrency(
+    window_label: String,
+    state: State<'_, AppState>,
+) -> Result<serde_json::Value, CommandError> {
+    sidecar_request(&state, &window_label, "system.get_plugin_concurrency", serde_json::json!({})).await
+}
+
+/// Change the plugin callback concurrency limit live and persist it for the vault.
+///
+/// `limit` of `None` means unlimited; `1` serializes callbacks for debugging.
+#[tauri::command]
+pub async fn set_plugin_concurrency(
+    window_label: String,
+    limit: Option<u32>,
+    state: State<'_, AppState>,
+) -> Result<serde_json::Value, CommandError> {
+    if limit == Some(0) {
+        return Err(CommandError::Validation("Plugin concurrency must be at least 1".to_string()));
+    }
+
+    let result = sidecar_request(
+        &state,
+        &window_label,
+        "system.set_plugin_concurrency",
+        serde_json::json!({ "limit": limit }),
+    )
+    .await?;
+
+    let vault_path = state.window_manager
+        .lock()
+        .await
+        .get_vault_path(&window_label)
+        .cloned();
+    if let Some(vault_path) = vault_path {
+        settings::save_vault_settings(
+            &PathBuf::from(&vault_path),
+            &serde_json::json!({ PLUGIN_CONCURRENCY_SETTING: limit }),
+        )
+        .map_err(|e| CommandError::Io(format!("Failed to persist plugin concurrency: {}", e)))?;
+    }
+
+    Ok(result)
+}
+
+/// Frontend event channels the window's sidecar has emitted, whether each
+/// is muted, and how many clients receive them
+#[tauri::command]
+pub async fn list_event_subscriptions(
+    window_label: String,
+    state: State<'_, AppState>,
+) -> Result<serde_json::Value, CommandError> {
+    sidecar_request(&state, &window_label, "events.list_subscriptions", serde_json::json!({})).await
+}
+
+/// Mute or unmute an event channel at the sidecar and persist the choice
+/// for the vault. Channels are enabled unless muted.
+#[tauri::command]
+pub async fn set_event_subscription(
+    window_label: String,
+    channel: String,
+    enabled: bool,
+    state: State<'_, AppState>,
+) -> Result<serde_json::Value, CommandError> {
+    let result = sidecar_request(
+        &state,
+        &window_label,
+        "events.set_subscription",
+        serde_json::json!({ "channel": channel, "enabled": enabled }),
+    )
+    .await?;
+
+    let vault_path = state.window_manager
+        .lock()
+        .await
+        .get_vault_path(&window_label)
+        .cloned();
+    if let (Some(vault_path), Some(muted)) = (vault_path, result.get("muted")) {
+        settings::save_vault_settings(
+            &PathBuf::from(&vault_path),
+            &serde_json::json!({ MUTED_EVENT_CHANNELS_SETTING: muted }),
+        )
+        .map_err(|e| CommandError::Io(format!("Failed to persist event subscriptions: {}", e)))?;
+    }
+
+    Ok(result)
+}
+
+/// Resilience settings; the sidecar saves them to `.vault.json` itself
+const PROVIDER_RESILIENCE_CONFIG: &str = "providerResilience";
+/// Settings the running sidecar can change in place
+const LIVE_SIDECAR_SETTINGS: &[&str] = &[
+    LOG_LEVEL_SETTING,
+    TICK_INTERVAL_SETTING,
+    PLUGIN_CONCURRENCY_SETTING,
+    MUTED_EVENT_CHANNELS_SETTING,
+    RESTRICT_PLUGIN_FS_SETTING,
+    PLUGIN_LIFECYCLE_EVENTS_SETTING,
+    PROVIDER_RESILIENCE_CONFIG,
+];
+/// Settings the host reads each time it needs them, so saving applies them
+const HOST_READ_SETTINGS: &[&str] = &[
+    MAX_IN_FLIGHT_COMMANDS_SETTING,
+    DRAIN_TIMEOUT_SETTING,
+    SHUTDOWN_TIMEOUT_SETTING,
+    CRASH_POLICY_SETTING,
+];
+/// Settings only read when the sidecar is spawned
+const RESTART_SETTINGS: &[&str] = &[ISOLATE_PLUGINS_SETTING];
+
+#[derive(Debug, Default, Serialize)]
+pub struct SidecarConfigResult {
+    /// Settings now in effect
+    pub applied: Vec<String>,
+    /// Settings saved but only picked up when the sidecar restarts
+    pub requires_restart: Vec<String>,
+    /// Settings rejected, with why; these are not saved
+    pub errors: HashMap<String, String>,
+}
+
+/// Apply a vault settings delta to the window's running sidecar, changing
+/// as much as possible live, and save it for the vault. Only settings in
+/// `requires_restart` need a sidecar restart to take effect.
+#[tauri::command]
+pub async fn apply_sidecar_config(
+    window_label: String,
+    config: serde_json::Value,
+    state: State<'_, AppState>,
+) -> Result<SidecarConfigResult, CommandError> {
+    let Some(config) = config.as_object() else {
+        return Err(CommandError::Validation("config must be an object".to_string()));
+    };
+    let known = |key: &str| {
+        LIVE_SIDECAR_SETTINGS.contains(&key) || HOST_READ_SETTINGS.contains(&key) || RESTART_SETTINGS.contains(&key)
+    };
+    let unknown: Vec<&str> = config.keys().map(String::as_str).filter(|key| !known(key)).collect();
+    if !unknown.is_empty() {
+        return Err(CommandError::Validation(format!("unknown settings: {}", unknown.join(", "))));
+    }
+
+    let vault_path = state.window_manager
+        .lock()
+        .await
+        .get_vault_path(&window_label)
+        .cloned()
+        .ok_or_else(|| CommandError::NotFound(format!("Vault not found for window: {}", window_label)))?;
+
+    let mut outcome = SidecarConfigResult::default();
+    let live: serde_json::Map<String, serde_json::Value> = config.iter()
+        .filter(|(key, _)| LIVE_SIDECAR_SETTINGS.contains(&key.as_str()))
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect();
+    if !live.is_empty() {
+        let result = sidecar_request(&state, &window_label, "system.apply_config", serde_json::json!({ "config": live })).await?;
+        let keys = |field: &str| -> Vec<String> {
+            result.get(field)
+                .and_then(|v| v.as_array())
+                .map(|keys| keys.iter().filter_map(|k| k.as_str()).map(str::to_string).collect())
+                .unwrap_or_default()
+        };
+        outcome.applied = keys("applied");
+        outcome.requires_restart = keys("requires_restart");
+        if let Some(errors) = result.get("errors").and_then(|e| e.as_object()) {
+            for (key, error) in errors {
+                outcome.errors.insert(key.clone(), error.as_str().unwrap_or("rejected").to_string());
+            }
+        }
+    }
+
+    for (key, value) in config.iter().filter(|(key, _)| HOST_READ_SETTINGS.contains(&key.as_str())) {
+        match check_host_setting(key, value) {
+            Ok(()) => {
+                if key == MAX_IN_FLIGHT_COMMANDS_SETTING {
+                    let limit = value.as_u64().map(|limit| limit as usize);
+                    state.command_queue.set_limit(&window_label, limit);
+                }
+                outcome.applied.push(key.clone());
+            }
+            Err(e) => {
+                outcome.errors.insert(key.clone(), e);
+            }
+        }
+    }
+    outcome.requires_restart.extend(
+        config.keys().filter(|key| RESTART_SETTINGS.contains(&key.as_str())).cloned(),
+    );
+
+    let saved: serde_json::Map<String, serde_json::Value> = outcome.applied.iter()
+        .chain(&outcome.requires_restart)
+        .filter(|key| key.as_str() != PROVIDER_RESILIENCE_CONFIG)
+        .filter_map(|key| config.get(key).map(|value| (key.clone(), value.clone())))
+        .collect();
+    if !saved.is_empty() {
+        settings::save_vault_settings(&PathBuf::from(&vault_path), &serde_json::Value::Object(saved))
+            .map_err(|e| CommandError::Io(format!("Failed to persist sidecar config: {}", e)))?;
+    }
+
+    println!(
+        "Applied sidecar config for '{}': applied={:?}, requires_restart={:?}, errors={:?}",
+        window_label, outcome.applied, outcome.requires_restart, outcome.errors
+    );
+    Ok(outcome)
+}
+
+/// Validate a setting the host reads on demand (null clears it)
+fn check_host_setting(key: &str, value: &serde_json::Value) -> Result<(), String> {
+    if value.is_null() {
+        return Ok(());
+    }
+    if key == CRASH_POLICY_SETTING {
+        return serde_json::from_value::<CrashPolicy>(value.clone())
+            .map(|_| ())
+            .map_err(|e| format!("invalid crash policy: {}", e));
+    }
+    match value.as_u64() {
+        Some(0) if key == MAX_IN_FLIGHT_COMMANDS_SETTING => Err(format!("{} must be at least 1", key)),
+        Some(_) => Ok(()),
+        None => Err(format!("{} must be a whole number or null", key)),
+    }
+}
+
+/// Paths a plugin has written to, with writes outside the vault flagged
+/// (and blocked when the vault sets `restrictPluginFs`)
+#[tauri::command]
+pub async fn get_plugin_fs_access(
+    window_label: String,
+    plugin_name: String,
+    state: State<'_, AppState>,
+) -> Result<serde_json::Value, CommandError> {
+    sidecar_request(
+        &state,
+        &window_label,
+        "plugins.get_fs_access",
+        serde_json::json!({ "plugin": plugin_name }),
+    )
+    .await
+}
+
+/// Per-provider `{requests, tokens, estimated_cost, window_reset}` for the
+/// current billing period, with the last rate-limit headers each returned
+#[tauri::command]
+pub async fn get_provider_usage(
+    window_label: String,
+    state: State<'_, AppState>,
+) -> Result<serde_json::Value, CommandError> {
+    sidecar_request(&state, &window_label, "settings.get_provider_usage", serde_json::json!({})).await
+}
+
+/// Circuit breaker state for a provider in this window's sidecar
+#[tauri::command]
+pub async fn get_provider_circuit_state(
+    window_label: String,
+    provider: String,
+    state: State<'_, AppState>,
+) -> Result<serde_json::Value, CommandError> {
+    sidecar_request(
+        &state,
+        &window_label,
+        "settings.get_provider_circuit_state",
+        serde_json::json!({ "provider": provider }),
+    )
+    .await
+}
+
+/// Close a provider's circuit so generations are attempted again
+#[tauri::command]
+pub async fn reset_provider_circuit(
+    window_label: String,
+    provider: String,
+    state: State<'_, AppState>,
+) -> Result<serde_json::Value, CommandError> {
+    sidecar_request(
+        &state,
+        &window_label,
+        "settings.reset_provider_circuit",
+        serde_json::json!({ "provider": provider }),
+    )
+    .await
+}
+
+/// Update retry count, backoff and circuit breaker thresholds.
+///
+/// Accepts any of `retries`, `backoff_seconds`, `backoff_max_seconds`,
+/// `failure_threshold` and `cooldown_seconds`; omitted keys are unchanged.
+#[tauri::command]
+pub async fn configure_provider_resilience(
+    window_label: String,
+    settings: serde_json::Value,
+    state: State<'_, AppState>,
+) -> Result<serde_json::Value, CommandError> {
+    if !settings.is_object() {
+        return Err(CommandError::Validation("Resilience settings must be an object".to_string()));
+    }
+
+    sidecar_request(
+        &state,
+        &window_label,
+        "settings.configure_provider_resilience",
+        serde_json::json!({ "settings": settings }),
+    )
+    .await
+}
+
+/// Page through captured sidecar output (stdout and stderr), newest first.
+/// Reading leaves the buffer as it is; new lines also arrive live as
+/// `sidecar://log/{window_label}` events.
+///
+/// Pass the returned `next_cursor` back as `before_ts` to fetch older entries.
+/// `max_lines` is shorthand for the query's `limit`.
+#[tauri::command]
+pub async fn get_sidecar_logs(
+    window_label: String,
+    query: Option<LogQuery>,
+    max_lines: Option<usize>,
+    state: State<'_, AppState>,
+) -> Result<LogPage, CommandError> {
+    let mut query = query.unwrap_or_default();
+    if max_lines.is_some() {
+        query.limit = max_lines;
+    }
+
+    state.sidecar_manager
+        .get_logs(&window_label, &query)
+        .await
+        .ok_or_else(|| CommandError::NotFound(format!("No sidecar logs for window: {}", window_label)))
+}
+
+/// Log entries included in a failure report
+const REPORT_LOG_LINES: usize = 100;
+
+#[derive(Debug, Serialize)]
+pub struct SidecarReportInfo {
+    pub vault_path: Option<String>,
+    pub ws_port: Option<u16>,
+    pub running: bool,
+    pub connection: Option<ConnectionDiagnostics>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReportEnvironment {
+    pub app_version: String,
+    pub os: String,
+    pub arch: String,
+    pub family: String,
+}
+
+impl ReportEnvironment {
+    fn current() -> Self {
+        Self {
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+            family: std::env::consts::FAMILY.to_string(),
+        }
+    }
+}
+
+/// Everything needed to file a bug about the last failed sidecar command
+#[derive(Debug, Serialize)]
+pub struct CommandFailureReport {
+    pub generated: String,
+    pub failure: CommandFailure,
+    pub recent_logs: Vec<LogEntry>,
+    pub sidecar: SidecarReportInfo,
+    pub environment: ReportEnvironment,
+    /// Where the report was written, when `save` was requested
+    pub saved_to: Option<String>,
+}
+
+/// Bundle the window's most recent failed command with logs, sidecar
+/// state and environment into one report. Secrets and home paths are
+/// redacted. With `save`, the report is also written under
+/// `bug-reports/` in the app data directory.
+#[tauri::command]
+pub async fn capture_command_failure(
+    app: AppHandle,
+    window_label: String,
+    save: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<CommandFailureReport, CommandError> {
+    let failure = state.failures
+        .last(&window_label)
+        .ok_or_else(|| CommandError::NotFound(format!("No failed commands recorded for window: {}", window_label)))?;
+
+    let query = LogQuery { limit: Some(REPORT_LOG_LINES), ..Default::default() };
+    let recent_logs = state.sidecar_manager
+        .get_logs(&window_label, &query)
+        .await
+        .map(|page| page.entries)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|mut entry| {
+            entry.message = redact_text(&entry.message);
+            entry
+        })
+        .collect();
+
+    let vault_path = state.window_manager.lock().await
+        .get_vault_path(&window_label)
+        .map(|path| redact_text(path));
+    let ws_port = state.sidecar_manager.get_ws_port(&window_label).await;
+    let connection = match ws_port {
+        Some(port) => Some(state.connection_pool.diagnostics(port).await),
+        None => None,
+    };
+
+    let mut report = CommandFailureReport {
+        generated: chrono::Utc::now().to_rfc3339(),
+        failure,
+        recent_logs,
+        sidecar: SidecarReportInfo {
+            vault_path,
+            ws_port,
+            running: state.sidecar_manager.is_running(&window_label).await,
+            connection,
+        },
+        environment: ReportEnvironment::current(),
+        saved_to: None,
+    };
+
+    if save.unwrap_or(false) {
+        let dir = app.path().app_data_dir()
+            .map_err(|e| CommandError::Internal(format!("Failed to get app data dir: {}", e)))?
+            .join("bug-reports");
+        let path = dir.join(format!("report-{}.json", chrono::Utc::now().format("%Y%m%d-%H%M%S")));
+        let contents = serde_json::to_string_pretty(&report)
+            .map_err(|e| CommandError::Internal(format!("Failed to serialize report: {}", e)))?;
+        atomic_write(&path, contents.as_bytes())
+            .map_err(|e| CommandError::Io(format!("Failed to write report: {}", e)))?;
+        report.saved_to = Some(path.to_string_lossy().to_string());
+    }
+
+    Ok(report)
+}
+
+/// What a plugin author needs to triage a user's report about one plugin
+#[derive(Debug, Serialize)]
+pub struct PluginSupportBundle {
+    pub generated: String,
+    pub plugin: String,
+    /// Whether state values (which may hold user content) were included
+    pub include_content: bool,
+    /// Config, init timings, last error and state snapshot from the sidecar
+    pub plugin_info: Option<serde_json::Value>,
+    /// Why `plugin_info` is missing, e.g. the sidecar is down
+    pub plugin_info_error: Option<String>,
+    pub recent_logs: Vec<LogEntry>,
+    /// Lifecycle trace, when the vault has `debugPluginLifecycle` on
+    pub lifecycle: Vec<LifecycleEntry>,
+    pub environment: ReportEnvironment,
+    pub saved_to: String,
+}
+
+/// Gather one plugin's config, recent logs, last error and traceback, init
+/// timing and state snapshot into a file under `support-bundles/` in the
+/// app data directory.
+///
+/// Secrets and home paths are always redacted. State values are left out
+/// unless `include_content` is set, since they often hold user content.
+#[tauri::command]
+pub async fn export_plugin_support_bundle(
+    app: AppHandle,
+    window_label: String,
+    plugin_name: String,
+    include_content: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<PluginSupportBundle, CommandError> {
+    if plugin_name.is_empty() || plugin_name.contains(['/', '\\']) || plugin_name.starts_with('.') {
+        return Err(CommandError::Validation(format!("invalid plugin name {:?}", plugin_name)));
+    }
+    let include_content = include_content.unwrap_or(false);
+
+    let (plugin_info, plugin_info_error) = match sidecar_request(
+        &state,
+        &window_label,
+        "plugins.get_support_info",
+        serde_json::json!({ "plugin": plugin_name, "include_content": include_content }),
+    ).await {
+        Ok(mut info) if info.get("status").and_then(|s| s.as_str()) == Some("success") => {
+            if let Some(map) = info.as_object_mut() {
+                map.remove("status");
+            }
+            redact_json(&mut info);
+            (Some(info), None)
+        }
+        Ok(info) => (None, Some(CommandError::Internal(
+            info.get("error").and_then(|e| e.as_str()).unwrap_or("Sidecar returned an error").to_string()
+        ))),
+        Err(e) => (None, Some(e)),
+    };
+
+    let query = LogQuery {
+        limit: Some(REPORT_LOG_LINES),
+        plugin: Some(plugin_name.clone()),
+        ..Default::default()
+    };
+    let recent_logs: Vec<LogEntry> = state.sidecar_manager
+        .get_logs(&window_label, &query)
+        .await
+        .map(|page| page.entries)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|mut entry| {
+            entry.message = redact_text(&entry.message);
+            entry
+        })
+        .collect();
+
+    let lifecycle: Vec<LifecycleEntry> = state.lifecycle
+        .history(&window_label, &plugin_name)
+        .into_iter()
+        .map(|mut entry| {
+            redact_json(&mut entry.details);
+            entry
+        })
+        .collect();
+
+    if plugin_info.is_none() && recent_logs.is_empty() && lifecycle.is_empty() {
+        return Err(plugin_info_error.unwrap_or_else(
+            || CommandError::NotFound(format!("Plugin not found: {}", plugin_name)),
+        ));
+    }
+
+    let generated = chrono::Utc::now();
+    let path = app.path().app_data_dir()
+        .map_err(|e| CommandError::Internal(format!("Failed to get app data dir: {}", e)))?
+        .join("support-bundles")
+        .join(format!("{}-{}.json", plugin_name, generated.format("%Y%m%d-%H%M%S")));
+
+    let bundle = PluginSupportBundle {
+        generated: generated.to_rfc3339(),
+        plugin: plugin_name,
+        include_content,
+        plugin_info,
+        plugin_info_error: plugin_info_error.map(|e| e.message().to_string()),
+        recent_logs,
+        lifecycle,
+        environment: ReportEnvironment::current(),
+        saved_to: path.to_string_lossy().to_string(),
+    };
+    let contents = serde_json::to_string_pretty(&bundle)
+        .map_err(|e| CommandError::Internal(format!("Failed to serialize support bundle: {}", e)))?;
+    atomic_write(&path, contents.as_bytes())
+        .map_err(|e| CommandError::Io(format!("Failed to write support bundle: {}", e)))?;
+    println!("Plugin support bundle written to {}", bundle.saved_to);
+
+    Ok(bundle)
+}
+
+#[derive(Debug, Serialize)]
+pub struct VaultCloseReport {
+    /// How the sidecar went down, including plugins still busy if it had
+    /// to be killed
+    pub shutdown: ShutdownReport,
+    /// New registry entry of a scratch vault that was kept
+    pub kept: Option<VaultListItem>,
+}
+
+/// Close a vault window and terminate its sidecar.
+///
+/// In-flight requests get `drainTimeoutMs` to finish and plugins
+/// `shutdownTimeoutMs` to unload before the sidecar is killed; either can be
+/// overridden for this call.
+///
+/// A scratch vault from `open_temp_vault` is deleted, unless `keep` is set:
+/// then it moves into the default vault directory (`defaultVaultDir`, or
+/// Documents/Tailor) and is added to recents, and its new entry is returned.
+#[tauri::command]
+pub async fn close_vault(
+    window_label: String,
+    keep: Option<bool>,
+    drain_timeout_ms: Option<u64>,
+    shutdown_timeout_ms: Option<u64>,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<VaultCloseReport, CommandError> {
+    let overrides = TimeoutOverrides { drain_ms: drain_timeout_ms, shutdown_ms: shutdown_timeout_ms };
+    let closed = shutdown_vault_window(&state, &window_label, overrides).await?;
+    let mut report = VaultCloseReport { shutdown: closed.shutdown, kept: None };
+    let Some(scratch) = closed.scratch else {
+        return Ok(report);
+    };
+
+    if !keep.unwrap_or(false) {
+        fs::remove_dir_all(&scratch)
+            .map_err(|e| CommandError::Io(format!("Failed to delete scratch vault {}: {}", scratch.display(), e)))?;
+        println!("Deleted scratch vault: {}", scratch.display());
+        return Ok(report);
+    }
+
+    let kept = keep_scratch_vault(&app, &scratch)?;
+    register_vault_in_registry(&app, &kept).await?;
+    println!("Kept scratch vault at {}", kept.path);
+    report.kept = Some(kept);
+    Ok(report)
+}
+
+/// Move a scratch vault into the default vault directory, under a name not
+/// yet taken there
+fn keep_scratch_vault(app: &AppHandle, scratch: &Path) -> Result<VaultListItem, CommandError> {
+    let global_settings = settings::load_global_settings(&app_config_dir(app)?)
+        .map_err(|e| CommandError::Io(format!("Failed to load global settings: {}", e)))?;
+    let parent = default_vault_dir(app, &global_settings)?;
+    fs::create_dir_all(&parent)
+        .map_err(|e| CommandError::Io(format!("Failed to create {}: {}", parent.display(), e)))?;
+
+    let target = (1..)
+        .map(|n| match n {
+            1 => parent.join(TEMP_VAULT_NAME),
+            n => parent.join(format!("{} {}", TEMP_VAULT_NAME, n)),
+        })
+        .find(|candidate| !candidate.exists())
+        .expect("unbounded range always yields a free name");
+
+    // The temp directory is often a different filesystem
+    if fs::rename(scratch, &target).is_err() {
+        crate::fs_utils::copy_dir_recursive(scratch, &target)
+            .map_err(|e| CommandError::Io(format!("Failed to move scratch vault to {}: {}", target.display(), e)))?;
+        let _ = fs::remove_dir_all(scratch);
+    }
+
+    let target_path = target.to_string_lossy().to_string();
+    Ok(vault_list_item(&target, &target_path))
+}
+
+struct ClosedWindow {
+    shutdown: ShutdownReport,
+    /// The vault's path if it was a scratch vault, for the caller to delete or keep
+    scratch: Option<PathBuf>,
+}
+
+/// Tear down a vault window's sidecar and tracking
+async fn shutdown_vault_window(
+    state: &State<'_, AppState>,
+    window_label: &str,
+    overrides: TimeoutOverrides,
+) -> Result<ClosedWindow, CommandError> {
+    let window_label = window_label.to_string();
+    println!("Closing vault window: {}", window_label);
+
+    // Step 1: Terminate the sidecar, then drop the pooled connection. The
+    // connection stays up while draining so in-flight requests can answer.
+    let ws_port = state.sidecar_manager.get_ws_port(&window_label).await;
+    let shutdown = state.sidecar_manager
+        .terminate_sidecar_with(&window_label, overrides)
+        .await
+        .map_err(|e| CommandError::SidecarUnavailable(format!("Failed to terminate sidecar: {}", e)))?;
+    if let Some(ws_port) = ws_port {
+        state.connection_pool.disconnect(ws_port).await;
+    }
+
+    // Step 2: Remove window from tracking
+    state.command_queue.remove(&window_label);
+    let scratch = {
+        let mut window_manager = state.window_manager.lock().await;
+        let scratch = window_manager.is_disposable(&window_label)
+            .then(|| window_manager.get_vault_path(&window_label).map(PathBuf::from))
+            .flatten();
+        window_manager.remove_window(&window_label);
+        scratch
+    };
+
+    println!("Vault closed successfully: {}", window_label);
+
+    Ok(ClosedWindow { shutdown, scratch })
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DiagnosticPhase {
+    pub name: String,
+    pub passed: bool,
+    pub duration_ms: u128,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DiagnosticReport {
+    pub vault_path: String,
+    pub passed: bool,
+    pub total_ms: u128,
+    pub phases: Vec<DiagnosticPhase>,
+    pub timestamp: String,
+}
+
+/// Time a single diagnostic phase and record its outcome
+async fn run_phase<T, F>(phases: &mut Vec<DiagnosticPhase>, name: &str, fut: F) -> Option<T>
+where
+    F: std::future::Future<Output = Result<T, String>>,
+{
+    let start = Instant::now();
+    let outcome = fut.await;
+    let duration_ms = start.elapsed().as_millis();
+
+    let (passed, error, value) = match outcome {
+        Ok(value) => (true, None, Some(value)),
+        Err(e) => (false, Some(e), None),
+    };
+    println!("Diagnostic phase '{}': passed={} ({}ms)", name, passed, duration_ms);
+
+    phases.push(DiagnosticPhase {
+        name: name.to_string(),
+        passed,
+        duration_ms,
+        error,
+    });
+    value
+}
+
+/// Run a scripted preflight/open/ping/command/close cycle against a vault.
+///
+/// The sidecar is spawned headless under a throwaway label, so no window is
+/// created, and it is always terminated before returning.
+#[tauri::command]
+pub async fn run_self_diagnostic(
+    vault_path: String,
+    state: State<'_, AppState>,
+) -> Result<DiagnosticReport, CommandError> {
+    println!("Running self diagnostic for vault: {}", vault_path);
+
+    let started = Instant::now();
+    let mut phases = Vec::new();
+    let diag_label = format!("diagnostic_{}", uuid::Uuid::new_v4());
+
+    let preflight = run_phase(&mut phases, "preflight", async {
+        let path = resolve_vault_path("vault_path", &vault_path).map_err(|e| e.to_string())?;
+        if !path.is_dir() {
+            return Err(format!("Vault directory not found: {}", vault_path));
+        }
+        DependencyChecker::check_and_install(&vault_path, |_| {})
+            .await
+            .map_err(|e| format!("Dependency check failed: {}", e))
+    })
+    .await;
+
+    let mut port = None;
+    if preflight.is_some() {
+        port = run_phase(&mut phases, "open", async {
+            let port = state.sidecar_manager
+                .spawn_sidecar(diag_label.clone(), vault_path.clone())
+                .await
+                .map_err(|e| format!("Failed to spawn sidecar: {}", e))?;
+            SidecarClient::wait_until_ready(port, Duration::from_secs(20))
+                .await
+                .map_err(|e| e.to_string())?;
+            Ok(port)
+        })
+        .await;
+    }
+
+    if let Some(port) = port {
+        let pinged = run_phase(&mut phases, "ping", async {
+            SidecarClient::request(port, "system.ping", serde_json::json!({}), Duration::from_secs(5))
+                .await
+                .map_err(|e| e.to_string())
+        })
+        .await;
+
+        if pinged.is_some() {
+            run_phase(&mut phases, "command", async {
+                SidecarClient::request(port, "system.info", serde_json::json!({}), Duration::from_secs(10))
+                    .await
+                    .map_err(|e| e.to_string())
+            })
+            .await;
+        }
+    }
+
+    // Always clean up, even if an earlier phase failed
+    run_phase(&mut phases, "close", async {
+        state.sidecar_manager
+            .terminate_sidecar(&diag_label)
+            .await
+            .map_err(|e| format!("Failed to terminate sidecar: {}", e))
+    })
+    .await;
+
+    // Every phase must have run and passed
+    let passed = phases.len() == 5 && phases.iter().all(|p| p.passed);
+
+    Ok(DiagnosticReport {
+        vault_path,
+        passed,
+        total_ms: started.elapsed().as_millis(),
+        phases,
+        timestamp: chrono::Utc::now().to_rfc3339(),
+    })
+}
+
+/// Get the current window's vault information
+#[tauri::command]
+pub async fn get_current_vault_info(
+    window: tauri::Window,
+    state: State<'_, AppState>,
+) -> Result<VaultInfo, CommandError> {
+    let window_label = window.label().to_string();
+    
+    // Get vault path
+    let (vault_path, disposable) = {
+        let window_manager = state.window_manager.lock().await;
+        let vault_path = window_manager
+            .get_vault_path(&window_label)
+            .ok_or_else(|| CommandError::NotFound("Vault not found for this window".to_string()))?
+            .clone();
+        (vault_path, window_manager.is_disposable(&window_label))
+    };
+    
+    // Get WebSocket port
+    let ws_port = state.sidecar_manager
+        .get_ws_port(&window_label)
+        .await
+        .ok_or_else(|| sidecar_not_found(&window_label))?;
+    
+    Ok(VaultInfo {
+        window_label,
+        vault_path,
+        ws_port,
+        plugin_updates: Vec::new(),
+        pending_migration: None,
+        incompatible_plugins: Vec::new(),
+        storage_warning: None,
+        disposable,
+    })
+}
+
+
+/// The `defaultVaultDir` setting, or `~/Documents/Tailor`
+fn default_vault_dir(app: &AppHandle, global_settings: &serde_json::Value) -> Result<PathBuf, CommandError> {
+    match global_settings.get(DEFAULT_VAULT_DIR_SETTING).and_then(|v| v.as_str()) {
+        Some(dir) if !dir.is_empty() => resolve_vault_path(DEFAULT_VAULT_DIR_SETTING, dir),
+        _ => Ok(app.path().document_dir()
+            .map_err(|e| CommandError::Internal(format!("Failed to get documents directory: {}", e)))?
+            .join("Tailor")),
+    }
+}
+
+/// List all known vaults: recently used ones first (most recent first),
+/// then any other vault found in the default vault directory. Recents
+/// whose directory is gone are dropped from the registry.
+#[tauri::command]
+pub async fn list_vaults(app: AppHandle) -> Result<Vec<VaultListItem>, CommandError> {
+    let config_dir = app_config_dir(&app)?;
+    let global_settings = settings::load_global_settings(&config_dir)
+        .map_err(|e| CommandError::Io(format!("Failed to load global settings: {}", e)))?;
+    let vaults_root = default_vault_dir(&app, &global_settings)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut seen = HashSet::new();
+        let recent = recents::load_existing(&config_dir).into_iter().map(|item| PathBuf::from(item.path));
+        recent
+            .chain(recents::discover(&vaults_root))
+            .filter(|path| seen.insert(fs::canonicalize(path).unwrap_or_else(|_| path.clone())))
+            .map(|path| vault_list_item(&path, &path.to_string_lossy()))
+            .collect()
+    })
+    .await
+    .map_err(|e| CommandError::Io(format!("Failed to list vaults: {}", e)))
+}
+
+/// Get vault information
+#[tauri::command]
+pub async fn get_vault_info(vault_path: String) -> Result<serde_json::Value, CommandError> {
+    let path = resolve_vault_path("vault_path", &vault_path)?;
+    let config_path = path.join(".vault.json");
+    
+    if !config_path.exists() {
+        return Err(CommandError::NotFound("Vault config file not found".to_string()));
+    }
+    
+    let contents = fs::read_to_string(&config_path)
+        .map_err(|e| CommandError::Io(format!("Failed to read vault config: {}", e)))?;
+    
+    let config: serde_json::Value = serde_json::from_str(&contents)
+        .map_err(|e| CommandError::Io(format!("Failed to parse vault config: {}", e)))?;
+    
+    Ok(config)
+}
+
+/// Update plugin configuration in .vault.json
+#[tauri::command]
+pub async fn update_plugin_config(
+    vault_path: String,
+    plugin_id: String,
+    config: serde_json::Value,
+) -> Result<(), CommandError> {
+    let path = resolve_vault_path("vault_path", &vault_path)?;
+    let config_path = path.join(".vault.json");
+    
+    // Read existing config
+    let contents = if config_path.exists() {
+        fs::read_to_string(&config_path)
+            .map_err(|e| CommandError::Io(format!("Failed to read vault config: {}", e)))?
+    } else {
+        r#"{"plugins": {}}"#.to_string()
+    };
+    
+    let mut vault_config: serde_json::Value = serde_json::from_str(&contents)
+        .map_err(|e| CommandError::Io(format!("Failed to parse vault config: {}", e)))?;
+    
+    // Ensure plugins object exists
+    if vault_config.get("plugins").is_none() {
+        vault_config["plugins"] = serde_json::json!({});
+    }
+    
+    // Get or create plugin config
+    if vault_config["plugins"].get(&plugin_id).is_none() {
+        vault_config["plugins"][&plugin_id] = serde_json::json!({});
+    }
+    
+    // Merge config values
+    if let Some(plugin_config) = vault_config["plugins"].get_mut(&plugin_id) {
+        if let serde_json::Value::Object(plugin_obj) = plugin_config {
+            if let serde_json::Value::Object(new_config) = config {
+                for (key, value) in new_config {
+                    plugin_obj.insert(key, value);
+                }
+            }
+        }
+    }
+    
+    // Write back
+    let updated = serde_json::to_string_pretty(&vault_config)
+        .map_err(|e| CommandError::Internal(format!("Failed to serialize config: {}", e)))?;
+    
+    fs::write(&config_path, updated)
+        .map_err(|e| CommandError::Io(format!("Failed to write vault config: {}", e)))?;
+    
+    println!("Updated plugin config for '{}' in {}", plugin_id, vault_path);
+    
+    Ok(())
+}
+
+/// Create a new vault
+#[tauri::command]
+pub async fn create_vault(
+    name: String,
+    path: String,
+    app: AppHandle,
+) -> Result<VaultListItem, CommandError> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err(CommandError::Validation("vault name must not be empty".to_string()));
+    }
+    let vault_path = resolve_vault_path("path", &path)?;
+    let created_iso = scaffold_vault(&vault_path, &name)?;
+    
+    println!("Created vault: {} at {}", name, vault_path.display());
+    
+    let vault_item = VaultListItem {
+        name,
+        path: vault_path.to_string_lossy().to_string(),
+        created: Some(created_iso),
+        id: artifact_scanner::vault_id(&vault_path),
+    };
+    
+    // Register vault in registry
+    register_vault_in_registry(&app, &vault_item).await?;
+    
+    Ok(vault_item)
+}
+
+/// Create a vault's directory layout and `.vault.json`, returning its
+/// creation timestamp. The vault is built in a hidden sibling directory
+/// and renamed into place, so a failure part way leaves nothing behind
+/// that looks like a vault. An existing empty directory is replaced.
+fn scaffold_vault(vault_path: &Path, name: &str) -> Result<String, CommandError> {
+    if vault_path.exists() {
+        let empty = fs::read_dir(vault_path)
+            .map(|mut entries| entries.next().is_none())
+            .unwrap_or(false);
+        if !empty {
+            return Err(CommandError::Validation(format!("Directory already exists and is not empty: {}", vault_path.display())));
+        }
+    }
+    let parent = vault_path.parent()
+        .ok_or_else(|| CommandError::Validation(format!("cannot create a vault at {}", vault_path.display())))?;
+    fs::create_dir_all(parent)
+        .map_err(|e| CommandError::Io(format!("Failed to create {}: {}", parent.display(), e)))?;
+
+    let dir_name = vault_path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let staging = parent.join(format!(".{}.creating-{}", dir_name, uuid::Uuid::new_v4().simple()));
+    let result = write_vault_layout(&staging, name).and_then(|created_iso| {
+        if vault_path.exists() {
+            fs::remove_dir(vault_path)
+                .map_err(|e| CommandError::Io(format!("Failed to replace empty directory {}: {}", vault_path.display(), e)))?;
+        }
+        fs::rename(&staging, vault_path)
+            .map_err(|e| CommandError::Io(format!("Failed to move vault into place: {}", e)))?;
+        Ok(created_iso)
+    });
+    if result.is_err() {
+        let _ = fs::remove_dir_all(&staging);
+    }
+    result
+}
+
+fn write_vault_layout(vault_path: &Path, name: &str) -> Result<String, CommandError> {
+    // Create vault directory
+    fs::create_dir_all(vault_path)
+        .map_err(|e| CommandError::Io(format!("Failed to create vault directory: {}", e)))?;
+    
+    // Create subdirectories
+    let plugins_dir = vault_path.join("plugins");
+    let lib_dir = vault_path.join("lib");
+    let memory_dir = vault_path.join(".memory");
+    let configs_dir = vault_path.join("configs");
+    let conversations_dir = conversations::conversations_dir(vault_path);
+    
+    fs::create_dir_all(&plugins_dir)
+        .map_err(|e| CommandError::Io(format!("Failed to create plugins directory: {}", e)))?;
+    fs::create_dir_all(&lib_dir)
+        .map_err(|e| CommandError::Io(format!("Failed to create lib directory: {}", e)))?;
+    fs::create_dir_all(&memory_dir)
+        .map_err(|e| CommandError::Io(format!("Failed to create memory directory: {}", e)))?;
+    fs::create_dir_all(&configs_dir)
+        .map_err(|e| CommandError::Io(format!("Failed to create configs directory: {}", e)))?;
+    fs::create_dir_all(&conversations_dir)
+        .map_err(|e| CommandError::Io(format!("Failed to create conversations directory: {}", e)))?;
     
-    // Merge config values
-    if let Some(plugin_config) = vault_config["plugins"].get_mut(&plugin_id) {
-        if let serde_json::Value::Object(plugin_obj) = plugin_config {
-            if let serde_json::Value::Object(new_config) = config {
-                for (key, value) in new_config {
-                    plugin_obj.insert(key, value);
-                }
-            }
-        }
+    // Create empty requirements.txt in plugins directory
+    let requirements_file = plugins_dir.join("requirements.txt");
+    if !requirements_file.exists() {
+        fs::write(&requirements_file, "# Shared plugin dependencies\n")
+            .map_err(|e| CommandError::Io(format!("Failed to create requirements.txt: {}", e)))?;
     }
     
-    // Write back
-    let updated = serde_json::to_string_pretty(&vault_config)
-        .map_err(|e| format!("Failed to serialize config: {}", e))?;
+    // Generate vault ID
+    let vault_id = format!("vault_{}", uuid::Uuid::new_v4().to_string().replace("-", ""));
     
-    fs::write(&config_path, updated)
-        .map_err(|e| format!("Failed to write vault config: {}", e))?;
+    // Get current timestamp in ISO 8601 format
+    let created_iso = chrono::Utc::now().to_rfc3339();
     
-    println!("Updated plugin config for '{}' in {}", plugin_id, vault_path);
+    // Create .vault.json file
+    let vault_config = serde_json::json!({
+        "id": vault_id,
+        "name": name,
+        "version": VAULT_CONFIG_VERSION,
+        "description": format!("Vault: {}", name),
+        "created": created_iso
+    });
     
+    let config_path = vault_path.join(".vault.json");
+    let config_json = serde_json::to_string_pretty(&vault_config)
+        .map_err(|e| CommandError::Internal(format!("Failed to serialize vault config: {}", e)))?;
+    
+    fs::write(&config_path, config_json)
+        .map_err(|e| CommandError::Io(format!("Failed to write vault config: {}", e)))?;
+    
+    Ok(created_iso)
+}
+
+/// Register a vault in the registry
+async fn register_vault_in_registry(
+    app: &AppHandle,
+    vault: &VaultListItem,
+) -> Result<(), CommandError> {
+    recents::add(&app_config_dir(app)?, vault)
+        .map_err(|e| CommandError::Io(format!("Failed to write registry: {}", e)))
+}
+
+/// Validate the recents file: drop malformed entries and vaults that no
+/// longer exist, canonicalize paths and remove duplicates
+#[tauri::command]
+pub async fn repair_recents(app: AppHandle) -> Result<RecentsRepair, CommandError> {
+    recents::repair(&app_config_dir(&app)?)
+        .map_err(|e| CommandError::Io(format!("Failed to repair recents: {}", e)))
+}
+
+/// The `.vault.json` ids of every vault in recents. Anything that leaves
+/// a vault's id unknown is an error, since its artifacts would otherwise be
+/// treated as orphans and deleted: an unreadable recents file, or a vault
+/// that is unreachable (e.g. on an unmounted drive) with no id recorded.
+fn known_vault_ids(app: &AppHandle) -> Result<HashSet<String>, CommandError> {
+    let vaults = recents::load_strict(&app_config_dir(app)?)
+        .map_err(|e| CommandError::Io(format!("Cannot tell which artifacts are orphaned: {:#}", e)))?;
+    let mut ids = HashSet::new();
+
+    for vault in vaults {
+        let vault_dir = PathBuf::from(&vault.path);
+        // Reachable when its config can be read, even if it has no usable id
+        let readable = fs::read_to_string(vault_dir.join(".vault.json"))
+            .is_ok_and(|contents| serde_json::from_str::<serde_json::Value>(&contents).is_ok());
+        if vault.id.is_none() && !readable {
+            return Err(CommandError::Io(format!(
+                "Cannot tell which artifacts are orphaned: vault {} is unavailable. \
+                 Reconnect it, or remove it from recents with repair_recents",
+                vault.path
+            )));
+        }
+        ids.extend(vault.id);
+        ids.extend(artifact_scanner::vault_id(&vault_dir));
+    }
+
+    Ok(ids)
+}
+
+/// Find per-vault venvs left behind by vaults that are no longer registered
+#[tauri::command]
+pub async fn find_orphaned_venvs(app: AppHandle) -> Result<Vec<OrphanedArtifact>, CommandError> {
+    let app_data_dir = app.path().app_data_dir()
+        .map_err(|e| CommandError::Internal(format!("Failed to get app data directory: {}", e)))?;
+    let known = known_vault_ids(&app)?;
+
+    ArtifactScanner::find_orphaned_venvs(&app_data_dir, &known)
+        .map_err(|e| CommandError::Io(format!("Failed to scan venvs: {}", e)))
+}
+
+/// Find per-vault caches left behind by vaults that are no longer registered
+#[tauri::command]
+pub async fn find_orphaned_caches(app: AppHandle) -> Result<Vec<OrphanedArtifact>, CommandError> {
+    let app_cache_dir = app.path().app_cache_dir()
+        .map_err(|e| CommandError::Internal(format!("Failed to get app cache directory: {}", e)))?;
+    let known = known_vault_ids(&app)?;
+
+    ArtifactScanner::find_orphaned_caches(&app_cache_dir, &known)
+        .map_err(|e| CommandError::Io(format!("Failed to scan caches: {}", e)))
+}
+
+/// Remove orphaned artifacts by id, reporting reclaimed disk space
+#[tauri::command]
+pub async fn clean_orphaned_artifacts(
+    app: AppHandle,
+    ids: Vec<String>,
+) -> Result<CleanupReport, CommandError> {
+    // Re-scan rather than trusting the ids blindly
+    let mut orphans = find_orphaned_venvs(app.clone()).await?;
+    orphans.extend(find_orphaned_caches(app).await?);
+
+    let report = ArtifactScanner::clean(&ids, &orphans);
+    println!(
+        "Cleaned {} orphaned artifacts, reclaimed {} bytes",
+        report.removed.len(),
+        report.reclaimed_bytes
+    );
+
+    Ok(report)
+}
+
+/// Disk used by every registered vault (content, venvs, caches, backups,
+/// trash, logs) and by the app's own caches, logs and data, with pruning
+/// suggestions. Emits `disk-usage://warning` when usage nears `maxTotalDiskBytes`.
+#[tauri::command]
+pub async fn get_total_tailor_disk_usage(app: AppHandle) -> Result<TotalDiskUsage, CommandError> {
+    tauri::async_runtime::spawn_blocking(move || disk_usage::check(&app))
+        .await
+        .map_err(|e| CommandError::Internal(format!("Disk usage task failed: {}", e)))?
+}
+
+/// Upgrade a legacy vault layout in place.
+///
+/// With `dry_run` the planned changes are reported without touching disk.
+#[tauri::command]
+pub async fn migrate_vault(vault_path: String, dry_run: Option<bool>) -> Result<MigrationReport, CommandError> {
+    VaultMigrator::migrate(&resolve_vault_path("vault_path", &vault_path)?, dry_run.unwrap_or(false))
+        .map_err(|e| CommandError::Io(format!("Failed to migrate vault: {}", e)))
+}
+
+/// What the vault's plugins require and which of those its `lib/` lacks,
+/// without installing anything
+#[tauri::command]
+pub async fn check_dependencies(vault_path: String) -> Result<DependencyStatus, CommandError> {
+    let vault = resolve_vault_path("vault_path", &vault_path)?;
+    if !vault.is_dir() {
+        return Err(CommandError::NotFound(format!("Vault directory not found: {}", vault_path)));
+    }
+    tauri::async_runtime::spawn_blocking(move || DependencyChecker::check(&vault))
+        .await
+        .map_err(|e| CommandError::DependencyFailed(format!("Dependency check failed: {}", e)))
+}
+
+/// Search plugins in the community store. The registry index is cached
+/// for a while between searches; if it can't be fetched the cached copy is
+/// searched and each result is marked `stale: true`.
+#[tauri::command]
+pub async fn search_plugins(
+    app: AppHandle,
+    query: String,
+    category: Option<String>,
+) -> Result<Vec<serde_json::Value>, CommandError> {
+    let index = load_registry_index(&app, false).await?;
+    Ok(registry::search(&index, &query, category.as_deref()))
+}
+
+/// Fetch the plugin registry index through the mirror failover list and cache it
+#[tauri::command]
+pub async fn refresh_registry(app: AppHandle) -> Result<registry::RegistryIndex, CommandError> {
+    load_registry_index(&app, true).await
+}
+
+async fn load_registry_index(app: &AppHandle, force: bool) -> Result<registry::RegistryIndex, CommandError> {
+    let data_dir = app.path().app_data_dir()
+        .map_err(|e| CommandError::Internal(format!("Failed to get app data dir: {}", e)))?;
+    registry::load_index(&app_config_dir(app)?, &data_dir, force)
+        .await
+        .map_err(|e| CommandError::Network(format!("Failed to fetch plugin registry: {}", e)))
+}
+
+/// Time each registry mirror. With `auto_select`, the fastest reachable one
+/// becomes the mirror tried first by `search_plugins` and `refresh_registry`.
+#[tauri::command]
+pub async fn benchmark_registry_mirrors(
+    app: AppHandle,
+    auto_select: Option<bool>,
+) -> Result<Vec<registry::MirrorLatency>, CommandError> {
+    registry::benchmark_and_select(&app_config_dir(&app)?, auto_select.unwrap_or(false))
+        .await
+        .map_err(|e| CommandError::Internal(format!("Failed to benchmark registry mirrors: {}", e)))
+}
+
+/// Full registry record for a plugin, from the same cached index as `search_plugins`
+#[tauri::command]
+pub async fn get_plugin_details(app: AppHandle, plugin_id: String) -> Result<serde_json::Value, CommandError> {
+    let index = load_registry_index(&app, false).await?;
+    registry::find_plugin(&index, &plugin_id)
+        .ok_or_else(|| CommandError::NotFound(format!("plugin '{}' is not in the registry", plugin_id)))
+}
+
+/// Install a plugin into a vault by shallow-cloning `plugin_repo` into
+/// `plugins/{plugin_name}`. An existing plugin is only replaced with
+/// `update`. See `plugin_installer::install_from_git` for the error variants.
+#[tauri::command]
+pub async fn install_plugin(
+    vault_path: String,
+    plugin_repo: String,
+    plugin_name: String,
+    update: Option<bool>,
+) -> Result<plugins::InstalledPlugin, CommandError> {
+    let vault = resolve_vault_path("vault_path", &vault_path)?;
+    if !vault.is_dir() {
+        return Err(CommandError::NotFound(format!("Vault directory not found: {}", vault_path)));
+    }
+    if !plugins::is_valid_plugin_name(&plugin_name) {
+        return Err(CommandError::Validation(format!("invalid plugin name {:?}", plugin_name)));
+    }
+
+    let dir = plugin_installer::install_from_git(&vault, plugin_repo.trim(), &plugin_name, update.unwrap_or(false)).await?;
+    println!("Installed plugin '{}' into {}", plugin_name, vault_path);
+    Ok(plugins::installed_plugins(&vault)
+        .into_iter()
+        .find(|plugin| plugin.name == plugin_name)
+        .unwrap_or_else(|| plugins::InstalledPlugin::new(
+            plugin_name,
+            &dir,
+            plugins::PluginScope::Local,
+            false,
+            &plugins::read_vault_config(&vault),
+        )))
+}
+
+/// One plugin for `queue_plugin_installs`; a `download_url` (zip archive)
+/// is preferred over cloning `repo_url`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginInstallRequest {
+    pub plugin_id: String,
+    #[serde(default)]
+    pub repo_url: Option<String>,
+    #[serde(default)]
+    pub download_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PluginInstallOutcome {
+    pub plugin_id: String,
+    pub success: bool,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PluginInstallQueue {
+    pub queue_id: String,
+    pub vault_path: String,
+    pub plugins: Vec<String>,
+}
+
+/// Limit for a single queued install, dependencies included
+const QUEUED_INSTALL_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Install several plugins into a vault one after another, as a single
+/// `TaskManager` task so nothing else rewrites the vault meanwhile (it waits
+/// for a task already running there). Returns once queued; progress arrives
+/// as `plugin-install://progress` per plugin and `plugin-install://done`
+/// with the succeeded and failed plugins. A failed install is reported and
+/// the queue moves on. Installs run in the sidecar of the vault's window,
+/// so the vault must be open.
+#[tauri::command]
+pub async fn queue_plugin_installs(
+    app: AppHandle,
+    vault_path: String,
+    plugins: Vec<PluginInstallRequest>,
+    state: State<'_, AppState>,
+) -> Result<PluginInstallQueue, CommandError> {
+    let vault = resolve_vault_path("vault_path", &vault_path)?;
+    if plugins.is_empty() {
+        return Err(CommandError::Validation("plugins must not be empty".to_string()));
+    }
+    if let Some(bad) = plugins.iter().find(|p| !plugins::is_valid_plugin_name(&p.plugin_id)) {
+        return Err(CommandError::Validation(format!("invalid plugin name {:?}", bad.plugin_id)));
+    }
+    if let Some(bad) = plugins.iter().find(|p| p.repo_url.is_none() && p.download_url.is_none()) {
+        return Err(CommandError::Validation(format!("plugin '{}' needs a repo_url or download_url", bad.plugin_id)));
+    }
+    if state.window_manager.lock().await.find_window_by_vault(&vault).is_none() {
+        return Err(CommandError::SidecarUnavailable(format!("Vault is not open: {}", vault_path)));
+    }
+
+    let queue = PluginInstallQueue {
+        queue_id: format!("install_{}", uuid::Uuid::new_v4()),
+        vault_path: vault_path.clone(),
+        plugins: plugins.iter().map(|p| p.plugin_id.clone()).collect(),
+    };
+    println!("Queued {} plugin install(s) for {}", plugins.len(), vault_path);
+
+    let queue_id = queue.queue_id.clone();
+    let task_manager = state.task_manager.clone();
+    tauri::async_runtime::spawn(async move {
+        let work = run_plugin_installs(&app, &queue_id, &vault, &plugins);
+        let record = task_manager.run_queued("plugin_install_queue", &vault_path, work).await;
+        if let Some(error) = record.error {
+            eprintln!("Plugin install queue {} failed: {}", queue_id, error);
+        }
+    });
+
+    Ok(queue)
+}
+
+async fn run_plugin_installs(
+    app: &AppHandle,
+    queue_id: &str,
+    vault: &Path,
+    plugins: &[PluginInstallRequest],
+) -> Result<String> {
+    let state = app.state::<AppState>();
+    let total = plugins.len();
+    let mut outcomes = Vec::with_capacity(total);
+
+    for (index, plugin) in plugins.iter().enumerate() {
+        let _ = app.emit("plugin-install://progress", serde_json::json!({
+            "queue_id": queue_id,
+            "plugin_id": plugin.plugin_id,
+            "index": index,
+            "total": total,
+            "status": "installing",
+        }));
+
+        // Looked up per plugin: the window may have closed or reopened meanwhile
+        let window_label = state.window_manager.lock().await.find_window_by_vault(vault);
+        let result = match window_label {
+            Some(label) => sidecar_request_with_timeout(&state, &label, "plugins.install", serde_json::json!({
+                "plugin_id": plugin.plugin_id,
+                "repo_url": plugin.repo_url.clone().unwrap_or_default(),
+                "download_url": plugin.download_url.clone().unwrap_or_default(),
+            }), QUEUED_INSTALL_TIMEOUT).await,
+            None => Err(CommandError::SidecarUnavailable("Vault was closed".to_string())),
+        };
+        let outcome = match result {
+            Ok(reply) => {
+                let status = reply.get("status").and_then(|s| s.as_str()).unwrap_or("error");
+                let message = reply.get("message")
+                    .or_else(|| reply.get("error"))
+                    .and_then(|m| m.as_str())
+                    .unwrap_or(status)
+                    .to_string();
+                PluginInstallOutcome { plugin_id: plugin.plugin_id.clone(), success: status == "success", message }
+            }
+            Err(e) => PluginInstallOutcome { plugin_id: plugin.plugin_id.clone(), success: false, message: e.to_string() },
+        };
+        if !outcome.success {
+            eprintln!("Warning: Installing plugin '{}' failed: {}", outcome.plugin_id, outcome.message);
+        }
+
+        let _ = app.emit("plugin-install://progress", serde_json::json!({
+            "queue_id": queue_id,
+            "plugin_id": plugin.plugin_id,
+            "index": index,
+            "total": total,
+            "status": if outcome.success { "installed" } else { "failed" },
+            "message": outcome.message,
+        }));
+        outcomes.push(outcome);
+    }
+
+    let (succeeded, failed): (Vec<_>, Vec<_>) = outcomes.into_iter().partition(|o| o.success);
+    let _ = app.emit("plugin-install://done", serde_json::json!({
+        "queue_id": queue_id,
+        "vault_path": vault.to_string_lossy(),
+        "succeeded": succeeded,
+        "failed": failed,
+    }));
+    Ok(format!("{} installed, {} failed", succeeded.len(), failed.len()))
+}
+
+/// Get installed plugins for a vault: its own plugins and the shared ones
+/// it lists in `sharedPlugins`, each marked with its `scope` and described
+/// by its manifest. A plugin without a usable manifest is still listed,
+/// with `valid: false` and the reason.
+#[tauri::command]
+pub async fn get_installed_plugins(vault_path: String) -> Result<Vec<plugins::InstalledPlugin>, CommandError> {
+    Ok(plugins::installed_plugins(&resolve_vault_path("vault_path", &vault_path)?))
+}
+
+/// Copy a plugin folder into the shared plugins directory, where any vault
+/// can use it through `link_shared_plugin`. With `replace`, an existing
+/// shared plugin of that name is swapped out, updating it for every vault
+/// that links it (open sidecars pick it up when they next load plugins).
+#[tauri::command]
+pub async fn install_shared_plugin(
+    source_path: String,
+    plugin_name: String,
+    replace: Option<bool>,
+) -> Result<plugins::InstalledPlugin, CommandError> {
+    let source = resolve_vault_path("source_path", &source_path)?;
+    if !plugins::is_valid_plugin_name(&plugin_name) {
+        return Err(CommandError::Validation(format!("invalid plugin name {:?}", plugin_name)));
+    }
+    if !source.join(plugins::PLUGIN_MAIN_FILE).is_file() {
+        return Err(CommandError::Validation(format!("{} has no {}", source.display(), plugins::PLUGIN_MAIN_FILE)));
+    }
+    let root = plugins::shared_plugins_root()
+        .ok_or_else(|| CommandError::Internal("Shared plugins directory is not configured".to_string()))?;
+    let target = root.join(&plugin_name);
+    if target.exists() && !replace.unwrap_or(false) {
+        return Err(CommandError::Validation(format!(
+            "shared plugin '{}' already exists; pass replace to update it",
+            plugin_name
+        )));
+    }
+
+    // Stage the copy next to the target so the swap is a pair of renames
+    let id = uuid::Uuid::new_v4();
+    let staging = root.join(format!(".{}.{}.tmp", plugin_name, id));
+    crate::fs_utils::copy_dir_recursive(&source, &staging)
+        .map_err(|e| {
+            let _ = fs::remove_dir_all(&staging);
+            CommandError::Io(format!("Failed to copy plugin: {}", e))
+        })?;
+    let previous = root.join(format!(".{}.{}.old", plugin_name, id));
+    if target.exists() {
+        fs::rename(&target, &previous).map_err(|e| {
+            let _ = fs::remove_dir_all(&staging);
+            CommandError::Io(format!("Failed to replace shared plugin '{}': {}", plugin_name, e))
+        })?;
+    }
+    if let Err(e) = fs::rename(&staging, &target) {
+        let _ = fs::rename(&previous, &target);
+        let _ = fs::remove_dir_all(&staging);
+        return Err(CommandError::Io(format!("Failed to install shared plugin '{}': {}", plugin_name, e)));
+    }
+    let _ = fs::remove_dir_all(&previous);
+
+    println!("Installed shared plugin '{}' from {}", plugin_name, source.display());
+    // Not tied to a vault, so `enabled` is the plugin's own default
+    Ok(plugins::InstalledPlugin::new(
+        plugin_name,
+        &target,
+        plugins::PluginScope::Shared,
+        false,
+        &serde_json::json!({}),
+    ))
+}
+
+/// Make a shared plugin available to a vault by adding it to the vault's
+/// `sharedPlugins` list. A local plugin of the same name still takes
+/// precedence; it is reported with `overrides_shared`. Returns the vault's
+/// plugins afterwards.
+#[tauri::command]
+pub async fn link_shared_plugin(
+    vault_path: String,
+    plugin_name: String,
+) -> Result<Vec<plugins::InstalledPlugin>, CommandError> {
+    let vault = resolve_vault_path("vault_path", &vault_path)?;
+    if !plugins::is_valid_plugin_name(&plugin_name) {
+        return Err(CommandError::Validation(format!("invalid plugin name {:?}", plugin_name)));
+    }
+    let root = plugins::shared_plugins_root()
+        .ok_or_else(|| CommandError::Internal("Shared plugins directory is not configured".to_string()))?;
+    if !root.join(&plugin_name).join(plugins::PLUGIN_MAIN_FILE).is_file() {
+        return Err(CommandError::Validation(format!("no shared plugin named '{}'", plugin_name)));
+    }
+
+    let mut vault_config = plugins::read_vault_config(&vault);
+    if !vault_config.is_object() {
+        return Err(CommandError::Validation("Vault config is not a JSON object".to_string()));
+    }
+    let mut names = plugins::shared_plugin_names(&vault_config);
+    if !names.contains(&plugin_name) {
+        names.push(plugin_name.clone());
+        vault_config[plugins::SHARED_PLUGINS_SETTING] = serde_json::json!(names);
+        let updated = serde_json::to_string_pretty(&vault_config)
+            .map_err(|e| CommandError::Internal(format!("Failed to serialize config: {}", e)))?;
+        atomic_write(&vault.join(".vault.json"), updated.as_bytes())
+            .map_err(|e| CommandError::Io(format!("Failed to write vault config: {}", e)))?;
+        println!("Linked shared plugin '{}' into {}", plugin_name, vault.display());
+    }
+
+    Ok(plugins::installed_plugins(&vault))
+}
+
+/// Get the stored global settings merged over the defaults
+#[tauri::command]
+pub async fn get_global_settings(app: AppHandle) -> Result<serde_json::Value, CommandError> {
+    settings::load_global_settings(&app_config_dir(&app)?)
+        .map_err(|e| CommandError::Io(format!("Failed to load global settings: {}", e)))
+}
+
+/// Merge settings into the global settings. Known keys are validated first
+/// and nothing is written if any is invalid.
+#[tauri::command]
+pub async fn save_global_settings(app: AppHandle, settings: serde_json::Value) -> Result<(), CommandError> {
+    settings::validate_global_settings(&settings)
+        .map_err(|e| CommandError::Validation(e.to_string()))?;
+    println!("Saving global settings: {:?}", settings);
+    let saved = settings::save_global_settings(&app_config_dir(&app)?, &settings)
+        .map_err(|e| CommandError::Io(format!("Failed to save global settings: {}", e)))?;
+    app.state::<AppState>().audit.set_enabled(settings::audit_log_enabled(&saved));
     Ok(())
 }
 
-/// Create a new vault
+fn app_config_dir(app: &AppHandle) -> Result<PathBuf, CommandError> {
+    app.path().app_config_dir()
+        .map_err(|e| CommandError::Internal(format!("Failed to get app config directory: {}", e)))
+}
+
+/// The app-wide cap on concurrent provider requests and how much of it is in use
+#[tauri::command]
+pub async fn get_request_budget(state: State<'_, AppState>) -> Result<RequestBudgetStatus, CommandError> {
+    Ok(state.request_budget.status(&state.sidecar_manager).await)
+}
+
+/// Cap concurrent provider requests across all windows at `limit`, or lift
+/// the cap with None. Saved to global settings and applied immediately.
+#[tauri::command]
+pub async fn set_request_budget(
+    app: AppHandle,
+    limit: Option<usize>,
+    state: State<'_, AppState>,
+) -> Result<RequestBudgetStatus, CommandError> {
+    if limit == Some(0) {
+        return Err(CommandError::Validation("limit must be at least 1".to_string()));
+    }
+    settings::save_global_settings(
+        &app_config_dir(&app)?,
+        &serde_json::json!({ REQUEST_BUDGET_SETTING: limit }),
+    )
+    .map_err(|e| CommandError::Io(format!("Failed to save request budget: {}", e)))?;
+
+    state.request_budget.set_limit(limit);
+    println!("Request budget set to {:?}", limit);
+    Ok(state.request_budget.status(&state.sidecar_manager).await)
+}
+
+/// Snapshot the current global settings (minus secrets) as a named profile
+#[tauri::command]
+pub async fn save_settings_profile(app: AppHandle, name: String) -> Result<SettingsProfile, CommandError> {
+    let config_dir = app_config_dir(&app)?;
+    let current = settings::load_global_settings(&config_dir)
+        .map_err(|e| CommandError::Io(format!("Failed to load global settings: {}", e)))?;
+
+    settings_profiles::save_profile(&config_dir, &name, &current)
+        .map_err(|e| CommandError::Io(format!("Failed to save settings profile: {}", e)))
+}
+
+/// List saved settings profiles
+#[tauri::command]
+pub async fn list_settings_profiles(app: AppHandle) -> Result<Vec<SettingsProfile>, CommandError> {
+    settings_profiles::list_profiles(&app_config_dir(&app)?)
+        .map_err(|e| CommandError::Io(format!("Failed to list settings profiles: {}", e)))
+}
+
+/// Swap a profile's preferences into the global settings and tell every
+/// window via `settings://global-changed`
+#[tauri::command]
+pub async fn apply_settings_profile(app: AppHandle, name: String) -> Result<serde_json::Value, CommandError> {
+    let config_dir = app_config_dir(&app)?;
+    let profile = settings_profiles::get_profile(&config_dir, &name)
+        .map_err(|e| CommandError::NotFound(e.to_string()))?;
+
+    settings_profiles::check_interpreter(&profile.settings)
+        .map_err(|e| CommandError::Validation(format!("Cannot apply profile '{}': {}", name, e)))?;
+
+    let applied = settings::save_global_settings(&config_dir, &profile.settings)
+        .map_err(|e| CommandError::Io(format!("Failed to save global settings: {}", e)))?;
+    app.state::<AppState>().audit.set_enabled(settings::audit_log_enabled(&applied));
+
+    let _ = app.emit("settings://global-changed", serde_json::json!({
+        "profile": profile.name,
+        "settings": applied,
+    }));
+    println!("Applied settings profile '{}'", profile.name);
+
+    Ok(applied)
+}
+
+/// Search the audit log (newest first). Empty unless `auditLog` is or was on.
+#[tauri::command]
+pub async fn query_audit_log(
+    filters: Option<AuditQuery>,
+    state: State<'_, AppState>,
+) -> Result<Vec<AuditEntry>, CommandError> {
+    let audit = state.audit.clone();
+    let query = filters.unwrap_or_default();
+    tauri::async_runtime::spawn_blocking(move || audit.query(&query))
+        .await
+        .map_err(|e| CommandError::Internal(format!("Audit log query failed: {}", e)))
+}
+
+/// Delete every audit log entry; returns how many were removed
+#[tauri::command]
+pub async fn clear_audit_log(state: State<'_, AppState>) -> Result<usize, CommandError> {
+    let removed = state.audit.clear()
+        .map_err(|e| CommandError::Io(format!("Failed to clear audit log: {}", e)))?;
+    println!("Cleared {} audit log entries", removed);
+    Ok(removed)
+}
+
+/// Get vault settings from `.vault-settings.json`, over the defaults.
+/// A corrupt file is an error rather than a silent reset.
+#[tauri::command]
+pub async fn get_vault_settings(vault_path: String) -> Result<serde_json::Value, CommandError> {
+    settings::load_vault_settings_with_defaults(&resolve_vault_path("vault_path", &vault_path)?)
+        .map_err(|e| CommandError::Io(format!("Failed to load vault settings: {:#}", e)))
+}
+
+/// Merge a (possibly partial) settings object into the vault's settings;
+/// returns the merged settings, over the defaults
+#[tauri::command]
+pub async fn save_vault_settings(vault_path: String, settings: serde_json::Value) -> Result<serde_json::Value, CommandError> {
+    let vault = resolve_vault_path("vault_path", &vault_path)?;
+    if !settings.is_object() {
+        return Err(CommandError::Validation("settings must be a JSON object".to_string()));
+    }
+    println!("Saving vault settings for {}", vault_path);
+    settings::save_vault_settings(&vault, &settings)
+        .map_err(|e| CommandError::Io(format!("Failed to save vault settings: {:#}", e)))?;
+    settings::load_vault_settings_with_defaults(&vault)
+        .map_err(|e| CommandError::Io(format!("Failed to load vault settings: {:#}", e)))
+}
+
+/// Run `task` against the API key store off the async runtime, since
+/// keychain calls block
+async fn with_key_store<T, F>(app: &AppHandle, task: F) -> Result<T, CommandError>
+where
+    T: Send + 'static,
+    F: FnOnce(api_keys::ApiKeyStore) -> anyhow::Result<T> + Send + 'static,
+{
+    let store = api_keys::ApiKeyStore::new(&app_config_dir(app)?);
+    tauri::async_runtime::spawn_blocking(move || task(store))
+        .await
+        .map_err(|e| CommandError::Internal(format!("API key task failed: {}", e)))?
+        .map_err(|e| CommandError::Io(format!("API key store error: {}", e)))
+}
+
+/// Names of the stored API keys (never their values), and where new keys go
+#[tauri::command]
+pub async fn get_api_keys(app: AppHandle) -> Result<serde_json::Value, CommandError> {
+    with_key_store(&app, |store| {
+        let names = store.names(&api_keys::known_providers())?;
+        Ok(serde_json::json!({
+            "keys": names,
+            "storage": store.storage(),
+        }))
+    }).await
+}
+
+/// The stored secret for `key_name`, for building sidecar requests
+#[tauri::command]
+pub async fn get_api_key(app: AppHandle, key_name: String) -> Result<serde_json::Value, CommandError> {
+    let name = key_name.clone();
+    let (value, storage) = with_key_store(&app, move |store| store.get(&name))
+        .await?
+        .ok_or_else(|| CommandError::NotFound(format!("no API key stored for '{}'", key_name)))?;
+    Ok(serde_json::json!({
+        "key_name": key_name,
+        "key_value": value,
+        "storage": storage,
+    }))
+}
+
+/// Save API key
+///
+/// The key is trimmed and checked against the provider's expected format
+/// first; a suspicious format is returned as `warning` rather than rejected.
+/// `storage` says whether it went to the OS keychain or the encrypted file.
+#[tauri::command]
+pub async fn save_api_key(app: AppHandle, key_name: String, key_value: String) -> Result<serde_json::Value, CommandError> {
+    let validation = api_keys::validate_api_key(&key_name, &key_value)
+        .map_err(CommandError::Validation)?;
+    let name = key_name.clone();
+    let key = validation.key;
+    let storage = with_key_store(&app, move |store| store.save(&name, &key)).await?;
+    Ok(serde_json::json!({
+        "key_name": key_name,
+        "warning": validation.warning,
+        "storage": storage,
+    }))
+}
+
+/// Delete API key from wherever it is stored; false if there was none
+#[tauri::command]
+pub async fn delete_api_key(app: AppHandle, key_name: String) -> Result<bool, CommandError> {
+    with_key_store(&app, move |store| store.delete(&key_name)).await
+}
+
+/// Export the keychain-held API keys as OS-native items where the platform
+/// has an export format (a password-protected keychain file on macOS),
+/// otherwise as an encrypted blob. The result's `note` says which was used.
+#[tauri::command]
+pub async fn export_secrets_to_os_format(
+    window_label: String,
+    path: String,
+    passphrase: String,
+    format: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<serde_json::Value, CommandError> {
+    let path = resolve_vault_path("path", &path)?;
+    sidecar_request(&state, &window_label, "settings.export_secrets", serde_json::json!({
+        "path": path,
+        "passphrase": passphrase,
+        "format": format.unwrap_or_else(|| "auto".to_string()),
+    })).await
+}
+
+/// Import API keys from a file written by `export_secrets_to_os_format`
+#[tauri::command]
+pub async fn import_secrets_from_os_format(
+    window_label: String,
+    path: String,
+    passphrase: String,
+    state: State<'_, AppState>,
+) -> Result<serde_json::Value, CommandError> {
+    let path = resolve_vault_path("path", &path)?;
+    sidecar_request(&state, &window_label, "settings.import_secrets", serde_json::json!({
+        "path": path,
+        "passphrase": passphrase,
+    })).await
+}
+
+/// Search a vault's conversations by title and message text. `filters`
+/// takes `since`/`until` (RFC 3339), `model` and `limit`; results are
+/// summaries with a snippet of the match, not full conversations.
+#[tauri::command]
+pub async fn search_conversations(
+    vault_path: String,
+    query: String,
+    filters: Option<conversation_index::SearchFilters>,
+) -> Result<Vec<conversation_index::SearchHit>, CommandError> {
+    let vault = resolve_vault_path("vault_path", &vault_path)?;
+    let filters = filters.unwrap_or_default();
+    tauri::async_runtime::spawn_blocking(move || conversation_index::search(&vault, &query, &filters))
+        .await
+        .map_err(|e| CommandError::Internal(format!("Conversation search failed: {}", e)))?
+        .map_err(|e| CommandError::Internal(format!("Conversation search failed: {}", e)))
+}
+
+/// Get conversation details, including the effective system prompt and
+/// whether it comes from the conversation or the vault default
+#[tauri::command]
+pub async fn get_conversation(vault_path: String, conversation_id: String) -> Result<serde_json::Value, CommandError> {
+    let vault = resolve_vault_path("vault_path", &vault_path)?;
+    let conversation = conversations::load_conversation(&vault, &conversation_id)
+        .map_err(|e| CommandError::Io(format!("Failed to load conversation: {}", e)))?;
+    let effective = conversations::effective_system_prompt(&plugins::read_vault_config(&vault), &conversation);
+
+    let mut value = serde_json::to_value(&conversation)
+        .map_err(|e| CommandError::Internal(format!("Failed to serialize conversation: {}", e)))?;
+    value["effective_system_prompt"] = serde_json::json!(effective);
+    Ok(value)
+}
+
+/// Give a conversation its own system prompt. An empty or missing `prompt`
+/// clears it so the vault default applies again.
+#[tauri::command]
+pub async fn set_conversation_system_prompt(
+    vault_path: String,
+    conversation_id: String,
+    prompt: Option<String>,
+) -> Result<conversations::EffectiveSystemPrompt, CommandError> {
+    let vault = resolve_vault_path("vault_path", &vault_path)?;
+    let prompt = prompt.filter(|p| !p.trim().is_empty());
+
+    if let Some(prompt) = &prompt {
+        let max = settings::load_vault_settings(&vault)
+            .ok()
+            .and_then(|s| s.get(settings::MAX_SYSTEM_PROMPT_LENGTH_SETTING).and_then(|v| v.as_u64()))
+            .map(|max| max as usize)
+            .unwrap_or(conversations::DEFAULT_MAX_SYSTEM_PROMPT_CHARS);
+        let length = prompt.chars().count();
+        if length > max {
+            return Err(CommandError::Validation(format!("prompt is {} characters; the limit is {}", length, max)));
+        }
+    }
+
+    let conversation = conversations::set_system_prompt(&vault, &conversation_id, prompt.as_deref())
+        .map_err(|e| CommandError::Internal(format!("Failed to set system prompt: {}", e)))?;
+    conversation_index::record_write(&vault, &conversation);
+    Ok(conversations::effective_system_prompt(&plugins::read_vault_config(&vault), &conversation))
+}
+
+/// Delete a conversation's file, failing with `NotFound` if there is none
 #[tauri::command]
-pub async fn create_vault(
-    name: String,
-    path: String,
-    app: AppHandle,
-) -> Result<VaultListItem, String> {
-    // Validate that path is provided
-    if path.is_empty() {
-        return Err("Vault path is required".to_string());
+pub async fn delete_conversation(vault_path: String, conversation_id: String) -> Result<(), CommandError> {
+    let vault = resolve_vault_path("vault_path", &vault_path)?;
+    conversations::conversation_path(&vault, &conversation_id)
+        .map_err(|e| CommandError::Validation(e.to_string()))?;
+    let deleted = conversations::delete_conversation(&vault, &conversation_id)
+        .map_err(|e| CommandError::Io(format!("Failed to delete conversation: {}", e)))?;
+    if !deleted {
+        return Err(CommandError::NotFound(format!("conversation '{}' does not exist", conversation_id)));
     }
-    
-    let vault_path = PathBuf::from(&path);
-    
-    // Check if vault already exists
-    if vault_path.exists() {
-        return Err(format!("Directory already exists: {}", path));
-    }
-    
-    // Create vault directory
-    fs::create_dir_all(&vault_path)
-        .map_err(|e| format!("Failed to create vault directory: {}", e))?;
-    
-    // Create subdirectories
-    let plugins_dir = vault_path.join("plugins");
-    let lib_dir = vault_path.join("lib");
-    let memory_dir = vault_path.join(".memory");
-    let configs_dir = vault_path.join("configs");
-    
-    fs::create_dir_all(&plugins_dir)
-        .map_err(|e| format!("Failed to create plugins directory: {}", e))?;
-    fs::create_dir_all(&lib_dir)
-        .map_err(|e| format!("Failed to create lib directory: {}", e))?;
-    fs::create_dir_all(&memory_dir)
-        .map_err(|e| format!("Failed to create memory directory: {}", e))?;
-    fs::create_dir_all(&configs_dir)
-        .map_err(|e| format!("Failed to create configs directory: {}", e))?;
-    
-    // Create empty requirements.txt in plugins directory
-    let requirements_file = plugins_dir.join("requirements.txt");
-    if !requirements_file.exists() {
-        fs::write(&requirements_file, "# Shared plugin dependencies\n")
-            .map_err(|e| format!("Failed to create requirements.txt: {}", e))?;
+    conversation_index::record_delete(&vault, &conversation_id);
+    println!("Deleted conversation {} from {}", conversation_id, vault_path);
+    Ok(())
+}
+
+/// List conversation summaries; entries sharing an id are flagged `duplicate`
+#[tauri::command]
+pub async fn list_conversations(vault_path: String) -> Result<Vec<conversations::ConversationSummary>, CommandError> {
+    Ok(conversations::list_conversations(&resolve_vault_path("vault_path", &vault_path)?))
+}
+
+/// Create (or import) a conversation, rejecting an id that is already in use.
+/// Its model id is normalized when the vault is open in a window.
+#[tauri::command]
+pub async fn create_conversation(
+    vault_path: String,
+    conversation: serde_json::Value,
+    state: State<'_, AppState>,
+) -> Result<conversations::Conversation, CommandError> {
+    let mut conversation: conversations::Conversation = serde_json::from_value(conversation)
+        .map_err(|e| CommandError::Validation(format!("Invalid conversation: {}", e)))?;
+
+    let vault = resolve_vault_path("vault_path", &vault_path)?;
+    if let Some(model) = conversation.model.take() {
+        conversation.model = Some(canonical_model_for_vault(&state, &vault, model).await?);
     }
-    
-    // Generate vault ID
-    let vault_id = format!("vault_{}", uuid::Uuid::new_v4().to_string().replace("-", ""));
-    
-    // Get current timestamp in ISO 8601 format
-    let created_iso = chrono::Utc::now().to_rfc3339();
-    
-    // Create .vault.json file
-    let vault_config = serde_json::json!({
-        "id": vault_id,
-        "name": name,
-        "version": "1.0.0",
-        "description": format!("Vault: {}", name),
-        "created": created_iso
-    });
-    
-    let config_path = vault_path.join(".vault.json");
-    let config_json = serde_json::to_string_pretty(&vault_config)
-        .map_err(|e| format!("Failed to serialize vault config: {}", e))?;
-    
-    fs::write(&config_path, config_json)
-        .map_err(|e| format!("Failed to write vault config: {}", e))?;
-    
-    println!("Created vault: {} at {}", name, path);
-    
-    let vault_item = VaultListItem {
-        name: name.clone(),
-        path: path.clone(),
-        created: Some(created_iso.clone()),
-    };
-    
-    // Register vault in registry
-    register_vault_in_registry(&app, &vault_item).await?;
-    
-    Ok(vault_item)
+    let conversation = conversations::create_conversation(&vault, conversation)
+        .map_err(|e| CommandError::Io(format!("Failed to create conversation: {}", e)))?;
+    conversation_index::record_write(&vault, &conversation);
+    Ok(conversation)
 }
 
-/// Register a vault in the registry
-async fn register_vault_in_registry(
-    app: &AppHandle,
-    vault: &VaultListItem,
-) -> Result<(), String> {
-    // Get app data directory
-    let app_data_dir = app.path().app_data_dir()
-        .map_err(|e| format!("Failed to get app data directory: {}", e))?;
-    
-    // Create app data directory if it doesn't exist
-    fs::create_dir_all(&app_data_dir)
-        .map_err(|e| format!("Failed to create app data directory: {}", e))?;
-    
-    let registry_path = app_data_dir.join("vaults.json");
-    
-    // Load existing registry
-    let mut vaults = if registry_path.exists() {
-        if let Ok(contents) = fs::read_to_string(&registry_path) {
-            serde_json::from_str::<Vec<VaultListItem>>(&contents).unwrap_or_default()
-        } else {
-            Vec::new()
-        }
-    } else {
-        Vec::new()
+/// Check a model id with the sidecar of a window showing `vault`: the
+/// canonical id if it is known (or unverifiable), a `Validation` error
+/// naming close matches if not. Unchanged when no window has the vault open.
+async fn canonical_model_for_vault(
+    state: &State<'_, AppState>,
+    vault: &std::path::Path,
+    model: String,
+) -> Result<String, CommandError> {
+    let Some(window_label) = state.window_manager.lock().await.find_window_by_vault(vault) else {
+        return Ok(model);
     };
-    
-    // Check if vault already exists in registry
-    if !vaults.iter().any(|v| v.path == vault.path) {
-        vaults.push(vault.clone());
-        
-        // Write registry back
-        let registry_json = serde_json::to_string_pretty(&vaults)
-            .map_err(|e| format!("Failed to serialize registry: {}", e))?;
-        fs::write(&registry_path, registry_json)
-            .map_err(|e| format!("Failed to write registry: {}", e))?;
+    let check = sidecar_request(state, &window_label, "settings.normalize_model_id", serde_json::json!({
+        "model": model,
+    })).await?;
+    if check.get("valid").and_then(|v| v.as_bool()) == Some(false) {
+        let error = check.get("error").and_then(|e| e.as_str()).unwrap_or("unknown model");
+        return Err(CommandError::Validation(error.to_string()));
     }
-    
-    Ok(())
+    Ok(check.get("canonical").and_then(|c| c.as_str()).map(str::to_string).unwrap_or(model))
+}
+
+/// Validate a provider model id against the models known to the window's
+/// sidecar. The result carries the canonical id when it is valid, or
+/// close-match `suggestions` when it is not; ids for unknown providers pass
+/// through with a `warning`.
+#[tauri::command]
+pub async fn normalize_model_id(
+    window_label: String,
+    provider: Option<String>,
+    model: String,
+    state: State<'_, AppState>,
+) -> Result<serde_json::Value, CommandError> {
+    if model.trim().is_empty() {
+        return Err(CommandError::Validation("model must not be empty".to_string()));
+    }
+    sidecar_request(&state, &window_label, "settings.normalize_model_id", serde_json::json!({
+        "provider": provider.unwrap_or_default(),
+        "model": model,
+    })).await
 }
 
-/// Search plugins in the community store
+/// Stored API keys against the providers the window's vault uses, from its
+/// category models and the providers its loaded plugins declare. Each entry
+/// is `{provider, key_present, used_by, missing, ...}`; `missing` lists a
+/// message for every provider in use without a key. The sidecar pushes the
+/// same payload as a `PROVIDER_KEY_STATUS` event whenever keys or plugins change.
 #[tauri::command]
-pub async fn search_plugins(_query: String, _category: Option<String>) -> Result<Vec<serde_json::Value>, String> {
-    Ok(vec![])
+pub async fn get_provider_key_status(
+    window_label: String,
+    state: State<'_, AppState>,
+) -> Result<serde_json::Value, CommandError> {
+    sidecar_request(&state, &window_label, "settings.get_provider_key_status", serde_json::json!({})).await
 }
 
-/// Get plugin details
+/// Append a message to a conversation, updating the search index in place
 #[tauri::command]
-pub async fn get_plugin_details(_plugin_id: String) -> Result<serde_json::Value, String> {
-    Err("Plugin details not yet implemented".to_string())
+pub async fn append_message(
+    vault_path: String,
+    conversation_id: String,
+    message: serde_json::Value,
+) -> Result<conversations::Conversation, CommandError> {
+    let message: conversations::Message = serde_json::from_value(message)
+        .map_err(|e| CommandError::Validation(format!("Invalid message: {}", e)))?;
+
+    let vault = resolve_vault_path("vault_path", &vault_path)?;
+    let conversation = conversations::append_message(&vault, &conversation_id, message)
+        .map_err(|e| CommandError::Io(format!("Failed to append message: {}", e)))?;
+    conversation_index::record_write(&vault, &conversation);
+    Ok(conversation)
 }
 
-/// Install plugin to vault
+/// Upper bound on a resumed generation, which streams before replying
+const RESUME_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Messages whose streamed generation was interrupted before it finished
 #[tauri::command]
-pub async fn install_plugin(_vault_path: String, _plugin_repo: String, _plugin_name: String) -> Result<(), String> {
-    Err("Plugin installation not yet implemented".to_string())
+pub async fn get_incomplete_messages(
+    vault_path: String,
+    conversation_id: String,
+) -> Result<Vec<conversations::IncompleteMessage>, CommandError> {
+    conversations::incomplete_messages(&resolve_vault_path("vault_path", &vault_path)?, &conversation_id)
+        .map_err(|e| CommandError::Io(format!("Failed to read conversation: {}", e)))
 }
 
-/// Get installed plugins for a vault
+/// Continue an interrupted reply from its committed text. The continuation
+/// streams like a normal reply and lands in the same message.
 #[tauri::command]
-pub async fn get_installed_plugins(vault_path: String) -> Result<Vec<serde_json::Value>, String> {
-    let path = PathBuf::from(&vault_path).join("plugins");
-    
-    if !path.exists() {
-        return Ok(vec![]);
+pub async fn resume_message_generation(
+    window_label: String,
+    conversation_id: String,
+    message_index: usize,
+    category: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<serde_json::Value, CommandError> {
+    let vault_path = state.window_manager.lock().await
+        .get_vault_path(&window_label)
+        .cloned()
+        .ok_or_else(|| CommandError::NotFound(format!("No vault open in window: {}", window_label)))?;
+    let conversation = conversations::load_conversation(&PathBuf::from(&vault_path), &conversation_id)
+        .map_err(|e| CommandError::Io(format!("Failed to read conversation: {}", e)))?;
+
+    let message = conversation.messages.get(message_index)
+        .ok_or_else(|| CommandError::Validation(format!("message_index {} is out of range", message_index)))?;
+    if message.is_complete() {
+        return Err(CommandError::Validation(format!("message {} is already complete", message_index)));
     }
-    
-    let mut plugins = vec![];
-    
-    if let Ok(entries) = fs::read_dir(&path) {
-        for entry in entries.flatten() {
-            if entry.path().is_dir() {
-                let plugin_name = entry.file_name().to_string_lossy().to_string();
-                plugins.push(serde_json::json!({
-                    "name": plugin_name,
-                    "path": entry.path().to_string_lossy().to_string(),
-                }));
-            }
-        }
+    let prompt_index = conversation.messages[..message_index]
+        .iter()
+        .rposition(|m| m.role == "user")
+        .ok_or_else(|| CommandError::Validation(format!("No user message precedes message {}", message_index)))?;
+
+    let history: Vec<_> = conversation.messages[..prompt_index]
+        .iter()
+        .map(|m| serde_json::json!({ "role": m.role, "content": m.content }))
+        .collect();
+
+    // Returns only once the continuation has finished streaming
+    sidecar_request_with_timeout(&state, &window_label, "chat.resume", serde_json::json!({
+        "chat_id": conversation_id,
+        "message_index": message_index,
+        "prompt": conversation.messages[prompt_index].content,
+        "partial": message.content,
+        "history": history,
+        "category": category.unwrap_or_else(|| "fast".to_string()),
+    }), RESUME_TIMEOUT).await
+}
+
+/// Upper bound on building the semantic index, which embeds every new or
+/// changed message in the vault
+const SEMANTIC_INDEX_TIMEOUT: Duration = Duration::from_secs(1800);
+
+/// Turn the sidecar's "semantic_unavailable" reply (no embedding model, or
+/// the provider failing) into a `DependencyFailed` error
+fn semantic_result(result: serde_json::Value) -> Result<serde_json::Value, CommandError> {
+    if result.get("status").and_then(|s| s.as_str()) != Some("error") {
+        return Ok(result);
     }
-    
-    Ok(plugins)
+    let error = result.get("error").and_then(|e| e.as_str()).unwrap_or("unknown error");
+    if result.get("code").and_then(|c| c.as_str()) == Some("semantic_unavailable") {
+        return Err(CommandError::DependencyFailed(error.to_string()));
+    }
+    Err(CommandError::Internal(error.to_string()))
 }
 
-/// Get global settings
+/// Embed the vault's conversation messages into `.tailor/embeddings/`.
+///
+/// Incremental and resumable: unchanged messages keep their vectors, and an
+/// interrupted build picks up at the conversation it stopped in.
 #[tauri::command]
-pub async fn get_global_settings() -> Result<serde_json::Value, String> {
-    Ok(serde_json::json!({
-        "theme": "dark",
-        "autoUpdate": false,
-    }))
+pub async fn build_semantic_index(
+    window_label: String,
+    vault_path: String,
+    state: State<'_, AppState>,
+) -> Result<serde_json::Value, CommandError> {
+    let vault = resolve_vault_path("vault_path", &vault_path)?;
+    let window_vault = state.window_manager.lock().await
+        .get_vault_path(&window_label)
+        .cloned()
+        .ok_or_else(|| CommandError::NotFound(format!("No vault open in window: {}", window_label)))?;
+    if PathBuf::from(&window_vault) != vault {
+        return Err(CommandError::Validation(format!(
+            "window '{}' has {} open, not {}",
+            window_label, window_vault, vault.display()
+        )));
+    }
+
+    let result = sidecar_request_with_timeout(
+        &state,
+        &window_label,
+        "search.build_semantic_index",
+        serde_json::json!({}),
+        SEMANTIC_INDEX_TIMEOUT,
+    ).await?;
+    semantic_result(result)
 }
 
-/// Save global settings
+/// Messages and conversations closest in meaning to `query`, best first
 #[tauri::command]
-pub async fn save_global_settings(settings: serde_json::Value) -> Result<(), String> {
-    println!("Saving global settings: {:?}", settings);
-    Ok(())
+pub async fn semantic_search(
+    window_label: String,
+    query: String,
+    top_k: Option<usize>,
+    state: State<'_, AppState>,
+) -> Result<serde_json::Value, CommandError> {
+    if query.trim().is_empty() {
+        return Err(CommandError::Validation("query must not be empty".to_string()));
+    }
+    let result = sidecar_request(&state, &window_label, "search.semantic_search", serde_json::json!({
+        "query": query,
+        "top_k": top_k.unwrap_or(10),
+    })).await?;
+    semantic_result(result)
 }
 
-/// Get vault settings
+/// What kind of storage holds a vault (local, network, FUSE or a cloud
+/// sync folder), whether it replaces files atomically by rename, and how
+/// slow it is. Writes into the vault adapt to the result.
 #[tauri::command]
-pub async fn get_vault_settings(_vault_path: String) -> Result<serde_json::Value, String> {
-    Ok(serde_json::json!({}))
+pub async fn detect_storage_backend(vault_path: String) -> Result<StorageBackend, CommandError> {
+    let vault = resolve_vault_path("vault_path", &vault_path)?;
+    if !vault.is_dir() {
+        return Err(CommandError::NotFound(format!("Vault not found: {}", vault_path)));
+    }
+    let backend = tauri::async_runtime::spawn_blocking(move || {
+        let backend = storage_backend::detect(&vault);
+        storage_backend::register(&vault, &backend);
+        backend
+    })
+    .await
+    .map_err(|e| CommandError::Internal(format!("Storage detection failed: {}", e)))?;
+    Ok(backend)
 }
 
-/// Save vault settings
+/// How far the conversation search index lags the conversation files
 #[tauri::command]
-pub async fn save_vault_settings(vault_path: String, settings: serde_json::Value) -> Result<(), String> {
-    println!("Saving vault settings for {}: {:?}", vault_path, settings);
-    Ok(())
+pub async fn index_freshness(vault_path: String) -> Result<conversation_index::IndexFreshness, CommandError> {
+    Ok(conversation_index::freshness(&resolve_vault_path("vault_path", &vault_path)?))
 }
 
-/// Get API keys
+/// Give fresh ids to all but the newest of each set of duplicate-id conversations
 #[tauri::command]
-pub async fn get_api_keys() -> Result<serde_json::Value, String> {
-    Ok(serde_json::json!({}))
+pub async fn resolve_duplicate_conversation_ids(
+    vault_path: String,
+) -> Result<Vec<conversations::IdReassignment>, CommandError> {
+    let vault = resolve_vault_path("vault_path", &vault_path)?;
+    let reassigned = conversations::resolve_duplicate_ids(&vault)
+        .map_err(|e| CommandError::Internal(format!("Failed to resolve duplicate conversation ids: {}", e)))?;
+    if !reassigned.is_empty() {
+        conversation_index::mark_dirty(&vault);
+    }
+    Ok(reassigned)
 }
 
-/// Save API key
+/// Get the exact on-disk JSON text of a conversation
 #[tauri::command]
-pub async fn save_api_key(key_name: String, _key_value: String) -> Result<(), String> {
-    println!("Saving API key: {}", key_name);
-    Ok(())
+pub async fn get_conversation_raw(vault_path: String, conversation_id: String) -> Result<String, CommandError> {
+    let path = conversations::conversation_path(&resolve_vault_path("vault_path", &vault_path)?, &conversation_id)
+        .map_err(|e| CommandError::Validation(e.to_string()))?;
+
+    if !path.exists() {
+        return Err(CommandError::NotFound(format!("Conversation not found: {}", conversation_id)));
+    }
+
+    fs::read_to_string(&path)
+        .map_err(|e| CommandError::Io(format!("Failed to read conversation: {}", e)))
 }
 
-/// Delete API key
+/// Replace a conversation file with raw JSON text after validating it.
+///
+/// The original file is backed up before being atomically overwritten.
 #[tauri::command]
-pub async fn delete_api_key(key_name: String) -> Result<(), String> {
-    println!("Deleting API key: {}", key_name);
-    Ok(())
+pub async fn set_conversation_raw(
+    vault_path: String,
+    conversation_id: String,
+    text: String,
+) -> Result<serde_json::Value, CommandError> {
+    let vault = resolve_vault_path("vault_path", &vault_path)?;
+
+    let conversation = conversations::parse_conversation(&text)
+        .map_err(CommandError::Validation)?;
+    if conversation.id != conversation_id {
+        return Err(CommandError::Validation(format!(
+            "Conversation id mismatch: file is '{}' but JSON declares '{}'",
+            conversation_id, conversation.id
+        )));
+    }
+
+    let backup = conversations::backup_conversation(&vault, &conversation_id)
+        .map_err(|e| CommandError::Io(format!("Failed to back up conversation: {}", e)))?;
+
+    conversations::write_conversation_raw(&vault, &conversation_id, &text)
+        .map_err(|e| CommandError::Io(format!("Failed to write conversation: {}", e)))?;
+    conversation_index::record_write(&vault, &conversation);
+
+    println!("Wrote raw conversation {} in {}", conversation_id, vault_path);
+
+    Ok(serde_json::json!({
+        "conversation_id": conversation_id,
+        "backup_path": backup.map(|p| p.to_string_lossy().to_string()),
+    }))
 }
 
-/// Search conversations
+/// Re-import one plugin's `main.py` and re-create its `Plugin` in the
+/// window's sidecar (its worker with `isolatePlugins`), leaving the rest
+/// running. A plugin that isn't loaded is loaded fresh. The result carries
+/// `status`, `valid` and `reloaded`, plus `error` and the import
+/// `traceback` when loading failed.
 #[tauri::command]
-pub async fn search_conversations(_query: String, _filters: serde_json::Value) -> Result<Vec<serde_json::Value>, String> {
-    Ok(vec![])
+pub async fn reload_plugin(
+    window_label: String,
+    plugin_name: String,
+    state: State<'_, AppState>,
+) -> Result<serde_json::Value, CommandError> {
+    if !plugins::is_valid_plugin_name(&plugin_name) {
+        return Err(CommandError::Validation(format!("invalid plugin name {:?}", plugin_name)));
+    }
+    let result = reload_plugin_live(&state, &window_label, &plugin_name).await?;
+    println!(
+        "Reloaded plugin '{}' in window '{}': {}",
+        plugin_name,
+        window_label,
+        result.get("status").and_then(|s| s.as_str()).unwrap_or("unknown"),
+    );
+    Ok(result)
+}
+
+/// `plugins.reload` in the plugin's host process, keeping the signature
+/// cache and worker command routing in step with the new instance
+async fn reload_plugin_live(
+    state: &State<'_, AppState>,
+    window_label: &str,
+    plugin_name: &str,
+) -> Result<serde_json::Value, CommandError> {
+    let (result, in_worker) = plugin_host_request(state, window_label, plugin_name, "plugins.reload").await?;
+
+    // Same process, new instance: cached signatures would be out of date
+    state.signatures.invalidate(window_label, plugin_name);
+    if in_worker {
+        if let Some(commands) = result.get("commands").and_then(|c| c.as_array()) {
+            let commands = commands.iter().filter_map(|c| c.as_str().map(str::to_string)).collect();
+            state.sidecar_manager.set_worker_commands(window_label, plugin_name, commands).await;
+        }
+    }
+    Ok(result)
+}
+
+/// Send a `{plugin}` request to whichever process hosts the plugin: its
+/// worker with `isolatePlugins`, else the window's sidecar. The flag says
+/// whether it went to a worker.
+async fn plugin_host_request(
+    state: &State<'_, AppState>,
+    window_label: &str,
+    plugin_name: &str,
+    method: &str,
+) -> Result<(serde_json::Value, bool), CommandError> {
+    let main_port = state.sidecar_manager
+        .get_ws_port(window_label)
+        .await
+        .ok_or_else(|| sidecar_not_found(window_label))?;
+    let host_port = state.sidecar_manager
+        .plugin_host(window_label, plugin_name)
+        .await
+        .map_or(main_port, |(_, port)| port);
+
+    let params = serde_json::json!({ "plugin": plugin_name });
+    if host_port == main_port {
+        return Ok((sidecar_request(state, window_label, method, params).await?, false));
+    }
+    let result = state.connection_pool
+        .request(host_port, method, params, DEFAULT_REQUEST_TIMEOUT)
+        .await
+        .map_err(|e| connection_pool::command_error(&e))?;
+    Ok((result, true))
 }
 
-/// Get conversation details
+/// Stop `plugin_name` in the sidecar of the window showing `vault`, if any;
+/// returns the sidecar's reply
+async fn unload_plugin_live(
+    state: &State<'_, AppState>,
+    vault: &Path,
+    plugin_name: &str,
+) -> Result<Option<serde_json::Value>, CommandError> {
+    let Some(window_label) = state.window_manager.lock().await.find_window_by_vault(vault) else {
+        return Ok(None);
+    };
+    let (result, in_worker) = plugin_host_request(state, &window_label, plugin_name, "plugins.unload").await?;
+    state.signatures.invalidate(&window_label, plugin_name);
+    if in_worker {
+        state.sidecar_manager.set_worker_commands(&window_label, plugin_name, Vec::new()).await;
+    }
+    Ok(Some(result))
+}
+
+/// Delete a local plugin's folder from `plugins/`, unloading it from the
+/// vault's sidecar first so no stale instance keeps running. Its
+/// `.vault.json` overrides are kept for a reinstall. Fails with `NotFound`
+/// when there is no such folder, and refuses folders without a `main.py`.
 #[tauri::command]
-pub async fn get_conversation(_vault_path: String, _conversation_id: String) -> Result<serde_json::Value, String> {
-    Err("Conversation loading not yet implemented".to_string())
+pub async fn uninstall_plugin(
+    vault_path: String,
+    plugin_name: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<plugins::InstalledPlugin>, CommandError> {
+    let vault = resolve_vault_path("vault_path", &vault_path)?;
+    if !plugins::is_valid_plugin_name(&plugin_name) {
+        return Err(CommandError::Validation(format!("invalid plugin name {:?}", plugin_name)));
+    }
+    let plugins_dir = vault.join(plugins::PLUGINS_DIR);
+    let dir = plugins_dir.join(&plugin_name);
+    let metadata = fs::symlink_metadata(&dir)
+        .map_err(|_| CommandError::NotFound(format!("plugin '{}' is not installed in {}", plugin_name, vault_path)))?;
+    // A linked shared plugin is unlinked, never deleted through the link
+    let linked = metadata.file_type().is_symlink();
+    if !linked {
+        let inside = fs::canonicalize(&plugins_dir)
+            .and_then(|root| fs::canonicalize(&dir).map(|dir| dir.starts_with(root)))
+            .unwrap_or(false);
+        if !inside || !metadata.is_dir() {
+            return Err(CommandError::Validation(format!("{} is not a plugin folder", dir.display())));
+        }
+    }
+    if !dir.join(plugins::PLUGIN_MAIN_FILE).is_file() {
+        return Err(CommandError::Validation(format!(
+            "{} has no {}; not deleting something that isn't a plugin",
+            dir.display(), plugins::PLUGIN_MAIN_FILE
+        )));
+    }
+
+    if let Err(e) = unload_plugin_live(&state, &vault, &plugin_name).await {
+        eprintln!("Warning: Failed to unload plugin '{}' before uninstalling: {}", plugin_name, e);
+    }
+
+    let removed = if linked {
+        fs::remove_file(&dir).or_else(|_| fs::remove_dir(&dir))
+    } else {
+        fs::remove_dir_all(&dir)
+    };
+    removed.map_err(|e| CommandError::Io(format!("Failed to remove plugin '{}': {}", plugin_name, e)))?;
+    println!("Uninstalled plugin '{}' from {}", plugin_name, vault.display());
+
+    Ok(plugins::installed_plugins(&vault))
 }
 
-/// Delete conversation
+/// Enable or disable a plugin in the vault's `.vault.json` and load or
+/// unload it live in the sidecar of a window showing the vault. `sidecar`
+/// is that sidecar's reply (carrying the load `error` and `traceback` if
+/// enabling failed), or null when the vault isn't open. The setting is
+/// saved even if the sidecar can't be reached.
 #[tauri::command]
-pub async fn delete_conversation(vault_path: String, conversation_id: String) -> Result<(), String> {
-    println!("Deleting conversation {} from {}", conversation_id, vault_path);
-    Ok(())
+pub async fn set_plugin_enabled(
+    vault_path: String,
+    plugin_name: String,
+    enabled: bool,
+    state: State<'_, AppState>,
+) -> Result<serde_json::Value, CommandError> {
+    let vault = resolve_vault_path("vault_path", &vault_path)?;
+    if !plugins::is_valid_plugin_name(&plugin_name) {
+        return Err(CommandError::Validation(format!("invalid plugin name {:?}", plugin_name)));
+    }
+    if plugins::plugin_dir(&vault, &plugin_name).is_none() {
+        return Err(CommandError::NotFound(format!("plugin '{}' is not installed in {}", plugin_name, vault_path)));
+    }
+
+    plugins::set_enabled(&vault, &plugin_name, enabled)
+        .map_err(|e| CommandError::Io(format!("Failed to save plugin state: {}", e)))?;
+    println!("Plugin '{}' {} in {}", plugin_name, if enabled { "enabled" } else { "disabled" }, vault.display());
+
+    let sidecar = if enabled {
+        let window_label = state.window_manager.lock().await.find_window_by_vault(&vault);
+        match window_label {
+            Some(window_label) => Some(reload_plugin_live(&state, &window_label, &plugin_name).await?),
+            None => None,
+        }
+    } else {
+        unload_plugin_live(&state, &vault, &plugin_name).await?
+    };
+
+    Ok(serde_json::json!({
+        "plugin": plugin_name,
+        "enabled": enabled,
+        "sidecar": sidecar,
+    }))
 }
 
 /// Get plugin template
 #[tauri::command]
-pub async fn get_plugin_template() -> Result<String, String> {
+pub async fn get_plugin_template() -> Result<String, CommandError> {
     Ok(r#"# plugins/my_plugin/main.py
 import sys
 from pathlib import Path
@@ -554,19 +3518,186 @@ class Plugin(PluginBase):
         return {"status": "ok"}"#.to_string())
 }
 
-/// Validate plugin structure
+/// Check a plugin against the contract the sidecar loads it by: `main.py`
+/// imports and defines a `Plugin` subclass of `PluginBase` with the
+/// constructor and methods the sidecar calls, and its manifest (if any) has
+/// a name and a semver version. Each check is listed with whether it
+/// passed; a failed import comes back as `valid: false` with its `traceback`.
 #[tauri::command]
-pub async fn validate_plugin(_vault_path: String, plugin_path: String) -> Result<serde_json::Value, String> {
-    let path = PathBuf::from(&plugin_path);
-    let main_py = path.join("main.py");
-    
-    if !main_py.exists() {
-        return Err("Plugin missing main.py file".to_string());
+pub async fn validate_plugin(_vault_path: String, plugin_path: String) -> Result<PluginValidation, CommandError> {
+    let path = resolve_vault_path("plugin_path", &plugin_path)?;
+    if !path.is_dir() {
+        return Err(CommandError::NotFound(format!("Plugin directory not found: {}", plugin_path)));
     }
-    
-    Ok(serde_json::json!({
-        "valid": true,
-        "message": "Plugin structure is valid",
-    }))
+
+    plugin_validator::validate(&path)
+        .await
+        .map_err(|e| CommandError::DependencyFailed(format!("Failed to validate plugin: {:#}", e)))
+}
+
+
+/// Schedule a heavy vault operation (backup, plugin updates, venv rebuild)
+/// to run at `at_time` (RFC 3339). Missed tasks run on the next startup.
+#[tauri::command]
+pub async fn schedule_vault_task(
+    vault_path: String,
+    task: ScheduledTaskKind,
+    at_time: String,
+    state: State<'_, AppState>,
+) -> Result<ScheduledTask, CommandError> {
+    if !resolve_vault_path("vault_path", &vault_path)?.is_dir() {
+        return Err(CommandError::NotFound(format!("Vault directory not found: {}", vault_path)));
+    }
+
+    let at_time = chrono::DateTime::parse_from_rfc3339(&at_time)
+        .map_err(|e| CommandError::Validation(format!("Invalid time '{}': {}", at_time, e)))?
+        .with_timezone(&chrono::Utc);
+    if at_time < chrono::Utc::now() - chrono::Duration::minutes(1) {
+        return Err(CommandError::Validation(format!("Scheduled time {} is in the past", at_time.to_rfc3339())));
+    }
+
+    state.scheduler
+        .schedule(&vault_path, task, at_time)
+        .await
+        .map_err(|e| CommandError::Internal(format!("Failed to schedule task: {}", e)))
+}
+
+/// List scheduled tasks, including finished and caught-up ones
+#[tauri::command]
+pub async fn list_scheduled_tasks(state: State<'_, AppState>) -> Result<Vec<ScheduledTask>, CommandError> {
+    Ok(state.scheduler.list().await)
+}
+
+/// Cancel a scheduled task that has not started yet
+#[tauri::command]
+pub async fn cancel_scheduled_task(
+    task_id: String,
+    state: State<'_, AppState>,
+) -> Result<ScheduledTask, CommandError> {
+    state.scheduler
+        .cancel(&task_id)
+        .await
+        .map_err(|e| CommandError::Internal(format!("Failed to cancel task: {}", e)))
+}
+
+/// Structurally compare two vaults: plugins and versions, settings and
+/// conversation counts
+#[tauri::command]
+pub async fn diff_vaults(vault_a: String, vault_b: String) -> Result<VaultDiff, CommandError> {
+    let vault_a = resolve_vault_path("vault_a", &vault_a)?;
+    let vault_b = resolve_vault_path("vault_b", &vault_b)?;
+    vault_diff::diff_vaults(&vault_a, &vault_b)
+        .map_err(|e| CommandError::Internal(format!("Failed to compare vaults: {}", e)))
+}
+
+/// List files under a vault subdirectory, optionally filtered by a name
+/// pattern such as `*.md`
+#[tauri::command]
+pub async fn list_vault_files(
+    vault_path: String,
+    subdir: Option<String>,
+    pattern: Option<String>,
+) -> Result<VaultFileListing, CommandError> {
+    vault_files::list_files(
+        &resolve_vault_path("vault_path", &vault_path)?,
+        subdir.as_deref().unwrap_or(""),
+        pattern.as_deref().filter(|p| !p.is_empty()),
+    )
+    .map_err(|e| CommandError::Io(format!("Failed to list vault files: {}", e)))
+}
+
+/// The vault's `.tailor/exclude` patterns (gitignore syntax), or the
+/// defaults when it has none
+#[tauri::command]
+pub async fn get_vault_excludes(vault_path: String) -> Result<VaultExcludes, CommandError> {
+    Ok(vault_excludes::read(&resolve_vault_path("vault_path", &vault_path)?))
 }
 
+/// Replace the vault's exclude patterns; they apply to vault backups
+#[tauri::command]
+pub async fn set_vault_excludes(vault_path: String, patterns: Vec<String>) -> Result<VaultExcludes, CommandError> {
+    vault_excludes::write(&resolve_vault_path("vault_path", &vault_path)?, &patterns)
+        .map_err(|e| CommandError::Io(format!("Failed to save vault excludes: {}", e)))
+}
+
+/// Preview a vault-relative file; binary files come back without content
+#[tauri::command]
+pub async fn read_vault_file(vault_path: String, rel_path: String) -> Result<VaultFilePreview, CommandError> {
+    vault_files::read_file(&resolve_vault_path("vault_path", &vault_path)?, &rel_path)
+        .map_err(|e| CommandError::Io(format!("Failed to read vault file: {}", e)))
+}
+
+/// Latest checkpoint for a long-running plugin operation.
+///
+/// Asks the live sidecar first and falls back to the copy persisted in the
+/// vault, so progress is still visible after a sidecar or app restart.
+#[tauri::command]
+pub async fn get_operation_checkpoint(
+    window_label: String,
+    operation_id: String,
+    state: State<'_, AppState>,
+) -> Result<OperationCheckpoint, CommandError> {
+    let vault_path = state.window_manager
+        .lock()
+        .await
+        .get_vault_path(&window_label)
+        .cloned()
+        .ok_or_else(|| CommandError::NotFound(format!("Window not found: {}", window_label)))?;
+
+    let live = sidecar_request(
+        &state,
+        &window_label,
+        "operations.get_checkpoint",
+        serde_json::json!({ "operation_id": operation_id }),
+    )
+    .await
+    .ok()
+    .and_then(|result| result.get("operation").cloned())
+    .and_then(|op| serde_json::from_value::<OperationCheckpoint>(op).ok());
+
+    if let Some(checkpoint) = live {
+        if let Err(e) = operations::save_checkpoint(&checkpoint) {
+            eprintln!("Warning: Failed to persist checkpoint {}: {}", operation_id, e);
+        }
+        return Ok(checkpoint);
+    }
+
+    operations::load_checkpoint(&PathBuf::from(&vault_path), &operation_id)
+        .map_err(|e| CommandError::Io(format!("Failed to load checkpoint: {}", e)))?
+        .ok_or_else(|| CommandError::NotFound(format!("No checkpoint recorded for operation: {}", operation_id)))
+}
+
+/// Ask the owning plugin to continue an interrupted operation from its last checkpoint
+#[tauri::command]
+pub async fn resume_operation(
+    window_label: String,
+    operation_id: String,
+    state: State<'_, AppState>,
+) -> Result<serde_json::Value, CommandError> {
+    let vault_path = state.window_manager
+        .lock()
+        .await
+        .get_vault_path(&window_label)
+        .cloned()
+        .ok_or_else(|| CommandError::NotFound(format!("Window not found: {}", window_label)))?;
+
+    let checkpoint = operations::load_checkpoint(&PathBuf::from(&vault_path), &operation_id)
+        .map_err(|e| CommandError::Io(format!("Failed to load checkpoint: {}", e)))?
+        .ok_or_else(|| CommandError::NotFound(format!("No checkpoint recorded for operation: {}", operation_id)))?;
+
+    if checkpoint.status == "completed" {
+        return Err(CommandError::Validation(format!("Operation {} already completed", operation_id)));
+    }
+
+    sidecar_request(
+        &state,
+        &window_label,
+        "operations.resume",
+        serde_json::json!({
+            "operation_id": checkpoint.operation_id,
+            "plugin": checkpoint.plugin,
+            "checkpoint": checkpoint.checkpoint,
+        }),
+    )
+    .await
+}
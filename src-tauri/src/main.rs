@@ -5,20 +5,96 @@ mod window_manager;
 mod sidecar_manager;
 mod dependency_checker;
 mod ipc_router;
+mod command_error;
 mod event_bus;
+mod artifact_scanner;
+mod sidecar_client;
+mod settings;
+mod plugin_updater;
+mod plugin_installer;
+mod plugin_validator;
+mod fs_utils;
+mod conversations;
+mod conversation_index;
+mod sidecar_logs;
+mod api_keys;
+mod vault_migration;
+mod connection_pool;
+mod task_manager;
+mod scheduler;
+mod plugins;
+mod vault_diff;
+mod vault_files;
+mod operations;
+mod settings_profiles;
+mod redact;
+mod failures;
+mod command_queue;
+mod python_compat;
+mod stream_recorder;
+mod registry;
+mod recents;
+mod hang_detector;
+mod vault_excludes;
+mod plugin_lifecycle;
+mod method_signatures;
+mod crash_monitor;
+mod request_coalescer;
+mod request_budget;
+mod storage_backend;
+mod disk_usage;
+mod audit_log;
+mod sidecar_streams;
+mod sidecar_batches;
+mod window_session;
 
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 use tauri::Manager;
 use tokio::sync::Mutex;
 
-use window_manager::WindowManager;
+use window_manager::{SessionUpdate, WindowManager};
 use sidecar_manager::SidecarManager;
 use event_bus::EventBus;
+use connection_pool::ConnectionPool;
+use task_manager::TaskManager;
+use scheduler::Scheduler;
+use failures::FailureLog;
+use command_queue::CommandQueue;
+use plugin_lifecycle::LifecycleLog;
+use method_signatures::SignatureCache;
+use crash_monitor::CrashHistory;
+use request_coalescer::RequestCoalescer;
+use request_budget::RequestBudget;
+use audit_log::AuditLog;
+use sidecar_streams::StreamRegistry;
+use sidecar_batches::BatchRegistry;
+use ipc_router::VaultInfo;
+
+/// How long each sidecar gets to finish requests and exit when the app quits
+const EXIT_DRAIN_TIMEOUT: Duration = Duration::from_secs(2);
+const EXIT_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(3);
 
-#[derive(Default)]
 struct AppState {
     window_manager: Arc<Mutex<WindowManager>>,
+    /// Session changes from window and exit events, applied in order
+    session_updates: tokio::sync::mpsc::UnboundedSender<SessionUpdate>,
     sidecar_manager: Arc<SidecarManager>,
+    connection_pool: Arc<ConnectionPool>,
+    scheduler: Arc<Scheduler>,
+    task_manager: Arc<TaskManager>,
+    failures: Arc<FailureLog>,
+    command_queue: Arc<CommandQueue>,
+    lifecycle: Arc<LifecycleLog>,
+    signatures: Arc<SignatureCache>,
+    crashes: Arc<CrashHistory>,
+    /// In-flight `open_vault` calls keyed by canonical vault path
+    vault_opens: Arc<RequestCoalescer<PathBuf, VaultInfo>>,
+    request_budget: Arc<RequestBudget>,
+    audit: Arc<AuditLog>,
+    streams: Arc<StreamRegistry>,
+    batches: Arc<BatchRegistry>,
     #[allow(dead_code)]
     event_bus: Arc<EventBus>,
 }
@@ -28,20 +104,123 @@ fn main() {
         .plugin(tauri_plugin_dialog::init())
         .setup(|app| {
             // Initialize application state
-            let window_manager = Arc::new(Mutex::new(WindowManager::new()));
+            let mut windows = WindowManager::new();
+            windows.set_session_dir(app.path().app_config_dir()?);
+            let window_manager = Arc::new(Mutex::new(windows));
+            let session_updates = window_manager::spawn_session_updater(app.handle().clone(), window_manager.clone());
             let sidecar_manager = Arc::new(SidecarManager::new());
+            let connection_pool = Arc::new(ConnectionPool::new());
+            operations::spawn_checkpoint_recorder(connection_pool.clone());
+            stream_recorder::spawn_stream_recorder(connection_pool.clone());
             let event_bus = Arc::new(EventBus::new());
+            let task_manager = Arc::new(TaskManager::new());
+            // Recents moved from the app data dir to the config dir
+            if let Err(e) = recents::migrate(&app.path().app_config_dir()?, &app.path().app_data_dir()?) {
+                eprintln!("Warning: Failed to migrate recents: {}", e);
+            }
+            let scheduler = Arc::new(Scheduler::load(&app.path().app_data_dir()?));
+            plugins::set_shared_plugins_root(app.path().app_data_dir()?.join(plugins::SHARED_PLUGINS_DIR));
+            artifact_scanner::set_artifact_dirs(app.path().app_data_dir()?, app.path().app_cache_dir()?);
+            scheduler.clone().start(app.handle().clone(), task_manager.clone());
+            registry::spawn_mirror_benchmark(app.handle().clone());
+            hang_detector::spawn_hang_watchdog(app.handle().clone(), sidecar_manager.clone());
+            sidecar_logs::spawn_log_forwarder(app.handle().clone(), sidecar_manager.subscribe_logs());
+            disk_usage::spawn_disk_usage_monitor(app.handle().clone());
+            let lifecycle = Arc::new(LifecycleLog::new());
+            plugin_lifecycle::spawn_lifecycle_forwarder(
+                app.handle().clone(),
+                connection_pool.clone(),
+                sidecar_manager.clone(),
+                lifecycle.clone(),
+            );
+            let crashes = Arc::new(CrashHistory::new());
+            crash_monitor::spawn_crash_monitor(
+                app.handle().clone(),
+                sidecar_manager.clone(),
+                connection_pool.clone(),
+                crashes.clone(),
+            );
+            let config_dir = app.path().app_config_dir()?;
+            if let Err(e) = settings::ensure_global_settings(&config_dir) {
+                eprintln!("Failed to write default global settings: {}", e);
+            }
+            let global_settings = settings::load_global_settings(&config_dir).ok();
+            let budget_limit = global_settings.as_ref().and_then(settings::request_budget);
+            let request_budget = Arc::new(RequestBudget::new(connection_pool.clone(), budget_limit));
+            request_budget::spawn_budget_coordinator(
+                connection_pool.clone(),
+                sidecar_manager.clone(),
+                request_budget.clone(),
+            );
+
+            let audit = Arc::new(AuditLog::new(
+                app.path().home_dir().ok().as_deref(),
+                global_settings.as_ref().is_some_and(settings::audit_log_enabled),
+            ));
 
             // Store state in app
             app.manage(AppState {
                 window_manager: window_manager.clone(),
+                session_updates,
                 sidecar_manager: sidecar_manager.clone(),
+                connection_pool: connection_pool.clone(),
+                scheduler: scheduler.clone(),
+                task_manager,
+                failures: Arc::new(FailureLog::new()),
+                command_queue: Arc::new(CommandQueue::new()),
+                lifecycle,
+                signatures: Arc::new(SignatureCache::new()),
+                crashes,
+                vault_opens: Arc::new(RequestCoalescer::new()),
+                request_budget,
+                audit,
+                streams: Arc::new(StreamRegistry::new()),
+                batches: Arc::new(BatchRegistry::new()),
                 event_bus: event_bus.clone(),
             });
 
+            if global_settings.as_ref().is_some_and(settings::restore_session_enabled) {
+                tauri::async_runtime::spawn(ipc_router::restore_session(app.handle().clone()));
+            }
+
             println!("Tailor initialized successfully");
             Ok(())
         })
+        .on_window_event(|window, event| {
+            match event {
+                tauri::WindowEvent::Moved(_) | tauri::WindowEvent::Resized(_) => {
+                    let (Ok(position), Ok(size), Ok(scale)) =
+                        (window.outer_position(), window.inner_size(), window.scale_factor())
+                    else {
+                        return;
+                    };
+                    // Minimizing reports a zero size; keep the last real one
+                    if size.width == 0 || size.height == 0 {
+                        return;
+                    }
+                    let position = position.to_logical::<f64>(scale);
+                    let size = size.to_logical::<f64>(scale);
+                    let geometry = window_session::WindowGeometry {
+                        width: size.width,
+                        height: size.height,
+                        x: position.x,
+                        y: position.y,
+                    };
+                    let label = window.label().to_string();
+                    let window_manager = window.state::<AppState>().window_manager.clone();
+                    tauri::async_runtime::spawn(async move {
+                        window_manager.lock().await.update_geometry(&label, geometry);
+                    });
+                }
+                tauri::WindowEvent::Destroyed => {
+                    // Queued ahead of the exit that follows closing the last
+                    // window, so it leaves the session before that freezes it
+                    let label = window.label().to_string();
+                    let _ = window.state::<AppState>().session_updates.send(SessionUpdate::Drop(label));
+                }
+                _ => {}
+            }
+        })
         .invoke_handler(tauri::generate_handler![
             ipc_router::open_vault,
             ipc_router::send_to_sidecar,
@@ -60,6 +239,7 @@ fn main() {
             ipc_router::get_vault_settings,
             ipc_router::save_vault_settings,
             ipc_router::get_api_keys,
+            ipc_router::get_api_key,
             ipc_router::save_api_key,
             ipc_router::delete_api_key,
             ipc_router::search_conversations,
@@ -67,14 +247,121 @@ fn main() {
             ipc_router::delete_conversation,
             ipc_router::get_plugin_template,
             ipc_router::validate_plugin,
+            ipc_router::find_orphaned_venvs,
+            ipc_router::find_orphaned_caches,
+            ipc_router::clean_orphaned_artifacts,
+            ipc_router::run_self_diagnostic,
+            ipc_router::get_conversation_raw,
+            ipc_router::set_conversation_raw,
+            ipc_router::get_sidecar_logs,
+            ipc_router::migrate_vault,
+            ipc_router::get_plugin_concurrency,
+            ipc_router::set_plugin_concurrency,
+            ipc_router::get_connection_diagnostics,
+            ipc_router::schedule_vault_task,
+            ipc_router::list_scheduled_tasks,
+            ipc_router::cancel_scheduled_task,
+            ipc_router::diff_vaults,
+            ipc_router::list_conversations,
+            ipc_router::create_conversation,
+            ipc_router::resolve_duplicate_conversation_ids,
+            ipc_router::get_provider_circuit_state,
+            ipc_router::reset_provider_circuit,
+            ipc_router::configure_provider_resilience,
+            ipc_router::list_vault_files,
+            ipc_router::read_vault_file,
+            ipc_router::get_operation_checkpoint,
+            ipc_router::resume_operation,
+            ipc_router::save_settings_profile,
+            ipc_router::list_settings_profiles,
+            ipc_router::apply_settings_profile,
+            ipc_router::get_open_vault_count,
+            ipc_router::capture_command_failure,
+            ipc_router::get_command_queue_depth,
+            ipc_router::set_command_queue_limit,
+            ipc_router::check_plugin_python_compat,
+            ipc_router::list_event_subscriptions,
+            ipc_router::set_event_subscription,
+            ipc_router::apply_sidecar_config,
+            ipc_router::append_message,
+            ipc_router::index_freshness,
+            ipc_router::get_plugin_fs_access,
+            ipc_router::get_incomplete_messages,
+            ipc_router::resume_message_generation,
+            ipc_router::refresh_registry,
+            ipc_router::benchmark_registry_mirrors,
+            ipc_router::export_secrets_to_os_format,
+            ipc_router::import_secrets_from_os_format,
+            ipc_router::get_plugin_process_info,
+            ipc_router::get_provider_usage,
+            ipc_router::repair_recents,
+            ipc_router::set_conversation_system_prompt,
+            ipc_router::diagnose_sidecar_hang,
+            ipc_router::get_vault_excludes,
+            ipc_router::set_vault_excludes,
+            ipc_router::get_plugin_lifecycle_history,
+            ipc_router::invoke_plugin_method,
+            ipc_router::get_crash_history,
+            ipc_router::restart_sidecar,
+            ipc_router::build_semantic_index,
+            ipc_router::semantic_search,
+            ipc_router::export_plugin_support_bundle,
+            ipc_router::get_request_budget,
+            ipc_router::set_request_budget,
+            ipc_router::detect_storage_backend,
+            ipc_router::send_to_sidecar_batch,
+            ipc_router::cancel_request,
+            ipc_router::cancel_batch,
+            ipc_router::stream_from_sidecar,
+            ipc_router::cancel_stream,
+            ipc_router::normalize_model_id,
+            ipc_router::install_shared_plugin,
+            ipc_router::link_shared_plugin,
+            ipc_router::get_provider_key_status,
+            ipc_router::open_temp_vault,
+            ipc_router::get_total_tailor_disk_usage,
+            ipc_router::queue_plugin_installs,
+            ipc_router::query_audit_log,
+            ipc_router::clear_audit_log,
+            ipc_router::get_sidecar_status,
+            ipc_router::check_dependencies,
+            ipc_router::reload_plugin,
+            ipc_router::uninstall_plugin,
+            ipc_router::set_plugin_enabled,
         ])
         .build(tauri::generate_context!())
         .expect("error while building tauri application")
-        .run(|app, event| {
-            if let tauri::RunEvent::Exit = event {
-                println!("Application exiting - performing cleanup");
-                let state = app.state::<AppState>();
-                state.sidecar_manager.shutdown_all();
+        .run({
+            let mut session_frozen = false;
+            move |app, event| {
+                if let tauri::RunEvent::ExitRequested { code, api, .. } = &event {
+                    // Quitting closes every window; keep them for the next launch.
+                    // The exit waits until the session is frozen, then is
+                    // requested again and goes ahead.
+                    if !session_frozen {
+                        session_frozen = true;
+                        let freeze = SessionUpdate::FreezeAndExit(code.unwrap_or(0));
+                        if app.state::<AppState>().session_updates.send(freeze).is_ok() {
+                            api.prevent_exit();
+                        }
+                    }
+                }
+                if let tauri::RunEvent::Exit = event {
+                    println!("Application exiting - performing cleanup");
+                    let state = app.state::<AppState>();
+                    // Give each sidecar a short window to exit cleanly; the
+                    // window manager is not touched, so a close already in
+                    // progress can't block this
+                    let overrides = sidecar_manager::TimeoutOverrides {
+                        drain_ms: Some(EXIT_DRAIN_TIMEOUT.as_millis() as u64),
+                        shutdown_ms: Some(EXIT_SHUTDOWN_TIMEOUT.as_millis() as u64),
+                    };
+                    let stopped = tauri::async_runtime::block_on(state.sidecar_manager.terminate_all(overrides));
+                    let forced = stopped.iter().filter(|(_, report)| !report.graceful).count();
+                    println!("Stopped {} sidecar(s), {} forcibly", stopped.len(), forced);
+                    // Anything spawned while that ran
+                    state.sidecar_manager.shutdown_all();
+                }
             }
         });
 }
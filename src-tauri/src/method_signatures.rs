@@ -0,0 +1,191 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use serde::Deserialize;
+
+use crate::command_error::CommandError;
+
+/// One handler parameter, as introspected by `system.describe_plugin_methods`
+#[derive(Debug, Clone, Deserialize)]
+pub struct ParamSpec {
+    pub name: String,
+    /// JSON type name; None when the parameter is unannotated
+    #[serde(rename = "type")]
+    pub json_type: Option<String>,
+    pub required: bool,
+    #[serde(default)]
+    pub nullable: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MethodSignature {
+    pub params: Vec<ParamSpec>,
+    /// Handler takes `**kwargs`, so unknown arguments are allowed
+    pub accepts_extra: bool,
+    /// False when no parameter is annotated; such calls are sent as-is
+    pub annotated: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PluginSignatures {
+    pub methods: HashMap<String, MethodSignature>,
+}
+
+/// Signatures per window and plugin, tagged with the pid of the process
+/// hosting the plugin, so the fresh plugin instances of a restarted sidecar
+/// or worker are introspected again
+#[derive(Default)]
+pub struct SignatureCache {
+    entries: Mutex<HashMap<(String, String), (u32, PluginSignatures)>>,
+}
+
+impl SignatureCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, window_label: &str, plugin: &str, pid: u32) -> Option<PluginSignatures> {
+        self.entries.lock().unwrap()
+            .get(&(window_label.to_string(), plugin.to_string()))
+            .filter(|(cached_pid, _)| *cached_pid == pid)
+            .map(|(_, signatures)| signatures.clone())
+    }
+
+    pub fn insert(&self, window_label: &str, plugin: &str, pid: u32, signatures: PluginSignatures) {
+        self.entries.lock().unwrap()
+            .insert((window_label.to_string(), plugin.to_string()), (pid, signatures));
+    }
+
+    /// Forget a plugin's signatures, e.g. after it was reloaded in place
+    pub fn invalidate(&self, window_label: &str, plugin: &str) {
+        self.entries.lock().unwrap().remove(&(window_label.to_string(), plugin.to_string()));
+    }
+}
+
+/// Check `args` against a method's signature, returning a `Validation`
+/// error for a missing required parameter, a wrong type, or an argument the
+/// method does not take
+pub fn validate_args(
+    command_id: &str,
+    signature: &MethodSignature,
+    args: &serde_json::Map<String, serde_json::Value>,
+) -> Result<(), CommandError> {
+    if !signature.annotated {
+        return Ok(());
+    }
+
+    for param in &signature.params {
+        let Some(value) = args.get(&param.name) else {
+            if param.required {
+                return Err(CommandError::Validation(format!(
+                    "'{}' is missing required parameter '{}'",
+                    command_id, param.name
+                )));
+            }
+            continue;
+        };
+
+        if value.is_null() {
+            if param.nullable {
+                continue;
+            }
+            return Err(CommandError::Validation(format!(
+                "parameter '{}' of '{}' must not be null",
+                param.name, command_id
+            )));
+        }
+        if let Some(expected) = &param.json_type {
+            if !matches_type(expected, value) {
+                return Err(CommandError::Validation(format!(
+                    "parameter '{}' of '{}' must be {}, got {}",
+                    param.name, command_id, with_article(expected), json_type_name(value)
+                )));
+            }
+        }
+    }
+
+    if !signature.accepts_extra {
+        if let Some(unknown) = args.keys().find(|k| !signature.params.iter().any(|p| &p.name == *k)) {
+            return Err(CommandError::Validation(format!(
+                "'{}' does not take a parameter '{}'",
+                command_id, unknown
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn matches_type(expected: &str, value: &serde_json::Value) -> bool {
+    match expected {
+        "string" => value.is_string(),
+        "integer" => value.is_i64() || value.is_u64(),
+        // Python accepts an int where a float is annotated
+        "number" => value.is_number(),
+        "boolean" => value.is_boolean(),
+        "array" => value.is_array(),
+        "object" => value.is_object(),
+        _ => true,
+    }
+}
+
+fn json_type_name(value: &serde_json::Value) -> &'static str {
+    match value {
+        serde_json::Value::Null => "null",
+        serde_json::Value::Bool(_) => "boolean",
+        serde_json::Value::Number(n) if n.is_f64() => "number",
+        serde_json::Value::Number(_) => "integer",
+        serde_json::Value::String(_) => "string",
+        serde_json::Value::Array(_) => "array",
+        serde_json::Value::Object(_) => "object",
+    }
+}
+
+fn with_article(type_name: &str) -> String {
+    match type_name {
+        "integer" | "array" | "object" => format!("an {}", type_name),
+        _ => format!("a {}", type_name),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signature(accepts_extra: bool) -> MethodSignature {
+        MethodSignature {
+            params: vec![
+                ParamSpec { name: "query".to_string(), json_type: Some("string".to_string()), required: true, nullable: false },
+                ParamSpec { name: "limit".to_string(), json_type: Some("integer".to_string()), required: false, nullable: true },
+            ],
+            accepts_extra,
+            annotated: true,
+        }
+    }
+
+    fn args(value: serde_json::Value) -> serde_json::Map<String, serde_json::Value> {
+        value.as_object().unwrap().clone()
+    }
+
+    #[test]
+    fn bad_arguments_are_validation_errors() {
+        let sig = signature(false);
+        for bad in [
+            serde_json::json!({}),
+            serde_json::json!({ "query": 3 }),
+            serde_json::json!({ "query": null }),
+            serde_json::json!({ "query": "notes", "extra": true }),
+        ] {
+            let error = validate_args("search.find", &sig, &args(bad.clone())).unwrap_err();
+            assert_eq!(error.code(), "VALIDATION", "{}", bad);
+        }
+        assert_eq!(
+            validate_args("search.find", &sig, &args(serde_json::json!({}))).unwrap_err().message(),
+            "'search.find' is missing required parameter 'query'"
+        );
+    }
+
+    #[test]
+    fn matching_arguments_pass() {
+        let sig = signature(true);
+        validate_args("search.find", &sig, &args(serde_json::json!({ "query": "notes", "limit": null, "extra": 1 }))).unwrap();
+    }
+}
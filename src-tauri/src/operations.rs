@@ -0,0 +1,93 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use serde::{Deserialize, Serialize};
+use anyhow::{Result, Context};
+
+use crate::connection_pool::ConnectionPool;
+use crate::fs_utils::atomic_write;
+
+/// Event type the sidecar emits whenever a plugin reports a checkpoint
+pub const CHECKPOINT_EVENT: &str = "operation.checkpoint";
+
+/// Last reported state of a long-running plugin operation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OperationCheckpoint {
+    pub operation_id: String,
+    pub plugin: String,
+    pub vault_path: String,
+    /// Opaque plugin state handed back on resume
+    #[serde(default)]
+    pub checkpoint: serde_json::Value,
+    #[serde(default)]
+    pub progress: Option<f64>,
+    #[serde(default)]
+    pub message: Option<String>,
+    /// "running", "completed" or "failed"
+    pub status: String,
+    /// Seconds since the epoch, as reported by the sidecar
+    pub updated: f64,
+}
+
+/// Checkpoints live in the vault so they survive app and sidecar restarts
+fn checkpoint_path(vault_path: &Path, operation_id: &str) -> Result<PathBuf> {
+    let valid = !operation_id.is_empty()
+        && operation_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        anyhow::bail!("Invalid operation id: {:?}", operation_id);
+    }
+    Ok(vault_path
+        .join(".tailor")
+        .join("operations")
+        .join(format!("{}.json", operation_id)))
+}
+
+pub fn save_checkpoint(checkpoint: &OperationCheckpoint) -> Result<()> {
+    let path = checkpoint_path(Path::new(&checkpoint.vault_path), &checkpoint.operation_id)?;
+    atomic_write(&path, serde_json::to_string_pretty(checkpoint)?.as_bytes())
+}
+
+pub fn load_checkpoint(vault_path: &Path, operation_id: &str) -> Result<Option<OperationCheckpoint>> {
+    let path = checkpoint_path(vault_path, operation_id)?;
+    if !path.exists() {
+        return Ok(None);
+    }
+    let contents = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let checkpoint = serde_json::from_str(&contents)
+        .with_context(|| format!("Failed to parse {}", path.display()))?;
+    Ok(Some(checkpoint))
+}
+
+/// Persist every checkpoint event the sidecars broadcast
+pub fn spawn_checkpoint_recorder(pool: Arc<ConnectionPool>) {
+    let mut events = pool.subscribe();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let params = match events.recv().await {
+                Ok((_port, params)) => params,
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                    eprintln!("Warning: Checkpoint recorder skipped {} events", skipped);
+                    continue;
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+            };
+
+            if params.get("event_type").and_then(|t| t.as_str()) != Some(CHECKPOINT_EVENT) {
+                continue;
+            }
+            let Some(data) = params.get("data").cloned() else { continue };
+
+            match serde_json::from_value::<OperationCheckpoint>(data) {
+                Ok(checkpoint) => {
+                    if let Err(e) = save_checkpoint(&checkpoint) {
+                        eprintln!("Warning: Failed to persist checkpoint {}: {}", checkpoint.operation_id, e);
+                    }
+                }
+                Err(e) => eprintln!("Warning: Malformed checkpoint event: {}", e),
+            }
+        }
+    });
+}
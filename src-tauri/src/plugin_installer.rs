@@ -0,0 +1,196 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use tokio::process::Command;
+
+use crate::command_error::CommandError;
+use crate::fs_utils::atomic_write;
+use crate::plugins::{PLUGINS_DIR, PLUGIN_MAIN_FILE};
+
+/// Written into an installed plugin's folder, so updates know where to pull from
+pub const PLUGIN_SOURCE_FILE: &str = ".tailor-source.json";
+/// Limit for cloning a plugin repository
+const CLONE_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Where an installed plugin came from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginSource {
+    pub repo_url: String,
+    pub revision: Option<String>,
+    pub installed_at: String,
+}
+
+/// Shallow-clone `repo_url` into the vault as `plugin_name`.
+///
+/// The clone is made in a hidden staging folder and only moved into
+/// `plugins/` once it has a `main.py`, so a failed install leaves nothing
+/// behind. An existing plugin of that name is an error unless `update` is
+/// set, in which case it is replaced (and restored if the swap fails).
+/// A missing git or a failed clone is `DependencyFailed`; a refused clone
+/// is `AuthRequired`, `Network` or `NotFound` depending on why.
+pub async fn install_from_git(
+    vault_path: &Path,
+    repo_url: &str,
+    plugin_name: &str,
+    update: bool,
+) -> Result<PathBuf, CommandError> {
+    if !is_valid_repo_url(repo_url) {
+        return Err(CommandError::Validation(format!("{:?} is not a git repository URL", repo_url)));
+    }
+    let plugins_dir = vault_path.join(PLUGINS_DIR);
+    let target = plugins_dir.join(plugin_name);
+    if target.exists() && !update {
+        return Err(CommandError::Validation(format!(
+            "plugin '{}' already exists; pass update to replace it",
+            plugin_name
+        )));
+    }
+    fs::create_dir_all(&plugins_dir)
+        .map_err(|e| CommandError::Io(format!("Failed to create plugins directory: {}", e)))?;
+
+    let id = uuid::Uuid::new_v4();
+    let staging = plugins_dir.join(format!(".{}.{}.tmp", plugin_name, id));
+    let result = async {
+        clone(repo_url, &staging).await?;
+        if !staging.join(PLUGIN_MAIN_FILE).is_file() {
+            return Err(CommandError::Validation(format!("repository has no {}", PLUGIN_MAIN_FILE)));
+        }
+        let source = PluginSource {
+            repo_url: repo_url.to_string(),
+            revision: revision(&staging).await,
+            installed_at: chrono::Utc::now().to_rfc3339(),
+        };
+        let contents = serde_json::to_vec_pretty(&source)
+            .map_err(|e| CommandError::Internal(e.to_string()))?;
+        atomic_write(&staging.join(PLUGIN_SOURCE_FILE), &contents)
+            .map_err(|e| CommandError::Io(format!("Failed to record plugin source: {}", e)))
+    }
+    .await;
+    if let Err(e) = result {
+        let _ = fs::remove_dir_all(&staging);
+        return Err(e);
+    }
+
+    let previous = plugins_dir.join(format!(".{}.{}.old", plugin_name, id));
+    if target.exists() {
+        fs::rename(&target, &previous).map_err(|e| {
+            let _ = fs::remove_dir_all(&staging);
+            CommandError::Io(format!("Failed to replace plugin '{}': {}", plugin_name, e))
+        })?;
+    }
+    if let Err(e) = fs::rename(&staging, &target) {
+        let _ = fs::rename(&previous, &target);
+        let _ = fs::remove_dir_all(&staging);
+        return Err(CommandError::Io(format!("Failed to install plugin '{}': {}", plugin_name, e)));
+    }
+    let _ = fs::remove_dir_all(&previous);
+    Ok(target)
+}
+
+/// URLs git can clone remotely: a known scheme or scp-like `user@host:path`.
+/// Anything starting with `-` is refused so it can't be read as an option.
+fn is_valid_repo_url(url: &str) -> bool {
+    const SCHEMES: &[&str] = &["https://", "http://", "ssh://", "git://"];
+    if url.is_empty() || url.starts_with('-') || url.chars().any(char::is_whitespace) {
+        return false;
+    }
+    if SCHEMES.iter().any(|scheme| url.starts_with(scheme)) {
+        return true;
+    }
+    url.split_once(':')
+        .is_some_and(|(host, path)| host.contains('@') && !host.contains('/') && !path.is_empty())
+}
+
+async fn clone(repo_url: &str, dest: &Path) -> Result<(), CommandError> {
+    let mut command = Command::new("git");
+    command
+        .args(["clone", "--depth", "1", "--quiet", "--"])
+        .arg(repo_url)
+        .arg(dest)
+        // Fail instead of waiting on a credential prompt nobody can answer
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .env("GIT_SSH_COMMAND", "ssh -o BatchMode=yes")
+        .kill_on_drop(true);
+
+    let output = match tokio::time::timeout(CLONE_TIMEOUT, command.output()).await {
+        Ok(Ok(output)) => output,
+        Ok(Err(e)) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Err(CommandError::DependencyFailed("git is not installed or not on PATH".to_string()));
+        }
+        Ok(Err(e)) => return Err(CommandError::DependencyFailed(format!("could not run git: {}", e))),
+        Err(_) => {
+            return Err(CommandError::Network(format!("clone timed out after {}s", CLONE_TIMEOUT.as_secs())));
+        }
+    };
+    if output.status.success() {
+        return Ok(());
+    }
+    Err(classify_clone_error(String::from_utf8_lossy(&output.stderr).trim()))
+}
+
+/// Turn git's stderr into an error the frontend can explain
+fn classify_clone_error(stderr: &str) -> CommandError {
+    let lower = stderr.to_lowercase();
+    let has = |needles: &[&str]| needles.iter().any(|n| lower.contains(n));
+
+    let (variant, message): (fn(String) -> CommandError, &str) = if has(&["authentication failed", "could not read username", "terminal prompts disabled", "permission denied", "host key verification failed"]) {
+        (CommandError::AuthRequired, "repository is private or needs credentials")
+    } else if has(&["could not resolve host", "unable to access", "connection refused", "connection timed out", "network is unreachable", "operation timed out"]) {
+        (CommandError::Network, "could not reach the repository host")
+    } else if has(&["repository not found", "not found", "does not appear to be a git repository"]) {
+        (CommandError::NotFound, "repository does not exist")
+    } else {
+        (CommandError::DependencyFailed, "git clone failed")
+    };
+    if stderr.is_empty() {
+        variant(message.to_string())
+    } else {
+        variant(format!("{} ({})", message, stderr))
+    }
+}
+
+async fn revision(dir: &Path) -> Option<String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .current_dir(dir)
+        .output()
+        .await
+        .ok()?;
+    output.status.success().then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clone_failures_are_classified_by_cause() {
+        let cases = [
+            ("fatal: Authentication failed for 'https://github.com/acme/private.git/'", "AUTH_REQUIRED"),
+            ("fatal: could not read Username for 'https://github.com': terminal prompts disabled", "AUTH_REQUIRED"),
+            ("fatal: unable to access 'https://example.invalid/x.git/': Could not resolve host: example.invalid", "NETWORK"),
+            ("remote: Repository not found.\nfatal: repository 'https://github.com/acme/nope.git/' not found", "NOT_FOUND"),
+            ("fatal: the remote end hung up unexpectedly", "DEPENDENCY_FAILED"),
+            ("", "DEPENDENCY_FAILED"),
+        ];
+        for (stderr, code) in cases {
+            assert_eq!(classify_clone_error(stderr).code(), code, "{}", stderr);
+        }
+        assert_eq!(classify_clone_error("").message(), "git clone failed");
+    }
+
+    #[tokio::test]
+    async fn bad_urls_and_existing_plugins_are_rejected_before_cloning() {
+        let vault = std::env::temp_dir().join(format!("tailor-installer-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(vault.join(PLUGINS_DIR).join("notes")).unwrap();
+
+        let error = install_from_git(&vault, "--upload-pack=evil", "notes", false).await.unwrap_err();
+        assert_eq!(error.code(), "VALIDATION");
+        let error = install_from_git(&vault, "https://github.com/acme/notes.git", "notes", false).await.unwrap_err();
+        assert_eq!(error.code(), "VALIDATION");
+        assert!(error.message().contains("already exists"));
+
+        fs::remove_dir_all(&vault).unwrap();
+    }
+}
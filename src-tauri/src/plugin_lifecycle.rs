@@ -0,0 +1,105 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+use crate::connection_pool::ConnectionPool;
+use crate::sidecar_manager::SidecarManager;
+
+/// Event type the sidecar emits for lifecycle traces
+pub const LIFECYCLE_EVENT: &str = "PLUGIN_LIFECYCLE";
+/// Entries remembered per plugin and window
+const ENTRIES_PER_PLUGIN: usize = 200;
+
+/// One step in a plugin's life: "loaded", "unloaded", "tick_started",
+/// "tick_finished", "command_received" or "command_completed"
+#[derive(Debug, Clone, Serialize)]
+pub struct LifecycleEntry {
+    pub window_label: String,
+    pub plugin: String,
+    pub phase: String,
+    /// Seconds since the epoch, as reported by the sidecar
+    pub timestamp: f64,
+    /// Phase-specific fields such as `command`, `duration_ms` and `error`
+    pub details: serde_json::Value,
+}
+
+/// Rolling buffer of recent lifecycle entries, keyed by window then plugin
+#[derive(Default)]
+pub struct LifecycleLog {
+    windows: Mutex<HashMap<String, HashMap<String, VecDeque<LifecycleEntry>>>>,
+}
+
+impl LifecycleLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&self, entry: LifecycleEntry) {
+        let mut windows = self.windows.lock().unwrap();
+        let entries = windows
+            .entry(entry.window_label.clone())
+            .or_default()
+            .entry(entry.plugin.clone())
+            .or_default();
+        if entries.len() == ENTRIES_PER_PLUGIN {
+            entries.pop_front();
+        }
+        entries.push_back(entry);
+    }
+
+    /// Oldest first
+    pub fn history(&self, window_label: &str, plugin: &str) -> Vec<LifecycleEntry> {
+        self.windows.lock().unwrap()
+            .get(window_label)
+            .and_then(|plugins| plugins.get(plugin))
+            .map(|entries| entries.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+}
+
+/// Keep lifecycle traces from every sidecar and forward each as
+/// `plugin-lifecycle://{window_label}`. The sidecar only emits them when
+/// the vault's `debugPluginLifecycle` setting is on.
+pub fn spawn_lifecycle_forwarder(
+    app: AppHandle,
+    pool: Arc<ConnectionPool>,
+    sidecar_manager: Arc<SidecarManager>,
+    log: Arc<LifecycleLog>,
+) {
+    let mut events = pool.subscribe();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let (port, params) = match events.recv().await {
+                Ok(event) => event,
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                    eprintln!("Warning: Lifecycle forwarder skipped {} events", skipped);
+                    continue;
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+            };
+
+            if params.get("event_type").and_then(|t| t.as_str()) != Some(LIFECYCLE_EVENT) {
+                continue;
+            }
+            let Some(data) = params.get("data").and_then(|d| d.as_object()) else { continue };
+            let Some(window_label) = sidecar_manager.label_for_port(port).await else { continue };
+
+            let mut details = data.clone();
+            let plugin = details.remove("plugin").and_then(|v| v.as_str().map(str::to_string));
+            let phase = details.remove("phase").and_then(|v| v.as_str().map(str::to_string));
+            let timestamp = details.remove("timestamp").and_then(|v| v.as_f64()).unwrap_or_default();
+            let (Some(plugin), Some(phase)) = (plugin, phase) else { continue };
+
+            let entry = LifecycleEntry {
+                window_label: window_label.clone(),
+                plugin,
+                phase,
+                timestamp,
+                details: serde_json::Value::Object(details),
+            };
+            let _ = app.emit(&format!("plugin-lifecycle://{}", window_label), &entry);
+            log.record(entry);
+        }
+    });
+}
@@ -0,0 +1,174 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use tokio::process::Command;
+use anyhow::{Result, Context};
+
+use crate::fs_utils::copy_dir_recursive;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UpdateStatus {
+    Updated,
+    UpToDate,
+    /// Update failed; the previously installed version was restored
+    RolledBack,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginUpdateOutcome {
+    pub plugin: String,
+    pub status: UpdateStatus,
+    pub from_rev: Option<String>,
+    pub to_rev: Option<String>,
+    pub error: Option<String>,
+}
+
+pub struct PluginUpdater;
+
+impl PluginUpdater {
+    /// Plugins that can be updated in place (git checkouts with a `main.py`)
+    pub fn updatable_plugins(vault_path: &Path) -> Vec<String> {
+        let plugins_dir = vault_path.join("plugins");
+        let mut names = Vec::new();
+
+        if let Ok(entries) = fs::read_dir(&plugins_dir) {
+            for entry in entries.flatten() {
+                let path = entry.path();
+                let name = entry.file_name().to_string_lossy().to_string();
+                if name.starts_with('.') || name.starts_with('_') {
+                    continue;
+                }
+                if path.join(".git").exists() && path.join("main.py").exists() {
+                    names.push(name);
+                }
+            }
+        }
+
+        names.sort();
+        names
+    }
+
+    /// Check for and apply an update to a single plugin.
+    ///
+    /// The plugin directory is backed up first; if the pull or the post-update
+    /// validation fails, the backup is restored so the vault keeps working.
+    pub async fn update_plugin(vault_path: &Path, plugin_name: &str) -> PluginUpdateOutcome {
+        let plugin_dir = vault_path.join("plugins").join(plugin_name);
+        let from_rev = Self::git_rev(&plugin_dir, "HEAD").await.ok();
+
+        let mut outcome = PluginUpdateOutcome {
+            plugin: plugin_name.to_string(),
+            status: UpdateStatus::Failed,
+            from_rev: from_rev.clone(),
+            to_rev: None,
+            error: None,
+        };
+
+        // Fetch and compare before touching anything on disk
+        if let Err(e) = Self::git(&plugin_dir, &["fetch", "--quiet"]).await {
+            outcome.error = Some(format!("Failed to check for updates: {}", e));
+            return outcome;
+        }
+        let upstream_rev = match Self::git_rev(&plugin_dir, "@{u}").await {
+            Ok(rev) => rev,
+            Err(e) => {
+                outcome.error = Some(format!("No upstream to update from: {}", e));
+                return outcome;
+            }
+        };
+        if from_rev.as_deref() == Some(upstream_rev.as_str()) {
+            outcome.status = UpdateStatus::UpToDate;
+            outcome.to_rev = from_rev;
+            return outcome;
+        }
+
+        let backup_dir = match Self::backup(vault_path, plugin_name) {
+            Ok(dir) => dir,
+            Err(e) => {
+                outcome.error = Some(format!("Failed to back up plugin: {}", e));
+                return outcome;
+            }
+        };
+
+        let result: Result<()> = async {
+            Self::git(&plugin_dir, &["pull", "--ff-only", "--quiet"]).await?;
+            if !plugin_dir.join("main.py").exists() {
+                anyhow::bail!("Updated plugin is missing main.py");
+            }
+            Ok(())
+        }
+        .await;
+
+        match result {
+            Ok(()) => {
+                outcome.status = UpdateStatus::Updated;
+                outcome.to_rev = Self::git_rev(&plugin_dir, "HEAD").await.ok();
+                let _ = fs::remove_dir_all(&backup_dir);
+            }
+            Err(e) => {
+                outcome.error = Some(e.to_string());
+                match Self::restore(&backup_dir, &plugin_dir) {
+                    Ok(()) => outcome.status = UpdateStatus::RolledBack,
+                    Err(restore_err) => {
+                        outcome.error = Some(format!(
+                            "{}; restore failed, backup kept at {}: {}",
+                            e,
+                            backup_dir.display(),
+                            restore_err
+                        ));
+                    }
+                }
+            }
+        }
+
+        outcome
+    }
+
+    /// Copy a plugin into `.tailor/plugin-backups/<name>-<timestamp>`
+    pub fn backup(vault_path: &Path, plugin_name: &str) -> Result<PathBuf> {
+        let backup_dir = vault_path
+            .join(".tailor")
+            .join("plugin-backups")
+            .join(format!("{}-{}", plugin_name, chrono::Utc::now().format("%Y%m%d%H%M%S")));
+
+        copy_dir_recursive(&vault_path.join("plugins").join(plugin_name), &backup_dir)?;
+        Ok(backup_dir)
+    }
+
+    /// Replace `plugin_dir` with the contents of `backup_dir`
+    pub fn restore(backup_dir: &Path, plugin_dir: &Path) -> Result<()> {
+        if plugin_dir.exists() {
+            fs::remove_dir_all(plugin_dir)
+                .with_context(|| format!("Failed to remove {}", plugin_dir.display()))?;
+        }
+        fs::rename(backup_dir, plugin_dir)
+            .or_else(|_| copy_dir_recursive(backup_dir, plugin_dir))
+            .context("Failed to restore plugin backup")?;
+        let _ = fs::remove_dir_all(backup_dir);
+        Ok(())
+    }
+
+    async fn git_rev(dir: &Path, rev: &str) -> Result<String> {
+        Self::git(dir, &["rev-parse", rev]).await
+    }
+
+    async fn git(dir: &Path, args: &[&str]) -> Result<String> {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .await
+            .context("Failed to run git")?;
+
+        if !output.status.success() {
+            anyhow::bail!(
+                "git {} failed: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+}
@@ -0,0 +1,148 @@
+use std::path::Path;
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use anyhow::{Result, Context};
+
+use crate::dependency_checker::DependencyChecker;
+use crate::plugins;
+use crate::python_compat::{self, PYTHON_REQUIRES_FIELD};
+
+/// Longest the Python checks may take; importing `main.py` runs its top-level code
+const CODE_CHECK_TIMEOUT: Duration = Duration::from_secs(30);
+/// Manifest fields every plugin must declare when it has a manifest
+const REQUIRED_MANIFEST_FIELDS: &[&str] = &["name", "version"];
+
+/// One named part of the plugin contract and whether the plugin meets it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidationCheck {
+    pub name: String,
+    pub passed: bool,
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginValidation {
+    /// Every check passed
+    pub valid: bool,
+    pub checks: Vec<ValidationCheck>,
+    /// Why importing `main.py` failed, e.g. a `SyntaxError`
+    pub traceback: Option<String>,
+}
+
+impl ValidationCheck {
+    fn new(name: &str, problem: Option<String>) -> Self {
+        Self { name: name.to_string(), passed: problem.is_none(), message: problem }
+    }
+}
+
+/// Check the plugin in `plugin_dir` the way the sidecar would load it: the
+/// code checks run `sidecar.plugin_validator` under the sidecar's Python,
+/// then its manifest (if it has one) is checked here
+pub async fn validate(plugin_dir: &Path) -> Result<PluginValidation> {
+    let mut validation = code_checks(plugin_dir).await?;
+    validation.checks.extend(manifest_checks(plugin_dir));
+    validation.valid = validation.checks.iter().all(|check| check.passed);
+    Ok(validation)
+}
+
+async fn code_checks(plugin_dir: &Path) -> Result<PluginValidation> {
+    let python = DependencyChecker::get_python_executable()?;
+    // The sidecar package lives next to src-tauri, as when spawning sidecars
+    let project_root = std::env::current_dir()?
+        .parent()
+        .context("Failed to get parent directory")?
+        .to_path_buf();
+
+    let run = tokio::process::Command::new(&python)
+        .args(["-m", "sidecar.plugin_validator"])
+        .arg(plugin_dir)
+        .current_dir(&project_root)
+        .kill_on_drop(true)
+        .output();
+    let Ok(output) = tokio::time::timeout(CODE_CHECK_TIMEOUT, run).await else {
+        return Ok(PluginValidation {
+            valid: false,
+            checks: vec![ValidationCheck::new("import", Some(format!(
+                "Importing {} did not finish within {}s",
+                plugins::PLUGIN_MAIN_FILE,
+                CODE_CHECK_TIMEOUT.as_secs()
+            )))],
+            traceback: None,
+        });
+    };
+    let output = output.with_context(|| format!("Failed to run {}", python))?;
+
+    serde_json::from_slice(&output.stdout).with_context(|| {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let reason = stderr.lines().rev().find(|line| !line.trim().is_empty()).unwrap_or("no output");
+        format!("Plugin validator failed: {}", reason.trim())
+    })
+}
+
+/// Checks on the plugin's manifest; none when it has no manifest file
+fn manifest_checks(plugin_dir: &Path) -> Vec<ValidationCheck> {
+    let has_manifest = plugins::MANIFEST_FILES.iter()
+        .chain([&plugins::TOML_MANIFEST_FILE])
+        .any(|name| plugin_dir.join(name).is_file());
+    if !has_manifest {
+        return Vec::new();
+    }
+    let manifest = match plugins::load_manifest(plugin_dir) {
+        Ok(manifest) => manifest,
+        Err(reason) => return vec![ValidationCheck::new("manifest", Some(reason))],
+    };
+    let field = |key: &str| manifest.get(key).and_then(|v| v.as_str()).map(str::trim).filter(|v| !v.is_empty());
+
+    let mut checks = vec![ValidationCheck::new("manifest", None)];
+    let missing: Vec<&str> = REQUIRED_MANIFEST_FIELDS.iter().copied().filter(|key| field(key).is_none()).collect();
+    checks.push(ValidationCheck::new(
+        "manifest_fields",
+        (!missing.is_empty()).then(|| format!("Manifest is missing {}", missing.join(", "))),
+    ));
+    if let Some(version) = field("version") {
+        checks.push(ValidationCheck::new(
+            "manifest_version",
+            (!is_semver(version)).then(|| format!("version {:?} is not MAJOR.MINOR.PATCH", version)),
+        ));
+    }
+    if let Some(spec) = field(PYTHON_REQUIRES_FIELD) {
+        checks.push(ValidationCheck::new(
+            "manifest_python_requires",
+            python_compat::satisfies(spec, "3.0.0")
+                .err()
+                .map(|e| format!("Invalid {} {:?}: {}", PYTHON_REQUIRES_FIELD, spec, e)),
+        ));
+    }
+    checks
+}
+
+/// `MAJOR.MINOR.PATCH`, optionally followed by `-prerelease` and/or `+build`
+fn is_semver(version: &str) -> bool {
+    let core = version.split(['-', '+']).next().unwrap_or("");
+    let parts: Vec<&str> = core.split('.').collect();
+    let suffix_ok = version[core.len()..].chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '+' | '.'));
+    parts.len() == 3
+        && parts.iter().all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit()))
+        && suffix_ok
+        && !version.ends_with(['-', '+', '.'])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_semver_versions() {
+        for version in ["1.0.0", "0.12.3", "2.0.0-beta.1", "1.4.2+build.7", "1.0.0-rc.1+sha.5114f85"] {
+            assert!(is_semver(version), "{} should be valid", version);
+        }
+    }
+
+    #[test]
+    fn rejects_other_version_formats() {
+        for version in ["1.0", "1", "v1.0.0", "1.0.0.0", "1..0", "1.0.0-", "1.x.0", "1.0.0 beta"] {
+            assert!(!is_semver(version), "{} should be invalid", version);
+        }
+    }
+}
@@ -0,0 +1,308 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use serde::{Deserialize, Serialize};
+
+use crate::fs_utils::atomic_write;
+
+/// Directory (relative to the vault root) holding one folder per plugin
+pub const PLUGINS_DIR: &str = "plugins";
+/// Manifest file names, in order of preference
+pub const MANIFEST_FILES: &[&str] = &["plugin.json", "manifest.json"];
+/// TOML manifest, used when a plugin has no JSON one
+pub const TOML_MANIFEST_FILE: &str = "plugin.toml";
+/// Per-plugin defaults file, also read by the sidecar
+pub const PLUGIN_SETTINGS_FILE: &str = "settings.json";
+/// Directory (under app data) of plugins shared across vaults
+pub const SHARED_PLUGINS_DIR: &str = "shared-plugins";
+/// `.vault.json` key listing the shared plugins a vault loads
+pub const SHARED_PLUGINS_SETTING: &str = "sharedPlugins";
+/// Entry point every plugin has
+pub const PLUGIN_MAIN_FILE: &str = "main.py";
+
+static SHARED_PLUGINS_ROOT: OnceLock<PathBuf> = OnceLock::new();
+
+/// Set where shared plugins live; called once at startup
+pub fn set_shared_plugins_root(root: PathBuf) {
+    let _ = SHARED_PLUGINS_ROOT.set(root);
+}
+
+pub fn shared_plugins_root() -> Option<&'static Path> {
+    SHARED_PLUGINS_ROOT.get().map(PathBuf::as_path)
+}
+
+/// Whether a plugin lives in the vault or in the shared plugins directory
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PluginScope {
+    Local,
+    Shared,
+}
+
+/// A plugin available to a vault, local or shared
+#[derive(Debug, Clone, Serialize)]
+pub struct InstalledPlugin {
+    pub name: String,
+    pub path: String,
+    pub scope: PluginScope,
+    /// A local plugin shadowing a shared plugin of the same name that the
+    /// vault also lists in `sharedPlugins`
+    pub overrides_shared: bool,
+    /// Manifest `name`, falling back to the folder name
+    pub display_name: String,
+    pub version: Option<String>,
+    pub description: Option<String>,
+    pub author: Option<String>,
+    pub enabled: bool,
+    /// False when the manifest is missing or unreadable; `invalid_reason` says why
+    pub valid: bool,
+    pub invalid_reason: Option<String>,
+}
+
+impl InstalledPlugin {
+    /// Describe the plugin in `dir` from its manifest; `vault_config` decides `enabled`
+    pub fn new(name: String, dir: &Path, scope: PluginScope, overrides_shared: bool, vault_config: &serde_json::Value) -> Self {
+        let manifest = load_manifest(dir);
+        let field = |key: &str| {
+            manifest.as_ref()
+                .ok()
+                .and_then(|m| m.get(key))
+                .and_then(|v| v.as_str())
+                .map(str::to_string)
+        };
+        Self {
+            display_name: field("name").unwrap_or_else(|| name.clone()),
+            version: field("version"),
+            description: field("description"),
+            author: field("author"),
+            enabled: is_enabled(vault_config, dir),
+            valid: manifest.is_ok(),
+            invalid_reason: manifest.err(),
+            path: dir.to_string_lossy().to_string(),
+            name,
+            scope,
+            overrides_shared,
+        }
+    }
+}
+
+/// Snapshot of an installed plugin, as exported for comparison or display
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PluginManifestEntry {
+    pub name: String,
+    pub version: Option<String>,
+    pub enabled: bool,
+    pub has_manifest: bool,
+}
+
+/// Plugin directories of a vault: its own folders (skipping hidden ones and
+/// `__pycache__`), then the shared plugins it lists that it doesn't override
+pub fn plugin_dirs(vault_path: &Path) -> Vec<PathBuf> {
+    let mut dirs = local_plugin_dirs(vault_path);
+    let local: Vec<String> = dirs.iter().filter_map(|d| dir_name(d)).collect();
+    dirs.extend(
+        shared_plugin_dirs(&read_vault_config(vault_path))
+            .into_iter()
+            .filter(|dir| dir_name(dir).is_some_and(|name| !local.contains(&name))),
+    );
+    dirs
+}
+
+/// Every plugin available to a vault, with where it comes from. A local
+/// folder that is a symlink into the shared directory counts as shared.
+pub fn installed_plugins(vault_path: &Path) -> Vec<InstalledPlugin> {
+    let vault_config = read_vault_config(vault_path);
+    let listed = shared_plugin_names(&vault_config);
+    let local = local_plugin_dirs(vault_path);
+    let local_names: Vec<String> = local.iter().filter_map(|d| dir_name(d)).collect();
+
+    let mut plugins: Vec<InstalledPlugin> = local.iter()
+        .filter_map(|dir| {
+            let name = dir_name(dir)?;
+            let shared = is_in_shared_root(dir);
+            let overrides_shared = !shared && listed.contains(&name);
+            let scope = if shared { PluginScope::Shared } else { PluginScope::Local };
+            Some(InstalledPlugin::new(name, dir, scope, overrides_shared, &vault_config))
+        })
+        .collect();
+    for dir in shared_plugin_dirs(&vault_config) {
+        let Some(name) = dir_name(&dir) else { continue };
+        if local_names.contains(&name) {
+            continue;
+        }
+        plugins.push(InstalledPlugin::new(name, &dir, PluginScope::Shared, false, &vault_config));
+    }
+    plugins.sort_by(|a, b| a.name.cmp(&b.name));
+    plugins
+}
+
+/// The directory a vault loads `plugin_name` from, local first
+pub fn plugin_dir(vault_path: &Path, plugin_name: &str) -> Option<PathBuf> {
+    plugin_dirs(vault_path).into_iter().find(|dir| dir_name(dir).as_deref() == Some(plugin_name))
+}
+
+/// Names in the vault's `sharedPlugins` list
+pub fn shared_plugin_names(vault_config: &serde_json::Value) -> Vec<String> {
+    vault_config.get(SHARED_PLUGINS_SETTING)
+        .and_then(|names| names.as_array())
+        .map(|names| {
+            names.iter()
+                .filter_map(|n| n.as_str())
+                .filter(|n| is_valid_plugin_name(n))
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Plugin folder names: no path separators, not hidden
+pub fn is_valid_plugin_name(name: &str) -> bool {
+    !name.is_empty() && !name.contains(['/', '\\']) && !name.starts_with(['.', '_'])
+}
+
+/// Listed shared plugins that exist in the shared directory
+fn shared_plugin_dirs(vault_config: &serde_json::Value) -> Vec<PathBuf> {
+    let Some(root) = shared_plugins_root() else { return Vec::new() };
+    shared_plugin_names(vault_config)
+        .into_iter()
+        .map(|name| root.join(name))
+        .filter(|dir| dir.join(PLUGIN_MAIN_FILE).is_file())
+        .collect()
+}
+
+fn is_in_shared_root(dir: &Path) -> bool {
+    let Some(root) = shared_plugins_root() else { return false };
+    let root = fs::canonicalize(root).unwrap_or_else(|_| root.to_path_buf());
+    fs::canonicalize(dir).is_ok_and(|dir| dir.starts_with(root))
+}
+
+fn dir_name(dir: &Path) -> Option<String> {
+    dir.file_name().map(|n| n.to_string_lossy().to_string())
+}
+
+/// The vault's own plugin folders, sorted
+fn local_plugin_dirs(vault_path: &Path) -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = fs::read_dir(vault_path.join(PLUGINS_DIR))
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|entry| {
+                    let name = entry.file_name().to_string_lossy().to_string();
+                    entry.path().is_dir() && !name.starts_with('.') && name != "__pycache__"
+                })
+                .map(|entry| entry.path())
+                .collect()
+        })
+        .unwrap_or_default();
+    dirs.sort();
+    dirs
+}
+
+/// Parsed manifest for a plugin directory, if it has one
+pub fn read_manifest(plugin_dir: &Path) -> Option<serde_json::Value> {
+    load_manifest(plugin_dir).ok()
+}
+
+/// The plugin's manifest (the first of `MANIFEST_FILES` present, else
+/// `plugin.toml`) as JSON, or why there isn't a usable one
+pub fn load_manifest(plugin_dir: &Path) -> Result<serde_json::Value, String> {
+    for name in MANIFEST_FILES {
+        let Ok(contents) = fs::read_to_string(plugin_dir.join(name)) else { continue };
+        return serde_json::from_str::<serde_json::Value>(&contents)
+            .map_err(|e| format!("{} is not valid JSON: {}", name, e))
+            .and_then(|manifest| require_object(manifest, name));
+    }
+    if let Ok(contents) = fs::read_to_string(plugin_dir.join(TOML_MANIFEST_FILE)) {
+        let manifest: toml::Value = toml::from_str(&contents)
+            .map_err(|e| format!("{} is not valid TOML: {}", TOML_MANIFEST_FILE, e))?;
+        let manifest = serde_json::to_value(manifest)
+            .map_err(|e| format!("{} could not be read: {}", TOML_MANIFEST_FILE, e))?;
+        return require_object(manifest, TOML_MANIFEST_FILE);
+    }
+    Err(format!("No manifest found (expected {} or {})", MANIFEST_FILES.join(", "), TOML_MANIFEST_FILE))
+}
+
+fn require_object(manifest: serde_json::Value, file: &str) -> Result<serde_json::Value, String> {
+    if manifest.is_object() {
+        Ok(manifest)
+    } else {
+        Err(format!("{} must contain an object", file))
+    }
+}
+
+/// Whether the sidecar will load a plugin: `.vault.json` `plugins.<name>.enabled`
+/// overrides the plugin's own `settings.json`, and the default is disabled.
+pub fn is_enabled(vault_config: &serde_json::Value, plugin_dir: &Path) -> bool {
+    let name = plugin_dir.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    if let Some(enabled) = vault_config
+        .get("plugins")
+        .and_then(|p| p.get(&name))
+        .and_then(|p| p.get("enabled"))
+        .and_then(|e| e.as_bool())
+    {
+        return enabled;
+    }
+
+    fs::read_to_string(plugin_dir.join(PLUGIN_SETTINGS_FILE))
+        .ok()
+        .and_then(|contents| serde_json::from_str::<serde_json::Value>(&contents).ok())
+        .and_then(|settings| settings.get("enabled").and_then(|e| e.as_bool()))
+        .unwrap_or(false)
+}
+
+/// The vault's `.vault.json`, or an empty object when missing or unreadable
+pub fn read_vault_config(vault_path: &Path) -> serde_json::Value {
+    fs::read_to_string(vault_path.join(".vault.json"))
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_else(|| serde_json::json!({}))
+}
+
+/// Record `plugins.<name>.enabled` in the vault's `.vault.json`, keeping the
+/// plugin's other overrides
+pub fn set_enabled(vault_path: &Path, plugin_name: &str, enabled: bool) -> anyhow::Result<()> {
+    let config_path = vault_path.join(".vault.json");
+    let mut vault_config: serde_json::Value = match fs::read_to_string(&config_path) {
+        Ok(contents) => serde_json::from_str(&contents)?,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => serde_json::json!({}),
+        Err(e) => return Err(e.into()),
+    };
+    let Some(config) = vault_config.as_object_mut() else {
+        anyhow::bail!("Vault config is not a JSON object");
+    };
+    let plugins = config.entry("plugins").or_insert_with(|| serde_json::json!({}));
+    if !plugins.is_object() {
+        *plugins = serde_json::json!({});
+    }
+    let entry = &mut plugins[plugin_name];
+    if !entry.is_object() {
+        *entry = serde_json::json!({});
+    }
+    entry["enabled"] = serde_json::json!(enabled);
+
+    atomic_write(&config_path, serde_json::to_string_pretty(&vault_config)?.as_bytes())
+}
+
+/// Export name, version and enabled state for every installed plugin
+pub fn export_manifests(vault_path: &Path) -> Vec<PluginManifestEntry> {
+    let vault_config = read_vault_config(vault_path);
+
+    plugin_dirs(vault_path)
+        .into_iter()
+        .map(|dir| {
+            let manifest = read_manifest(&dir);
+            PluginManifestEntry {
+                name: dir.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default(),
+                version: manifest.as_ref()
+                    .and_then(|m| m.get("version"))
+                    .and_then(|v| v.as_str())
+                    .map(|v| v.to_string()),
+                enabled: is_enabled(&vault_config, &dir),
+                has_manifest: manifest.is_some(),
+            }
+        })
+        .collect()
+}
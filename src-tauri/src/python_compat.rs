@@ -0,0 +1,120 @@
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use anyhow::{Result, Context};
+
+use crate::plugins;
+
+/// Manifest field holding a PEP 440 style specifier, e.g. ">=3.10,<3.14"
+pub const PYTHON_REQUIRES_FIELD: &str = "python_requires";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PythonCompat {
+    pub plugin: String,
+    pub compatible: bool,
+    /// None when the plugin does not declare `python_requires`
+    pub required: Option<String>,
+    pub current: String,
+}
+
+/// Compare a plugin's `python_requires` with the interpreter version `current`
+pub fn check_plugin(vault_path: &Path, plugin_name: &str, current: &str) -> Result<PythonCompat> {
+    let Some(plugin_dir) = plugins::plugin_dir(vault_path, plugin_name) else {
+        anyhow::bail!("Plugin not found: {}", plugin_name);
+    };
+
+    let required = plugins::read_manifest(&plugin_dir)
+        .and_then(|manifest| {
+            manifest.get(PYTHON_REQUIRES_FIELD)
+                .and_then(|v| v.as_str())
+                .map(str::to_string)
+        })
+        .filter(|spec| !spec.trim().is_empty());
+
+    let compatible = match &required {
+        Some(spec) => satisfies(spec, current)
+            .with_context(|| format!("Plugin '{}' has an invalid {}", plugin_name, PYTHON_REQUIRES_FIELD))?,
+        None => true,
+    };
+
+    Ok(PythonCompat {
+        plugin: plugin_name.to_string(),
+        compatible,
+        required,
+        current: current.to_string(),
+    })
+}
+
+/// Whether `version` matches every comma-separated clause of `spec`.
+///
+/// Supports `>=`, `<=`, `>`, `<`, `==`, `!=` (both with `.*`) and `~=`.
+pub fn satisfies(spec: &str, version: &str) -> Result<bool> {
+    let current = parse_version(version)?;
+
+    for clause in spec.split(',').map(str::trim).filter(|c| !c.is_empty()) {
+        let op = ["~=", "==", "!=", ">=", "<=", ">", "<"]
+            .into_iter()
+            .find(|op| clause.starts_with(op))
+            .with_context(|| format!("Invalid specifier clause: {:?}", clause))?;
+        let target_text = clause[op.len()..].trim();
+
+        if let Some(prefix_text) = target_text.strip_suffix(".*") {
+            if op != "==" && op != "!=" {
+                anyhow::bail!("Wildcards only work with == and !=: {:?}", clause);
+            }
+            let prefix = parse_version(prefix_text)?;
+            let matches = current.len() >= prefix.len() && current[..prefix.len()] == prefix[..];
+            if matches != (op == "==") {
+                return Ok(false);
+            }
+            continue;
+        }
+
+        let target = parse_version(target_text)?;
+        let width = current.len().max(target.len());
+        let lhs = padded(&current, width);
+        let rhs = padded(&target, width);
+
+        let ok = match op {
+            "~=" => {
+                if target.len() < 2 {
+                    anyhow::bail!("~= needs at least two version parts: {:?}", clause);
+                }
+                let stem = target.len() - 1;
+                lhs >= rhs && current.len() >= stem && current[..stem] == target[..stem]
+            }
+            "==" => lhs == rhs,
+            "!=" => lhs != rhs,
+            ">=" => lhs >= rhs,
+            "<=" => lhs <= rhs,
+            ">" => lhs > rhs,
+            _ => lhs < rhs,
+        };
+        if !ok {
+            return Ok(false);
+        }
+    }
+
+    Ok(true)
+}
+
+/// Leading numeric release segments, so "3.12.1rc1" parses as [3, 12, 1]
+fn parse_version(version: &str) -> Result<Vec<u64>> {
+    let mut parts = Vec::new();
+    for piece in version.trim().split('.') {
+        let digits: String = piece.chars().take_while(|c| c.is_ascii_digit()).collect();
+        if digits.is_empty() {
+            break;
+        }
+        parts.push(digits.parse()?);
+    }
+    if parts.is_empty() {
+        anyhow::bail!("Invalid version: {:?}", version);
+    }
+    Ok(parts)
+}
+
+fn padded(parts: &[u64], width: usize) -> Vec<u64> {
+    let mut parts = parts.to_vec();
+    parts.resize(width, 0);
+    parts
+}
@@ -0,0 +1,209 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use anyhow::{Result, Context};
+
+use crate::fs_utils::atomic_write;
+
+/// Recently opened/created vaults, most recent first, under the app config dir
+pub const RECENTS_FILE: &str = "vaults.json";
+/// Marks a directory as a vault
+const VAULT_CONFIG_FILE: &str = ".vault.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VaultListItem {
+    pub name: String,
+    pub path: String,
+    pub created: Option<String>,
+    /// The vault's `.vault.json` id, so its venv and cache stay attributed
+    /// while the vault itself is unreachable
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct RecentsRepair {
+    pub kept: usize,
+    /// Entries that weren't a `{name, path}` object
+    pub malformed: usize,
+    /// Entries whose vault directory no longer exists
+    pub missing: Vec<String>,
+    /// Entries pointing at a vault already listed
+    pub duplicates: Vec<String>,
+    /// Entries whose path was rewritten to its canonical form
+    pub canonicalized: Vec<String>,
+    /// Where the unreadable original was copied, if it could not be parsed at all
+    pub backup: Option<String>,
+}
+
+pub fn recents_path(app_config_dir: &Path) -> PathBuf {
+    app_config_dir.join(RECENTS_FILE)
+}
+
+/// Move a recents file left in the app data dir by older versions into the
+/// app config dir, unless one is already there
+pub fn migrate(app_config_dir: &Path, app_data_dir: &Path) -> Result<()> {
+    let old = recents_path(app_data_dir);
+    let new = recents_path(app_config_dir);
+    if old == new || !old.exists() || new.exists() {
+        return Ok(());
+    }
+    fs::create_dir_all(app_config_dir)?;
+    if fs::rename(&old, &new).is_err() {
+        // Different volumes: copy, then drop the original
+        fs::copy(&old, &new).context("Failed to copy recents")?;
+        let _ = fs::remove_file(&old);
+    }
+    println!("Moved recents to {}", new.display());
+    Ok(())
+}
+
+/// Load the recents list. A file that isn't a JSON array is backed up and
+/// treated as empty; individual malformed entries are skipped.
+pub fn load(app_config_dir: &Path) -> Vec<VaultListItem> {
+    let (entries, _) = read_entries(app_config_dir);
+    entries.into_iter()
+        .filter_map(|entry| serde_json::from_value(entry).ok())
+        .collect()
+}
+
+/// Load the recents list, failing instead of skipping anything it can't
+/// read. A missing file is an empty list; a corrupt one is left in place.
+pub fn load_strict(app_config_dir: &Path) -> Result<Vec<VaultListItem>> {
+    let path = recents_path(app_config_dir);
+    let contents = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    };
+    serde_json::from_str(&contents)
+        .with_context(|| format!("Recents file {} is corrupt", path.display()))
+}
+
+pub fn save(app_config_dir: &Path, vaults: &[VaultListItem]) -> Result<()> {
+    fs::create_dir_all(app_config_dir)?;
+    let contents = serde_json::to_string_pretty(vaults)?;
+    atomic_write(&recents_path(app_config_dir), contents.as_bytes())
+        .context("Failed to write recents")
+}
+
+/// Put a vault first in the list, adding it if it isn't listed yet
+pub fn add(app_config_dir: &Path, vault: &VaultListItem) -> Result<()> {
+    let mut vaults = load(app_config_dir);
+    if vaults.first().is_some_and(|v| v.path == vault.path && v.id == vault.id) {
+        return Ok(());
+    }
+    vaults.retain(|v| v.path != vault.path);
+    vaults.insert(0, vault.clone());
+    save(app_config_dir, &vaults)
+}
+
+/// The recents list without vaults whose directory is gone; those are
+/// dropped from the file too
+pub fn load_existing(app_config_dir: &Path) -> Vec<VaultListItem> {
+    let vaults = load(app_config_dir);
+    let total = vaults.len();
+    let existing: Vec<VaultListItem> = vaults.into_iter()
+        .filter(|v| Path::new(&v.path).join(VAULT_CONFIG_FILE).is_file())
+        .collect();
+    if existing.len() < total {
+        match save(app_config_dir, &existing) {
+            Ok(()) => println!("Dropped {} missing vault(s) from recents", total - existing.len()),
+            Err(e) => eprintln!("Warning: Failed to drop missing vaults from recents: {}", e),
+        }
+    }
+    existing
+}
+
+/// Vault directories directly under `root` (and `root` itself, if it is
+/// one). Hidden directories, such as vaults still being created, are skipped.
+pub fn discover(root: &Path) -> Vec<PathBuf> {
+    let mut found = Vec::new();
+    if root.join(VAULT_CONFIG_FILE).is_file() {
+        found.push(root.to_path_buf());
+    }
+    let Ok(entries) = fs::read_dir(root) else { return found };
+    let mut dirs: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| !entry.file_name().to_string_lossy().starts_with('.'))
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir() && path.join(VAULT_CONFIG_FILE).is_file())
+        .collect();
+    dirs.sort();
+    found.extend(dirs);
+    found
+}
+
+/// Drop malformed, missing and duplicate entries, canonicalize the rest
+/// and write the result back
+pub fn repair(app_config_dir: &Path) -> Result<RecentsRepair> {
+    let (entries, backup) = read_entries(app_config_dir);
+    let mut report = RecentsRepair { backup, ..Default::default() };
+    let mut seen = HashSet::new();
+    let mut vaults = Vec::new();
+
+    for entry in entries {
+        let Ok(mut vault) = serde_json::from_value::<VaultListItem>(entry) else {
+            report.malformed += 1;
+            continue;
+        };
+        if vault.path.trim().is_empty() {
+            report.malformed += 1;
+            continue;
+        }
+
+        let Ok(canonical) = fs::canonicalize(&vault.path) else {
+            report.missing.push(vault.path);
+            continue;
+        };
+        if !canonical.is_dir() {
+            report.missing.push(vault.path);
+            continue;
+        }
+
+        let canonical = canonical.to_string_lossy().to_string();
+        if !seen.insert(canonical.clone()) {
+            report.duplicates.push(vault.path);
+            continue;
+        }
+        if canonical != vault.path {
+            report.canonicalized.push(vault.path);
+            vault.path = canonical;
+        }
+        vaults.push(vault);
+    }
+
+    report.kept = vaults.len();
+    save(app_config_dir, &vaults)?;
+    Ok(report)
+}
+
+/// Raw entries, plus the backup path when the file had to be set aside
+fn read_entries(app_config_dir: &Path) -> (Vec<serde_json::Value>, Option<String>) {
+    let path = recents_path(app_config_dir);
+    let Ok(contents) = fs::read_to_string(&path) else {
+        return (Vec::new(), None);
+    };
+
+    match serde_json::from_str::<serde_json::Value>(&contents) {
+        Ok(serde_json::Value::Array(entries)) => (entries, None),
+        Ok(_) | Err(_) => {
+            let backup = path.with_file_name(format!(
+                "{}.corrupt-{}",
+                RECENTS_FILE,
+                chrono::Utc::now().format("%Y%m%d-%H%M%S"),
+            ));
+            match fs::rename(&path, &backup) {
+                Ok(()) => {
+                    eprintln!("Warning: Recents file was corrupt, moved to {}", backup.display());
+                    (Vec::new(), Some(backup.to_string_lossy().to_string()))
+                }
+                Err(e) => {
+                    eprintln!("Warning: Recents file is corrupt and could not be backed up: {}", e);
+                    (Vec::new(), None)
+                }
+            }
+        }
+    }
+}
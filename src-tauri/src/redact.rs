@@ -0,0 +1,65 @@
+/// Key fragments that mark a JSON key as holding a secret
+pub const SECRET_KEY_MARKERS: &[&str] = &["apikey", "api_key", "token", "secret", "password", "passphrase", "credential"];
+/// Prefixes of provider API keys that may appear inside free text
+const SECRET_VALUE_PREFIXES: &[&str] = &["sk-", "gsk_", "AIza"];
+/// Shortest run after a prefix that is treated as a key rather than prose
+const MIN_SECRET_LEN: usize = 20;
+
+pub const REDACTED: &str = "[REDACTED]";
+
+pub fn is_secret_key(key: &str) -> bool {
+    let key = key.to_ascii_lowercase();
+    SECRET_KEY_MARKERS.iter().any(|marker| key.contains(marker))
+}
+
+/// Replace secret-keyed values and redact every string, at any depth
+pub fn redact_json(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, item) in map.iter_mut() {
+                if is_secret_key(key) {
+                    *item = serde_json::Value::String(REDACTED.to_string());
+                } else {
+                    redact_json(item);
+                }
+            }
+        }
+        serde_json::Value::Array(items) => items.iter_mut().for_each(redact_json),
+        serde_json::Value::String(text) => *text = redact_text(text),
+        _ => {}
+    }
+}
+
+/// Replace the user's home directory with `~` and mask anything shaped
+/// like a provider API key
+pub fn redact_text(text: &str) -> String {
+    let mut output = match home_dir() {
+        Some(home) if !home.is_empty() => text.replace(&home, "~"),
+        _ => text.to_string(),
+    };
+
+    for prefix in SECRET_VALUE_PREFIXES {
+        let mut search_from = 0;
+        while let Some(offset) = output[search_from..].find(prefix) {
+            let start = search_from + offset;
+            let end = output[start..]
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '-' || c == '_'))
+                .map_or(output.len(), |len| start + len);
+
+            if end - start >= MIN_SECRET_LEN {
+                output.replace_range(start..end, REDACTED);
+                search_from = start + REDACTED.len();
+            } else {
+                search_from = end.max(start + prefix.len());
+            }
+        }
+    }
+
+    output
+}
+
+fn home_dir() -> Option<String> {
+    std::env::var("HOME")
+        .or_else(|_| std::env::var("USERPROFILE"))
+        .ok()
+}
@@ -0,0 +1,295 @@
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use anyhow::{Result, Context};
+
+use crate::fs_utils::atomic_write;
+use crate::settings;
+
+/// Registry index used when no mirrors are configured
+pub const DEFAULT_REGISTRY_URL: &str =
+    "https://raw.githubusercontent.com/AGS-Lab/tailor/main/plugin-registry.json";
+/// Global setting: extra registry index URLs to consider alongside the default
+pub const REGISTRY_MIRRORS_SETTING: &str = "registryMirrors";
+/// Global setting: the mirror tried first, normally the last benchmark's winner
+pub const REGISTRY_MIRROR_SETTING: &str = "registryMirror";
+/// Global setting: RFC 3339 time of the last mirror benchmark
+pub const REGISTRY_BENCHMARKED_AT_SETTING: &str = "registryMirrorBenchmarkedAt";
+
+/// Per-mirror limit for a benchmark probe
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+/// Per-mirror limit for fetching the full index
+const FETCH_TIMEOUT: Duration = Duration::from_secs(20);
+/// How stale the chosen mirror may get before it is benchmarked again
+const REBENCHMARK_AFTER: chrono::Duration = chrono::Duration::hours(24);
+/// How often the background task checks whether a benchmark is due
+const REBENCHMARK_CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+/// A cached index younger than this is used without fetching
+const INDEX_CACHE_TTL: chrono::Duration = chrono::Duration::minutes(30);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MirrorLatency {
+    pub url: String,
+    /// None when the probe failed
+    pub latency_ms: Option<u64>,
+    pub status: Option<u16>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegistryIndex {
+    /// Mirror the index was fetched from
+    pub mirror: String,
+    pub fetched: String,
+    pub index: serde_json::Value,
+    /// Served from the cache because every mirror failed
+    #[serde(default)]
+    pub stale: bool,
+}
+
+/// Mirrors in the order they should be tried: the chosen one, then the
+/// configured list, then the default
+pub fn candidate_mirrors(global_settings: &serde_json::Value) -> Vec<String> {
+    let mut mirrors: Vec<String> = Vec::new();
+    let chosen = global_settings.get(REGISTRY_MIRROR_SETTING)
+        .and_then(|v| v.as_str())
+        .map(str::to_string);
+    let configured = settings::get_string_list(global_settings, REGISTRY_MIRRORS_SETTING);
+
+    for url in chosen.into_iter().chain(configured).chain([DEFAULT_REGISTRY_URL.to_string()]) {
+        let url = url.trim().to_string();
+        if !url.is_empty() && !mirrors.contains(&url) {
+            mirrors.push(url);
+        }
+    }
+    mirrors
+}
+
+fn client(timeout: Duration) -> Result<reqwest::Client> {
+    reqwest::Client::builder()
+        .timeout(timeout)
+        .user_agent(concat!("tailor/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .context("Failed to build HTTP client")
+}
+
+/// Time a HEAD request against each mirror, fastest first; failed probes sort last
+pub async fn benchmark(mirrors: &[String]) -> Result<Vec<MirrorLatency>> {
+    let client = client(PROBE_TIMEOUT)?;
+
+    let probes = mirrors.iter().map(|url| {
+        let client = client.clone();
+        async move {
+            let started = Instant::now();
+            match client.head(url).send().await {
+                Ok(response) if response.status().is_success() => MirrorLatency {
+                    url: url.clone(),
+                    latency_ms: Some(started.elapsed().as_millis() as u64),
+                    status: Some(response.status().as_u16()),
+                    error: None,
+                },
+                Ok(response) => MirrorLatency {
+                    url: url.clone(),
+                    latency_ms: None,
+                    status: Some(response.status().as_u16()),
+                    error: Some(format!("HTTP {}", response.status())),
+                },
+                Err(e) => MirrorLatency {
+                    url: url.clone(),
+                    latency_ms: None,
+                    status: None,
+                    error: Some(e.to_string()),
+                },
+            }
+        }
+    });
+
+    let mut results = futures::future::join_all(probes).await;
+    results.sort_by_key(|r| r.latency_ms.unwrap_or(u64::MAX));
+    Ok(results)
+}
+
+/// Benchmark the candidates and store the fastest reachable one as the chosen mirror
+pub async fn benchmark_and_select(app_config_dir: &Path, auto_select: bool) -> Result<Vec<MirrorLatency>> {
+    let global = settings::load_global_settings(app_config_dir)?;
+    let results = benchmark(&candidate_mirrors(&global)).await?;
+
+    if auto_select {
+        let mut updates = serde_json::json!({
+            REGISTRY_BENCHMARKED_AT_SETTING: chrono::Utc::now().to_rfc3339(),
+        });
+        if let Some(fastest) = results.iter().find(|r| r.latency_ms.is_some()) {
+            updates[REGISTRY_MIRROR_SETTING] = serde_json::json!(fastest.url);
+        }
+        settings::save_global_settings(app_config_dir, &updates)?;
+    }
+    Ok(results)
+}
+
+/// Fetch the registry index, failing over through the candidate mirrors
+pub async fn fetch_index(app_config_dir: &Path) -> Result<RegistryIndex> {
+    let global = settings::load_global_settings(app_config_dir)?;
+    let client = client(FETCH_TIMEOUT)?;
+
+    let mut errors = Vec::new();
+    for mirror in candidate_mirrors(&global) {
+        let result = async {
+            let response = client.get(&mirror).send().await?.error_for_status()?;
+            response.json::<serde_json::Value>().await
+        }.await;
+
+        match result {
+            Ok(index) => {
+                return Ok(RegistryIndex {
+                    mirror,
+                    fetched: chrono::Utc::now().to_rfc3339(),
+                    index,
+                    stale: false,
+                });
+            }
+            Err(e) => {
+                eprintln!("Warning: Registry mirror {} failed: {}", mirror, e);
+                errors.push(format!("{}: {}", mirror, e));
+            }
+        }
+    }
+    anyhow::bail!("All registry mirrors failed: {}", errors.join("; "))
+}
+
+/// Where the last fetched index is kept
+pub fn index_cache_path(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join("registry-index.json")
+}
+
+/// The registry index: the cached copy while it is younger than the TTL
+/// (unless `force`), else a fresh fetch, which is cached. When every mirror
+/// fails the cached copy is returned anyway, marked `stale`.
+pub async fn load_index(app_config_dir: &Path, app_data_dir: &Path, force: bool) -> Result<RegistryIndex> {
+    let cache = index_cache_path(app_data_dir);
+    let cached = read_cache(&cache);
+    if !force {
+        if let Some(cached) = cached.as_ref().filter(|c| is_fresh(c)) {
+            return Ok(cached.clone());
+        }
+    }
+
+    match fetch_index(app_config_dir).await {
+        Ok(fetched) => {
+            match serde_json::to_vec(&fetched) {
+                Ok(bytes) => if let Err(e) = atomic_write(&cache, &bytes) {
+                    eprintln!("Warning: Failed to cache plugin registry: {}", e);
+                },
+                Err(e) => eprintln!("Warning: Failed to serialize plugin registry: {}", e),
+            }
+            Ok(fetched)
+        }
+        Err(e) => {
+            let Some(mut cached) = cached else { return Err(e) };
+            eprintln!("Warning: Using cached plugin registry from {}: {}", cached.fetched, e);
+            cached.stale = true;
+            Ok(cached)
+        }
+    }
+}
+
+fn read_cache(path: &Path) -> Option<RegistryIndex> {
+    let contents = std::fs::read(path).ok()?;
+    serde_json::from_slice(&contents).ok()
+}
+
+fn is_fresh(index: &RegistryIndex) -> bool {
+    chrono::DateTime::parse_from_rfc3339(&index.fetched)
+        .is_ok_and(|at| chrono::Utc::now() - at.with_timezone(&chrono::Utc) < INDEX_CACHE_TTL)
+}
+
+fn plugins(index: &RegistryIndex) -> &[serde_json::Value] {
+    index.index.get("plugins").and_then(|v| v.as_array()).map_or(&[], Vec::as_slice)
+}
+
+fn text(plugin: &serde_json::Value, key: &str) -> String {
+    plugin.get(key).and_then(|v| v.as_str()).unwrap_or("").to_lowercase()
+}
+
+/// Plugins matching `query` (case-insensitive, name or description) in
+/// `category`, best first: exact name, name prefix, name substring, then
+/// description substring. Each record carries the index's `stale` flag.
+pub fn search(index: &RegistryIndex, query: &str, category: Option<&str>) -> Vec<serde_json::Value> {
+    let query = query.trim().to_lowercase();
+    let category = category.map(str::to_lowercase).filter(|c| !c.is_empty());
+
+    let rank = |plugin: &serde_json::Value| {
+        let name = text(plugin, "name");
+        if query.is_empty() || name == query {
+            Some(0)
+        } else if name.starts_with(&query) {
+            Some(1)
+        } else if name.contains(&query) {
+            Some(2)
+        } else if text(plugin, "description").contains(&query) {
+            Some(3)
+        } else {
+            None
+        }
+    };
+    let mut matches: Vec<(u8, &serde_json::Value)> = plugins(index)
+        .iter()
+        .filter(|p| category.as_ref().map_or(true, |c| text(p, "category") == *c))
+        .filter_map(|p| rank(p).map(|r| (r, p)))
+        .collect();
+    // Stable, so plugins of equal rank keep the index's order
+    matches.sort_by_key(|(rank, _)| *rank);
+    matches.into_iter().map(|(_, plugin)| with_stale(plugin, index.stale)).collect()
+}
+
+/// The full record for `plugin_id`, matched on `id` and then on `name`
+pub fn find_plugin(index: &RegistryIndex, plugin_id: &str) -> Option<serde_json::Value> {
+    let field = |p: &serde_json::Value, key: &str| p.get(key).and_then(|v| v.as_str()) == Some(plugin_id);
+    let plugins = plugins(index);
+    plugins.iter()
+        .find(|p| field(p, "id"))
+        .or_else(|| plugins.iter().find(|p| field(p, "name")))
+        .map(|plugin| with_stale(plugin, index.stale))
+}
+
+fn with_stale(plugin: &serde_json::Value, stale: bool) -> serde_json::Value {
+    let mut plugin = plugin.clone();
+    if let Some(record) = plugin.as_object_mut() {
+        record.insert("stale".to_string(), serde_json::json!(stale));
+    }
+    plugin
+}
+
+/// Re-benchmark mirrors in the background once the last result is older
+/// than a day. Only runs when there is more than one mirror to choose from.
+pub fn spawn_mirror_benchmark(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            if let Ok(config_dir) = app.path().app_config_dir() {
+                if let Err(e) = rebenchmark_if_due(&config_dir).await {
+                    eprintln!("Warning: Registry mirror benchmark failed: {}", e);
+                }
+            }
+            tokio::time::sleep(REBENCHMARK_CHECK_INTERVAL).await;
+        }
+    });
+}
+
+async fn rebenchmark_if_due(app_config_dir: &Path) -> Result<()> {
+    let global = settings::load_global_settings(app_config_dir)?;
+    if candidate_mirrors(&global).len() < 2 {
+        return Ok(());
+    }
+
+    let last = global.get(REGISTRY_BENCHMARKED_AT_SETTING)
+        .and_then(|v| v.as_str())
+        .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok());
+    let due = match last {
+        Some(last) => chrono::Utc::now() - last.with_timezone(&chrono::Utc) > REBENCHMARK_AFTER,
+        None => true,
+    };
+    if due {
+        benchmark_and_select(app_config_dir, true).await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,236 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use serde::Serialize;
+
+use crate::connection_pool::ConnectionPool;
+use crate::sidecar_manager::SidecarManager;
+
+/// Event types sidecars use to ask for and hand back permits
+const REQUEST_PERMIT_EVENT: &str = "REQUEST_PERMIT";
+const RELEASE_PERMIT_EVENT: &str = "RELEASE_PERMIT";
+/// Permits held longer than this are reclaimed, in case a release was lost
+const LEASE_TTL: Duration = Duration::from_secs(900);
+/// How often expired permits and permits of exited sidecars are reclaimed
+const SWEEP_INTERVAL: Duration = Duration::from_secs(30);
+/// Limit for telling a sidecar its permit was granted
+const GRANT_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone)]
+struct Lease {
+    port: u16,
+    lease_id: String,
+    provider: String,
+    since: Instant,
+}
+
+#[derive(Default)]
+struct BudgetState {
+    /// None for unlimited
+    limit: Option<usize>,
+    active: Vec<Lease>,
+    queue: VecDeque<Lease>,
+}
+
+impl BudgetState {
+    /// Move queued leases into the free slots, returning those to grant
+    fn dispatch(&mut self) -> Vec<Lease> {
+        let mut granted = Vec::new();
+        while !self.limit.is_some_and(|limit| self.active.len() >= limit) {
+            let Some(mut lease) = self.queue.pop_front() else { break };
+            lease.since = Instant::now();
+            self.active.push(lease.clone());
+            granted.push(lease);
+        }
+        granted
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RequestBudgetStatus {
+    /// None when requests are unlimited
+    pub limit: Option<usize>,
+    pub in_use: usize,
+    pub waiting: usize,
+    /// Share of the limit in use, 0.0 to 1.0; None when unlimited
+    pub utilization: Option<f64>,
+    /// Permits held per provider
+    pub by_provider: HashMap<String, usize>,
+    /// Permits held per window
+    pub by_window: HashMap<String, usize>,
+}
+
+/// App-wide semaphore for provider requests. Sidecars ask with a
+/// `REQUEST_PERMIT` event, are told through `budget.grant` once a slot is
+/// free, and send `RELEASE_PERMIT` when their request ends.
+pub struct RequestBudget {
+    state: Mutex<BudgetState>,
+    pool: Arc<ConnectionPool>,
+}
+
+impl RequestBudget {
+    pub fn new(pool: Arc<ConnectionPool>, limit: Option<usize>) -> Self {
+        Self {
+            state: Mutex::new(BudgetState { limit, ..Default::default() }),
+            pool,
+        }
+    }
+
+    /// Change the cap; raising it grants waiting requests right away, while
+    /// lowering it lets permits already held run to completion
+    pub fn set_limit(self: &Arc<Self>, limit: Option<usize>) {
+        let granted = {
+            let mut state = self.state.lock().unwrap();
+            state.limit = limit;
+            state.dispatch()
+        };
+        self.send_grants(granted);
+    }
+
+    fn request(self: &Arc<Self>, port: u16, lease_id: String, provider: String) {
+        let granted = {
+            let mut state = self.state.lock().unwrap();
+            state.queue.push_back(Lease { port, lease_id, provider, since: Instant::now() });
+            state.dispatch()
+        };
+        self.send_grants(granted);
+    }
+
+    /// Hand back a permit, or withdraw a request still waiting for one
+    fn release(self: &Arc<Self>, port: u16, lease_id: &str) {
+        let granted = {
+            let mut state = self.state.lock().unwrap();
+            state.active.retain(|l| !(l.port == port && l.lease_id == lease_id));
+            state.queue.retain(|l| !(l.port == port && l.lease_id == lease_id));
+            state.dispatch()
+        };
+        self.send_grants(granted);
+    }
+
+    /// Drop permits held past `LEASE_TTL` and everything belonging to ports
+    /// not in `live_ports`
+    fn reclaim(self: &Arc<Self>, live_ports: &HashSet<u16>) {
+        let granted = {
+            let mut state = self.state.lock().unwrap();
+            let before = state.active.len();
+            state.active.retain(|l| live_ports.contains(&l.port) && l.since.elapsed() < LEASE_TTL);
+            state.queue.retain(|l| live_ports.contains(&l.port));
+            let reclaimed = before - state.active.len();
+            if reclaimed > 0 {
+                println!("Reclaimed {} request permit(s) that were never released", reclaimed);
+            }
+            state.dispatch()
+        };
+        self.send_grants(granted);
+    }
+
+    fn ports(&self) -> HashSet<u16> {
+        let state = self.state.lock().unwrap();
+        state.active.iter().chain(state.queue.iter()).map(|l| l.port).collect()
+    }
+
+    /// Tell each sidecar its permit is granted. A permit nobody is waiting
+    /// for any more (the request gave up, or the sidecar is gone) is
+    /// released again so the slot is not lost.
+    fn send_grants(self: &Arc<Self>, granted: Vec<Lease>) {
+        for lease in granted {
+            let budget = self.clone();
+            tauri::async_runtime::spawn(async move {
+                let accepted = budget.pool
+                    .request(lease.port, "budget.grant", serde_json::json!({ "lease_id": lease.lease_id }), GRANT_TIMEOUT)
+                    .await
+                    .map(|result| result.get("granted").and_then(|g| g.as_bool()).unwrap_or(false));
+                match accepted {
+                    Ok(true) => {}
+                    Ok(false) => budget.release(lease.port, &lease.lease_id),
+                    Err(e) => {
+                        eprintln!("Failed to grant request permit on port {}: {}", lease.port, e);
+                        budget.release(lease.port, &lease.lease_id);
+                    }
+                }
+            });
+        }
+    }
+
+    pub async fn status(&self, sidecar_manager: &SidecarManager) -> RequestBudgetStatus {
+        let (limit, active, waiting) = {
+            let state = self.state.lock().unwrap();
+            (state.limit, state.active.clone(), state.queue.len())
+        };
+
+        let mut by_provider: HashMap<String, usize> = HashMap::new();
+        let mut by_port: HashMap<u16, usize> = HashMap::new();
+        for lease in &active {
+            *by_provider.entry(lease.provider.clone()).or_default() += 1;
+            *by_port.entry(lease.port).or_default() += 1;
+        }
+        let mut by_window: HashMap<String, usize> = HashMap::new();
+        for (port, count) in by_port {
+            let label = sidecar_manager.label_for_port(port).await.unwrap_or_else(|| format!("port {}", port));
+            *by_window.entry(label).or_default() += count;
+        }
+
+        RequestBudgetStatus {
+            limit,
+            in_use: active.len(),
+            waiting,
+            utilization: limit.map(|limit| active.len() as f64 / limit as f64),
+            by_provider,
+            by_window,
+        }
+    }
+}
+
+/// Serve permit requests from every sidecar, and periodically reclaim
+/// permits that were never released
+pub fn spawn_budget_coordinator(
+    pool: Arc<ConnectionPool>,
+    sidecar_manager: Arc<SidecarManager>,
+    budget: Arc<RequestBudget>,
+) {
+    let mut events = pool.subscribe();
+    let coordinator = budget.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let (port, params) = match events.recv().await {
+                Ok(event) => event,
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                    eprintln!("Warning: Request budget skipped {} events", skipped);
+                    continue;
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+            };
+
+            let event_type = params.get("event_type").and_then(|t| t.as_str());
+            if event_type != Some(REQUEST_PERMIT_EVENT) && event_type != Some(RELEASE_PERMIT_EVENT) {
+                continue;
+            }
+            let data = params.get("data");
+            let Some(lease_id) = data.and_then(|d| d.get("lease_id")).and_then(|l| l.as_str()) else { continue };
+
+            if event_type == Some(REQUEST_PERMIT_EVENT) {
+                let provider = data
+                    .and_then(|d| d.get("provider"))
+                    .and_then(|p| p.as_str())
+                    .unwrap_or_default()
+                    .to_string();
+                coordinator.request(port, lease_id.to_string(), provider);
+            } else {
+                coordinator.release(port, lease_id);
+            }
+        }
+    });
+
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(SWEEP_INTERVAL).await;
+            let mut live_ports = HashSet::new();
+            for port in budget.ports() {
+                if sidecar_manager.label_for_port(port).await.is_some() {
+                    live_ports.insert(port);
+                }
+            }
+            budget.reclaim(&live_ports);
+        }
+    });
+}
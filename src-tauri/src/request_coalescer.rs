@@ -0,0 +1,191 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex};
+use tokio::sync::oneshot;
+
+use crate::command_error::CommandError;
+
+type Waiters<T> = Vec<oneshot::Sender<Result<T, CommandError>>>;
+
+/// Runs at most one request per key at a time. A request for a key that is
+/// already in flight waits for that request and shares its result instead
+/// of starting again.
+pub struct RequestCoalescer<K, T> {
+    in_flight: Arc<Mutex<HashMap<K, Waiters<T>>>>,
+}
+
+impl<K, T> Default for RequestCoalescer<K, T> {
+    fn default() -> Self {
+        Self { in_flight: Arc::new(Mutex::new(HashMap::new())) }
+    }
+}
+
+impl<K: Eq + Hash + Clone, T: Clone> RequestCoalescer<K, T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub async fn run<F>(&self, key: K, request: F) -> Result<T, CommandError>
+    where
+        F: Future<Output = Result<T, CommandError>>,
+    {
+        let receiver = {
+            let mut in_flight = self.in_flight.lock().unwrap();
+            match in_flight.get_mut(&key) {
+                Some(waiters) => {
+                    let (sender, receiver) = oneshot::channel();
+                    waiters.push(sender);
+                    Some(receiver)
+                }
+                None => {
+                    in_flight.insert(key.clone(), Vec::new());
+                    None
+                }
+            }
+        };
+
+        if let Some(receiver) = receiver {
+            return receiver
+                .await
+                .unwrap_or_else(|_| Err(CommandError::Cancelled(
+                    "The request this one was waiting on was cancelled".to_string(),
+                )));
+        }
+
+        // Clears the key even if this future is dropped mid-request, so
+        // waiters see a cancellation rather than hanging and the next
+        // request for the key starts afresh
+        let guard = InFlightGuard { in_flight: &self.in_flight, key: Some(key) };
+        let result = request.await;
+        for waiter in guard.finish() {
+            let _ = waiter.send(result.clone());
+        }
+        result
+    }
+}
+
+struct InFlightGuard<'a, K: Eq + Hash, T> {
+    in_flight: &'a Mutex<HashMap<K, Waiters<T>>>,
+    key: Option<K>,
+}
+
+impl<K: Eq + Hash, T> InFlightGuard<'_, K, T> {
+    fn finish(mut self) -> Waiters<T> {
+        let key = self.key.take().expect("guard finished twice");
+        self.in_flight.lock().unwrap().remove(&key).unwrap_or_default()
+    }
+}
+
+impl<K: Eq + Hash, T> Drop for InFlightGuard<'_, K, T> {
+    fn drop(&mut self) {
+        if let Some(key) = self.key.take() {
+            self.in_flight.lock().unwrap().remove(&key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs_utils::vault_key;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn concurrent_requests_for_one_key_run_once() {
+        let coalescer = RequestCoalescer::new();
+        let spawned = AtomicUsize::new(0);
+        let open = || async {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            Ok::<_, CommandError>(spawned.fetch_add(1, Ordering::SeqCst) + 9000)
+        };
+
+        let (first, second) = tokio::join!(
+            coalescer.run("/vaults/notes", open()),
+            coalescer.run("/vaults/notes", open()),
+        );
+
+        assert_eq!(spawned.load(Ordering::SeqCst), 1);
+        assert_eq!(first, Ok(9000));
+        assert_eq!(second, Ok(9000));
+    }
+
+    #[tokio::test]
+    async fn two_spellings_of_one_vault_open_it_once() {
+        let parent = std::env::temp_dir().join(format!("tailor-coalesce-{}", uuid::Uuid::new_v4()));
+        let vault = parent.join("notes");
+        std::fs::create_dir_all(&vault).unwrap();
+        let plain = vault.to_string_lossy().to_string();
+        let roundabout = parent.join(".").join("notes").join("..").join("notes").to_string_lossy().to_string();
+
+        let coalescer = RequestCoalescer::new();
+        let spawned = AtomicUsize::new(0);
+        let open = || async {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            Ok::<_, CommandError>(spawned.fetch_add(1, Ordering::SeqCst) + 9000)
+        };
+
+        let first_key = vault_key("vault_path", &plain).unwrap();
+        let second_key = vault_key("vault_path", &roundabout).unwrap();
+        assert_eq!(first_key, second_key);
+        let (first, second) = tokio::join!(
+            coalescer.run(first_key, open()),
+            coalescer.run(second_key, open()),
+        );
+
+        assert_eq!(spawned.load(Ordering::SeqCst), 1);
+        assert_eq!(first, Ok(9000));
+        assert_eq!(second, Ok(9000));
+        std::fs::remove_dir_all(&parent).unwrap();
+    }
+
+    #[tokio::test]
+    async fn different_keys_and_later_requests_run_separately() {
+        let coalescer = RequestCoalescer::new();
+        let calls = AtomicUsize::new(0);
+        let open = || async {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            Ok::<_, CommandError>(calls.fetch_add(1, Ordering::SeqCst))
+        };
+
+        let _ = tokio::join!(coalescer.run("a", open()), coalescer.run("b", open()));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        coalescer.run("a", open()).await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn errors_are_shared_with_waiters() {
+        let coalescer: RequestCoalescer<&str, u16> = RequestCoalescer::new();
+        let fail = || async {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            Err(CommandError::SidecarUnavailable("Failed to spawn sidecar".to_string()))
+        };
+
+        let (first, second) = tokio::join!(coalescer.run("a", fail()), coalescer.run("a", fail()));
+        assert_eq!(first, second);
+        assert_eq!(first.unwrap_err().code(), "SIDECAR_UNAVAILABLE");
+    }
+
+    #[tokio::test]
+    async fn waiters_of_a_dropped_request_are_cancelled() {
+        let coalescer: RequestCoalescer<&str, u16> = RequestCoalescer::new();
+        let slow = async {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            Ok(1)
+        };
+        let waiter = async {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            coalescer.run("a", async { Ok(2) }).await
+        };
+
+        let (first, second) = tokio::join!(
+            tokio::time::timeout(Duration::from_millis(50), coalescer.run("a", slow)),
+            waiter,
+        );
+        assert!(first.is_err());
+        assert_eq!(second.unwrap_err().code(), "CANCELLED");
+    }
+}
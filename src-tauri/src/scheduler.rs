@@ -0,0 +1,374 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+use tokio::sync::Mutex;
+use anyhow::{Result, Context};
+
+use crate::artifact_scanner::{ArtifactScanner, ARTIFACT_MARKER};
+use crate::dependency_checker::DependencyChecker;
+use crate::fs_utils::atomic_write;
+use crate::plugin_updater::{PluginUpdater, UpdateStatus};
+use crate::task_manager::{TaskManager, TaskRecord, TaskStatus};
+use crate::vault_excludes;
+
+/// Scheduled tasks are persisted here, relative to the app data directory
+pub const SCHEDULE_FILE: &str = "scheduled_tasks.json";
+/// How often the scheduler checks for due tasks
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScheduledTaskKind {
+    /// Copy the vault, minus `.tailor/exclude` matches, into `<app_data>/backups/`
+    Backup,
+    /// Update every git-managed plugin in the vault
+    PluginUpdates,
+    /// Delete the vault's venv and reinstall dependencies
+    VenvRebuild,
+}
+
+impl ScheduledTaskKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Backup => "backup",
+            Self::PluginUpdates => "plugin_updates",
+            Self::VenvRebuild => "venv_rebuild",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScheduleStatus {
+    Pending,
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledTask {
+    pub id: String,
+    pub vault_path: String,
+    pub task: ScheduledTaskKind,
+    pub at_time: DateTime<Utc>,
+    pub status: ScheduleStatus,
+    pub created: DateTime<Utc>,
+    #[serde(default)]
+    pub ran_at: Option<DateTime<Utc>>,
+    /// True when the task was missed (app not running) and ran on startup
+    #[serde(default)]
+    pub caught_up: bool,
+    #[serde(default)]
+    pub result: Option<String>,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+/// Persisted queue of vault operations to run at a given time.
+///
+/// Due tasks run through the `TaskManager` while the app is up; anything that
+/// came due while the app was closed runs once on the next startup and is
+/// reported via `scheduler://caught-up`.
+pub struct Scheduler {
+    app_data_dir: PathBuf,
+    tasks: Mutex<Vec<ScheduledTask>>,
+}
+
+impl Scheduler {
+    pub fn load(app_data_dir: &Path) -> Self {
+        let file = app_data_dir.join(SCHEDULE_FILE);
+        let mut tasks: Vec<ScheduledTask> = fs::read_to_string(&file)
+            .ok()
+            .and_then(|contents| match serde_json::from_str(&contents) {
+                Ok(tasks) => Some(tasks),
+                Err(e) => {
+                    eprintln!("Warning: Ignoring unreadable {}: {}", file.display(), e);
+                    None
+                }
+            })
+            .unwrap_or_default();
+
+        // A task still marked running was interrupted by an exit; run it again
+        for task in tasks.iter_mut().filter(|t| t.status == ScheduleStatus::Running) {
+            task.status = ScheduleStatus::Pending;
+        }
+
+        Self {
+            app_data_dir: app_data_dir.to_path_buf(),
+            tasks: Mutex::new(tasks),
+        }
+    }
+
+    pub async fn schedule(
+        &self,
+        vault_path: &str,
+        task: ScheduledTaskKind,
+        at_time: DateTime<Utc>,
+    ) -> Result<ScheduledTask> {
+        let scheduled = ScheduledTask {
+            id: format!("sched_{}", uuid::Uuid::new_v4()),
+            vault_path: vault_path.to_string(),
+            task,
+            at_time,
+            status: ScheduleStatus::Pending,
+            created: Utc::now(),
+            ran_at: None,
+            caught_up: false,
+            result: None,
+            error: None,
+        };
+
+        let mut tasks = self.tasks.lock().await;
+        tasks.push(scheduled.clone());
+        self.persist(&tasks)?;
+
+        println!("Scheduled {} for {} at {}", task.as_str(), vault_path, at_time.to_rfc3339());
+        Ok(scheduled)
+    }
+
+    /// All tasks, soonest first
+    pub async fn list(&self) -> Vec<ScheduledTask> {
+        let mut tasks = self.tasks.lock().await.clone();
+        tasks.sort_by_key(|t| t.at_time);
+        tasks
+    }
+
+    /// Cancel a task that has not started yet
+    pub async fn cancel(&self, id: &str) -> Result<ScheduledTask> {
+        let mut tasks = self.tasks.lock().await;
+        let task = tasks.iter_mut()
+            .find(|t| t.id == id)
+            .with_context(|| format!("Scheduled task not found: {}", id))?;
+
+        if task.status != ScheduleStatus::Pending {
+            anyhow::bail!("Task {} can't be cancelled: it is {:?}", id, task.status);
+        }
+        task.status = ScheduleStatus::Cancelled;
+        let cancelled = task.clone();
+        self.persist(&tasks)?;
+
+        Ok(cancelled)
+    }
+
+    /// Start the background loop. The first pass catches up on tasks that
+    /// came due while the app was not running.
+    pub fn start(self: Arc<Self>, app: AppHandle, task_manager: Arc<TaskManager>) {
+        tauri::async_runtime::spawn(async move {
+            let caught_up = self.run_due(&task_manager, true).await;
+            if !caught_up.is_empty() {
+                println!("Scheduler caught up on {} missed task(s)", caught_up.len());
+                let _ = app.emit("scheduler://caught-up", &caught_up);
+            }
+
+            loop {
+                tokio::time::sleep(POLL_INTERVAL).await;
+                for task in self.run_due(&task_manager, false).await {
+                    let _ = app.emit("scheduler://task-finished", &task);
+                }
+            }
+        });
+    }
+
+    /// Run every pending task whose time has passed, returning their final state
+    async fn run_due(&self, task_manager: &TaskManager, startup: bool) -> Vec<ScheduledTask> {
+        let now = Utc::now();
+        let due: Vec<ScheduledTask> = {
+            let mut tasks = self.tasks.lock().await;
+            let mut due = Vec::new();
+            for task in tasks.iter_mut() {
+                if task.status == ScheduleStatus::Pending && task.at_time <= now {
+                    task.status = ScheduleStatus::Running;
+                    task.caught_up = startup;
+                    due.push(task.clone());
+                }
+            }
+            if !due.is_empty() {
+                if let Err(e) = self.persist(&tasks) {
+                    eprintln!("Warning: Failed to save scheduled tasks: {}", e);
+                }
+            }
+            due
+        };
+
+        let mut finished = Vec::with_capacity(due.len());
+        for task in due {
+            let outcome = task_manager
+                .run(task.task.as_str(), &task.vault_path, self.execute(&task))
+                .await;
+            finished.push(self.record_outcome(task, outcome).await);
+        }
+        finished
+    }
+
+    async fn record_outcome(&self, mut task: ScheduledTask, outcome: Result<TaskRecord>) -> ScheduledTask {
+        task.ran_at = Some(Utc::now());
+        match outcome {
+            Ok(record) if record.status == TaskStatus::Completed => {
+                task.status = ScheduleStatus::Completed;
+                task.result = record.summary;
+            }
+            Ok(record) => {
+                task.status = ScheduleStatus::Failed;
+                task.error = record.error;
+            }
+            Err(e) => {
+                task.status = ScheduleStatus::Failed;
+                task.error = Some(e.to_string());
+            }
+        }
+
+        let mut tasks = self.tasks.lock().await;
+        if let Some(stored) = tasks.iter_mut().find(|t| t.id == task.id) {
+            *stored = task.clone();
+        }
+        if let Err(e) = self.persist(&tasks) {
+            eprintln!("Warning: Failed to save scheduled tasks: {}", e);
+        }
+        task
+    }
+
+    async fn execute(&self, task: &ScheduledTask) -> Result<String> {
+        let vault_path = PathBuf::from(&task.vault_path);
+        if !vault_path.is_dir() {
+            anyhow::bail!("Vault directory not found: {}", task.vault_path);
+        }
+
+        match task.task {
+            ScheduledTaskKind::Backup => {
+                let name = vault_path.file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_else(|| "vault".to_string());
+                let dest = self.app_data_dir
+                    .join("backups")
+                    .join(format!("{}-{}", name, Utc::now().format("%Y%m%d%H%M%S")));
+
+                let target = dest.clone();
+                let copied = tokio::task::spawn_blocking(move || vault_excludes::copy_vault(&vault_path, &target))
+                    .await
+                    .context("Backup task panicked")??;
+
+                Ok(format!("Backed up {} files to {}", copied, dest.display()))
+            }
+            ScheduledTaskKind::PluginUpdates => {
+                let plugins = PluginUpdater::updatable_plugins(&vault_path);
+                let mut updated = 0;
+                let mut failed = 0;
+                for plugin in &plugins {
+                    let outcome = PluginUpdater::update_plugin(&vault_path, plugin).await;
+                    match outcome.status {
+                        UpdateStatus::Updated => updated += 1,
+                        UpdateStatus::RolledBack | UpdateStatus::Failed => failed += 1,
+                        UpdateStatus::UpToDate => {}
+                    }
+                }
+
+                Ok(format!("{} plugins checked, {} updated, {} failed", plugins.len(), updated, failed))
+            }
+            ScheduledTaskKind::VenvRebuild => {
+                let config = fs::read_to_string(vault_path.join(".vault.json"))
+                    .context("Failed to read vault config")?;
+                let config: serde_json::Value = serde_json::from_str(&config)
+                    .context("Failed to parse vault config")?;
+                let vault_id = config.get("id")
+                    .and_then(|v| v.as_str())
+                    .context("Vault config has no id")?;
+
+                let venv = ArtifactScanner::venvs_root(&self.app_data_dir).join(vault_id);
+                // Only remove directories tailor created itself
+                if venv.join(ARTIFACT_MARKER).is_file() {
+                    fs::remove_dir_all(&venv)
+                        .with_context(|| format!("Failed to remove {}", venv.display()))?;
+                }
+                DependencyChecker::check_and_install(&task.vault_path, |_| {}).await?;
+
+                Ok(format!("Rebuilt environment for {}", vault_id))
+            }
+        }
+    }
+
+    fn persist(&self, tasks: &[ScheduledTask]) -> Result<()> {
+        let json = serde_json::to_string_pretty(tasks)?;
+        atomic_write(&self.app_data_dir.join(SCHEDULE_FILE), json.as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("tailor-scheduler-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[tokio::test]
+    async fn tasks_survive_a_restart_and_interrupted_ones_run_again() {
+        let dir = temp_dir();
+        let scheduler = Scheduler::load(&dir);
+        let at = Utc::now() + chrono::Duration::hours(1);
+        let task = scheduler.schedule("/vaults/notes", ScheduledTaskKind::Backup, at).await.unwrap();
+        {
+            let mut tasks = scheduler.tasks.lock().await;
+            tasks[0].status = ScheduleStatus::Running;
+            scheduler.persist(&tasks).unwrap();
+        }
+
+        let reloaded = Scheduler::load(&dir).list().await;
+        assert_eq!(reloaded.len(), 1);
+        assert_eq!(reloaded[0].id, task.id);
+        assert_eq!(reloaded[0].at_time, at);
+        assert_eq!(reloaded[0].status, ScheduleStatus::Pending);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn tasks_list_soonest_first_and_only_pending_ones_cancel() {
+        let dir = temp_dir();
+        let scheduler = Scheduler::load(&dir);
+        let now = Utc::now();
+        let later = scheduler.schedule("/vaults/a", ScheduledTaskKind::Backup, now + chrono::Duration::hours(2)).await.unwrap();
+        let sooner = scheduler.schedule("/vaults/b", ScheduledTaskKind::VenvRebuild, now + chrono::Duration::hours(1)).await.unwrap();
+
+        let ids: Vec<String> = scheduler.list().await.into_iter().map(|t| t.id).collect();
+        assert_eq!(ids, [sooner.id.clone(), later.id.clone()]);
+
+        assert_eq!(scheduler.cancel(&sooner.id).await.unwrap().status, ScheduleStatus::Cancelled);
+        assert!(scheduler.cancel(&sooner.id).await.is_err());
+        assert!(scheduler.cancel("sched_missing").await.is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn only_pending_tasks_past_their_time_run() {
+        let dir = temp_dir();
+        let scheduler = Scheduler::load(&dir);
+        let now = Utc::now();
+        let missing = dir.join("no-such-vault").to_string_lossy().to_string();
+        let due = scheduler.schedule(&missing, ScheduledTaskKind::Backup, now - chrono::Duration::minutes(5)).await.unwrap();
+        let future = scheduler.schedule(&missing, ScheduledTaskKind::Backup, now + chrono::Duration::hours(1)).await.unwrap();
+        let cancelled = scheduler.schedule(&missing, ScheduledTaskKind::Backup, now - chrono::Duration::minutes(1)).await.unwrap();
+        scheduler.cancel(&cancelled.id).await.unwrap();
+
+        let finished = scheduler.run_due(&TaskManager::new(), true).await;
+        assert_eq!(finished.len(), 1);
+        assert_eq!(finished[0].id, due.id);
+        assert!(finished[0].caught_up);
+        assert!(finished[0].ran_at.is_some());
+        assert_eq!(finished[0].status, ScheduleStatus::Failed);
+        assert!(finished[0].error.as_deref().unwrap().contains("not found"));
+
+        let tasks = scheduler.list().await;
+        let status = |id: &str| tasks.iter().find(|t| t.id == id).unwrap().status;
+        assert_eq!(status(&future.id), ScheduleStatus::Pending);
+        assert_eq!(status(&cancelled.id), ScheduleStatus::Cancelled);
+        assert!(scheduler.run_due(&TaskManager::new(), false).await.is_empty());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,220 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use anyhow::{Result, Context};
+
+use crate::fs_utils::atomic_write;
+
+/// Per-vault settings file, stored at the vault root
+pub const VAULT_SETTINGS_FILE: &str = ".vault-settings.json";
+/// App-wide settings file, stored in the app config directory
+pub const GLOBAL_SETTINGS_FILE: &str = "settings.json";
+/// Global setting naming an explicit Python interpreter for sidecars
+pub const PYTHON_PATH_SETTING: &str = "pythonPath";
+/// Global cap on simultaneously open vault windows (absent or 0 = unlimited)
+pub const MAX_OPEN_VAULTS_SETTING: &str = "maxOpenVaults";
+/// Global cap on concurrent provider requests across all windows (absent = unlimited)
+pub const REQUEST_BUDGET_SETTING: &str = "requestBudget";
+/// Global cap on disk used by all vaults, caches and logs combined (absent = no cap)
+pub const MAX_TOTAL_DISK_BYTES_SETTING: &str = "maxTotalDiskBytes";
+/// Global setting naming the vaults folder: scanned by `list_vaults`, and
+/// where kept scratch vaults move (absent = ~/Documents/Tailor)
+pub const DEFAULT_VAULT_DIR_SETTING: &str = "defaultVaultDir";
+/// Global switch for the IPC audit log at `~/.tailor/audit.jsonl` (absent = off)
+pub const AUDIT_LOG_SETTING: &str = "auditLog";
+/// Global switch to reopen last session's vault windows on launch (absent = off)
+pub const RESTORE_SESSION_SETTING: &str = "restoreSession";
+/// Vault setting capping per-conversation system prompt length (characters)
+pub const MAX_SYSTEM_PROMPT_LENGTH_SETTING: &str = "maxSystemPromptLength";
+
+pub fn vault_settings_path(vault_path: &Path) -> PathBuf {
+    vault_path.join(VAULT_SETTINGS_FILE)
+}
+
+/// Load vault settings, returning an empty object when the file is absent
+pub fn load_vault_settings(vault_path: &Path) -> Result<serde_json::Value> {
+    let path = vault_settings_path(vault_path);
+    if !path.exists() {
+        return Ok(serde_json::json!({}));
+    }
+
+    let contents = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    serde_json::from_str(&contents)
+        .with_context(|| format!("Failed to parse {}", path.display()))
+}
+
+/// Defaults shown for any vault key that has never been saved
+pub fn default_vault_settings() -> serde_json::Value {
+    serde_json::json!({
+        "isolatePlugins": false,
+        "autoUpdatePlugins": false,
+    })
+}
+
+/// Stored vault settings merged over the defaults, for display
+pub fn load_vault_settings_with_defaults(vault_path: &Path) -> Result<serde_json::Value> {
+    let stored = load_vault_settings(vault_path)?;
+    let mut settings = default_vault_settings();
+    merge_top_level(&mut settings, &stored);
+    Ok(settings)
+}
+
+/// Merge `updates` into the stored vault settings (top-level keys only) and
+/// write them atomically, so a partial update keeps every other key. Returns
+/// the stored settings after the merge.
+pub fn save_vault_settings(vault_path: &Path, updates: &serde_json::Value) -> Result<serde_json::Value> {
+    if !updates.is_object() {
+        anyhow::bail!("Vault settings must be a JSON object");
+    }
+    let mut settings = load_vault_settings(vault_path)?;
+    if !settings.is_object() {
+        anyhow::bail!("{} does not contain a JSON object", vault_settings_path(vault_path).display());
+    }
+    merge_top_level(&mut settings, updates);
+
+    let contents = serde_json::to_string_pretty(&settings)?;
+    atomic_write(&vault_settings_path(vault_path), contents.as_bytes())
+        .context("Failed to write vault settings")?;
+    Ok(settings)
+}
+
+/// Defaults returned for any global key that has never been saved
+pub fn default_global_settings() -> serde_json::Value {
+    serde_json::json!({
+        "theme": "dark",
+        "autoUpdate": false,
+    })
+}
+
+/// Values accepted for the global `theme` setting
+pub const THEMES: &[&str] = &["dark", "light", "system"];
+
+/// Check the keys of a global settings update that have a known shape.
+/// Keys this version doesn't know are left alone, so settings written by a
+/// newer frontend survive a round trip.
+pub fn validate_global_settings(updates: &serde_json::Value) -> Result<()> {
+    let Some(updates) = updates.as_object() else {
+        anyhow::bail!("global settings must be a JSON object");
+    };
+    if let Some(theme) = updates.get("theme") {
+        if !theme.as_str().is_some_and(|t| THEMES.contains(&t)) {
+            anyhow::bail!("theme must be one of {}, got {}", THEMES.join(", "), theme);
+        }
+    }
+    for key in ["autoUpdate", AUDIT_LOG_SETTING, RESTORE_SESSION_SETTING] {
+        if let Some(value) = updates.get(key).filter(|v| !v.is_boolean()) {
+            anyhow::bail!("{} must be true or false, got {}", key, value);
+        }
+    }
+    Ok(())
+}
+
+/// Write the defaults out on first launch, when there's no settings file yet
+pub fn ensure_global_settings(app_config_dir: &Path) -> Result<()> {
+    let path = global_settings_path(app_config_dir);
+    if path.exists() {
+        return Ok(());
+    }
+    fs::create_dir_all(app_config_dir)
+        .with_context(|| format!("Failed to create {}", app_config_dir.display()))?;
+    let contents = serde_json::to_string_pretty(&default_global_settings())?;
+    atomic_write(&path, contents.as_bytes())
+        .context("Failed to write default global settings")
+}
+
+pub fn global_settings_path(app_config_dir: &Path) -> PathBuf {
+    app_config_dir.join(GLOBAL_SETTINGS_FILE)
+}
+
+/// Load global settings merged over the defaults
+pub fn load_global_settings(app_config_dir: &Path) -> Result<serde_json::Value> {
+    let mut settings = default_global_settings();
+
+    let path = global_settings_path(app_config_dir);
+    if path.exists() {
+        let contents = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let stored: serde_json::Value = serde_json::from_str(&contents)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+        merge_top_level(&mut settings, &stored);
+    }
+
+    Ok(settings)
+}
+
+/// Validate `updates`, merge them into the stored global settings and write
+/// them atomically
+pub fn save_global_settings(app_config_dir: &Path, updates: &serde_json::Value) -> Result<serde_json::Value> {
+    validate_global_settings(updates)?;
+    let mut settings = load_global_settings(app_config_dir)?;
+    merge_top_level(&mut settings, updates);
+
+    let contents = serde_json::to_string_pretty(&settings)?;
+    atomic_write(&global_settings_path(app_config_dir), contents.as_bytes())
+        .context("Failed to write global settings")?;
+    Ok(settings)
+}
+
+/// The `maxOpenVaults` cap, if one is configured
+pub fn max_open_vaults(global_settings: &serde_json::Value) -> Option<usize> {
+    global_settings
+        .get(MAX_OPEN_VAULTS_SETTING)
+        .and_then(|v| v.as_u64())
+        .filter(|&max| max > 0)
+        .map(|max| max as usize)
+}
+
+/// The `requestBudget` cap on concurrent provider requests, if configured
+pub fn request_budget(global_settings: &serde_json::Value) -> Option<usize> {
+    global_settings
+        .get(REQUEST_BUDGET_SETTING)
+        .and_then(|v| v.as_u64())
+        .filter(|&max| max > 0)
+        .map(|max| max as usize)
+}
+
+/// The `maxTotalDiskBytes` cap, if configured
+pub fn max_total_disk_bytes(global_settings: &serde_json::Value) -> Option<u64> {
+    global_settings
+        .get(MAX_TOTAL_DISK_BYTES_SETTING)
+        .and_then(|v| v.as_u64())
+        .filter(|&max| max > 0)
+}
+
+/// Whether the `auditLog` setting turns the audit log on
+pub fn audit_log_enabled(global_settings: &serde_json::Value) -> bool {
+    global_settings
+        .get(AUDIT_LOG_SETTING)
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false)
+}
+
+/// Whether last session's vault windows are reopened on launch
+pub fn restore_session_enabled(global_settings: &serde_json::Value) -> bool {
+    get_bool(global_settings, RESTORE_SESSION_SETTING, false)
+}
+
+fn merge_top_level(target: &mut serde_json::Value, updates: &serde_json::Value) {
+    if let (Some(existing), Some(new_values)) = (target.as_object_mut(), updates.as_object()) {
+        for (key, value) in new_values {
+            existing.insert(key.clone(), value.clone());
+        }
+    }
+}
+
+/// Read a boolean setting, falling back to `default` when missing or mistyped
+pub fn get_bool(settings: &serde_json::Value, key: &str, default: bool) -> bool {
+    settings.get(key).and_then(|v| v.as_bool()).unwrap_or(default)
+}
+
+/// Read a list of strings, ignoring non-string entries
+pub fn get_string_list(settings: &serde_json::Value, key: &str) -> Vec<String> {
+    settings.get(key)
+        .and_then(|v| v.as_array())
+        .map(|items| {
+            items.iter()
+                .filter_map(|item| item.as_str().map(|s| s.to_string()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
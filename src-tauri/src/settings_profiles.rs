@@ -0,0 +1,106 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use serde::{Deserialize, Serialize};
+use anyhow::{Result, Context};
+
+use crate::fs_utils::atomic_write;
+use crate::redact::is_secret_key;
+use crate::settings::PYTHON_PATH_SETTING;
+
+/// Named snapshots of global settings, stored in the app config directory
+pub const PROFILES_FILE: &str = "settings-profiles.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettingsProfile {
+    pub name: String,
+    pub settings: serde_json::Value,
+    pub saved: String,
+}
+
+fn profiles_path(app_config_dir: &Path) -> PathBuf {
+    app_config_dir.join(PROFILES_FILE)
+}
+
+fn load_all(app_config_dir: &Path) -> Result<BTreeMap<String, SettingsProfile>> {
+    let path = profiles_path(app_config_dir);
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+    let contents = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    serde_json::from_str(&contents)
+        .with_context(|| format!("Failed to parse {}", path.display()))
+}
+
+pub fn list_profiles(app_config_dir: &Path) -> Result<Vec<SettingsProfile>> {
+    Ok(load_all(app_config_dir)?.into_values().collect())
+}
+
+pub fn get_profile(app_config_dir: &Path, name: &str) -> Result<SettingsProfile> {
+    load_all(app_config_dir)?
+        .remove(name)
+        .with_context(|| format!("Settings profile not found: {}", name))
+}
+
+/// Snapshot `settings` under `name`, replacing any profile of that name.
+/// Secret-looking keys are dropped, at any depth.
+pub fn save_profile(app_config_dir: &Path, name: &str, settings: &serde_json::Value) -> Result<SettingsProfile> {
+    let name = name.trim();
+    if name.is_empty() || name.len() > 64 {
+        anyhow::bail!("Profile name must be 1-64 characters");
+    }
+
+    let mut settings = settings.clone();
+    strip_secrets(&mut settings);
+
+    let profile = SettingsProfile {
+        name: name.to_string(),
+        settings,
+        saved: chrono::Utc::now().to_rfc3339(),
+    };
+
+    let mut profiles = load_all(app_config_dir)?;
+    profiles.insert(profile.name.clone(), profile.clone());
+    atomic_write(&profiles_path(app_config_dir), serde_json::to_string_pretty(&profiles)?.as_bytes())?;
+
+    Ok(profile)
+}
+
+/// Refuse a profile whose `pythonPath` no longer points at a working interpreter
+pub fn check_interpreter(settings: &serde_json::Value) -> Result<()> {
+    let Some(python) = settings.get(PYTHON_PATH_SETTING).and_then(|v| v.as_str()) else {
+        return Ok(());
+    };
+    if python.trim().is_empty() {
+        return Ok(());
+    }
+
+    let path = Path::new(python);
+    let explicit = path.is_absolute() || path.components().count() > 1;
+    if explicit && !path.is_file() {
+        anyhow::bail!("Profile references a missing Python interpreter: {}", python);
+    }
+
+    let works = Command::new(python)
+        .arg("--version")
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(false);
+    if !works {
+        anyhow::bail!("Profile's Python interpreter does not run: {}", python);
+    }
+    Ok(())
+}
+
+fn strip_secrets(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(map) => {
+            map.retain(|key, _| !is_secret_key(key));
+            map.values_mut().for_each(strip_secrets);
+        }
+        serde_json::Value::Array(items) => items.iter_mut().for_each(strip_secrets),
+        _ => {}
+    }
+}
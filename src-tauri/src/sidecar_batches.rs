@@ -0,0 +1,47 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Most commands of one batch in flight at once
+pub const BATCH_CONCURRENCY: usize = 8;
+
+struct ActiveBatch {
+    window_label: String,
+    request_ids: Vec<String>,
+}
+
+/// Batches started with `send_to_sidecar_batch` under a `batch_id`, with the
+/// request ids reserved for their commands
+#[derive(Default)]
+pub struct BatchRegistry {
+    batches: Mutex<HashMap<String, ActiveBatch>>,
+}
+
+impl BatchRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Claim `batch_id` for the given requests; false if it is in use
+    pub fn open(&self, batch_id: &str, window_label: &str, request_ids: &[String]) -> bool {
+        let mut batches = self.batches.lock().unwrap();
+        if batches.contains_key(batch_id) {
+            return false;
+        }
+        batches.insert(batch_id.to_string(), ActiveBatch {
+            window_label: window_label.to_string(),
+            request_ids: request_ids.to_vec(),
+        });
+        true
+    }
+
+    /// The request ids of the window's batch, so they can be cancelled
+    pub fn request_ids(&self, window_label: &str, batch_id: &str) -> Option<Vec<String>> {
+        let batches = self.batches.lock().unwrap();
+        let batch = batches.get(batch_id).filter(|b| b.window_label == window_label)?;
+        Some(batch.request_ids.clone())
+    }
+
+    pub fn close(&self, batch_id: &str) {
+        self.batches.lock().unwrap().remove(batch_id);
+    }
+}
@@ -0,0 +1,91 @@
+use std::time::Duration;
+use futures::{SinkExt, StreamExt};
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+use anyhow::{Result, Context};
+
+/// Default time to wait for a sidecar response
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Minimal JSON-RPC 2.0 client for talking to a sidecar's WebSocket server
+pub struct SidecarClient;
+
+impl SidecarClient {
+    /// Send a single request and wait for the response with the matching id.
+    ///
+    /// Event notifications (`trigger_event`) received while waiting are ignored.
+    pub async fn request(
+        port: u16,
+        method: &str,
+        params: serde_json::Value,
+        timeout: Duration,
+    ) -> Result<serde_json::Value> {
+        let url = format!("ws://127.0.0.1:{}", port);
+
+        let (mut ws, _) = tokio::time::timeout(timeout, connect_async(&url))
+            .await
+            .context("Timed out connecting to sidecar")?
+            .with_context(|| format!("Failed to connect to {}", url))?;
+
+        let request_id = format!("rust_{}", uuid::Uuid::new_v4());
+        let message = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": request_id,
+            "method": method,
+            "params": params,
+        });
+
+        ws.send(Message::Text(message.to_string()))
+            .await
+            .context("Failed to send request to sidecar")?;
+
+        let response = tokio::time::timeout(timeout, async {
+            while let Some(frame) = ws.next().await {
+                let frame = frame.context("WebSocket read error")?;
+                let Message::Text(text) = frame else { continue };
+
+                let Ok(data) = serde_json::from_str::<serde_json::Value>(&text) else {
+                    continue;
+                };
+                if data.get("id").and_then(|v| v.as_str()) == Some(request_id.as_str())
+                    && data.get("method").is_none()
+                {
+                    return Ok(data);
+                }
+            }
+            anyhow::bail!("Sidecar closed the connection before responding")
+        })
+        .await
+        .with_context(|| format!("Timed out waiting for '{}' response", method))??;
+
+        let _ = ws.close(None).await;
+
+        if let Some(error) = response.get("error") {
+            let code = error.get("code").and_then(|c| c.as_i64()).unwrap_or(0);
+            let message = error.get("message").and_then(|m| m.as_str()).unwrap_or("Unknown error");
+            anyhow::bail!("Sidecar error {}: {}", code, message);
+        }
+
+        Ok(response.get("result").cloned().unwrap_or(serde_json::Value::Null))
+    }
+
+    /// Poll until the sidecar accepts WebSocket connections or `timeout` elapses
+    pub async fn wait_until_ready(port: u16, timeout: Duration) -> Result<()> {
+        let url = format!("ws://127.0.0.1:{}", port);
+        let deadline = tokio::time::Instant::now() + timeout;
+
+        loop {
+            match connect_async(&url).await {
+                Ok((mut ws, _)) => {
+                    let _ = ws.close(None).await;
+                    return Ok(());
+                }
+                Err(e) => {
+                    if tokio::time::Instant::now() >= deadline {
+                        anyhow::bail!("Sidecar not ready after {:?}: {}", timeout, e);
+                    }
+                    tokio::time::sleep(Duration::from_millis(250)).await;
+                }
+            }
+        }
+    }
+}
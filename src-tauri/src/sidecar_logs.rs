@@ -0,0 +1,353 @@
+use std::collections::VecDeque;
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+use tokio::sync::broadcast;
+
+/// Lines kept in memory per sidecar
+const RING_CAPACITY: usize = 2000;
+/// Rotate the on-disk log once it grows past this size
+const MAX_LOG_FILE_BYTES: u64 = 5 * 1024 * 1024;
+/// Cap on entries returned in a single page
+const MAX_PAGE_SIZE: usize = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum LogLevel {
+    Trace,
+    Debug,
+    Info,
+    Success,
+    Warning,
+    Error,
+    Critical,
+}
+
+impl LogLevel {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_uppercase().as_str() {
+            "TRACE" => Some(Self::Trace),
+            "DEBUG" => Some(Self::Debug),
+            "INFO" => Some(Self::Info),
+            "SUCCESS" => Some(Self::Success),
+            "WARNING" | "WARN" => Some(Self::Warning),
+            "ERROR" => Some(Self::Error),
+            "CRITICAL" => Some(Self::Critical),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogEntry {
+    /// Capture time in ms since the epoch, strictly increasing per store
+    pub ts: i64,
+    pub level: LogLevel,
+    /// "stdout" or "stderr"
+    pub stream: String,
+    /// Logger name reported by loguru (module or plugin name), if parseable
+    pub source: Option<String>,
+    pub message: String,
+}
+
+#[derive(Debug, Default, Clone, Deserialize)]
+pub struct LogQuery {
+    /// Only return entries strictly older than this timestamp (ms)
+    pub before_ts: Option<i64>,
+    pub limit: Option<usize>,
+    pub min_level: Option<String>,
+    /// Only return entries whose logger name matches this plugin
+    pub plugin: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LogPage {
+    /// Entries in chronological order
+    pub entries: Vec<LogEntry>,
+    /// Pass as `before_ts` to fetch the next (older) page; None when exhausted
+    pub next_cursor: Option<i64>,
+}
+
+struct StoreState {
+    ring: VecDeque<LogEntry>,
+    last_ts: i64,
+}
+
+/// Captured output for one sidecar: an in-memory ring backed by a rolling
+/// JSONL file under `.tailor/logs/` so history survives restarts.
+pub struct LogStore {
+    state: Mutex<StoreState>,
+    log_file: PathBuf,
+}
+
+impl LogStore {
+    pub fn new(vault_path: &Path) -> Self {
+        let log_dir = vault_path.join(".tailor").join("logs");
+        let _ = fs::create_dir_all(&log_dir);
+
+        Self {
+            state: Mutex::new(StoreState {
+                ring: VecDeque::with_capacity(RING_CAPACITY),
+                last_ts: 0,
+            }),
+            log_file: log_dir.join("sidecar-output.jsonl"),
+        }
+    }
+
+    /// Record one raw line of sidecar output
+    pub fn push_line(&self, stream: &str, raw: &str) -> LogEntry {
+        let line = strip_ansi(raw);
+        let (level, source, message) = parse_loguru_line(&line, stream);
+
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let ts = chrono::Utc::now().timestamp_millis().max(state.last_ts + 1);
+        state.last_ts = ts;
+
+        let entry = LogEntry {
+            ts,
+            level,
+            stream: stream.to_string(),
+            source,
+            message,
+        };
+
+        if state.ring.len() >= RING_CAPACITY {
+            state.ring.pop_front();
+        }
+        state.ring.push_back(entry.clone());
+        self.append_to_disk(&entry);
+
+        entry
+    }
+
+    /// Return one page of entries matching `query`, newest page first
+    pub fn query(&self, query: &LogQuery) -> LogPage {
+        let limit = query.limit.unwrap_or(200).clamp(1, MAX_PAGE_SIZE);
+        let min_level = query.min_level.as_deref().and_then(LogLevel::parse);
+
+        let matches = |entry: &LogEntry| {
+            query.before_ts.map_or(true, |before| entry.ts < before)
+                && min_level.map_or(true, |min| entry.level >= min)
+                && query.plugin.as_deref().map_or(true, |plugin| {
+                    entry.source.as_deref().is_some_and(|source| source_matches(source, plugin))
+                })
+        };
+
+        // Serve from memory when the requested window is still in the ring
+        let ring_covers = {
+            let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+            match (state.ring.front(), query.before_ts) {
+                (Some(oldest), Some(before)) => before > oldest.ts,
+                (Some(_), None) => true,
+                (None, _) => false,
+            }
+        };
+
+        let mut candidates: Vec<LogEntry> = if ring_covers {
+            let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+            state.ring.iter().filter(|e| matches(e)).cloned().collect()
+        } else {
+            Vec::new()
+        };
+
+        // Fall back to (or top up from) disk history
+        if candidates.len() <= limit {
+            let disk: Vec<LogEntry> = self.read_disk()
+                .into_iter()
+                .filter(|e| matches(e))
+                .collect();
+            if disk.len() > candidates.len() {
+                candidates = disk;
+            }
+        }
+
+        let has_more = candidates.len() > limit;
+        let start = candidates.len().saturating_sub(limit);
+        let entries: Vec<LogEntry> = candidates.split_off(start);
+        let next_cursor = if has_more { entries.first().map(|e| e.ts) } else { None };
+
+        LogPage { entries, next_cursor }
+    }
+
+    fn append_to_disk(&self, entry: &LogEntry) {
+        if let Ok(metadata) = fs::metadata(&self.log_file) {
+            if metadata.len() > MAX_LOG_FILE_BYTES {
+                let _ = fs::rename(&self.log_file, self.rotated_file());
+            }
+        }
+
+        let Ok(line) = serde_json::to_string(entry) else { return };
+        if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(&self.log_file) {
+            let _ = writeln!(file, "{}", line);
+        }
+    }
+
+    fn rotated_file(&self) -> PathBuf {
+        self.log_file.with_extension("jsonl.1")
+    }
+
+    /// Read rotated + current log files in chronological order
+    fn read_disk(&self) -> Vec<LogEntry> {
+        let mut entries = Vec::new();
+        for path in [self.rotated_file(), self.log_file.clone()] {
+            let Ok(file) = fs::File::open(&path) else { continue };
+            for line in BufReader::new(file).lines().map_while(Result::ok) {
+                if let Ok(entry) = serde_json::from_str::<LogEntry>(&line) {
+                    entries.push(entry);
+                }
+            }
+        }
+        entries
+    }
+}
+
+/// Emit each captured line as `sidecar://log/{window_label}` so a log
+/// panel can tail a sidecar live
+pub fn spawn_log_forwarder(app: AppHandle, mut lines: broadcast::Receiver<(String, LogEntry)>) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let (window_label, entry) = match lines.recv().await {
+                Ok(line) => line,
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    eprintln!("Warning: Log forwarder skipped {} lines", skipped);
+                    continue;
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            };
+            let _ = app.emit(&format!("sidecar://log/{}", window_label), entry);
+        }
+    });
+}
+
+/// Plugins are loaded under their directory name, so match on the first
+/// dotted segment as well as the full logger name.
+fn source_matches(source: &str, plugin: &str) -> bool {
+    source == plugin || source.split('.').next() == Some(plugin) || source.ends_with(&format!(".{}", plugin))
+}
+
+/// Parse the sidecar's loguru format:
+/// `2026-01-01 12:00:00 | INFO     | module:function:line - message`
+fn parse_loguru_line(line: &str, stream: &str) -> (LogLevel, Option<String>, String) {
+    let fallback_level = if stream == "stderr" { LogLevel::Error } else { LogLevel::Info };
+
+    let parts: Vec<&str> = line.splitn(3, " | ").collect();
+    if parts.len() == 3 {
+        if let Some(level) = LogLevel::parse(parts[1]) {
+            let (location, message) = parts[2].split_once(" - ").unwrap_or(("", parts[2]));
+            let source = location.split(':').next()
+                .filter(|s| !s.is_empty())
+                .map(|s| s.to_string());
+            return (level, source, message.to_string());
+        }
+    }
+
+    (fallback_level, None, line.to_string())
+}
+
+/// Remove ANSI colour escape sequences (loguru colorizes console output)
+fn strip_ansi(input: &str) -> String {
+    let mut output = String::with_capacity(input.len());
+    let mut chars = input.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c == '\u{1b}' && chars.peek() == Some(&'[') {
+            chars.next();
+            // Skip parameters until the final byte of the sequence
+            for next in chars.by_ref() {
+                if next.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            output.push(c);
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_vault() -> PathBuf {
+        std::env::temp_dir().join(format!("tailor-logs-{}", uuid::Uuid::new_v4()))
+    }
+
+    #[test]
+    fn loguru_lines_are_parsed_and_uncoloured() {
+        let vault = temp_vault();
+        let store = LogStore::new(&vault);
+        let entry = store.push_line(
+            "stderr",
+            "2026-01-01 12:00:00 | \u{1b}[33mWARNING \u{1b}[0m | search.index:build:42 - slow rebuild",
+        );
+        assert_eq!(entry.level, LogLevel::Warning);
+        assert_eq!(entry.source.as_deref(), Some("search.index"));
+        assert_eq!(entry.message, "slow rebuild");
+
+        let plain = store.push_line("stderr", "Traceback (most recent call last):");
+        assert_eq!(plain.level, LogLevel::Error);
+        assert_eq!(plain.source, None);
+        assert!(plain.ts > entry.ts);
+        fs::remove_dir_all(&vault).unwrap();
+    }
+
+    #[test]
+    fn pages_are_cut_to_the_limit_and_continue_from_the_cursor() {
+        let vault = temp_vault();
+        let store = LogStore::new(&vault);
+        for i in 0..5 {
+            store.push_line("stdout", &format!("line {}", i));
+        }
+
+        let messages = |page: &LogPage| page.entries.iter().map(|e| e.message.clone()).collect::<Vec<_>>();
+        let first = store.query(&LogQuery { limit: Some(2), ..Default::default() });
+        assert_eq!(messages(&first), ["line 3", "line 4"]);
+        let second = store.query(&LogQuery { limit: Some(2), before_ts: first.next_cursor, ..Default::default() });
+        assert_eq!(messages(&second), ["line 1", "line 2"]);
+        let last = store.query(&LogQuery { limit: Some(2), before_ts: second.next_cursor, ..Default::default() });
+        assert_eq!(messages(&last), ["line 0"]);
+        assert_eq!(last.next_cursor, None);
+        fs::remove_dir_all(&vault).unwrap();
+    }
+
+    #[test]
+    fn the_ring_drops_the_oldest_lines_and_disk_still_has_them() {
+        let vault = temp_vault();
+        let store = LogStore::new(&vault);
+        for i in 0..RING_CAPACITY + 5 {
+            store.push_line("stdout", &format!("line {}", i));
+        }
+
+        let oldest_in_ring = {
+            let state = store.state.lock().unwrap();
+            assert_eq!(state.ring.len(), RING_CAPACITY);
+            assert_eq!(state.ring.front().unwrap().message, "line 5");
+            state.ring.front().unwrap().ts
+        };
+        let older = store.query(&LogQuery { before_ts: Some(oldest_in_ring), ..Default::default() });
+        assert_eq!(older.entries.len(), 5);
+        assert_eq!(older.entries[0].message, "line 0");
+        fs::remove_dir_all(&vault).unwrap();
+    }
+
+    #[test]
+    fn an_oversized_log_file_is_rotated_before_the_next_line() {
+        let vault = temp_vault();
+        let store = LogStore::new(&vault);
+        store.push_line("stdout", "before rotation");
+        fs::OpenOptions::new().write(true).open(&store.log_file).unwrap()
+            .set_len(MAX_LOG_FILE_BYTES + 1).unwrap();
+
+        store.push_line("stdout", "after rotation");
+        assert_eq!(fs::metadata(store.rotated_file()).unwrap().len(), MAX_LOG_FILE_BYTES + 1);
+        let current = fs::read_to_string(&store.log_file).unwrap();
+        assert_eq!(current.lines().count(), 1);
+        assert!(current.contains("after rotation"));
+        fs::remove_dir_all(&vault).unwrap();
+    }
+}
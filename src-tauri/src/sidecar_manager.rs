@@ -1,19 +1,175 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::process::{Child, Command, Stdio};
 use std::sync::Arc;
-use tokio::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::io::AsyncBufReadExt;
+use tokio::sync::{broadcast, Mutex};
+use tokio::task::JoinHandle;
 use anyhow::{Result, Context};
 
+use crate::artifact_scanner::ArtifactScanner;
+use crate::dependency_checker::DependencyChecker;
+use crate::hang_detector;
+use crate::sidecar_client::SidecarClient;
+use crate::plugins;
+use crate::settings;
+use crate::sidecar_logs::{LogEntry, LogPage, LogQuery, LogStore};
+
+/// Vault setting holding the max number of concurrent plugin callbacks
+pub const PLUGIN_CONCURRENCY_SETTING: &str = "maxConcurrentPluginCallbacks";
+/// Vault setting listing event channels the sidecar should not emit
+pub const MUTED_EVENT_CHANNELS_SETTING: &str = "mutedEventChannels";
+/// Vault setting that blocks plugin writes outside the vault
+pub const RESTRICT_PLUGIN_FS_SETTING: &str = "restrictPluginFs";
+/// Vault setting that runs each enabled plugin in its own worker process
+pub const ISOLATE_PLUGINS_SETTING: &str = "isolatePlugins";
+/// Vault setting that turns on plugin lifecycle trace events (debug)
+pub const PLUGIN_LIFECYCLE_EVENTS_SETTING: &str = "debugPluginLifecycle";
+/// Vault setting: sidecar log level ("DEBUG", "INFO", ...)
+pub const LOG_LEVEL_SETTING: &str = "logLevel";
+/// Vault setting: seconds between plugin tick events
+pub const TICK_INTERVAL_SETTING: &str = "tickInterval";
+/// Vault setting: how long in-flight requests may run on close (ms)
+pub const DRAIN_TIMEOUT_SETTING: &str = "drainTimeoutMs";
+/// Vault setting: how long plugins get to unload and the sidecar to exit
+/// before it is killed (ms)
+pub const SHUTDOWN_TIMEOUT_SETTING: &str = "shutdownTimeoutMs";
+
+const DEFAULT_DRAIN_TIMEOUT: Duration = Duration::from_secs(10);
+const DEFAULT_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+/// Extra time for the drain reply itself to arrive
+const DRAIN_REPLY_SLACK: Duration = Duration::from_secs(2);
+/// Limit for the sidecar to acknowledge `system.shutdown`
+const SHUTDOWN_ACK_TIMEOUT: Duration = Duration::from_secs(2);
+const EXIT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+/// How long a killed sidecar may take to be reaped
+const KILL_EXIT_TIMEOUT: Duration = Duration::from_secs(5);
+/// How long output readers may take to reach end of stream once the process exited
+const READER_DRAIN_TIMEOUT: Duration = Duration::from_secs(1);
+/// Captured lines buffered for slow `subscribe_logs` receivers
+const LOG_LINE_BUFFER: usize = 1024;
+
+/// How long closing a vault waits at each step
+#[derive(Debug, Clone, Copy)]
+pub struct ShutdownTimeouts {
+    pub drain: Duration,
+    pub shutdown: Duration,
+}
+
+impl ShutdownTimeouts {
+    /// `drainTimeoutMs` and `shutdownTimeoutMs` from the vault's settings
+    pub fn for_vault(vault_path: &Path) -> Self {
+        let vault_settings = settings::load_vault_settings(vault_path).unwrap_or_default();
+        let ms = |key: &str| vault_settings.get(key).and_then(|v| v.as_u64()).map(Duration::from_millis);
+        Self {
+            drain: ms(DRAIN_TIMEOUT_SETTING).unwrap_or(DEFAULT_DRAIN_TIMEOUT),
+            shutdown: ms(SHUTDOWN_TIMEOUT_SETTING).unwrap_or(DEFAULT_SHUTDOWN_TIMEOUT),
+        }
+    }
+}
+
+/// Per-call replacements for the vault's timeouts, in milliseconds
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TimeoutOverrides {
+    pub drain_ms: Option<u64>,
+    pub shutdown_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct DrainReport {
+    /// Every in-flight request finished within the drain timeout
+    pub drained: bool,
+    /// Methods of requests still running when it elapsed
+    pub pending_requests: Vec<String>,
+    /// Plugins with callbacks still running when it elapsed
+    pub busy_plugins: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct ShutdownReport {
+    pub drain: DrainReport,
+    /// The sidecar exited on its own within the shutdown timeout
+    pub graceful: bool,
+    /// Plugins still busy when the sidecar was killed; their work may not
+    /// have been flushed
+    pub busy_plugins: Vec<String>,
+}
+
 pub struct SidecarProcess {
     pub child: Child,
-    #[allow(dead_code)]
     pub vault_path: String,
     pub ws_port: u16,
+    pub started_at: chrono::DateTime<chrono::Utc>,
+    /// When the sidecar last answered a heartbeat ping
+    pub last_seen: Option<chrono::DateTime<chrono::Utc>>,
+    /// Tasks copying its stdout and stderr into the window's log store
+    readers: Vec<JoinHandle<()>>,
+}
+
+/// Poll `child` until it exits or `timeout` passes, without blocking the
+/// runtime the way `Child::wait` would. True if it exited.
+async fn wait_for_exit(child: &mut Child, timeout: Duration) -> bool {
+    let deadline = Instant::now() + timeout;
+    loop {
+        match child.try_wait() {
+            Ok(Some(_)) => return true,
+            Ok(None) if Instant::now() < deadline => tokio::time::sleep(EXIT_POLL_INTERVAL).await,
+            _ => return false,
+        }
+    }
+}
+
+impl SidecarProcess {
+    /// Let the output readers reach end of stream after the process exited,
+    /// aborting any still held open (by a grandchild, say)
+    async fn stop_readers(&mut self) {
+        for mut reader in self.readers.drain(..) {
+            if tokio::time::timeout(READER_DRAIN_TIMEOUT, &mut reader).await.is_err() {
+                reader.abort();
+            }
+        }
+    }
+}
+
+/// A worker sidecar hosting a single plugin, in isolated mode
+pub struct PluginWorker {
+    pub process: SidecarProcess,
+    /// Command ids the worker registered; None until first asked
+    pub commands: Option<HashSet<String>>,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct PluginProcessInfo {
+    pub plugin: String,
+    pub pid: u32,
+    pub ws_port: u16,
+    pub running: bool,
+    pub exit_code: Option<i32>,
+    /// Filled in by the caller after pinging the worker
+    pub responsive: Option<bool>,
+}
+
+/// PID and exit state of a window's main sidecar
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SidecarProcessStatus {
+    pub pid: u32,
+    pub ws_port: u16,
+    pub vault_path: String,
+    pub running: bool,
+    pub exit_code: Option<i32>,
+    pub started_at: chrono::DateTime<chrono::Utc>,
+    pub last_seen: Option<chrono::DateTime<chrono::Utc>>,
 }
 
 pub struct SidecarManager {
     processes: Arc<Mutex<HashMap<String, SidecarProcess>>>,
+    /// Per-plugin workers by window label, then plugin name
+    workers: Arc<Mutex<HashMap<String, HashMap<String, PluginWorker>>>>,
     next_port: Arc<Mutex<u16>>,
+    logs: Arc<Mutex<HashMap<String, Arc<LogStore>>>>,
+    /// Every captured output line, with the window it came from
+    log_lines: broadcast::Sender<(String, LogEntry)>,
 }
 
 impl Default for SidecarManager {
@@ -26,21 +182,79 @@ impl SidecarManager {
     pub fn new() -> Self {
         Self {
             processes: Arc::new(Mutex::new(HashMap::new())),
+            workers: Arc::new(Mutex::new(HashMap::new())),
             next_port: Arc::new(Mutex::new(9000)),
+            logs: Arc::new(Mutex::new(HashMap::new())),
+            log_lines: broadcast::channel(LOG_LINE_BUFFER).0,
         }
     }
 
-    /// Spawn a Python sidecar process for a vault
+    /// Spawn a Python sidecar process for a vault.
+    ///
+    /// With `isolatePlugins` set, the returned sidecar loads no plugins and
+    /// each enabled plugin gets a worker sidecar of its own, so one crashing
+    /// or hanging plugin cannot take the others down.
     pub async fn spawn_sidecar(
         &self,
         window_label: String,
         vault_path: String,
     ) -> Result<u16> {
+        let vault_settings = settings::load_vault_settings(Path::new(&vault_path))
+            .unwrap_or_else(|_| serde_json::json!({}));
+        let isolated = settings::get_bool(&vault_settings, ISOLATE_PLUGINS_SETTING, false);
+
+        // Reuse the window's log store across respawns so history is continuous
+        let log_store = self.logs.lock().await
+            .entry(window_label.clone())
+            .or_insert_with(|| Arc::new(LogStore::new(Path::new(&vault_path))))
+            .clone();
+
+        let main_args: Vec<String> = if isolated { vec!["--no-plugins".to_string()] } else { Vec::new() };
+        let process = self
+            .spawn_process(&window_label, &vault_path, &vault_settings, &main_args, "Sidecar", log_store.clone())
+            .await?;
+        let ws_port = process.ws_port;
+        self.processes.lock().await.insert(window_label.clone(), process);
+
+        if isolated {
+            let vault_config = plugins::read_vault_config(Path::new(&vault_path));
+            let mut workers = HashMap::new();
+            for dir in plugins::plugin_dirs(Path::new(&vault_path)) {
+                if !plugins::is_enabled(&vault_config, &dir) {
+                    continue;
+                }
+                let Some(plugin) = dir.file_name().map(|n| n.to_string_lossy().to_string()) else { continue };
+                let args = vec!["--only-plugin".to_string(), plugin.clone()];
+                let tag = format!("Plugin {}", plugin);
+                match self.spawn_process(&window_label, &vault_path, &vault_settings, &args, &tag, log_store.clone()).await {
+                    Ok(process) => {
+                        workers.insert(plugin, PluginWorker { process, commands: None });
+                    }
+                    // The plugin is simply unavailable; the vault still opens
+                    Err(e) => eprintln!("Failed to spawn worker for plugin '{}': {}", plugin, e),
+                }
+            }
+            println!("Spawned {} plugin worker(s) for window '{}'", workers.len(), window_label);
+            self.workers.lock().await.insert(window_label, workers);
+        }
+
+        Ok(ws_port)
+    }
+
+    async fn spawn_process(
+        &self,
+        window_label: &str,
+        vault_path: &str,
+        vault_settings: &serde_json::Value,
+        extra_args: &[String],
+        tag: &str,
+        log_store: Arc<LogStore>,
+    ) -> Result<SidecarProcess> {
         // Allocate port
         let ws_port = self.allocate_port().await;
 
         // Get Python executable path
-        let python_exe = self.get_python_executable()?;
+        let python_exe = DependencyChecker::get_python_executable()?;
         
         // Get project root (parent of src-tauri) to set as CWD
         let project_root = std::env::current_dir()?
@@ -48,20 +262,70 @@ impl SidecarManager {
             .context("Failed to get parent directory")?
             .to_path_buf();
 
-        println!("Spawning sidecar for window '{}': vault={}, port={}", 
-                 window_label, vault_path, ws_port);
+        println!("Spawning {} for window '{}': vault={}, port={}", 
+                 tag, window_label, vault_path, ws_port);
         println!("Python executable: {}", python_exe);
         println!("Project root: {}", project_root.display());
 
         // Spawn Python process with unbuffered output
-        let mut child = Command::new(&python_exe)
+        let mut command = Command::new(&python_exe);
+        command
             .arg("-u")  // Unbuffered output
             .arg("-m")
             .arg("sidecar")
             .arg("--vault")
-            .arg(&vault_path)
+            .arg(vault_path)
             .arg("--ws-port")
             .arg(ws_port.to_string())
+            .args(extra_args);
+
+        // Per-vault limit on concurrently running plugin callbacks
+        if let Some(limit) = vault_settings.get(PLUGIN_CONCURRENCY_SETTING).and_then(|v| v.as_u64()) {
+            if limit >= 1 {
+                command.arg("--plugin-concurrency").arg(limit.to_string());
+            }
+        }
+
+        // Event channels muted with `set_event_subscription`
+        if let Some(muted) = vault_settings.get(MUTED_EVENT_CHANNELS_SETTING).and_then(|v| v.as_array()) {
+            for channel in muted.iter().filter_map(|c| c.as_str()) {
+                command.arg("--mute-event").arg(channel);
+            }
+        }
+        if let Some(level) = vault_settings.get(LOG_LEVEL_SETTING).and_then(|v| v.as_str()) {
+            command.arg("--log-level").arg(level.to_ascii_uppercase());
+        }
+        if let Some(interval) = vault_settings.get(TICK_INTERVAL_SETTING).and_then(|v| v.as_f64()) {
+            command.arg("--tick-interval").arg(interval.to_string());
+        }
+        if vault_settings.get(RESTRICT_PLUGIN_FS_SETTING).and_then(|v| v.as_bool()) == Some(true) {
+            command.arg("--restrict-plugin-fs");
+        }
+        if vault_settings.get(PLUGIN_LIFECYCLE_EVENTS_SETTING).and_then(|v| v.as_bool()) == Some(true) {
+            command.arg("--lifecycle-events");
+        }
+        // Provider requests are metered by the app-wide request budget
+        command.arg("--request-budget");
+        if let Some(root) = plugins::shared_plugins_root() {
+            command.arg("--shared-plugins-dir").arg(root);
+        }
+
+        // Dependencies installed in the vault's venv, ahead of any PYTHONPATH
+        // already set; the sidecar adds the vault's own `lib/` itself
+        let vault = Path::new(vault_path);
+        if let Some(venv) = ArtifactScanner::vault_venv(vault).filter(|venv| venv.is_dir()) {
+            let inherited = std::env::var_os("PYTHONPATH");
+            let paths = std::iter::once(venv).chain(inherited.iter().flat_map(std::env::split_paths));
+            if let Ok(python_path) = std::env::join_paths(paths) {
+                command.env("PYTHONPATH", python_path);
+            }
+        }
+        // Bytecode for plugins goes to the vault's cache rather than next to them
+        if let Some(cache) = ArtifactScanner::vault_cache(vault) {
+            command.env("PYTHONPYCACHEPREFIX", cache.join("pycache"));
+        }
+
+        let mut child = command
             .current_dir(&project_root)
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
@@ -69,70 +333,236 @@ impl SidecarManager {
             .context("Failed to spawn Python sidecar")?;
 
         let pid = child.id();
-        println!("Sidecar spawned with PID: {}", pid);
+        println!("{} spawned with PID: {}", tag, pid);
 
-        // Capture stdout for debugging
+        // Capture output for get_sidecar_logs and live tailing
+        let mut readers = Vec::new();
         if let Some(stdout) = child.stdout.take() {
-            use std::io::BufRead;
-            std::thread::spawn(move || {
-                let reader = std::io::BufReader::new(stdout);
-                for line in reader.lines() {
-                    if let Ok(line) = line {
-                        println!("[Sidecar] {}", line);
-                    }
-                }
-            });
+            let stdout = tokio::process::ChildStdout::from_std(stdout)
+                .context("Failed to capture sidecar stdout")?;
+            readers.push(self.spawn_output_reader(stdout, "stdout", tag, window_label, log_store.clone()));
         }
-
-        // Capture stderr for debugging
         if let Some(stderr) = child.stderr.take() {
-            use std::io::BufRead;
-            std::thread::spawn(move || {
-                let reader = std::io::BufReader::new(stderr);
-                for line in reader.lines() {
-                    if let Ok(line) = line {
-                        eprintln!("[Sidecar Error] {}", line);
-                    }
-                }
-            });
+            let stderr = tokio::process::ChildStderr::from_std(stderr)
+                .context("Failed to capture sidecar stderr")?;
+            readers.push(self.spawn_output_reader(stderr, "stderr", tag, window_label, log_store.clone()));
         }
 
-        // Store process
-        let process = SidecarProcess {
+        Ok(SidecarProcess {
             child,
-            vault_path: vault_path.clone(),
+            vault_path: vault_path.to_string(),
+            ws_port,
+            started_at: chrono::Utc::now(),
+            last_seen: None,
+            readers,
+        })
+    }
+
+    /// Copy one output stream into `log_store` and out to `subscribe_logs`,
+    /// line by line, until the stream closes
+    fn spawn_output_reader<R>(
+        &self,
+        stream: R,
+        stream_name: &'static str,
+        tag: &str,
+        window_label: &str,
+        log_store: Arc<LogStore>,
+    ) -> JoinHandle<()>
+    where
+        R: tokio::io::AsyncRead + Unpin + Send + 'static,
+    {
+        let tag = tag.to_string();
+        let window_label = window_label.to_string();
+        let log_lines = self.log_lines.clone();
+        tokio::spawn(async move {
+            let mut reader = tokio::io::BufReader::new(stream);
+            let mut buf = Vec::new();
+            loop {
+                buf.clear();
+                match reader.read_until(b'\n', &mut buf).await {
+                    Ok(0) | Err(_) => break,
+                    Ok(_) => {}
+                }
+                let line = String::from_utf8_lossy(&buf);
+                let line = line.trim_end_matches(['\r', '\n']);
+                if stream_name == "stderr" {
+                    eprintln!("[{} Error] {}", tag, line);
+                } else {
+                    println!("[{}] {}", tag, line);
+                }
+                let entry = log_store.push_line(stream_name, line);
+                // No receivers just means no log panel is open
+                let _ = log_lines.send((window_label.clone(), entry));
+            }
+        })
+    }
+
+    /// Output lines from every sidecar as they are captured
+    pub fn subscribe_logs(&self) -> broadcast::Receiver<(String, LogEntry)> {
+        self.log_lines.subscribe()
+    }
+
+    /// Replace a window's sidecar (and its workers) with a fresh one under
+    /// the same window label, returning the new WebSocket port
+    pub async fn restart_sidecar(&self, window_label: &str) -> Result<u16> {
+        let vault_path = self.processes.lock().await
+            .get(window_label)
+            .map(|p| p.vault_path.clone())
+            .with_context(|| format!("No sidecar for window '{}'", window_label))?;
+        self.terminate_sidecar(window_label).await?;
+        self.spawn_sidecar(window_label.to_string(), vault_path).await
+    }
+
+    /// Ask a window's sidecar to stop taking requests and wait, up to
+    /// `timeout`, for the ones in flight
+    pub async fn drain_sidecar(&self, window_label: &str, timeout: Duration) -> Result<DrainReport> {
+        let ws_port = self.get_ws_port(window_label).await
+            .with_context(|| format!("No sidecar for window '{}'", window_label))?;
+        Self::drain_port(ws_port, timeout).await
+    }
+
+    async fn drain_port(ws_port: u16, timeout: Duration) -> Result<DrainReport> {
+        let reply = SidecarClient::request(
             ws_port,
+            "system.drain",
+            serde_json::json!({ "timeout_ms": timeout.as_millis() as u64 }),
+            timeout + DRAIN_REPLY_SLACK,
+        ).await?;
+        let strings = |key: &str| -> Vec<String> {
+            reply.get(key)
+                .and_then(|v| v.as_array())
+                .map(|items| items.iter().filter_map(|i| i.as_str().map(str::to_string)).collect())
+                .unwrap_or_default()
         };
+        Ok(DrainReport {
+            drained: reply.get("drained").and_then(|v| v.as_bool()).unwrap_or(false),
+            pending_requests: strings("pending"),
+            busy_plugins: strings("busy_plugins"),
+        })
+    }
 
-        self.processes.lock().await.insert(window_label.clone(), process);
+    /// Terminate a sidecar process, with the vault's configured timeouts
+    pub async fn terminate_sidecar(&self, window_label: &str) -> Result<ShutdownReport> {
+        self.terminate_sidecar_with(window_label, TimeoutOverrides::default()).await
+    }
 
-        Ok(ws_port)
+    /// Drain the sidecar and its plugin workers, let them unload their
+    /// plugins and exit, and kill any that have not exited in time
+    pub async fn terminate_sidecar_with(&self, window_label: &str, overrides: TimeoutOverrides) -> Result<ShutdownReport> {
+        let process = self.processes.lock().await.remove(window_label);
+        let workers = self.workers.lock().await.remove(window_label).unwrap_or_default();
+
+        let main = async {
+            match process {
+                Some(mut process) => {
+                    let name = format!("sidecar for window '{}'", window_label);
+                    Self::stop_gracefully(&name, &mut process, overrides).await
+                }
+                None => ShutdownReport { graceful: true, ..Default::default() },
+            }
+        };
+        let workers = futures::future::join_all(workers.into_iter().map(|(plugin, mut worker)| async move {
+            let name = format!("worker for plugin '{}' in window '{}'", plugin, window_label);
+            Self::stop_gracefully(&name, &mut worker.process, overrides).await
+        }));
+        let (mut report, worker_reports) = tokio::join!(main, workers);
+
+        for worker in worker_reports {
+            report.graceful &= worker.graceful;
+            report.busy_plugins.extend(worker.busy_plugins);
+        }
+        report.busy_plugins.sort();
+        report.busy_plugins.dedup();
+        Ok(report)
     }
 
-    /// Terminate a sidecar process
-    pub async fn terminate_sidecar(&self, window_label: &str) -> Result<()> {
-        let mut processes = self.processes.lock().await;
-        
-        if let Some(mut process) = processes.remove(window_label) {
-            println!("Terminating sidecar for window '{}'", window_label);
-            
-            // Try graceful shutdown first
+    /// Drain one sidecar process, ask it to shut down, wait for it to exit
+    /// and kill it past the shutdown timeout. `name` describes it in logs.
+    async fn stop_gracefully(name: &str, process: &mut SidecarProcess, overrides: TimeoutOverrides) -> ShutdownReport {
+        let mut report = ShutdownReport { graceful: true, ..Default::default() };
+        // Nothing to drain in a sidecar that already exited
+        if matches!(process.child.try_wait(), Ok(Some(_))) {
+            process.stop_readers().await;
+            return report;
+        }
+
+        println!("Terminating {}", name);
+        let vault_path = PathBuf::from(&process.vault_path);
+        let mut timeouts = ShutdownTimeouts::for_vault(&vault_path);
+        if let Some(ms) = overrides.drain_ms {
+            timeouts.drain = Duration::from_millis(ms);
+        }
+        if let Some(ms) = overrides.shutdown_ms {
+            timeouts.shutdown = Duration::from_millis(ms);
+        }
+
+        match Self::drain_port(process.ws_port, timeouts.drain).await {
+            Ok(drain) => report.drain = drain,
+            Err(e) => eprintln!("Failed to drain {}: {}", name, e),
+        }
+        if !report.drain.drained && !report.drain.pending_requests.is_empty() {
+            println!(
+                "Drain timed out for {} with {} request(s) still running",
+                name,
+                report.drain.pending_requests.len()
+            );
+        }
+
+        // The sidecar unloads its plugins and exits after acknowledging
+        let asked = SidecarClient::request(process.ws_port, "system.shutdown", serde_json::json!({}), SHUTDOWN_ACK_TIMEOUT).await;
+        let exited = asked.is_ok() && wait_for_exit(&mut process.child, timeouts.shutdown).await;
+
+        if !exited {
+            report.graceful = false;
+            let mut busy = report.drain.busy_plugins.clone();
+            busy.extend(hang_detector::busy_plugins(&vault_path));
+            busy.sort();
+            busy.dedup();
+            if !busy.is_empty() {
+                eprintln!("Warning: Killing {} while plugins were busy: {}", name, busy.join(", "));
+            }
+            report.busy_plugins = busy;
+
             if let Err(e) = process.child.kill() {
-                eprintln!("Failed to kill sidecar process: {}", e);
+                eprintln!("Failed to kill {}: {}", name, e);
             }
-            
-            // Wait for process to exit
-            if let Err(e) = process.child.wait() {
-                eprintln!("Failed to wait for sidecar exit: {}", e);
+            if !wait_for_exit(&mut process.child, KILL_EXIT_TIMEOUT).await {
+                eprintln!("Warning: {} did not exit after being killed", name);
             }
-            
-            println!("Sidecar terminated for window '{}'", window_label);
         }
+        process.stop_readers().await;
+
+        println!("Terminated {}", name);
+        report
+    }
 
-        Ok(())
+    /// Terminate every tracked sidecar concurrently, each the way
+    /// `terminate_sidecar_with` does. The process map is locked only to list
+    /// labels and to take each entry, so a window closing meanwhile finds its
+    /// sidecar already gone rather than waiting on this.
+    pub async fn terminate_all(&self, overrides: TimeoutOverrides) -> Vec<(String, ShutdownReport)> {
+        let mut labels = self.window_labels().await;
+        labels.extend(self.workers.lock().await.keys().cloned());
+        labels.sort();
+        labels.dedup();
+
+        let results = futures::future::join_all(
+            labels.iter().map(|label| self.terminate_sidecar_with(label, overrides)),
+        ).await;
+        labels.into_iter()
+            .zip(results)
+            .filter_map(|(label, result)| match result {
+                Ok(report) => Some((label, report)),
+                Err(e) => {
+                    eprintln!("Failed to terminate sidecar for window '{}': {}", label, e);
+                    None
+                }
+            })
+            .collect()
     }
 
-    /// Terminate ALL sidecar processes (used for app shutdown)
+    /// Kill any sidecar processes still tracked, without waiting for them
+    /// to exit cleanly (the last resort on app shutdown)
     pub fn shutdown_all(&self) {
         println!("Shutting down all sidecars...");
         // Use blocking lock for shutdown
@@ -145,6 +575,12 @@ impl SidecarManager {
                      let _ = process.child.wait(); // Best effort wait
                 }
              }
+             if let Ok(mut workers) = self.workers.try_lock() {
+                 for (_, mut worker) in workers.drain().flat_map(|(_, w)| w) {
+                     let _ = worker.process.child.kill();
+                     let _ = worker.process.child.wait();
+                 }
+             }
         } else {
             // Fallback: If we can't lock (unlikely in shutdown), we might leak. 
             // Better to force lock if possible, but try_lock avoids deadlock potential in panic paths.
@@ -159,8 +595,124 @@ impl SidecarManager {
             .map(|p| p.ws_port)
     }
 
+    /// Window whose main sidecar or plugin worker listens on `ws_port`
+    pub async fn label_for_port(&self, ws_port: u16) -> Option<String> {
+        if let Some((label, _)) = self.processes.lock().await.iter().find(|(_, p)| p.ws_port == ws_port) {
+            return Some(label.clone());
+        }
+        self.workers.lock().await
+            .iter()
+            .find(|(_, workers)| workers.values().any(|w| w.process.ws_port == ws_port))
+            .map(|(label, _)| label.clone())
+    }
+
+    /// PID and port of the process hosting `plugin`: its worker in isolated
+    /// mode, otherwise the main sidecar
+    pub async fn plugin_host(&self, window_label: &str, plugin: &str) -> Option<(u32, u16)> {
+        if let Some(worker) = self.workers.lock().await
+            .get(window_label)
+            .and_then(|workers| workers.get(plugin))
+        {
+            return Some((worker.process.child.id(), worker.process.ws_port));
+        }
+        self.processes.lock().await
+            .get(window_label)
+            .map(|p| (p.child.id(), p.ws_port))
+    }
+
+    /// Labels of windows that have a main sidecar
+    pub async fn window_labels(&self) -> Vec<String> {
+        self.processes.lock().await.keys().cloned().collect()
+    }
+
+    pub async fn process_status(&self, window_label: &str) -> Option<SidecarProcessStatus> {
+        let mut processes = self.processes.lock().await;
+        let process = processes.get_mut(window_label)?;
+        let exit = process.child.try_wait().ok().flatten();
+        Some(SidecarProcessStatus {
+            pid: process.child.id(),
+            ws_port: process.ws_port,
+            vault_path: process.vault_path.clone(),
+            running: exit.is_none(),
+            exit_code: exit.and_then(|status| status.code()),
+            started_at: process.started_at,
+            last_seen: process.last_seen,
+        })
+    }
+
+    /// Note that the window's sidecar answered a heartbeat
+    pub async fn mark_seen(&self, window_label: &str) {
+        if let Some(process) = self.processes.lock().await.get_mut(window_label) {
+            process.last_seen = Some(chrono::Utc::now());
+        }
+    }
+
+    /// Worker ports whose command list has not been fetched yet
+    pub async fn workers_without_commands(&self, window_label: &str) -> Vec<(String, u16)> {
+        self.workers.lock().await
+            .get(window_label)
+            .map(|workers| {
+                workers.iter()
+                    .filter(|(_, w)| w.commands.is_none())
+                    .map(|(plugin, w)| (plugin.clone(), w.process.ws_port))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    pub async fn set_worker_commands(&self, window_label: &str, plugin: &str, commands: HashSet<String>) {
+        if let Some(worker) = self.workers.lock().await
+            .get_mut(window_label)
+            .and_then(|workers| workers.get_mut(plugin))
+        {
+            worker.commands = Some(commands);
+        }
+    }
+
+    /// The worker port registered for `method`, if any worker owns it
+    pub async fn route_command(&self, window_label: &str, method: &str) -> Option<u16> {
+        self.workers.lock().await
+            .get(window_label)?
+            .values()
+            .find(|w| w.commands.as_ref().is_some_and(|c| c.contains(method)))
+            .map(|w| w.process.ws_port)
+    }
+
+    pub async fn has_workers(&self, window_label: &str) -> bool {
+        self.workers.lock().await
+            .get(window_label)
+            .is_some_and(|workers| !workers.is_empty())
+    }
+
+    /// PID and exit state of each plugin worker for a window
+    pub async fn plugin_process_info(&self, window_label: &str) -> Vec<PluginProcessInfo> {
+        let mut workers = self.workers.lock().await;
+        let Some(workers) = workers.get_mut(window_label) else { return Vec::new() };
+
+        let mut info: Vec<_> = workers.iter_mut()
+            .map(|(plugin, worker)| {
+                let exit = worker.process.child.try_wait().ok().flatten();
+                PluginProcessInfo {
+                    plugin: plugin.clone(),
+                    pid: worker.process.child.id(),
+                    ws_port: worker.process.ws_port,
+                    running: exit.is_none(),
+                    exit_code: exit.and_then(|status| status.code()),
+                    responsive: None,
+                }
+            })
+            .collect();
+        info.sort_by(|a, b| a.plugin.cmp(&b.plugin));
+        info
+    }
+
+    /// Query captured sidecar output for a window
+    pub async fn get_logs(&self, window_label: &str, query: &LogQuery) -> Option<LogPage> {
+        let store = self.logs.lock().await.get(window_label).cloned()?;
+        Some(store.query(query))
+    }
+
     /// Check if sidecar is still running
-    #[allow(dead_code)]
     pub async fn is_running(&self, window_label: &str) -> bool {
         self.processes.lock().await.contains_key(window_label)
     }
@@ -190,29 +742,6 @@ impl SidecarManager {
         use std::net::TcpListener;
         TcpListener::bind(("127.0.0.1", port)).is_ok()
     }
-
-    /// Get Python executable path
-    fn get_python_executable(&self) -> Result<String> {
-        // Try to find Python in PATH
-        #[cfg(target_os = "windows")]
-        let python_candidates = vec!["python.exe", "python3.exe"];
-        
-        #[cfg(not(target_os = "windows"))]
-        let python_candidates = vec!["python3", "python"];
-
-        for candidate in python_candidates {
-            if let Ok(output) = Command::new(candidate)
-                .arg("--version")
-                .output()
-            {
-                if output.status.success() {
-                    return Ok(candidate.to_string());
-                }
-            }
-        }
-
-        anyhow::bail!("Python not found in PATH")
-    }
 }
 
 impl Drop for SidecarManager {
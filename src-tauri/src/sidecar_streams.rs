@@ -0,0 +1,85 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Event type of the sidecar's partial stream output
+pub const STREAM_CHUNK_EVENT: &str = "STREAM_CHUNK";
+/// Request param naming the channel the sidecar streams to
+pub const STREAM_CHANNEL_PARAM: &str = "stream_channel";
+
+struct ActiveStream {
+    window_label: String,
+    request_id: String,
+    cancelled: bool,
+}
+
+/// One piece of a stream, as sent by the sidecar
+pub struct StreamChunk {
+    pub data: serde_json::Value,
+    /// The command returned; `data` is its result
+    pub is_final: bool,
+}
+
+/// Streams started with `stream_from_sidecar`, by channel id
+#[derive(Default)]
+pub struct StreamRegistry {
+    streams: Mutex<HashMap<String, ActiveStream>>,
+}
+
+impl StreamRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Claim `channel_id` for a request; false if the channel is in use
+    pub fn open(&self, channel_id: &str, window_label: &str, request_id: &str) -> bool {
+        let mut streams = self.streams.lock().unwrap();
+        if streams.contains_key(channel_id) {
+            return false;
+        }
+        streams.insert(channel_id.to_string(), ActiveStream {
+            window_label: window_label.to_string(),
+            request_id: request_id.to_string(),
+            cancelled: false,
+        });
+        true
+    }
+
+    /// Mark the window's stream cancelled; returns its request id so the
+    /// request itself can be cancelled
+    pub fn cancel(&self, window_label: &str, channel_id: &str) -> Option<String> {
+        let mut streams = self.streams.lock().unwrap();
+        let stream = streams.get_mut(channel_id).filter(|s| s.window_label == window_label)?;
+        stream.cancelled = true;
+        Some(stream.request_id.clone())
+    }
+
+    pub fn is_cancelled(&self, channel_id: &str) -> bool {
+        self.streams.lock().unwrap().get(channel_id).is_some_and(|s| s.cancelled)
+    }
+
+    pub fn close(&self, channel_id: &str) {
+        self.streams.lock().unwrap().remove(channel_id);
+    }
+}
+
+/// The chunk in a `trigger_event` notification's params, if it belongs to
+/// `channel_id`
+pub fn chunk_for(params: &serde_json::Value, channel_id: &str) -> Option<StreamChunk> {
+    if params.get("event_type").and_then(|t| t.as_str()) != Some(STREAM_CHUNK_EVENT) {
+        return None;
+    }
+    let data = params.get("data")?;
+    if data.get("channel_id").and_then(|c| c.as_str()) != Some(channel_id) {
+        return None;
+    }
+    Some(StreamChunk {
+        data: data.get("data").cloned().unwrap_or(serde_json::Value::Null),
+        is_final: data.get("final").and_then(|f| f.as_bool()).unwrap_or(false),
+    })
+}
+
+/// Channel ids become part of event names, which allow only these characters
+pub fn is_valid_channel_id(channel_id: &str) -> bool {
+    !channel_id.is_empty()
+        && channel_id.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | ':'))
+}
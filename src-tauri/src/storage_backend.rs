@@ -0,0 +1,340 @@
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::RwLock;
+use std::time::Instant;
+use serde::Serialize;
+
+/// Folder names only sync clients use, matched against each path component
+/// (case-insensitively)
+const CLOUD_SYNC_FOLDERS: &[(&str, &str)] = &[
+    ("dropbox", "Dropbox"),
+    ("icloud drive", "iCloud"),
+    ("mobile documents", "iCloud"),
+    ("onedrive", "OneDrive"),
+    ("google drive", "Google Drive"),
+    ("googledrive", "Google Drive"),
+    ("pclouddrive", "pCloud"),
+    ("nextcloud", "Nextcloud"),
+    ("owncloud", "ownCloud"),
+];
+/// Sync roots whose names are ordinary words elsewhere, so they only count
+/// directly in the home directory
+const HOME_SYNC_FOLDERS: &[(&str, &str)] = &[
+    ("box", "Box"),
+    ("box sync", "Box"),
+];
+/// Marker files a sync client drops in the root of its folder
+const CLOUD_SYNC_MARKERS: &[(&str, &str)] = &[
+    (".dropbox", "Dropbox"),
+    (".dropbox.cache", "Dropbox"),
+    (".sync", "Resilio Sync"),
+    (".stfolder", "Syncthing"),
+];
+/// Mount types for network filesystems
+const NETWORK_FS_TYPES: &[&str] = &["nfs", "nfs4", "cifs", "smb", "smb2", "smb3", "smbfs", "afpfs", "webdav", "davfs", "9p", "ncpfs"];
+/// Network filesystems on which replacing a file by rename is atomic
+const ATOMIC_NETWORK_FS_TYPES: &[&str] = &["nfs", "nfs4"];
+/// Probe round trips (ms) above which a backend counts as medium or high latency
+const MEDIUM_LATENCY_MS: f64 = 5.0;
+const HIGH_LATENCY_MS: f64 = 50.0;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StorageBackend {
+    /// "local", "network", "fuse" or "cloud_sync"
+    pub kind: String,
+    /// Mount type, e.g. "ext4", "apfs", "cifs", "fuse.sshfs", when known
+    pub filesystem: Option<String>,
+    /// Sync client owning the folder, for "cloud_sync"
+    pub sync_provider: Option<String>,
+    /// Whether replacing a file by rename is atomic here. When false,
+    /// `atomic_write` falls back to copy-based writes for this vault.
+    pub supports_atomic_rename: bool,
+    /// "low", "medium" or "high", from a small write/rename/read probe
+    pub latency_class: String,
+    pub probe_ms: Option<f64>,
+    /// Why this location is risky for a vault, if it is
+    pub warning: Option<String>,
+}
+
+/// Vault roots whose storage cannot replace files atomically by rename,
+/// both as opened and canonicalized, decided once when the vault opens
+static COPY_WRITE_ROOTS: RwLock<Vec<PathBuf>> = RwLock::new(Vec::new());
+/// Whether `COPY_WRITE_ROOTS` has any entries, so writes into ordinary local
+/// vaults need no lock
+static ANY_COPY_WRITE_ROOTS: AtomicBool = AtomicBool::new(false);
+
+/// Remember how to write into `vault_path`, so `atomic_write` adapts to it
+pub fn register(vault_path: &Path, backend: &StorageBackend) {
+    let canonical = fs::canonicalize(vault_path).unwrap_or_else(|_| vault_path.to_path_buf());
+    let mut roots = COPY_WRITE_ROOTS.write().unwrap_or_else(|e| e.into_inner());
+    roots.retain(|root| root != vault_path && *root != canonical);
+    if !backend.supports_atomic_rename {
+        roots.push(vault_path.to_path_buf());
+        if canonical != vault_path {
+            roots.push(canonical);
+        }
+    }
+    ANY_COPY_WRITE_ROOTS.store(!roots.is_empty(), Ordering::Release);
+}
+
+/// Whether writes to `path` must avoid rename-over. Paths are matched as
+/// given; commands build them from the vault path the window opened with.
+pub fn needs_copy_write(path: &Path) -> bool {
+    if !ANY_COPY_WRITE_ROOTS.load(Ordering::Acquire) {
+        return false;
+    }
+    let roots = COPY_WRITE_ROOTS.read().unwrap_or_else(|e| e.into_inner());
+    roots.iter().any(|root| path.starts_with(root))
+}
+
+/// Work out what kind of storage holds `vault_path` and how it behaves
+pub fn detect(vault_path: &Path) -> StorageBackend {
+    let path = fs::canonicalize(vault_path).unwrap_or_else(|_| vault_path.to_path_buf());
+    let filesystem = mount_type(&path);
+    let home = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE")).map(PathBuf::from);
+    let sync_provider = cloud_sync_provider(&path, home.as_deref());
+
+    let fs_kind = filesystem.as_deref().map(classify_filesystem);
+    let kind = if sync_provider.is_some() {
+        "cloud_sync"
+    } else if is_unc(&path) {
+        "network"
+    } else {
+        fs_kind.unwrap_or("local")
+    };
+
+    let probe = probe_rename(&path);
+    let rename_safe = match (kind, filesystem.as_deref()) {
+        ("network", Some(fs_type)) => ATOMIC_NETWORK_FS_TYPES.contains(&base_type(fs_type)),
+        ("network", None) | ("fuse", _) => false,
+        _ => true,
+    };
+    let supports_atomic_rename = rename_safe && probe.as_ref().is_ok_and(|(ok, _)| *ok);
+
+    let probe_ms = probe.as_ref().ok().map(|(_, ms)| *ms);
+    let latency_class = match probe_ms {
+        Some(ms) if ms >= HIGH_LATENCY_MS => "high",
+        Some(ms) if ms >= MEDIUM_LATENCY_MS || kind == "network" => "medium",
+        Some(_) => "low",
+        None if kind == "local" => "low",
+        None => "high",
+    };
+
+    let warning = match kind {
+        "cloud_sync" => Some(format!(
+            "Vault is inside a {} folder. Sync conflicts can corrupt conversations and settings if the vault is open on two devices, or while the sync client rewrites files.",
+            sync_provider.as_deref().unwrap_or("cloud sync")
+        )),
+        "network" | "fuse" if !supports_atomic_rename => Some(format!(
+            "Vault is on {} storage{} where file replacement is not atomic; writes fall back to slower copy-based updates and an interrupted write may leave a partial file next to its recovery copy.",
+            kind,
+            filesystem.as_deref().map(|f| format!(" ({})", f)).unwrap_or_default(),
+        )),
+        "network" => Some("Vault is on network storage; expect slower opens and avoid opening it from two machines at once.".to_string()),
+        _ => None,
+    };
+
+    StorageBackend {
+        kind: kind.to_string(),
+        filesystem,
+        sync_provider,
+        supports_atomic_rename,
+        latency_class: latency_class.to_string(),
+        probe_ms,
+        warning,
+    }
+}
+
+fn classify_filesystem(fs_type: &str) -> &'static str {
+    if NETWORK_FS_TYPES.contains(&base_type(fs_type)) {
+        "network"
+    } else if fs_type == "fuse" || fs_type.starts_with("fuse.") || fs_type.starts_with("macfuse") || fs_type == "osxfuse" {
+        // sshfs, rclone and friends are network storage behind FUSE
+        if fs_type.ends_with("sshfs") || fs_type.ends_with("rclone") {
+            "network"
+        } else {
+            "fuse"
+        }
+    } else {
+        "local"
+    }
+}
+
+/// "nfs4" for "nfs4", "smb3" for "smb3", "cifs" for "cifs"; drops FUSE prefixes
+fn base_type(fs_type: &str) -> &str {
+    fs_type.strip_prefix("fuse.").unwrap_or(fs_type)
+}
+
+fn is_unc(path: &Path) -> bool {
+    let text = path.to_string_lossy();
+    (text.starts_with(r"\\") && !text.starts_with(r"\\?\")) || text.starts_with(r"\\?\UNC\")
+}
+
+fn cloud_sync_provider(path: &Path, home: Option<&Path>) -> Option<String> {
+    let names: Vec<String> = path.components()
+        .filter_map(|component| match component {
+            Component::Normal(name) => Some(name.to_string_lossy().to_lowercase()),
+            _ => None,
+        })
+        .collect();
+    let known = |name: &str| {
+        CLOUD_SYNC_FOLDERS.iter()
+            .chain(HOME_SYNC_FOLDERS)
+            .find(|(folder, _)| *folder == name)
+            .map(|(_, provider)| provider.to_string())
+    };
+
+    // macOS File Provider folders: ~/Library/CloudStorage/<Provider>-<account>
+    let cloud_storage = names.windows(2).position(|pair| pair[0] == "library" && pair[1] == "cloudstorage");
+    if let Some(entry) = cloud_storage.and_then(|at| names.get(at + 2)) {
+        let provider = entry.split('-').next().unwrap_or(entry.as_str());
+        return Some(known(provider).unwrap_or_else(|| "cloud storage".to_string()));
+    }
+
+    // e.g. "Dropbox (Personal)", "OneDrive - Contoso", "GoogleDrive-me@example.com"
+    for name in &names {
+        let matched = CLOUD_SYNC_FOLDERS.iter().find(|(folder, _)| {
+            name.as_str() == *folder
+                || (name.starts_with(folder) && name[folder.len()..].starts_with([' ', '-', '(']))
+        });
+        if let Some((_, provider)) = matched {
+            return Some(provider.to_string());
+        }
+    }
+
+    // ~/Box, ~/Box Sync
+    let in_home = home.and_then(|home| path.strip_prefix(home).ok());
+    if let Some(Component::Normal(first)) = in_home.and_then(|rest| rest.components().next()) {
+        let first = first.to_string_lossy().to_lowercase();
+        if let Some((_, provider)) = HOME_SYNC_FOLDERS.iter().find(|(folder, _)| *folder == first) {
+            return Some(provider.to_string());
+        }
+    }
+
+    // Google Drive for desktop on Windows mounts a drive with "My Drive" at its root
+    let on_drive = matches!(path.components().next(), Some(Component::Prefix(_)));
+    if on_drive && names.first().is_some_and(|name| name == "my drive") {
+        return Some("Google Drive".to_string());
+    }
+
+    path.ancestors().find_map(|dir| {
+        CLOUD_SYNC_MARKERS.iter()
+            .find(|(marker, _)| dir.join(marker).exists())
+            .map(|(_, provider)| provider.to_string())
+    })
+}
+
+/// Filesystem type of the mount holding `path`, from the mount table
+fn mount_type(path: &Path) -> Option<String> {
+    let table = fs::read_to_string("/proc/self/mounts")
+        .ok()
+        .map(|contents| parse_proc_mounts(&contents))
+        .or_else(|| {
+            let output = std::process::Command::new("mount").output().ok()?;
+            Some(parse_mount_output(&String::from_utf8_lossy(&output.stdout)))
+        })?;
+
+    table.into_iter()
+        .filter(|(mount_point, _)| path.starts_with(mount_point))
+        .max_by_key(|(mount_point, _)| mount_point.components().count())
+        .map(|(_, fs_type)| fs_type)
+}
+
+/// `/proc/self/mounts`: "device mount_point type options 0 0", with spaces
+/// in paths escaped as `\040`
+fn parse_proc_mounts(contents: &str) -> Vec<(PathBuf, String)> {
+    contents.lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let _device = fields.next()?;
+            let mount_point = fields.next()?.replace("\\040", " ").replace("\\011", "\t");
+            let fs_type = fields.next()?;
+            Some((PathBuf::from(mount_point), fs_type.to_string()))
+        })
+        .collect()
+}
+
+/// BSD/macOS `mount`: "device on /mount/point (type, option, ...)"
+fn parse_mount_output(output: &str) -> Vec<(PathBuf, String)> {
+    output.lines()
+        .filter_map(|line| {
+            let (_, rest) = line.split_once(" on ")?;
+            let open = rest.rfind(" (")?;
+            let mount_point = &rest[..open];
+            let fs_type = rest[open + 2..].split([',', ')']).next()?.trim();
+            Some((PathBuf::from(mount_point), fs_type.to_string()))
+        })
+        .collect()
+}
+
+/// Write two files under `.tailor/`, rename one over the other and read it
+/// back. Returns whether the replacement took, and how long it all took.
+fn probe_rename(vault_path: &Path) -> std::io::Result<(bool, f64)> {
+    let dir = vault_path.join(".tailor");
+    fs::create_dir_all(&dir)?;
+    let id = uuid::Uuid::new_v4();
+    let target = dir.join(format!(".storage-probe-{}", id));
+    let replacement = dir.join(format!(".storage-probe-{}.tmp", id));
+
+    let started = Instant::now();
+    let result = (|| {
+        fs::write(&target, b"old")?;
+        fs::write(&replacement, b"new")?;
+        fs::rename(&replacement, &target)?;
+        Ok(fs::read(&target)? == b"new")
+    })();
+    let elapsed = started.elapsed().as_secs_f64() * 1000.0;
+
+    let _ = fs::remove_file(&target);
+    let _ = fs::remove_file(&replacement);
+    result.map(|ok| (ok, elapsed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn provider(path: &str) -> Option<String> {
+        cloud_sync_provider(Path::new(path), Some(Path::new("/home/me")))
+    }
+
+    #[test]
+    fn provider_roots_are_recognised() {
+        assert_eq!(provider("/home/me/Dropbox (Personal)/vault").as_deref(), Some("Dropbox"));
+        assert_eq!(provider("/home/me/OneDrive - Contoso/vault").as_deref(), Some("OneDrive"));
+        assert_eq!(provider("/home/me/Box/vault").as_deref(), Some("Box"));
+        assert_eq!(provider("/home/me/Google Drive/My Drive/vault").as_deref(), Some("Google Drive"));
+        assert_eq!(
+            provider("/Users/me/Library/CloudStorage/GoogleDrive-me@example.com/My Drive/vault").as_deref(),
+            Some("Google Drive")
+        );
+        assert_eq!(provider("/Users/me/Library/CloudStorage/Box-Box/vault").as_deref(), Some("Box"));
+        assert_eq!(provider("/Users/me/Library/CloudStorage/SomeSync-me/vault").as_deref(), Some("cloud storage"));
+    }
+
+    #[test]
+    fn ordinary_folders_named_like_sync_roots_are_not_cloud_sync() {
+        assert_eq!(provider("/home/me/projects/box/vault"), None);
+        assert_eq!(provider("/srv/box/vault"), None);
+        assert_eq!(provider("/home/me/notes/My Drive/vault"), None);
+        assert_eq!(provider("/home/me/dropboxes/vault"), None);
+    }
+
+    #[test]
+    fn copy_writes_apply_under_registered_roots_only() {
+        let vault = std::env::temp_dir().join(format!("tailor-storage-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&vault).unwrap();
+        let mut backend = detect(&vault);
+
+        backend.supports_atomic_rename = false;
+        register(&vault, &backend);
+        assert!(needs_copy_write(&vault.join("conversations").join("a.json")));
+        assert!(!needs_copy_write(&std::env::temp_dir().join("elsewhere.json")));
+
+        backend.supports_atomic_rename = true;
+        register(&vault, &backend);
+        assert!(!needs_copy_write(&vault.join("conversations").join("a.json")));
+        fs::remove_dir_all(&vault).unwrap();
+    }
+}
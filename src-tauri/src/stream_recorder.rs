@@ -0,0 +1,277 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use serde_json::{json, Value};
+use anyhow::Result;
+
+use crate::connection_pool::ConnectionPool;
+use crate::conversation_index;
+use crate::conversations::{self, Message, COMMITTED_OFFSET_FIELD, COMPLETE_FIELD, STREAM_ID_FIELD};
+
+const STREAM_START_EVENT: &str = "CHAT_STREAM_START";
+const TOKEN_EVENT: &str = "CHAT_TOKEN";
+const STREAM_END_EVENT: &str = "CHAT_STREAM_END";
+/// Minimum time between writes of a streaming reply's partial text
+const FLUSH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Error recorded on replies whose stream events were dropped
+const LOST_EVENTS_ERROR: &str = "Stream events were lost; the reply may be incomplete";
+
+/// A streamed reply being mirrored into its conversation file
+struct ActiveStream {
+    vault_path: PathBuf,
+    conversation_id: String,
+    message_index: usize,
+    /// Text already committed before this stream started (when resuming)
+    prefix: String,
+    /// Latest accumulated text, written when the stream can no longer be followed
+    text: String,
+    last_flush: Instant,
+}
+
+/// Mirror streamed chat replies into their conversation files as they arrive.
+///
+/// The reply is saved as `complete: false` with a `committed_offset` while
+/// tokens are flowing and flipped to complete when the stream ends, so an
+/// interrupted reply can be found and resumed later.
+pub fn spawn_stream_recorder(pool: Arc<ConnectionPool>) {
+    let mut events = pool.subscribe();
+    tauri::async_runtime::spawn(async move {
+        let mut recorder = StreamRecorder::default();
+        loop {
+            match events.recv().await {
+                Ok((_port, params)) => recorder.handle(&params),
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                    eprintln!("Warning: Stream recorder skipped {} events", skipped);
+                    recorder.interrupt_all();
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    });
+}
+
+/// The streams being followed, updated one sidecar event at a time
+#[derive(Default)]
+struct StreamRecorder {
+    streams: HashMap<String, ActiveStream>,
+}
+
+impl StreamRecorder {
+    fn handle(&mut self, params: &Value) {
+        let Some(event_type) = params.get("event_type").and_then(|t| t.as_str()) else { return };
+        let Some(data) = params.get("data") else { return };
+        let Some(stream_id) = data.get("stream_id").and_then(|v| v.as_str()) else { return };
+
+        let result = match event_type {
+            STREAM_START_EVENT => start_stream(stream_id, data).map(|stream| {
+                if let Some(stream) = stream {
+                    self.streams.insert(stream_id.to_string(), stream);
+                }
+            }),
+            TOKEN_EVENT => match self.streams.get_mut(stream_id) {
+                Some(stream) => {
+                    let accumulated = data.get("accumulated").and_then(|v| v.as_str()).unwrap_or("");
+                    stream.text = accumulated.to_string();
+                    if stream.last_flush.elapsed() >= FLUSH_INTERVAL {
+                        stream.last_flush = Instant::now();
+                        write_reply(stream, stream_id, accumulated, false, None)
+                    } else {
+                        Ok(())
+                    }
+                }
+                None => Ok(()),
+            },
+            STREAM_END_EVENT => match self.streams.remove(stream_id) {
+                Some(stream) => {
+                    let response = data.get("response").and_then(|v| v.as_str()).unwrap_or("");
+                    let success = data.get("status").and_then(|v| v.as_str()) == Some("success");
+                    let error = data.get("error").and_then(|v| v.as_str());
+                    write_reply(&stream, stream_id, response, success, error)
+                }
+                None => Ok(()),
+            },
+            _ => Ok(()),
+        };
+
+        if let Err(e) = result {
+            eprintln!("Warning: Failed to record stream {}: {}", stream_id, e);
+        }
+    }
+
+    /// Events were dropped, possibly a stream's end: save every reply as
+    /// interrupted, with the text received so far, and stop following it.
+    /// Left alone it would stay unfinished with a stale offset for good.
+    fn interrupt_all(&mut self) {
+        for (stream_id, stream) in self.streams.drain() {
+            if let Err(e) = write_reply(&stream, &stream_id, &stream.text, false, Some(LOST_EVENTS_ERROR)) {
+                eprintln!("Warning: Failed to record stream {}: {}", stream_id, e);
+            }
+        }
+    }
+}
+
+/// Register a stream tied to a stored conversation, adding its placeholder reply
+fn start_stream(stream_id: &str, data: &Value) -> Result<Option<ActiveStream>> {
+    let Some(conversation_id) = data.get("chat_id").and_then(|v| v.as_str()) else { return Ok(None) };
+    let Some(vault_path) = data.get("vault_path").and_then(|v| v.as_str()) else { return Ok(None) };
+    let vault_path = PathBuf::from(vault_path);
+
+    // Streams for chats the app hasn't persisted are not ours to record
+    if !conversations::conversation_path(&vault_path, conversation_id)?.exists() {
+        return Ok(None);
+    }
+
+    let (message_index, prefix) = match data.get("resume") {
+        Some(resume) => {
+            let conversation = conversations::load_conversation(&vault_path, conversation_id)?;
+            let index = resume.get("message_index")
+                .and_then(|v| v.as_u64())
+                .map(|i| i as usize)
+                .filter(|i| *i < conversation.messages.len())
+                .ok_or_else(|| anyhow::anyhow!("Resume targets a missing message"))?;
+            let partial = resume.get("partial").and_then(|v| v.as_str()).unwrap_or("");
+            (index, partial.to_string())
+        }
+        None => {
+            let mut message = Message {
+                role: "assistant".to_string(),
+                content: String::new(),
+                timestamp: Some(chrono::Utc::now().to_rfc3339()),
+                extra: Default::default(),
+            };
+            message.extra.insert(COMPLETE_FIELD.to_string(), json!(false));
+            message.extra.insert(COMMITTED_OFFSET_FIELD.to_string(), json!(0));
+            message.extra.insert(STREAM_ID_FIELD.to_string(), json!(stream_id));
+            let conversation = conversations::update_conversation(&vault_path, conversation_id, |conversation| {
+                conversation.messages.push(message);
+                Ok(())
+            })?;
+            (conversation.messages.len() - 1, String::new())
+        }
+    };
+
+    Ok(Some(ActiveStream {
+        vault_path,
+        conversation_id: conversation_id.to_string(),
+        message_index,
+        prefix,
+        text: String::new(),
+        last_flush: Instant::now(),
+    }))
+}
+
+/// Store `prefix + text` as the reply, marking it complete when `finished`
+fn write_reply(stream: &ActiveStream, stream_id: &str, text: &str, finished: bool, error: Option<&str>) -> Result<()> {
+    let conversation = conversations::update_conversation(&stream.vault_path, &stream.conversation_id, |conversation| {
+        let Some(message) = conversation.messages.get_mut(stream.message_index) else {
+            anyhow::bail!("Message {} no longer exists", stream.message_index);
+        };
+
+        let content = format!("{}{}", stream.prefix, text);
+        let offset = content.chars().count();
+        message.content = content;
+        message.extra.insert(STREAM_ID_FIELD.to_string(), json!(stream_id));
+        message.extra.insert(COMPLETE_FIELD.to_string(), json!(finished));
+        message.extra.insert(COMMITTED_OFFSET_FIELD.to_string(), json!(offset));
+        match error {
+            Some(error) if !finished => { message.extra.insert("error".to_string(), json!(error)); }
+            _ => { message.extra.remove("error"); }
+        }
+        conversation.updated = Some(chrono::Utc::now().to_rfc3339());
+        Ok(())
+    })?;
+
+    if finished || error.is_some() {
+        conversation_index::record_write(&stream.vault_path, &conversation);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_vault() -> PathBuf {
+        let vault = std::env::temp_dir().join(format!("tailor-streams-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(conversations::conversations_dir(&vault)).unwrap();
+        let conversation = serde_json::from_value(json!({ "id": "chat", "messages": [] })).unwrap();
+        conversations::create_conversation(&vault, conversation).unwrap();
+        vault
+    }
+
+    fn event(event_type: &str, vault: &std::path::Path, data: Value) -> Value {
+        let mut data = data;
+        data["stream_id"] = json!("s1");
+        data["chat_id"] = json!("chat");
+        data["vault_path"] = json!(vault.to_string_lossy());
+        json!({ "event_type": event_type, "data": data })
+    }
+
+    fn reply(vault: &std::path::Path) -> Message {
+        conversations::load_conversation(vault, "chat").unwrap().messages.pop().unwrap()
+    }
+
+    #[test]
+    fn a_finished_stream_is_saved_complete() {
+        let vault = temp_vault();
+        let mut recorder = StreamRecorder::default();
+        recorder.handle(&event(STREAM_START_EVENT, &vault, json!({})));
+        assert!(!reply(&vault).is_complete());
+
+        recorder.handle(&event(TOKEN_EVENT, &vault, json!({ "accumulated": "Hel" })));
+        recorder.handle(&event(STREAM_END_EVENT, &vault, json!({ "response": "Hello", "status": "success" })));
+        let message = reply(&vault);
+        assert!(message.is_complete());
+        assert_eq!(message.content, "Hello");
+        assert_eq!(message.extra[COMMITTED_OFFSET_FIELD], json!(5));
+        std::fs::remove_dir_all(&vault).unwrap();
+    }
+
+    #[test]
+    fn lost_events_leave_streams_interrupted_with_the_text_so_far() {
+        let vault = temp_vault();
+        let mut recorder = StreamRecorder::default();
+        recorder.handle(&event(STREAM_START_EVENT, &vault, json!({})));
+        // Within the flush interval, so only held in memory
+        recorder.handle(&event(TOKEN_EVENT, &vault, json!({ "accumulated": "Partial" })));
+
+        recorder.interrupt_all();
+        let message = reply(&vault);
+        assert!(!message.is_complete());
+        assert_eq!(message.content, "Partial");
+        assert_eq!(message.extra[COMMITTED_OFFSET_FIELD], json!(7));
+        assert_eq!(message.extra["error"], json!(LOST_EVENTS_ERROR));
+
+        // The stream is no longer followed; a late end doesn't resurrect it
+        recorder.handle(&event(STREAM_END_EVENT, &vault, json!({ "response": "Partial reply", "status": "success" })));
+        assert_eq!(conversations::incomplete_messages(&vault, "chat").unwrap().len(), 1);
+        std::fs::remove_dir_all(&vault).unwrap();
+    }
+
+    #[test]
+    fn stream_writes_do_not_drop_concurrent_appends() {
+        let vault = temp_vault();
+        let stream = start_stream("s1", &event(STREAM_START_EVENT, &vault, json!({}))["data"]).unwrap().unwrap();
+
+        std::thread::scope(|scope| {
+            scope.spawn(|| {
+                for i in 0..20 {
+                    write_reply(&stream, "s1", &"x".repeat(i), false, None).unwrap();
+                }
+            });
+            scope.spawn(|| {
+                for i in 0..20 {
+                    let message = serde_json::from_value(json!({ "role": "user", "content": i.to_string() })).unwrap();
+                    conversations::append_message(&vault, "chat", message).unwrap();
+                }
+            });
+        });
+
+        let conversation = conversations::load_conversation(&vault, "chat").unwrap();
+        assert_eq!(conversation.messages.len(), 21);
+        assert_eq!(conversation.messages[0].content, "x".repeat(19));
+        std::fs::remove_dir_all(&vault).unwrap();
+    }
+}
@@ -0,0 +1,129 @@
+use std::collections::{HashSet, VecDeque};
+use std::future::Future;
+use serde::Serialize;
+use tokio::sync::{Mutex, Notify};
+use anyhow::Result;
+
+/// Finished tasks kept for inspection
+const HISTORY_CAPACITY: usize = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskStatus {
+    Running,
+    Completed,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskRecord {
+    pub id: String,
+    pub kind: String,
+    pub vault_path: String,
+    pub status: TaskStatus,
+    pub started: String,
+    pub finished: Option<String>,
+    /// Short human-readable outcome on success
+    pub summary: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Default)]
+struct TaskState {
+    history: VecDeque<TaskRecord>,
+    /// Vaults with a heavy task in flight
+    busy_vaults: HashSet<String>,
+}
+
+/// Runs long vault operations (backups, plugin updates, venv rebuilds) and
+/// keeps a short history of their outcomes.
+///
+/// Only one task runs per vault at a time, so two heavy operations can never
+/// rewrite the same vault concurrently.
+#[derive(Default)]
+pub struct TaskManager {
+    state: Mutex<TaskState>,
+    /// Signalled whenever a vault's task finishes
+    freed: Notify,
+}
+
+impl TaskManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Run `work` as a tracked task for `vault_path`, returning its summary
+    pub async fn run<F>(&self, kind: &str, vault_path: &str, work: F) -> Result<TaskRecord>
+    where
+        F: Future<Output = Result<String>>,
+    {
+        {
+            let mut state = self.state.lock().await;
+            if !state.busy_vaults.insert(vault_path.to_string()) {
+                anyhow::bail!("Another task is already running for {}", vault_path);
+            }
+        }
+        Ok(self.execute(kind, vault_path, work).await)
+    }
+
+    /// `run`, but waiting for the vault's current task to finish instead of
+    /// failing while one is in flight
+    pub async fn run_queued<F>(&self, kind: &str, vault_path: &str, work: F) -> TaskRecord
+    where
+        F: Future<Output = Result<String>>,
+    {
+        loop {
+            // Created before the check so a task finishing in between is not missed
+            let freed = self.freed.notified();
+            if self.state.lock().await.busy_vaults.insert(vault_path.to_string()) {
+                break;
+            }
+            freed.await;
+        }
+        self.execute(kind, vault_path, work).await
+    }
+
+    /// Run `work` for a vault already marked busy, then free the vault
+    async fn execute<F>(&self, kind: &str, vault_path: &str, work: F) -> TaskRecord
+    where
+        F: Future<Output = Result<String>>,
+    {
+        let mut record = TaskRecord {
+            id: format!("task_{}", uuid::Uuid::new_v4()),
+            kind: kind.to_string(),
+            vault_path: vault_path.to_string(),
+            status: TaskStatus::Running,
+            started: chrono::Utc::now().to_rfc3339(),
+            finished: None,
+            summary: None,
+            error: None,
+        };
+
+        println!("Task {} ({}) started for {}", record.id, kind, vault_path);
+        let result = work.await;
+
+        record.finished = Some(chrono::Utc::now().to_rfc3339());
+        match &result {
+            Ok(summary) => {
+                record.status = TaskStatus::Completed;
+                record.summary = Some(summary.clone());
+            }
+            Err(e) => {
+                record.status = TaskStatus::Failed;
+                record.error = Some(format!("{:#}", e));
+            }
+        }
+        println!("Task {} ({}) finished: {:?}", record.id, kind, record.status);
+
+        let mut state = self.state.lock().await;
+        state.busy_vaults.remove(vault_path);
+        if state.history.len() >= HISTORY_CAPACITY {
+            state.history.pop_front();
+        }
+        state.history.push_back(record.clone());
+        drop(state);
+        self.freed.notify_waiters();
+
+        record
+    }
+}
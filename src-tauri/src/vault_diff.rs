@@ -0,0 +1,147 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+use serde::Serialize;
+use anyhow::Result;
+
+use crate::conversations;
+use crate::plugins::{self, PluginManifestEntry};
+use crate::settings;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiffStatus {
+    Same,
+    Changed,
+    OnlyInA,
+    OnlyInB,
+}
+
+impl DiffStatus {
+    fn of<T: PartialEq>(a: Option<&T>, b: Option<&T>) -> Self {
+        match (a, b) {
+            (Some(a), Some(b)) if a == b => Self::Same,
+            (Some(_), Some(_)) => Self::Changed,
+            (Some(_), None) => Self::OnlyInA,
+            (None, _) => Self::OnlyInB,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct PluginDiff {
+    pub name: String,
+    pub status: DiffStatus,
+    pub a: Option<PluginManifestEntry>,
+    pub b: Option<PluginManifestEntry>,
+    /// Which fields differ when `status` is `changed` ("version", "enabled")
+    pub changed_fields: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SettingDiff {
+    pub key: String,
+    pub status: DiffStatus,
+    pub a: Option<serde_json::Value>,
+    pub b: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ConversationCounts {
+    pub a: usize,
+    pub b: usize,
+}
+
+/// Structural comparison of two vaults, laid out for side-by-side display
+#[derive(Debug, Serialize)]
+pub struct VaultDiff {
+    pub vault_a: String,
+    pub vault_b: String,
+    /// True when plugins, settings and conversation counts all match
+    pub identical: bool,
+    pub plugins: Vec<PluginDiff>,
+    pub settings: Vec<SettingDiff>,
+    pub conversations: ConversationCounts,
+}
+
+/// Compare installed plugins (by manifest export), top-level vault settings
+/// and conversation counts. Conversation content is not diffed.
+pub fn diff_vaults(vault_a: &Path, vault_b: &Path) -> Result<VaultDiff> {
+    for vault in [vault_a, vault_b] {
+        if !vault.is_dir() {
+            anyhow::bail!("Vault directory not found: {}", vault.display());
+        }
+    }
+
+    let plugins = diff_plugins(
+        plugins::export_manifests(vault_a),
+        plugins::export_manifests(vault_b),
+    );
+    let settings = diff_settings(
+        &settings::load_vault_settings(vault_a)?,
+        &settings::load_vault_settings(vault_b)?,
+    );
+    let conversations = ConversationCounts {
+        a: conversations::conversation_files(vault_a).len(),
+        b: conversations::conversation_files(vault_b).len(),
+    };
+
+    let identical = plugins.iter().all(|p| p.status == DiffStatus::Same)
+        && settings.iter().all(|s| s.status == DiffStatus::Same)
+        && conversations.a == conversations.b;
+
+    Ok(VaultDiff {
+        vault_a: vault_a.to_string_lossy().to_string(),
+        vault_b: vault_b.to_string_lossy().to_string(),
+        identical,
+        plugins,
+        settings,
+        conversations,
+    })
+}
+
+fn diff_plugins(a: Vec<PluginManifestEntry>, b: Vec<PluginManifestEntry>) -> Vec<PluginDiff> {
+    let mut by_name: BTreeMap<String, (Option<PluginManifestEntry>, Option<PluginManifestEntry>)> =
+        BTreeMap::new();
+    for entry in a {
+        by_name.entry(entry.name.clone()).or_default().0 = Some(entry);
+    }
+    for entry in b {
+        by_name.entry(entry.name.clone()).or_default().1 = Some(entry);
+    }
+
+    by_name
+        .into_iter()
+        .map(|(name, (a, b))| {
+            let mut changed_fields = Vec::new();
+            if let (Some(a), Some(b)) = (&a, &b) {
+                if a.version != b.version {
+                    changed_fields.push("version".to_string());
+                }
+                if a.enabled != b.enabled {
+                    changed_fields.push("enabled".to_string());
+                }
+            }
+            let status = DiffStatus::of(a.as_ref(), b.as_ref());
+            PluginDiff { name, status, a, b, changed_fields }
+        })
+        .collect()
+}
+
+fn diff_settings(a: &serde_json::Value, b: &serde_json::Value) -> Vec<SettingDiff> {
+    let empty = serde_json::Map::new();
+    let a = a.as_object().unwrap_or(&empty);
+    let b = b.as_object().unwrap_or(&empty);
+
+    let mut keys: Vec<&String> = a.keys().chain(b.keys()).collect();
+    keys.sort();
+    keys.dedup();
+
+    keys.into_iter()
+        .map(|key| SettingDiff {
+            key: key.clone(),
+            status: DiffStatus::of(a.get(key), b.get(key)),
+            a: a.get(key).cloned(),
+            b: b.get(key).cloned(),
+        })
+        .collect()
+}
@@ -0,0 +1,187 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde::Serialize;
+use anyhow::{Result, Context};
+
+use crate::fs_utils::atomic_write;
+use crate::vault_files::glob_match;
+
+/// Per-vault exclusion list, in gitignore syntax
+pub const EXCLUDE_FILE: &str = ".tailor/exclude";
+
+/// Used while the vault has no exclude file: the venv, caches and trash
+pub const DEFAULT_EXCLUDES: &[&str] = &[
+    ".venv/",
+    "venv/",
+    "__pycache__/",
+    "*.pyc",
+    ".cache/",
+    ".tailor/cache/",
+    ".trash/",
+    ".tailor/trash/",
+];
+
+#[derive(Debug, Serialize)]
+pub struct VaultExcludes {
+    pub patterns: Vec<String>,
+    /// True when the vault has no exclude file and the defaults apply
+    pub is_default: bool,
+}
+
+#[derive(Debug)]
+struct Rule {
+    /// Pattern split on `/`; `**` matches any number of segments
+    segments: Vec<String>,
+    negated: bool,
+    dir_only: bool,
+    /// Contains a `/` before the end, so matches from the vault root only
+    anchored: bool,
+}
+
+/// Decides whether a vault-relative path is excluded. Follows gitignore:
+/// the last matching rule wins, `!` re-includes, a trailing `/` matches
+/// directories only, and nothing inside an excluded directory can be
+/// re-included.
+#[derive(Debug)]
+pub struct ExcludeMatcher {
+    rules: Vec<Rule>,
+}
+
+impl ExcludeMatcher {
+    pub fn new<S: AsRef<str>>(patterns: &[S]) -> Self {
+        Self { rules: patterns.iter().filter_map(|p| parse_rule(p.as_ref())).collect() }
+    }
+
+    /// The vault's exclude file, or the defaults if there is none
+    pub fn load(vault_path: &Path) -> Self {
+        Self::new(&read(vault_path).patterns)
+    }
+
+    pub fn is_excluded(&self, rel_path: &Path, is_dir: bool) -> bool {
+        let segments: Vec<String> = rel_path
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy().to_string())
+            .collect();
+        (1..segments.len()).any(|len| self.matches(&segments[..len], true))
+            || (!segments.is_empty() && self.matches(&segments, is_dir))
+    }
+
+    fn matches(&self, segments: &[String], is_dir: bool) -> bool {
+        let mut excluded = false;
+        for rule in &self.rules {
+            if rule.dir_only && !is_dir {
+                continue;
+            }
+            let hit = if rule.anchored {
+                match_segments(&rule.segments, segments)
+            } else {
+                segments.last().is_some_and(|name| glob_match(&rule.segments[0], name))
+            };
+            if hit {
+                excluded = !rule.negated;
+            }
+        }
+        excluded
+    }
+}
+
+fn parse_rule(line: &str) -> Option<Rule> {
+    let line = line.trim_end();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    let (negated, line) = match line.strip_prefix('!') {
+        Some(rest) => (true, rest),
+        None => (false, line.strip_prefix('\\').unwrap_or(line)),
+    };
+    let (dir_only, line) = match line.strip_suffix('/') {
+        Some(rest) => (true, rest),
+        None => (false, line),
+    };
+    let anchored = line.contains('/');
+    let line = line.trim_start_matches('/');
+    if line.is_empty() {
+        return None;
+    }
+    Some(Rule {
+        segments: line.split('/').map(str::to_string).collect(),
+        negated,
+        dir_only,
+        anchored,
+    })
+}
+
+fn match_segments(pattern: &[String], path: &[String]) -> bool {
+    match pattern.first().map(String::as_str) {
+        None => path.is_empty(),
+        Some("**") => (0..=path.len()).any(|skip| match_segments(&pattern[1..], &path[skip..])),
+        Some(segment) => {
+            !path.is_empty()
+                && glob_match(segment, &path[0])
+                && match_segments(&pattern[1..], &path[1..])
+        }
+    }
+}
+
+pub fn read(vault_path: &Path) -> VaultExcludes {
+    match fs::read_to_string(vault_path.join(EXCLUDE_FILE)) {
+        Ok(contents) => VaultExcludes {
+            patterns: contents.lines().map(str::to_string).collect(),
+            is_default: false,
+        },
+        Err(_) => VaultExcludes {
+            patterns: DEFAULT_EXCLUDES.iter().map(|p| p.to_string()).collect(),
+            is_default: true,
+        },
+    }
+}
+
+pub fn write(vault_path: &Path, patterns: &[String]) -> Result<VaultExcludes> {
+    if let Some(bad) = patterns.iter().find(|p| p.contains(['\n', '\r', '\0'])) {
+        anyhow::bail!("Exclude patterns must be single lines: {:?}", bad);
+    }
+    let mut contents = patterns.join("\n");
+    contents.push('\n');
+    atomic_write(&vault_path.join(EXCLUDE_FILE), contents.as_bytes())
+        .context("Failed to write exclude file")?;
+    Ok(read(vault_path))
+}
+
+/// Vault-relative paths of every file that is not excluded, without
+/// following symlinks
+pub fn included_files(vault_path: &Path, matcher: &ExcludeMatcher) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    collect_files(vault_path, Path::new(""), matcher, &mut files);
+    files.sort();
+    files
+}
+
+fn collect_files(root: &Path, rel: &Path, matcher: &ExcludeMatcher, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(root.join(rel)) else { return };
+    for entry in entries.flatten() {
+        let Ok(file_type) = entry.file_type() else { continue };
+        let child = rel.join(entry.file_name());
+        if file_type.is_dir() {
+            if !matcher.is_excluded(&child, true) {
+                collect_files(root, &child, matcher, files);
+            }
+        } else if file_type.is_file() && !matcher.is_excluded(&child, false) {
+            files.push(child);
+        }
+    }
+}
+
+/// Copy the vault's non-excluded files into `dst`
+pub fn copy_vault(vault_path: &Path, dst: &Path) -> Result<usize> {
+    let files = included_files(vault_path, &ExcludeMatcher::load(vault_path));
+    for rel in &files {
+        let target = dst.join(rel);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        fs::copy(vault_path.join(rel), &target)
+            .with_context(|| format!("Failed to copy {}", rel.display()))?;
+    }
+    Ok(files.len())
+}
@@ -0,0 +1,206 @@
+use std::fs;
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+use serde::Serialize;
+use anyhow::{Result, Context};
+
+/// Largest file preview returned by `read_vault_file`
+pub const MAX_PREVIEW_BYTES: u64 = 1024 * 1024;
+/// Stop listing after this many matches
+const MAX_LISTED_FILES: usize = 5000;
+/// Bytes inspected when sniffing for binary content
+const SNIFF_BYTES: usize = 8192;
+
+#[derive(Debug, Serialize)]
+pub struct VaultFileEntry {
+    /// Path relative to the vault root, always with `/` separators
+    pub path: String,
+    pub size_bytes: u64,
+    /// Modification time in ms since the epoch
+    pub modified_ms: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VaultFileListing {
+    pub files: Vec<VaultFileEntry>,
+    /// True when the listing stopped at the entry limit
+    pub truncated: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FileContentKind {
+    Text,
+    Binary,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VaultFilePreview {
+    pub path: String,
+    pub kind: FileContentKind,
+    pub size_bytes: u64,
+    /// UTF-8 content for text files; None for binary files
+    pub content: Option<String>,
+    /// True when the file is larger than the preview limit
+    pub truncated: bool,
+}
+
+/// Resolve a vault-relative path, rejecting absolute paths, `..` and
+/// anything that escapes the vault through a symlink.
+pub fn resolve_in_vault(vault_path: &Path, rel_path: &str) -> Result<PathBuf> {
+    let rel = Path::new(rel_path);
+    for component in rel.components() {
+        match component {
+            Component::Normal(_) | Component::CurDir => {}
+            Component::ParentDir => anyhow::bail!("Path may not contain '..': {}", rel_path),
+            Component::RootDir | Component::Prefix(_) => {
+                anyhow::bail!("Path must be relative to the vault: {}", rel_path)
+            }
+        }
+    }
+
+    let root = vault_path.canonicalize()
+        .with_context(|| format!("Vault directory not found: {}", vault_path.display()))?;
+    let candidate = root.join(rel);
+
+    // Existing paths are canonicalized so symlinks can't point outside the vault
+    if candidate.exists() {
+        let resolved = candidate.canonicalize()
+            .with_context(|| format!("Failed to resolve {}", rel_path))?;
+        if !resolved.starts_with(&root) {
+            anyhow::bail!("Path escapes the vault: {}", rel_path);
+        }
+        return Ok(resolved);
+    }
+
+    Ok(candidate)
+}
+
+/// List files under `subdir` (recursively) whose name matches `pattern`
+/// (`*` and `?` wildcards; None matches everything).
+pub fn list_files(vault_path: &Path, subdir: &str, pattern: Option<&str>) -> Result<VaultFileListing> {
+    let root = vault_path.canonicalize()
+        .with_context(|| format!("Vault directory not found: {}", vault_path.display()))?;
+    let start = resolve_in_vault(vault_path, subdir)?;
+    if !start.is_dir() {
+        anyhow::bail!("Not a directory: {}", subdir);
+    }
+
+    let mut listing = VaultFileListing { files: Vec::new(), truncated: false };
+    let mut stack = vec![start];
+
+    while let Some(dir) = stack.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else { continue };
+        for entry in entries.flatten() {
+            let Ok(file_type) = entry.file_type() else { continue };
+            // Symlinks are not followed so the walk stays inside the vault
+            if file_type.is_dir() {
+                stack.push(entry.path());
+                continue;
+            }
+            if !file_type.is_file() {
+                continue;
+            }
+
+            let name = entry.file_name().to_string_lossy().to_string();
+            if pattern.is_some_and(|p| !glob_match(p, &name)) {
+                continue;
+            }
+
+            if listing.files.len() >= MAX_LISTED_FILES {
+                listing.truncated = true;
+                return Ok(sorted(listing));
+            }
+
+            let metadata = entry.metadata().ok();
+            let path = entry.path();
+            let relative = path.strip_prefix(&root).unwrap_or(&path);
+            listing.files.push(VaultFileEntry {
+                path: relative.components()
+                    .map(|c| c.as_os_str().to_string_lossy().to_string())
+                    .collect::<Vec<_>>()
+                    .join("/"),
+                size_bytes: metadata.as_ref().map(|m| m.len()).unwrap_or(0),
+                modified_ms: metadata
+                    .and_then(|m| m.modified().ok())
+                    .map(|t| chrono::DateTime::<chrono::Utc>::from(t).timestamp_millis()),
+            });
+        }
+    }
+
+    Ok(sorted(listing))
+}
+
+/// Read up to `MAX_PREVIEW_BYTES` of a vault file for previewing
+pub fn read_file(vault_path: &Path, rel_path: &str) -> Result<VaultFilePreview> {
+    let path = resolve_in_vault(vault_path, rel_path)?;
+    if !path.is_file() {
+        anyhow::bail!("File not found: {}", rel_path);
+    }
+
+    let size_bytes = fs::metadata(&path)?.len();
+    let mut buffer = Vec::new();
+    fs::File::open(&path)
+        .with_context(|| format!("Failed to open {}", rel_path))?
+        .take(MAX_PREVIEW_BYTES)
+        .read_to_end(&mut buffer)?;
+    let truncated = size_bytes > MAX_PREVIEW_BYTES;
+
+    let sniff = &buffer[..buffer.len().min(SNIFF_BYTES)];
+    let text = if sniff.contains(&0) {
+        None
+    } else {
+        match std::str::from_utf8(&buffer) {
+            Ok(text) => Some(text.to_string()),
+            // A multi-byte character cut off by the preview limit is still text
+            Err(e) if truncated && e.error_len().is_none() => {
+                Some(String::from_utf8_lossy(&buffer[..e.valid_up_to()]).to_string())
+            }
+            Err(_) => None,
+        }
+    };
+
+    Ok(VaultFilePreview {
+        path: rel_path.to_string(),
+        kind: if text.is_some() { FileContentKind::Text } else { FileContentKind::Binary },
+        size_bytes,
+        content: text,
+        truncated,
+    })
+}
+
+fn sorted(mut listing: VaultFileListing) -> VaultFileListing {
+    listing.files.sort_by(|a, b| a.path.cmp(&b.path));
+    listing
+}
+
+/// Match a file name against a pattern with `*` (any run) and `?` (one char)
+pub fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                Some((star_p, star_n)) => {
+                    p = star_p + 1;
+                    n = star_n + 1;
+                    backtrack = Some((star_p, star_n + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
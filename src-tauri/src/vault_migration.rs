@@ -0,0 +1,204 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use anyhow::{Result, Context};
+
+use crate::conversations::{self, CONVERSATIONS_DIR};
+use crate::fs_utils::atomic_write;
+
+/// Version written into `.vault.json` by `create_vault` and by migration
+pub const VAULT_CONFIG_VERSION: &str = "1.0.0";
+
+/// Root-level JSON files that are vault metadata, never conversations
+const RESERVED_ROOT_FILES: &[&str] = &[".vault.json", ".vault-settings.json"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum MigrationChange {
+    CreateDir { path: String },
+    MoveConversation { from: String, to: String },
+    SkipConversation { path: String, reason: String },
+    CreateConfig { path: String },
+    AddConfigVersion { path: String, version: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MigrationReport {
+    pub vault_path: String,
+    /// True when at least one change is (or was) needed
+    pub legacy: bool,
+    pub dry_run: bool,
+    pub changes: Vec<MigrationChange>,
+    pub backup_path: Option<String>,
+}
+
+pub struct VaultMigrator;
+
+impl VaultMigrator {
+    /// Work out what a migration would change without touching the vault
+    pub fn plan(vault_path: &Path) -> Result<Vec<MigrationChange>> {
+        let mut changes = Vec::new();
+
+        for dir in [".tailor", CONVERSATIONS_DIR] {
+            let path = vault_path.join(dir);
+            if !path.is_dir() {
+                changes.push(MigrationChange::CreateDir { path: display(&path) });
+            }
+        }
+
+        let conversations_dir = conversations::conversations_dir(vault_path);
+        for loose in Self::loose_conversations(vault_path)? {
+            let (path, id) = loose;
+            let target = conversations_dir.join(format!("{}.json", id));
+            if target.exists() {
+                changes.push(MigrationChange::SkipConversation {
+                    path: display(&path),
+                    reason: format!("{} already exists", display(&target)),
+                });
+            } else {
+                changes.push(MigrationChange::MoveConversation {
+                    from: display(&path),
+                    to: display(&target),
+                });
+            }
+        }
+
+        let config_path = vault_path.join(".vault.json");
+        if !config_path.exists() {
+            changes.push(MigrationChange::CreateConfig { path: display(&config_path) });
+        } else {
+            let contents = fs::read_to_string(&config_path)
+                .context("Failed to read vault config")?;
+            let config: serde_json::Value = serde_json::from_str(&contents)
+                .context("Failed to parse vault config")?;
+            if config.get("version").and_then(|v| v.as_str()).is_none() {
+                changes.push(MigrationChange::AddConfigVersion {
+                    path: display(&config_path),
+                    version: VAULT_CONFIG_VERSION.to_string(),
+                });
+            }
+        }
+
+        Ok(changes)
+    }
+
+    /// Upgrade a legacy vault in place (or only report when `dry_run`).
+    ///
+    /// Every file that will be rewritten or moved is copied into
+    /// `.tailor/backups/migration-<timestamp>/` first.
+    pub fn migrate(vault_path: &Path, dry_run: bool) -> Result<MigrationReport> {
+        if !vault_path.is_dir() {
+            anyhow::bail!("Vault directory not found: {}", vault_path.display());
+        }
+
+        let changes = Self::plan(vault_path)?;
+        let legacy = Self::has_work(&changes);
+
+        let mut report = MigrationReport {
+            vault_path: display(vault_path),
+            legacy,
+            dry_run,
+            changes: changes.clone(),
+            backup_path: None,
+        };
+
+        if dry_run || !legacy {
+            return Ok(report);
+        }
+
+        let backup_dir = vault_path
+            .join(".tailor")
+            .join("backups")
+            .join(format!("migration-{}", chrono::Utc::now().format("%Y%m%d%H%M%S")));
+        fs::create_dir_all(&backup_dir)
+            .with_context(|| format!("Failed to create {}", backup_dir.display()))?;
+        report.backup_path = Some(display(&backup_dir));
+
+        for change in &changes {
+            match change {
+                MigrationChange::CreateDir { path } => {
+                    fs::create_dir_all(path)
+                        .with_context(|| format!("Failed to create {}", path))?;
+                }
+                MigrationChange::MoveConversation { from, to } => {
+                    let from = PathBuf::from(from);
+                    if let Some(name) = from.file_name() {
+                        fs::copy(&from, backup_dir.join(name))
+                            .with_context(|| format!("Failed to back up {}", from.display()))?;
+                    }
+                    fs::rename(&from, to)
+                        .with_context(|| format!("Failed to move {}", from.display()))?;
+                }
+                MigrationChange::SkipConversation { .. } => {}
+                MigrationChange::CreateConfig { path } => {
+                    let name = vault_path.file_name()
+                        .map(|n| n.to_string_lossy().to_string())
+                        .unwrap_or_else(|| "Vault".to_string());
+                    let config = serde_json::json!({
+                        "id": format!("vault_{}", uuid::Uuid::new_v4().to_string().replace('-', "")),
+                        "name": name,
+                        "version": VAULT_CONFIG_VERSION,
+                        "created": chrono::Utc::now().to_rfc3339(),
+                    });
+                    atomic_write(Path::new(path), serde_json::to_string_pretty(&config)?.as_bytes())?;
+                }
+                MigrationChange::AddConfigVersion { path, version } => {
+                    let path = Path::new(path);
+                    fs::copy(path, backup_dir.join(".vault.json"))
+                        .context("Failed to back up vault config")?;
+                    let mut config: serde_json::Value =
+                        serde_json::from_str(&fs::read_to_string(path)?)?;
+                    config["version"] = serde_json::json!(version);
+                    atomic_write(path, serde_json::to_string_pretty(&config)?.as_bytes())?;
+                }
+            }
+        }
+
+        println!(
+            "Migrated vault {}: {} changes (backup at {})",
+            vault_path.display(),
+            changes.len(),
+            backup_dir.display()
+        );
+
+        Ok(report)
+    }
+
+    fn has_work(changes: &[MigrationChange]) -> bool {
+        changes.iter().any(|c| !matches!(c, MigrationChange::SkipConversation { .. }))
+    }
+
+    /// Conversation files sitting at the vault root, paired with their ids
+    fn loose_conversations(vault_path: &Path) -> Result<Vec<(PathBuf, String)>> {
+        let mut found = Vec::new();
+
+        for entry in fs::read_dir(vault_path).context("Failed to read vault directory")?.flatten() {
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().to_string();
+            if !path.is_file() || !name.ends_with(".json") || RESERVED_ROOT_FILES.contains(&name.as_str()) {
+                continue;
+            }
+
+            let Ok(text) = fs::read_to_string(&path) else { continue };
+            // Require an explicit messages array so unrelated JSON with an `id` is left alone
+            let has_messages = serde_json::from_str::<serde_json::Value>(&text)
+                .map(|v| v.get("messages").is_some_and(|m| m.is_array()))
+                .unwrap_or(false);
+            if !has_messages {
+                continue;
+            }
+            let Ok(conversation) = conversations::parse_conversation(&text) else { continue };
+            // Only move files whose id is safe to use as a file name
+            if conversations::conversation_path(vault_path, &conversation.id).is_ok() {
+                found.push((path, conversation.id));
+            }
+        }
+
+        found.sort();
+        Ok(found)
+    }
+}
+
+fn display(path: &Path) -> String {
+    path.to_string_lossy().to_string()
+}
@@ -1,9 +1,43 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tauri::{AppHandle, WebviewWindowBuilder};
+use tokio::sync::{mpsc, Mutex};
 use anyhow::Result;
 
+use crate::window_session::{self, SessionWindow, WindowGeometry};
+
+/// Least time between session writes caused by moving or resizing
+const GEOMETRY_WRITE_INTERVAL: Duration = Duration::from_millis(500);
+
 pub struct WindowManager {
     windows: HashMap<String, String>, // window_label -> vault_path
+    last_used: HashMap<String, Instant>, // window_label -> last command or open
+    disposable: HashSet<String>, // windows whose vault is deleted on close
+    geometry: HashMap<String, WindowGeometry>, // window_label -> last size and position
+    session: Vec<String>, // windows to reopen next launch, in opening order
+    session_dir: Option<PathBuf>, // app config dir the session is saved in
+    session_frozen: bool, // set while quitting, so closing windows keeps them
+    session_written: Option<Instant>,
+    reserved: usize, // slots held by `SlotReservation`s of windows still opening
+}
+
+/// A window slot held while a vault opens, from `WindowManager::reserve_slot`.
+/// `register_window` uses it up; dropping it unused frees the slot.
+pub struct SlotReservation {
+    window_manager: Option<Arc<Mutex<WindowManager>>>,
+}
+
+impl Drop for SlotReservation {
+    fn drop(&mut self) {
+        if let Some(window_manager) = self.window_manager.take() {
+            tauri::async_runtime::spawn(async move {
+                let mut window_manager = window_manager.lock().await;
+                window_manager.reserved = window_manager.reserved.saturating_sub(1);
+            });
+        }
+    }
 }
 
 impl Default for WindowManager {
@@ -16,35 +50,89 @@ impl WindowManager {
     pub fn new() -> Self {
         Self {
             windows: HashMap::new(),
+            last_used: HashMap::new(),
+            disposable: HashSet::new(),
+            geometry: HashMap::new(),
+            session: Vec::new(),
+            session_dir: None,
+            session_frozen: false,
+            session_written: None,
+            reserved: 0,
         }
     }
 
-    /// Create a new vault window
-    pub fn create_vault_window(
-        &mut self,
+    /// Save the open windows to `window_session::SESSION_FILE` in `app_config_dir`
+    pub fn set_session_dir(&mut self, app_config_dir: PathBuf) {
+        self.session_dir = Some(app_config_dir);
+    }
+
+    /// Create a new vault window, at `geometry` when restoring one. Building
+    /// a window needs the main thread, so this takes no lock on the manager;
+    /// track the window afterwards with `register_window`.
+    pub fn build_vault_window(
         app: &AppHandle,
-        vault_path: String,
+        vault_path: &str,
+        geometry: Option<WindowGeometry>,
     ) -> Result<String> {
         // Generate unique window label
         let window_label = format!("vault_{}", uuid::Uuid::new_v4());
 
         // Create the window
-        let _window = WebviewWindowBuilder::new(
+        let mut builder = WebviewWindowBuilder::new(
             app,
             &window_label,
             tauri::WebviewUrl::App("vault.html".into()),
         )
-        .title(format!("Tailor - {}", Self::extract_vault_name(&vault_path)))
+        .title(format!("Tailor - {}", Self::extract_vault_name(vault_path)))
         .inner_size(1200.0, 800.0)
-        .resizable(true)
-        .build()?;
+        .resizable(true);
+        if let Some(geometry) = geometry {
+            builder = builder
+                .inner_size(geometry.width, geometry.height)
+                .position(geometry.x, geometry.y);
+        }
+        let _window = builder.build()?;
 
-        // Store window reference
-        self.windows.insert(window_label.clone(), vault_path.clone());
+        Ok(window_label)
+    }
 
-        println!("Created window '{}' for vault: {}", window_label, vault_path);
+    /// Hold a slot for a window about to be opened, unless `max` windows are
+    /// already open or being opened; the error is that number. Counting and
+    /// holding happen under one lock, so concurrent opens can't both take
+    /// the last slot.
+    pub async fn reserve_slot(
+        window_manager: &Arc<Mutex<Self>>,
+        max: Option<usize>,
+    ) -> std::result::Result<SlotReservation, usize> {
+        let mut manager = window_manager.lock().await;
+        let taken = manager.windows.len() + manager.reserved;
+        if max.is_some_and(|max| taken >= max) {
+            return Err(taken);
+        }
+        manager.reserved += 1;
+        Ok(SlotReservation { window_manager: Some(window_manager.clone()) })
+    }
 
-        Ok(window_label)
+    /// Start tracking a window made by `build_vault_window`, in the slot
+    /// `reservation` held for it
+    pub fn register_window(
+        &mut self,
+        mut reservation: SlotReservation,
+        window_label: &str,
+        vault_path: String,
+        geometry: Option<WindowGeometry>,
+    ) {
+        reservation.window_manager = None;
+        self.reserved = self.reserved.saturating_sub(1);
+        self.windows.insert(window_label.to_string(), vault_path.clone());
+        self.last_used.insert(window_label.to_string(), Instant::now());
+        if let Some(geometry) = geometry {
+            self.geometry.insert(window_label.to_string(), geometry);
+        }
+        self.session.push(window_label.to_string());
+        self.save_session(true);
+
+        println!("Created window '{}' for vault: {}", window_label, vault_path);
     }
 
     /// Get vault path for a window
@@ -52,12 +140,107 @@ impl WindowManager {
         self.windows.get(window_label)
     }
 
+    /// A window showing the vault at `vault_path`, if one is open. Paths are
+    /// compared canonicalized, so `/foo`, `/foo/` and a symlink to it match.
+    pub fn find_window_by_vault(&self, vault_path: &Path) -> Option<String> {
+        let target = canonical_vault_path(vault_path);
+        self.windows.iter()
+            .find(|(_, path)| canonical_vault_path(Path::new(path.as_str())) == target)
+            .map(|(label, _)| label.clone())
+    }
+
     /// Remove window from tracking
     pub fn remove_window(&mut self, window_label: &str) {
         self.windows.remove(window_label);
+        self.last_used.remove(window_label);
+        self.disposable.remove(window_label);
+        self.geometry.remove(window_label);
+        self.drop_from_session(window_label);
         println!("Removed window: {}", window_label);
     }
 
+    /// Mark a window's vault as a scratch vault, deleted when it closes.
+    /// Scratch vaults are never reopened, so it leaves the session.
+    pub fn mark_disposable(&mut self, window_label: &str) {
+        self.disposable.insert(window_label.to_string());
+        self.drop_from_session(window_label);
+    }
+
+    /// Record a window's new size and position. Writes are spaced out while
+    /// it is being dragged; the latest geometry is always written on quit.
+    pub fn update_geometry(&mut self, window_label: &str, geometry: WindowGeometry) {
+        if !self.windows.contains_key(window_label) {
+            return;
+        }
+        if self.geometry.insert(window_label.to_string(), geometry) != Some(geometry) {
+            self.save_session(false);
+        }
+    }
+
+    /// Stop reopening a window next launch, e.g. once it was closed
+    pub fn drop_from_session(&mut self, window_label: &str) {
+        let before = self.session.len();
+        self.session.retain(|label| label != window_label);
+        if self.session.len() < before {
+            self.save_session(true);
+        }
+    }
+
+    /// Write the session one last time and keep it as it is while the app
+    /// quits, since quitting closes every window
+    pub fn freeze_session(&mut self) {
+        self.save_session(true);
+        self.session_frozen = true;
+    }
+
+    fn save_session(&mut self, force: bool) {
+        let Some(dir) = self.session_dir.as_ref() else { return };
+        if self.session_frozen {
+            return;
+        }
+        if !force && self.session_written.is_some_and(|at| at.elapsed() < GEOMETRY_WRITE_INTERVAL) {
+            return;
+        }
+        let windows: Vec<SessionWindow> = self.session.iter()
+            .filter_map(|label| Some(SessionWindow {
+                vault_path: self.windows.get(label)?.clone(),
+                geometry: self.geometry.get(label).copied(),
+                last_used: self.last_used.get(label).map(|used| {
+                    chrono::Utc::now().timestamp_millis() - used.elapsed().as_millis() as i64
+                }),
+            }))
+            .collect();
+        if let Err(e) = window_session::save(dir, &windows) {
+            eprintln!("Warning: Failed to save window session: {}", e);
+        }
+        self.session_written = Some(Instant::now());
+    }
+
+    /// Whether closing the window deletes its vault
+    pub fn is_disposable(&self, window_label: &str) -> bool {
+        self.disposable.contains(window_label)
+    }
+
+    /// Record activity in a window, for least-recently-used eviction
+    pub fn touch(&mut self, window_label: &str) {
+        if let Some(last_used) = self.last_used.get_mut(window_label) {
+            *last_used = Instant::now();
+        }
+    }
+
+    /// Number of open vault windows
+    pub fn window_count(&self) -> usize {
+        self.windows.len()
+    }
+
+    /// The window with the oldest activity
+    pub fn least_recently_used(&self) -> Option<String> {
+        self.last_used
+            .iter()
+            .min_by_key(|(_, used)| **used)
+            .map(|(label, _)| label.clone())
+    }
+
     /// Get all active window labels
     #[allow(dead_code)]
     pub fn get_active_windows(&self) -> Vec<String> {
@@ -73,3 +256,41 @@ impl WindowManager {
             .to_string()
     }
 }
+
+/// A change to the saved session requested from the event loop
+pub enum SessionUpdate {
+    /// A window was closed; stop reopening it
+    Drop(String),
+    /// The app is quitting: keep the session as it is, then exit with this code
+    FreezeAndExit(i32),
+}
+
+/// Apply session updates from the event loop in the order they were sent.
+/// The event loop must not wait on the window manager lock itself, since a
+/// command holding it may be waiting on the main thread.
+pub fn spawn_session_updater(
+    app: AppHandle,
+    window_manager: Arc<Mutex<WindowManager>>,
+) -> mpsc::UnboundedSender<SessionUpdate> {
+    let (updates, mut pending) = mpsc::unbounded_channel();
+    tauri::async_runtime::spawn(async move {
+        while let Some(update) = pending.recv().await {
+            match update {
+                SessionUpdate::Drop(window_label) => {
+                    window_manager.lock().await.drop_from_session(&window_label);
+                }
+                SessionUpdate::FreezeAndExit(code) => {
+                    window_manager.lock().await.freeze_session();
+                    app.exit(code);
+                }
+            }
+        }
+    });
+    updates
+}
+
+/// `path` with symlinks resolved; when it can't be resolved (e.g. the vault
+/// was deleted), at least normalized so trailing separators don't matter
+fn canonical_vault_path(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| path.components().collect())
+}
@@ -0,0 +1,95 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use anyhow::{Result, Context};
+
+use crate::fs_utils::atomic_write;
+
+/// Vault windows open at last save, under the app config dir
+pub const SESSION_FILE: &str = "session.json";
+
+/// A window's size and position in logical pixels
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct WindowGeometry {
+    pub width: f64,
+    pub height: f64,
+    pub x: f64,
+    pub y: f64,
+}
+
+/// One vault window to reopen on the next launch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionWindow {
+    pub vault_path: String,
+    pub geometry: Option<WindowGeometry>,
+    /// Last activity in the window, in ms since the epoch
+    #[serde(default)]
+    pub last_used: Option<i64>,
+}
+
+pub fn session_path(app_config_dir: &Path) -> PathBuf {
+    app_config_dir.join(SESSION_FILE)
+}
+
+/// The saved session; empty when there is none or it can't be read
+pub fn load(app_config_dir: &Path) -> Vec<SessionWindow> {
+    let path = session_path(app_config_dir);
+    let Ok(contents) = fs::read_to_string(&path) else {
+        return Vec::new();
+    };
+    serde_json::from_str(&contents).unwrap_or_else(|e| {
+        eprintln!("Warning: Ignoring unreadable session {}: {}", path.display(), e);
+        Vec::new()
+    })
+}
+
+pub fn save(app_config_dir: &Path, windows: &[SessionWindow]) -> Result<()> {
+    let contents = serde_json::to_string_pretty(windows)?;
+    atomic_write(&session_path(app_config_dir), contents.as_bytes())
+        .context("Failed to write session")
+}
+
+/// Split `windows` into the `limit` most recently used, still in opening
+/// order, and the rest. Windows saved before recency was recorded count as
+/// least recent.
+pub fn most_recent(windows: Vec<SessionWindow>, limit: usize) -> (Vec<SessionWindow>, Vec<SessionWindow>) {
+    let mut by_recency: Vec<usize> = (0..windows.len()).collect();
+    by_recency.sort_by_key(|&i| std::cmp::Reverse(windows[i].last_used));
+    by_recency.truncate(limit);
+
+    let mut kept = Vec::new();
+    let mut dropped = Vec::new();
+    for (i, window) in windows.into_iter().enumerate() {
+        if by_recency.contains(&i) {
+            kept.push(window);
+        } else {
+            dropped.push(window);
+        }
+    }
+    (kept, dropped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window(vault_path: &str, last_used: Option<i64>) -> SessionWindow {
+        SessionWindow { vault_path: vault_path.to_string(), geometry: None, last_used }
+    }
+
+    #[test]
+    fn most_recent_keeps_latest_in_opening_order() {
+        let windows = vec![
+            window("/old", Some(10)),
+            window("/newest", Some(30)),
+            window("/unknown", None),
+            window("/newer", Some(20)),
+        ];
+
+        let (kept, dropped) = most_recent(windows, 2);
+
+        let paths = |windows: &[SessionWindow]| windows.iter().map(|w| w.vault_path.clone()).collect::<Vec<_>>();
+        assert_eq!(paths(&kept), ["/newest", "/newer"]);
+        assert_eq!(paths(&dropped), ["/old", "/unknown"]);
+    }
+}
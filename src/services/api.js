@@ -3,9 +3,44 @@
  * Provides typed functions for all IPC commands
  */
 
-import { invoke } from '@tauri-apps/api/core';
+import { invoke as tauriInvoke } from '@tauri-apps/api/core';
 import { open } from '@tauri-apps/plugin-dialog';
 
+/**
+ * Error thrown when a command fails. `code` is stable (NOT_FOUND,
+ * VALIDATION, DEPENDENCY_FAILED, SIDECAR_UNAVAILABLE, IO, AUTH_REQUIRED,
+ * NETWORK, CANCELLED, TOO_MANY_WINDOWS, INTERNAL) for choosing a
+ * recovery flow; `message` is for display. TOO_MANY_WINDOWS also
+ * carries `open` and `max`.
+ */
+export class CommandError extends Error {
+    constructor(code, message, details = {}) {
+        super(message);
+        this.name = 'CommandError';
+        this.code = code;
+        Object.assign(this, details);
+    }
+
+    toString() {
+        return this.message;
+    }
+}
+
+/**
+ * Invoke a command, rethrowing its `{ code, message }` error as a CommandError
+ */
+export async function invoke(command, args) {
+    try {
+        return await tauriInvoke(command, args);
+    } catch (e) {
+        if (e && typeof e === 'object' && typeof e.code === 'string') {
+            const { code, message, ...details } = e;
+            throw new CommandError(code, message, details);
+        }
+        throw new CommandError('INTERNAL', String(e));
+    }
+}
+
 /**
  * Vault Management
  */
@@ -25,12 +60,19 @@ export const vaultApi = {
     },
 
     /**
-     * Open a vault by path
+     * Open a vault by path; an already-open vault's window is focused instead
      */
     async openVaultByPath(vaultPath) {
         return await invoke('open_vault', { vaultPath });
     },
 
+    /**
+     * Open a disposable scratch vault in a temporary directory
+     */
+    async openTempVault() {
+        return await invoke('open_temp_vault', {});
+    },
+
     /**
      * Get list of known vaults
      */
@@ -63,10 +105,11 @@ export const vaultApi = {
     },
 
     /**
-     * Close a vault window
+     * Close a vault window. A scratch vault is deleted unless keep is true,
+     * which moves it to the default vault directory and adds it to recents.
      */
-    async closeVault(windowLabel) {
-        return await invoke('close_vault', { windowLabel });
+    async closeVault(windowLabel, keep = false) {
+        return await invoke('close_vault', { windowLabel, keep });
     },
 
     /**
@@ -98,8 +141,8 @@ export const pluginStoreApi = {
     /**
      * Install plugin to vault
      */
-    async installPlugin(vaultPath, pluginRepo, pluginName) {
-        return await invoke('install_plugin', { vaultPath, pluginRepo, pluginName });
+    async installPlugin(vaultPath, pluginRepo, pluginName, update = false) {
+        return await invoke('install_plugin', { vaultPath, pluginRepo, pluginName, update });
     },
 
     /**
@@ -108,6 +151,20 @@ export const pluginStoreApi = {
     async getInstalledPlugins(vaultPath) {
         return await invoke('get_installed_plugins', { vaultPath });
     },
+
+    /**
+     * Unload and delete a plugin from a vault
+     */
+    async uninstallPlugin(vaultPath, pluginName) {
+        return await invoke('uninstall_plugin', { vaultPath, pluginName });
+    },
+
+    /**
+     * Enable or disable a plugin, loading or unloading it live
+     */
+    async setPluginEnabled(vaultPath, pluginName, enabled) {
+        return await invoke('set_plugin_enabled', { vaultPath, pluginName, enabled });
+    },
 };
 
 /**
@@ -136,7 +193,7 @@ export const settingsApi = {
     },
 
     /**
-     * Save vault settings
+     * Merge settings into the vault's settings; resolves to the merged result
      */
     async saveVaultSettings(vaultPath, settings) {
         return await invoke('save_vault_settings', { vaultPath, settings });
@@ -149,6 +206,13 @@ export const settingsApi = {
         return await invoke('get_api_keys', {});
     },
 
+    /**
+     * Get the stored secret for an API key
+     */
+    async getApiKey(keyName) {
+        return await invoke('get_api_key', { keyName });
+    },
+
     /**
      * Save API key
      */
@@ -171,8 +235,8 @@ export const conversationApi = {
     /**
      * Search conversations
      */
-    async searchConversations(query, filters = {}) {
-        return await invoke('search_conversations', { query, filters });
+    async searchConversations(vaultPath, query, filters = {}) {
+        return await invoke('search_conversations', { vaultPath, query, filters });
     },
 
     /**
@@ -202,10 +266,17 @@ export const developerApi = {
     },
 
     /**
-     * Validate plugin structure
+     * Check a plugin against the loading contract; lists each check and whether it passed
      */
     async validatePlugin(vaultPath, pluginPath) {
         return await invoke('validate_plugin', { vaultPath, pluginPath });
     },
+
+    /**
+     * Hot-reload one plugin in a window's sidecar
+     */
+    async reloadPlugin(windowLabel, pluginName) {
+        return await invoke('reload_plugin', { windowLabel, pluginName });
+    },
 };
 